@@ -0,0 +1,44 @@
+/* C interface to graphs-rs, built with the `ffi` feature. Kept in sync
+ * with src/ffi.rs, whose documentation has the full contracts. */
+
+#ifndef GRAPHS_RS_H
+#define GRAPHS_RS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Graph with unweighted nodes, named by the index they were added at, and
+ * edges weighted by a double. */
+typedef struct GraphsGraph GraphsGraph;
+
+GraphsGraph *graphs_graph_new(bool directed);
+void graphs_graph_free(GraphsGraph *graph);
+
+size_t graphs_graph_add_node(GraphsGraph *graph);
+/* false if an endpoint is not in the graph */
+bool graphs_graph_add_edge(GraphsGraph *graph, size_t source, size_t target, double weight);
+
+size_t graphs_graph_node_count(const GraphsGraph *graph);
+size_t graphs_graph_edge_count(const GraphsGraph *graph);
+
+/* Writes up to capacity nodes in breadth-first order; returns how many were
+ * reached, or SIZE_MAX if start is not in the graph. */
+size_t graphs_bfs(const GraphsGraph *graph, size_t start, size_t *order, size_t capacity);
+
+/* Writes one distance per node, INFINITY where unreachable; false on a bad
+ * source, a short buffer or a negative weight. */
+bool graphs_dijkstra(const GraphsGraph *graph, size_t source, double *distances, size_t len);
+
+/* Writes one component number per node and returns the component count, or
+ * SIZE_MAX if the buffer is short. */
+size_t graphs_connected_components(const GraphsGraph *graph, size_t *labels, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
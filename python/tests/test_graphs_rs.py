@@ -0,0 +1,74 @@
+import unittest
+
+import graphs_rs as nx
+
+
+class GraphTest(unittest.TestCase):
+    def test_builds_like_networkx(self):
+        G = nx.Graph([("a", "b"), ("b", "c", 2.5)])
+        G.add_edge("a", "b", weight=0.5)
+        G.add_edge("d", "d")
+        G.add_node("e")
+
+        self.assertEqual((5, 3), (G.number_of_nodes(), G.number_of_edges()))
+        self.assertEqual(["a", "b", "c", "d", "e"], G.nodes)
+        self.assertEqual(["a", "c"], G.neighbors("b"))
+        self.assertEqual(0.5, G.get_edge_weight("b", "a"))
+        self.assertEqual([("a", "b", 0.5), ("b", "c", 2.5), ("d", "d", 1.0)], G.edges(data=True))
+        self.assertTrue(G.has_edge("b", "a") and not G.has_edge("a", "c"))
+        self.assertEqual((2, 2), (G.degree("b"), G.degree("d")))
+        self.assertEqual(["a", "b", "c", "d", "e"], list(G))
+
+        G.remove_node("b")
+        self.assertEqual((4, 1), (len(G), G.number_of_edges()))
+        self.assertNotIn("b", G)
+
+        with self.assertRaises(nx.NodeNotFound):
+            G.neighbors("b")
+
+    def test_runs_the_algorithms_in_rust(self):
+        G = nx.Graph()
+        G.add_weighted_edges_from([(0, 1, 4.0), (0, 2, 1.0), (2, 1, 1.0), (1, 3, 1.0), (4, 5, 1.0)])
+
+        self.assertEqual([0, 1, 3], nx.shortest_path(G, 0, 3))
+        self.assertEqual([0, 2, 1, 3], nx.shortest_path(G, 0, 3, weight="weight"))
+        self.assertEqual(3.0, nx.shortest_path_length(G, 0, 3, weight="weight"))
+        self.assertEqual({0: 0.0, 1: 2.0, 2: 1.0, 3: 3.0}, nx.single_source_dijkstra_path_length(G, 0))
+        self.assertEqual([{0, 1, 2, 3}, {4, 5}], nx.connected_components(G))
+        self.assertEqual(2, nx.number_connected_components(G))
+        self.assertEqual({0: 2, 1: 2, 2: 2, 3: 1, 4: 1, 5: 1}, nx.core_number(G))
+        self.assertEqual(1.0, nx.harmonic_centrality(G)[4])
+        self.assertFalse(nx.has_path(G, 0, 4))
+
+        with self.assertRaises(nx.NetworkXNoPath):
+            nx.shortest_path(G, 0, 5)
+
+        G.add_edge(3, 4, weight=-1.0)
+
+        with self.assertRaises(ValueError):
+            nx.shortest_path_length(G, 0, 5, weight="weight")
+
+    def test_directed_graphs(self):
+        D = nx.DiGraph([("x", "y"), ("y", "z"), ("x", "z")])
+
+        self.assertIsInstance(D, nx.Graph)
+        self.assertTrue(D.is_directed())
+        self.assertEqual(["x", "y", "z"], nx.topological_sort(D))
+        self.assertEqual((["y", "z"], ["y", "x"]), (D.successors("x"), D.predecessors("z")))
+        self.assertEqual((0, 2, 2), (D.out_degree("z"), D.in_degree("z"), D.degree("z")))
+        self.assertTrue(nx.has_path(D, "x", "z") and not nx.has_path(D, "z", "x"))
+
+        D.add_edge("z", "x")
+
+        with self.assertRaises(nx.NetworkXUnfeasible):
+            nx.topological_sort(D)
+
+        D.add_edge("w", "x")
+        self.assertEqual([{"x", "y", "z"}, {"w"}], nx.strongly_connected_components(D))
+
+        with self.assertRaises(nx.NetworkXNotImplemented):
+            nx.connected_components(D)
+
+
+if __name__ == "__main__":
+    unittest.main()
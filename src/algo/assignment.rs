@@ -0,0 +1,138 @@
+/// Cheapest assignment of every row of a cost matrix to a distinct column,
+/// by the Hungarian algorithm in `O(rows² · columns)`. Returns the total
+/// cost and the column of each row.
+///
+/// Panics if there are more rows than columns or the rows differ in
+/// length. Costs must be finite.
+pub fn min_cost_assignment(costs: &[Vec<f64>]) -> (f64, Vec<usize>) {
+    let rows = costs.len();
+    let cols = costs.first().map_or(0, Vec::len);
+
+    assert!(costs.iter().all(|row| row.len() == cols), "rows differ in length");
+    assert!(rows <= cols, "more rows than columns");
+
+    // potentials and matching with a sentinel column 0, rows numbered from 1
+    let mut u = vec![0.0; rows + 1];
+    let mut v = vec![0.0; cols + 1];
+    let mut owner = vec![0; cols + 1];
+    let mut way = vec![0; cols + 1];
+
+    for row in 1..=rows {
+        owner[0] = row;
+
+        let mut col = 0;
+        let mut min = vec![f64::INFINITY; cols + 1];
+        let mut used = vec![false; cols + 1];
+
+        loop {
+            used[col] = true;
+
+            let current = owner[col];
+            let mut delta = f64::INFINITY;
+            let mut next = 0;
+
+            for j in 1..=cols {
+                if used[j] {
+                    continue;
+                }
+
+                let reduced = costs[current - 1][j - 1] - u[current] - v[j];
+
+                if reduced < min[j] {
+                    min[j] = reduced;
+                    way[j] = col;
+                }
+
+                if min[j] < delta {
+                    delta = min[j];
+                    next = j;
+                }
+            }
+
+            for j in 0..=cols {
+                if used[j] {
+                    u[owner[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min[j] -= delta;
+                }
+            }
+
+            col = next;
+
+            if owner[col] == 0 {
+                break;
+            }
+        }
+
+        // flip the alternating path back to the sentinel
+        while col != 0 {
+            let prev = way[col];
+            owner[col] = owner[prev];
+            col = prev;
+        }
+    }
+
+    let mut assigned = vec![0; rows];
+
+    for j in 1..=cols {
+        if owner[j] != 0 {
+            assigned[owner[j] - 1] = j - 1;
+        }
+    }
+
+    let total = assigned.iter().enumerate().map(|(i, &j)| costs[i][j]).sum();
+
+    (total, assigned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Rng, SplitMix64};
+
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![Vec::new()];
+        }
+
+        let mut all = Vec::new();
+
+        for perm in permutations(n - 1) {
+            for i in 0..n {
+                let mut next = perm.clone();
+                next.insert(i, n - 1);
+                all.push(next);
+            }
+        }
+
+        all
+    }
+
+    #[test]
+    fn matches_exhaustive_search() {
+        let mut rng = SplitMix64::new(361);
+
+        for _ in 0..100 {
+            let rows = rng.below(6);
+            let cols = rows + rng.below(2);
+            let costs: Vec<Vec<f64>> = (0..rows)
+                .map(|_| (0..cols).map(|_| rng.below(40) as f64 - 10.0).collect())
+                .collect();
+
+            let best = permutations(cols)
+                .into_iter()
+                .map(|perm| (0..rows).map(|i| costs[i][perm[i]]).sum::<f64>())
+                .fold(f64::INFINITY, f64::min);
+
+            let (total, assigned) = min_cost_assignment(&costs);
+            let mut seen = assigned.clone();
+
+            seen.sort();
+            seen.dedup();
+
+            assert_eq!(rows, seen.len());
+            assert_eq!(if rows == 0 { 0.0 } else { best }, total);
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use crate::graph::{EdgeType, Graph, NodeId};
+
+/// Canonical form of a graph computed by [`canonical_form`]. Two forms
+/// compare equal exactly when their graphs are isomorphic, so forms can be
+/// collected into a hash set to deduplicate graphs.
+#[derive(Clone, Debug)]
+pub struct CanonicalForm {
+    order: Vec<NodeId>,
+    bytes: Vec<u8>
+}
+
+impl CanonicalForm {
+    /// Nodes in canonical order: isomorphic graphs list corresponding nodes
+    /// at the same positions, up to automorphisms.
+    pub fn order(&self) -> &[NodeId] {
+        &self.order
+    }
+
+    /// Node count, directedness and the edge multiplicity of every pair of
+    /// positions in canonical order.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl PartialEq for CanonicalForm {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl Eq for CanonicalForm {}
+
+impl Hash for CanonicalForm {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes.hash(state);
+    }
+}
+
+/// Computes the canonical form of a graph by individualization and
+/// refinement in the style of nauty: the node partition is refined until
+/// equitable, then the search branches on each node of a smallest
+/// non-trivial cell. Leaves are ranked by their refinement trace and then
+/// by the adjacency they induce, and automorphisms found along the way
+/// prune branches that would only repeat earlier ones.
+///
+/// Node and edge weights are ignored; parallel edges and self loops count.
+/// Worst-case running time is exponential, but small graphs, even highly
+/// symmetric ones, are fast.
+pub fn canonical_form<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> CanonicalForm {
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let n = nodes.len();
+    let mut dense = vec![0; graph.node_bound()];
+
+    for (i, node) in nodes.iter().enumerate() {
+        dense[node.index()] = i;
+    }
+
+    let mut adjacency = vec![vec![0u32; n]; n];
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        let (a, b) = (dense[a.index()], dense[b.index()]);
+
+        adjacency[a][b] += 1;
+
+        if !Ty::is_directed() && a != b {
+            adjacency[b][a] += 1;
+        }
+    }
+
+    let mut search = Search {
+        adjacency,
+        directed: Ty::is_directed(),
+        best: None,
+        automorphisms: Vec::new()
+    };
+    let cells = search.refine(vec![(0..n).collect()]);
+    let mut trace = vec![cells.iter().map(Vec::len).collect()];
+
+    search.search(cells, &mut Vec::new(), &mut trace);
+
+    let best = search.best.unwrap();
+
+    CanonicalForm { order: best.order.into_iter().map(|i| nodes[i]).collect(), bytes: best.bytes }
+}
+
+struct Leaf {
+    path: Vec<usize>,
+    trace: Vec<Vec<usize>>,
+    bytes: Vec<u8>,
+    order: Vec<usize>
+}
+
+struct Search {
+    adjacency: Vec<Vec<u32>>,
+    directed: bool,
+    best: Option<Leaf>,
+    automorphisms: Vec<Vec<usize>>
+}
+
+impl Search {
+    /// Splits cells by how many edges each member has to and from every
+    /// cell until no cell splits any more. Split pieces keep the position
+    /// of their cell, ordered by signature, so the result only depends on
+    /// the graph's structure and the input partition.
+    fn refine(&self, mut cells: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        loop {
+            let signature = |v: usize| -> Vec<(u32, u32)> {
+                cells
+                    .iter()
+                    .map(|cell| {
+                        let out = cell.iter().map(|&w| self.adjacency[v][w]).sum();
+                        let incoming = cell.iter().map(|&w| self.adjacency[w][v]).sum();
+                        (out, if self.directed { incoming } else { 0 })
+                    })
+                    .collect()
+            };
+
+            let mut next = Vec::with_capacity(cells.len());
+
+            for cell in &cells {
+                if cell.len() == 1 {
+                    next.push(cell.clone());
+                    continue;
+                }
+
+                let mut keyed: Vec<_> = cell.iter().map(|&v| (signature(v), v)).collect();
+                keyed.sort();
+
+                let mut start = 0;
+
+                for i in 1..=keyed.len() {
+                    if i == keyed.len() || keyed[i].0 != keyed[start].0 {
+                        next.push(keyed[start..i].iter().map(|&(_, v)| v).collect());
+                        start = i;
+                    }
+                }
+            }
+
+            if next.len() == cells.len() {
+                return next;
+            }
+
+            cells = next;
+        }
+    }
+
+    /// Explores the children of a search node, returning the depth to
+    /// resume at when an automorphism shows that the rest of this subtree
+    /// repeats a part already searched.
+    fn search(
+        &mut self,
+        cells: Vec<Vec<usize>>,
+        fixed: &mut Vec<usize>,
+        trace: &mut Vec<Vec<usize>>
+    ) -> usize {
+        let target = (0..cells.len()).filter(|&c| cells[c].len() > 1).min_by_key(|&c| cells[c].len());
+
+        let Some(target) = target else {
+            return self.leaf(cells.into_iter().flatten().collect(), fixed, trace);
+        };
+
+        let mut tried: Vec<usize> = Vec::new();
+
+        for &v in &cells[target] {
+            if tried.iter().any(|&w| self.same_orbit(v, w, fixed)) {
+                continue;
+            }
+
+            tried.push(v);
+
+            let mut split = cells.clone();
+            let rest: Vec<_> = cells[target].iter().copied().filter(|&w| w != v).collect();
+
+            split[target] = vec![v];
+            split.insert(target + 1, rest);
+
+            let split = self.refine(split);
+
+            trace.push(split.iter().map(Vec::len).collect());
+
+            // every leaf below has a trace starting with this one, so a
+            // prefix beyond the best leaf's rules the whole subtree out
+            let hopeless = self.best.as_ref().is_some_and(|best| {
+                let depth = trace.len().min(best.trace.len());
+                trace.as_slice() > &best.trace[..depth]
+            });
+
+            let resume = if hopeless {
+                usize::MAX
+            } else {
+                fixed.push(v);
+                let resume = self.search(split, fixed, trace);
+                fixed.pop();
+                resume
+            };
+
+            trace.pop();
+
+            if resume < fixed.len() {
+                return resume;
+            }
+        }
+
+        usize::MAX
+    }
+
+    /// Records a leaf, ranked by trace and then by the adjacency it induces.
+    /// A leaf equivalent to the best yields an automorphism that maps
+    /// everything below their common ancestor onto the part of the tree
+    /// explored already, so the search resumes at that ancestor.
+    fn leaf(&mut self, order: Vec<usize>, fixed: &[usize], trace: &[Vec<usize>]) -> usize {
+        let bytes = self.encode(&order);
+
+        let ordering = match &self.best {
+            Some(best) => trace.cmp(&best.trace).then_with(|| bytes.cmp(&best.bytes)),
+            None => Ordering::Less
+        };
+
+        match ordering {
+            Ordering::Less => {
+                self.best = Some(Leaf { path: fixed.to_vec(), trace: trace.to_vec(), bytes, order });
+                usize::MAX
+            },
+            Ordering::Equal => {
+                let best = self.best.as_ref().unwrap();
+                let mut automorphism = vec![0; order.len()];
+
+                for (&from, &to) in order.iter().zip(&best.order) {
+                    automorphism[from] = to;
+                }
+
+                let common = fixed.iter().zip(&best.path).take_while(|(a, b)| a == b).count();
+
+                self.automorphisms.push(automorphism);
+
+                common
+            },
+            Ordering::Greater => usize::MAX
+        }
+    }
+
+    /// Whether an automorphism found so far that fixes every individualized
+    /// node connects `v` and `w`.
+    fn same_orbit(&self, v: usize, w: usize, fixed: &[usize]) -> bool {
+        let mut parent: Vec<_> = (0..self.adjacency.len()).collect();
+
+        fn find(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+
+            x
+        }
+
+        let stabilizing = self.automorphisms.iter().filter(|g| fixed.iter().all(|&f| g[f] == f));
+
+        for g in stabilizing {
+            for (x, &y) in g.iter().enumerate() {
+                let (a, b) = (find(&mut parent, x), find(&mut parent, y));
+                parent[a] = b;
+            }
+        }
+
+        find(&mut parent, v) == find(&mut parent, w)
+    }
+
+    fn encode(&self, order: &[usize]) -> Vec<u8> {
+        let n = order.len();
+        let mut bytes = Vec::new();
+
+        write_varint(&mut bytes, n as u64);
+        bytes.push(self.directed as u8);
+
+        for i in 0..n {
+            let start = if self.directed { 0 } else { i };
+
+            for j in start..n {
+                write_varint(&mut bytes, self.adjacency[order[i]][order[j]] as u64);
+            }
+        }
+
+        bytes
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, Directed, UnGraph, Undirected};
+    use crate::rng::{Rng, SplitMix64};
+
+    fn random(rng: &mut SplitMix64, nodes: usize, edges: usize) -> Vec<(usize, usize)> {
+        (0..edges).map(|_| (rng.below(nodes), rng.below(nodes))).collect()
+    }
+
+    fn build<Ty: EdgeType>(nodes: usize, edges: &[(usize, usize)], perm: &[usize]) -> Graph<(), (), Ty> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..nodes).map(|_| graph.add_node(())).collect();
+
+        for &(a, b) in edges {
+            graph.add_edge(n[perm[a]], n[perm[b]], ());
+        }
+
+        graph
+    }
+
+    fn isomorphic<Ty: EdgeType>(nodes: usize, a: &[(usize, usize)], b: &[(usize, usize)]) -> bool {
+        fn permutations(n: usize) -> Vec<Vec<usize>> {
+            if n == 0 {
+                return vec![Vec::new()];
+            }
+
+            let mut all = Vec::new();
+
+            for perm in permutations(n - 1) {
+                for i in 0..n {
+                    let mut next = perm.clone();
+                    next.insert(i, n - 1);
+                    all.push(next);
+                }
+            }
+
+            all
+        }
+
+        let normalize = |edges: &[(usize, usize)], perm: &[usize]| {
+            let mut edges: Vec<_> = edges
+                .iter()
+                .map(|&(x, y)| (perm[x], perm[y]))
+                .map(|(x, y)| if Ty::is_directed() || x <= y { (x, y) } else { (y, x) })
+                .collect();
+            edges.sort();
+            edges
+        };
+
+        let target = normalize(b, &(0..nodes).collect::<Vec<_>>());
+
+        permutations(nodes).iter().any(|perm| normalize(a, perm) == target)
+    }
+
+    #[test]
+    fn forms_agree_exactly_on_isomorphic_graphs() {
+        let mut rng = SplitMix64::new(363);
+
+        for round in 0..300 {
+            let nodes = 1 + rng.below(6);
+            let edges = rng.below(9);
+            let a = random(&mut rng, nodes, edges);
+            let b = if round % 2 == 0 { a.clone() } else { random(&mut rng, nodes, a.len()) };
+            let mut perm: Vec<_> = (0..nodes).collect();
+
+            rng.shuffle(&mut perm);
+
+            let identity: Vec<_> = (0..nodes).collect();
+
+            let x: UnGraph<(), ()> = build(nodes, &a, &identity);
+            let y: UnGraph<(), ()> = build(nodes, &b, &perm);
+            let same = isomorphic::<Undirected>(nodes, &a, &b);
+
+            assert_eq!(same, canonical_form(&x) == canonical_form(&y), "round {round}");
+
+            let x: DiGraph<(), ()> = build(nodes, &a, &identity);
+            let y: DiGraph<(), ()> = build(nodes, &b, &perm);
+            let same = isomorphic::<Directed>(nodes, &a, &b);
+
+            assert_eq!(same, canonical_form(&x) == canonical_form(&y), "round {round}");
+        }
+    }
+
+    #[test]
+    fn symmetric_graphs_finish_quickly() {
+        let empty: UnGraph<(), ()> = build(40, &[], &(0..40).collect::<Vec<_>>());
+        let cycle: Vec<_> = (0..30).map(|i| (i, (i + 1) % 30)).collect();
+        let cycle: UnGraph<(), ()> = build(30, &cycle, &(0..30).collect::<Vec<_>>());
+        let form = canonical_form(&cycle);
+
+        assert_eq!(40, canonical_form(&empty).order().len());
+        assert_eq!(30, form.order().len());
+
+        // a relabelled 4 by 5 grid, full of automorphisms
+        let mut rng = SplitMix64::new(363);
+        let grid: Vec<_> = (0..20)
+            .flat_map(|i| [(i, i + 1), (i, i + 5)])
+            .filter(|&(i, j)| j < 20 && (j != i + 1 || j % 5 != 0))
+            .collect();
+        let mut perm: Vec<_> = (0..20).collect();
+
+        rng.shuffle(&mut perm);
+
+        let a: UnGraph<(), ()> = build(20, &grid, &(0..20).collect::<Vec<_>>());
+        let b: UnGraph<(), ()> = build(20, &grid, &perm);
+
+        assert_eq!(canonical_form(&a), canonical_form(&b));
+        assert_ne!(canonical_form(&a), canonical_form(&cycle));
+    }
+}
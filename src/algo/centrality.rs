@@ -0,0 +1,349 @@
+use std::collections::VecDeque;
+
+use crate::attr::NodeMap;
+use crate::graph::{DiGraph, Direction, EdgeType, Graph, NodeId};
+use crate::rng::Rng;
+
+/// How an iterative centrality measure stopped.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Convergence {
+    /// Rounds run.
+    pub iterations: usize,
+    /// Total absolute change of the scores in the last round.
+    pub delta: f64,
+    /// Whether `delta` fell to the tolerance before the round limit.
+    pub converged: bool
+}
+
+/// Hub and authority scores found by [`hits`], each summing to one.
+pub struct Hits {
+    pub hubs: NodeMap<f64>,
+    pub authorities: NodeMap<f64>,
+    pub convergence: Convergence
+}
+
+/// Scores of an iterative centrality measure, summing to one.
+pub struct Centrality {
+    pub scores: NodeMap<f64>,
+    pub convergence: Convergence
+}
+
+/// Harmonic centrality estimated by [`harmonic_centrality_sampled`].
+pub struct HarmonicEstimate {
+    pub scores: NodeMap<f64>,
+    /// Sources searched from.
+    pub samples: usize,
+    nodes: usize
+}
+
+impl HarmonicEstimate {
+    /// Half-width of an interval holding each score with probability at
+    /// least `confidence`, by Hoeffding's inequality; zero when every node
+    /// was a source.
+    pub fn error_bound(&self, confidence: f64) -> f64 {
+        if self.samples >= self.nodes {
+            return 0.0;
+        }
+
+        let n = self.nodes as f64;
+        n * ((2.0 / (1.0 - confidence)).ln() / (2.0 * self.samples as f64)).sqrt()
+    }
+}
+
+/// Repeats `step`, which computes the next scores from the current ones,
+/// until their total change is at most `tolerance` or `max_iters` rounds
+/// have run. Scores are indexed by node index.
+fn iterate(
+    scores: &mut Vec<f64>,
+    tolerance: f64,
+    max_iters: usize,
+    mut step: impl FnMut(&[f64], &mut [f64])
+) -> Convergence {
+    let mut next = vec![0.0; scores.len()];
+    let mut convergence = Convergence { iterations: 0, delta: f64::INFINITY, converged: false };
+
+    while convergence.iterations < max_iters {
+        next.iter_mut().for_each(|x| *x = 0.0);
+        step(scores, &mut next);
+
+        convergence.iterations += 1;
+        convergence.delta = scores.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        std::mem::swap(scores, &mut next);
+
+        if convergence.delta <= tolerance {
+            convergence.converged = true;
+            break;
+        }
+    }
+
+    convergence
+}
+
+/// Scales `scores` to sum to one, leaving them if they are all zero.
+fn normalize(scores: &mut [f64]) {
+    let total: f64 = scores.iter().sum();
+
+    if total > 0.0 {
+        scores.iter_mut().for_each(|x| *x /= total);
+    }
+}
+
+fn to_map<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, scores: &[f64]) -> NodeMap<f64> {
+    let mut map = NodeMap::with_capacity(graph.node_bound());
+
+    for node in graph.node_ids() {
+        map.insert(node, scores[node.index()]);
+    }
+
+    map
+}
+
+/// Sets every node's score in `into` to the sum of the scores in `from` of
+/// its neighbors in direction `dir`, parallel edges counting each time.
+fn pull<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, from: &[f64], into: &mut [f64], dir: Direction) {
+    for node in graph.node_ids() {
+        into[node.index()] = graph.neighbors_directed(node, dir).map(|n| from[n.index()]).sum();
+    }
+}
+
+/// Kleinberg's hubs and authorities: a good hub points at good
+/// authorities and a good authority is pointed at by good hubs. Runs power
+/// iteration until the hub scores change by at most `tolerance` in total,
+/// or for `max_iters` rounds.
+pub fn hits<N, E>(graph: &DiGraph<N, E>, tolerance: f64, max_iters: usize) -> Hits {
+    let bound = graph.node_bound();
+    let share = 1.0 / graph.node_count().max(1) as f64;
+    let mut hubs = vec![0.0; bound];
+    let mut authorities = vec![0.0; bound];
+
+    for node in graph.node_ids() {
+        hubs[node.index()] = share;
+    }
+
+    let convergence = iterate(&mut hubs, tolerance, max_iters, |hubs, next| {
+        pull(graph, hubs, &mut authorities, Direction::Incoming);
+        pull(graph, &authorities, next, Direction::Outgoing);
+        normalize(next);
+    });
+
+    pull(graph, &hubs, &mut authorities, Direction::Incoming);
+    normalize(&mut authorities);
+
+    Hits { hubs: to_map(graph, &hubs), authorities: to_map(graph, &authorities), convergence }
+}
+
+/// Power iteration towards the leading eigenvector of the adjacency
+/// matrix: a node is central when the nodes pointing at it are. Undirected
+/// graphs use all neighbors. Each round adds the current scores back in,
+/// which keeps bipartite graphs from oscillating without moving the result.
+pub fn eigenvector_centrality<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    tolerance: f64,
+    max_iters: usize
+) -> Centrality {
+    let mut scores = vec![0.0; graph.node_bound()];
+    let share = 1.0 / graph.node_count().max(1) as f64;
+
+    for node in graph.node_ids() {
+        scores[node.index()] = share;
+    }
+
+    let convergence = iterate(&mut scores, tolerance, max_iters, |scores, next| {
+        pull(graph, scores, next, Direction::Incoming);
+        next.iter_mut().zip(scores).for_each(|(x, own)| *x += own);
+        normalize(next);
+    });
+
+    Centrality { scores: to_map(graph, &scores), convergence }
+}
+
+/// Katz centrality: every node starts with `beta` and receives `alpha`
+/// times the score of each node pointing at it, so walks of length `k`
+/// count `alpha^k`. Converges when `alpha` is below one over the largest
+/// eigenvalue of the adjacency matrix. Scores are scaled to sum to one at
+/// the end.
+pub fn katz_centrality<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    alpha: f64,
+    beta: f64,
+    tolerance: f64,
+    max_iters: usize
+) -> Centrality {
+    let mut scores = vec![0.0; graph.node_bound()];
+    let mut present = vec![false; graph.node_bound()];
+
+    for node in graph.node_ids() {
+        present[node.index()] = true;
+    }
+
+    let convergence = iterate(&mut scores, tolerance, max_iters, |scores, next| {
+        pull(graph, scores, next, Direction::Incoming);
+
+        for (x, &here) in next.iter_mut().zip(&present) {
+            if here {
+                *x = alpha * *x + beta;
+            }
+        }
+    });
+
+    normalize(&mut scores);
+    Centrality { scores: to_map(graph, &scores), convergence }
+}
+
+/// Adds one over the hop distance from `source` to every other node it
+/// reaches into `scores`, with `dist` as scratch space.
+fn add_reciprocals<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    scores: &mut [f64],
+    dist: &mut [usize]
+) {
+    dist.iter_mut().for_each(|d| *d = usize::MAX);
+    dist[source.index()] = 0;
+
+    let mut queue = VecDeque::from([source]);
+
+    while let Some(node) = queue.pop_front() {
+        for next in graph.neighbors(node) {
+            if dist[next.index()] == usize::MAX {
+                dist[next.index()] = dist[node.index()] + 1;
+                scores[next.index()] += 1.0 / dist[next.index()] as f64;
+                queue.push_back(next);
+            }
+        }
+    }
+}
+
+/// Sum over all other nodes of one over their hop distance to each node,
+/// unreachable ones adding nothing, so unlike closeness it stays meaningful
+/// on disconnected graphs. Runs a BFS from every node, in `O(n m)`.
+pub fn harmonic_centrality<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> NodeMap<f64> {
+    let mut scores = vec![0.0; graph.node_bound()];
+    let mut dist = vec![0; graph.node_bound()];
+
+    for source in graph.node_ids() {
+        add_reciprocals(graph, source, &mut scores, &mut dist);
+    }
+
+    to_map(graph, &scores)
+}
+
+/// [`harmonic_centrality`] estimated from BFS runs out of `samples`
+/// sources drawn at random, scaled up to the node count. Every score is
+/// off by at most [`HarmonicEstimate::error_bound`] with the chosen
+/// confidence; with `samples` at least the node count the result is exact.
+pub fn harmonic_centrality_sampled<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    samples: usize,
+    rng: &mut R
+) -> HarmonicEstimate
+where
+    Ty: EdgeType,
+    R: Rng
+{
+    let mut sources: Vec<_> = graph.node_ids().collect();
+    let nodes = sources.len();
+    let samples = samples.min(nodes);
+
+    for i in 0..samples {
+        let j = i + rng.below(nodes - i);
+        sources.swap(i, j);
+    }
+
+    let mut scores = vec![0.0; graph.node_bound()];
+    let mut dist = vec![0; graph.node_bound()];
+
+    for &source in &sources[..samples] {
+        add_reciprocals(graph, source, &mut scores, &mut dist);
+    }
+
+    if samples > 0 {
+        let scale = nodes as f64 / samples as f64;
+        scores.iter_mut().for_each(|x| *x *= scale);
+    }
+
+    HarmonicEstimate { scores: to_map(graph, &scores), samples, nodes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+    use crate::rng::SplitMix64;
+
+    #[test]
+    fn hits_separates_hubs_from_authorities() {
+        // 0 and 1 both point at 2 and 3; 1 also points at 0
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 2), (0, 3), (1, 2), (1, 3), (1, 0)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        let scores = hits(&graph, 1e-12, 100);
+
+        assert!(scores.convergence.converged);
+        assert!(scores.hubs[n[1]] > scores.hubs[n[0]]);
+        assert!(scores.hubs[n[0]] > scores.hubs[n[2]]);
+        assert_eq!(0.0, scores.hubs[n[3]]);
+        assert!((scores.authorities[n[2]] - scores.authorities[n[3]]).abs() < 1e-12);
+        assert!((scores.hubs.iter().map(|(_, x)| x).sum::<f64>() - 1.0).abs() < 1e-12);
+
+        let capped = hits(&graph, 0.0, 3);
+        assert_eq!((3, false), (capped.convergence.iterations, capped.convergence.converged));
+    }
+
+    #[test]
+    fn eigenvector_and_katz_favor_well_linked_nodes() {
+        let mut star: UnGraph<(), ()> = Graph::new();
+        let hub = star.add_node(());
+        let leaves: Vec<_> = (0..3).map(|_| star.add_node(())).collect();
+
+        for &leaf in &leaves {
+            star.add_edge(hub, leaf, ());
+        }
+
+        let eigen = eigenvector_centrality(&star, 1e-12, 1000);
+        assert!(eigen.convergence.converged);
+        assert!(eigen.scores[hub] > eigen.scores[leaves[0]]);
+        assert!((eigen.scores[leaves[0]] - eigen.scores[leaves[2]]).abs() < 1e-12);
+
+        // along 0 -> 1 -> 2, scores settle at 1, 1.5 and 1.75 before scaling
+        let mut path: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| path.add_node(())).collect();
+        path.add_edge(n[0], n[1], ());
+        path.add_edge(n[1], n[2], ());
+
+        let katz = katz_centrality(&path, 0.5, 1.0, 0.0, 100);
+        assert_eq!(4, katz.convergence.iterations);
+        assert_eq!([1.0, 1.5, 1.75].map(|x| x / 4.25), [0, 1, 2].map(|i| katz.scores[n[i]]));
+    }
+
+    #[test]
+    fn harmonic_centrality_exact_and_sampled() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..40).map(|_| graph.add_node(())).collect();
+
+        for pair in n.windows(2) {
+            graph.add_edge(pair[0], pair[1], ());
+        }
+
+        let lonely = graph.add_node(());
+        let exact = harmonic_centrality(&graph);
+
+        // three nodes on one side of n[3] and thirty-six on the other
+        let expected: f64 = (1..=3).chain(1..=36).map(|d| 1.0 / d as f64).sum();
+        assert!((exact[n[3]] - expected).abs() < 1e-12);
+        assert_eq!(0.0, exact[lonely]);
+
+        let mut rng = SplitMix64::new(3);
+        let full = harmonic_centrality_sampled(&graph, 100, &mut rng);
+        assert_eq!((41, 0.0), (full.samples, full.error_bound(0.95)));
+        assert!(graph.node_ids().all(|v| (full.scores[v] - exact[v]).abs() < 1e-9));
+
+        let estimate = harmonic_centrality_sampled(&graph, 20, &mut rng);
+        let bound = estimate.error_bound(0.99);
+        assert!(graph.node_ids().all(|v| (estimate.scores[v] - exact[v]).abs() <= bound));
+    }
+}
@@ -0,0 +1,168 @@
+use crate::algo::undirected_neighbors;
+use crate::bitset::FixedBitSet;
+use crate::graph::{DiGraph, EdgeType, Graph, NodeId};
+
+/// Connected components, each listed in discovery order. Directed graphs
+/// are treated as undirected, giving their weakly connected components.
+pub fn connected_components<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<Vec<NodeId>> {
+    let mut seen = FixedBitSet::with_capacity(graph.node_bound());
+    let mut components = Vec::new();
+
+    for start in graph.node_ids() {
+        if seen.put(start.index()) {
+            continue;
+        }
+
+        let mut component = vec![start];
+        let mut next = 0;
+
+        while let Some(&node) = component.get(next) {
+            next += 1;
+
+            for neighbor in undirected_neighbors(graph, node) {
+                if !seen.put(neighbor.index()) {
+                    component.push(neighbor);
+                }
+            }
+        }
+
+        components.push(component);
+    }
+
+    components
+}
+
+/// Strongly connected components by Tarjan's algorithm, in reverse
+/// topological order of the condensation: no component has an edge into a
+/// component listed after it.
+pub fn strongly_connected_components<N, E>(graph: &DiGraph<N, E>) -> Vec<Vec<NodeId>> {
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let mut members = FixedBitSet::with_capacity(graph.node_bound());
+
+    for node in &nodes {
+        members.insert(node.index());
+    }
+
+    scc_within(graph, &nodes, &members)
+}
+
+/// Tarjan's algorithm on the subgraph induced by `members`, starting from
+/// `nodes` in order.
+pub(crate) fn scc_within<N, E>(
+    graph: &DiGraph<N, E>,
+    nodes: &[NodeId],
+    members: &FixedBitSet
+) -> Vec<Vec<NodeId>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; graph.node_bound()];
+    let mut low = vec![0; graph.node_bound()];
+    let mut on_stack = FixedBitSet::with_capacity(graph.node_bound());
+    let mut stack = Vec::new();
+    let mut frames = Vec::new();
+    let mut components = Vec::new();
+    let mut counter = 0;
+
+    for &root in nodes {
+        if index[root.index()] != UNVISITED {
+            continue;
+        }
+
+        index[root.index()] = counter;
+        low[root.index()] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack.insert(root.index());
+        frames.push((root, graph.neighbors(root)));
+
+        while let Some((node, neighbors)) = frames.last_mut() {
+            let node = *node;
+
+            match neighbors.find(|next| members.contains(next.index())) {
+                Some(next) if index[next.index()] == UNVISITED => {
+                    index[next.index()] = counter;
+                    low[next.index()] = counter;
+                    counter += 1;
+                    stack.push(next);
+                    on_stack.insert(next.index());
+                    frames.push((next, graph.neighbors(next)));
+                },
+                Some(next) => {
+                    if on_stack.contains(next.index()) {
+                        low[node.index()] = low[node.index()].min(index[next.index()]);
+                    }
+                },
+                None => {
+                    frames.pop();
+
+                    if let Some(&(parent, _)) = frames.last() {
+                        low[parent.index()] = low[parent.index()].min(low[node.index()]);
+                    }
+
+                    if low[node.index()] == index[node.index()] {
+                        let mut component = Vec::new();
+
+                        while let Some(member) = stack.pop() {
+                            on_stack.remove(member.index());
+                            component.push(member);
+
+                            if member == node {
+                                break;
+                            }
+                        }
+
+                        components.push(component);
+                    }
+                }
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn directed_edges_join_components_both_ways() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+
+        graph.add_edge(n[1], n[0], ());
+        graph.add_edge(n[1], n[2], ());
+        graph.add_edge(n[4], n[3], ());
+
+        assert_eq!(vec![vec![n[0], n[1], n[2]], vec![n[3], n[4]]], connected_components(&graph));
+    }
+
+    #[test]
+    fn isolated_nodes_are_singletons() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+
+        graph.add_edge(a, a, ());
+
+        assert_eq!(vec![vec![a], vec![b]], connected_components(&graph));
+    }
+
+    #[test]
+    fn strong_components_follow_cycles() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3), (5, 5)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        let components = strongly_connected_components(&graph);
+        let sizes: Vec<_> = components.iter().map(Vec::len).collect();
+
+        assert_eq!(vec![2, 3, 1], sizes);
+        assert!(components[0].contains(&n[3]) && components[0].contains(&n[4]));
+        assert_eq!(vec![n[5]], components[2]);
+    }
+}
@@ -0,0 +1,175 @@
+use crate::algo::{max_flow, neighborhood};
+use crate::graph::{DiGraph, EdgeType, Graph, NodeId, UnGraph};
+
+/// Graph with every node split into an entry and an exit joined by an edge
+/// of capacity one, so that cutting a node means cutting that edge. Edges of
+/// the original graph lead from exits to entries and cannot be cut.
+struct Split {
+    graph: DiGraph<(), i64>,
+    entry: Vec<NodeId>,
+    exit: Vec<NodeId>,
+    /// The node each edge of capacity one stands for, by edge index.
+    inner: Vec<Option<NodeId>>
+}
+
+impl Split {
+    fn new<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Split {
+        let uncuttable = graph.node_count() as i64 + 1;
+        let mut split = Split {
+            graph: Graph::with_capacity(2 * graph.node_bound(), graph.node_count() + 2 * graph.edge_count()),
+            entry: vec![NodeId::new(0); graph.node_bound()],
+            exit: vec![NodeId::new(0); graph.node_bound()],
+            inner: Vec::new()
+        };
+
+        for node in graph.node_ids() {
+            let (entry, exit) = (split.graph.add_node(()), split.graph.add_node(()));
+            let edge = split.graph.add_edge(entry, exit, 1);
+
+            split.entry[node.index()] = entry;
+            split.exit[node.index()] = exit;
+            split.inner.resize(edge.index() + 1, None);
+            split.inner[edge.index()] = Some(node);
+        }
+
+        for edge in graph.edge_ids() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+            split.graph.add_edge(split.exit[a.index()], split.entry[b.index()], uncuttable);
+
+            if !Ty::is_directed() {
+                split.graph.add_edge(split.exit[b.index()], split.entry[a.index()], uncuttable);
+            }
+        }
+
+        split
+    }
+
+    /// Fewest nodes other than `u` and `v` whose removal leaves no path
+    /// from `u` to `v`.
+    fn cut(&self, u: NodeId, v: NodeId) -> Vec<NodeId> {
+        let flow = max_flow(&self.graph, self.exit[u.index()], self.entry[v.index()], |&c| c);
+
+        flow.min_cut(&self.graph).into_iter().filter_map(|edge| self.inner[edge.index()]).collect()
+    }
+}
+
+/// Fewest nodes other than `u` and `v` whose removal leaves no path from
+/// `u` to `v`, found by max flow over a graph with every node split in two.
+/// `None` if there is an edge from `u` to `v` or they are the same node,
+/// as then no set of other nodes separates them.
+pub fn minimum_vertex_cut<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    u: NodeId,
+    v: NodeId
+) -> Option<Vec<NodeId>> {
+    if u == v || graph.has_edge(u, v) {
+        return None;
+    }
+
+    Some(Split::new(graph).cut(u, v))
+}
+
+/// Fewest nodes whose removal disconnects `graph` or leaves one node,
+/// so `n - 1` for a complete graph and zero for a disconnected one.
+///
+/// Runs the algorithm of Esfahanian and Hakimi: some minimum cut either
+/// avoids a node `v` of minimum degree, so separates it from a node not
+/// adjacent to it, or contains it, so separates two of its neighbors. That
+/// takes `O(n + d²)` max flows instead of one per pair of nodes.
+pub fn vertex_connectivity<N, E>(graph: &UnGraph<N, E>) -> usize {
+    let nodes: Vec<_> = graph.node_ids().collect();
+
+    if nodes.len() < 2 {
+        return 0;
+    }
+
+    let around: Vec<_> = nodes.iter().map(|&node| neighborhood(graph, node)).collect();
+    let (v, of_v) = nodes.iter().zip(&around).min_by_key(|(_, next)| next.len()).unwrap();
+    let split = Split::new(graph);
+    let mut best = of_v.len();
+
+    for &w in &nodes {
+        if w != *v && of_v.binary_search(&w).is_err() {
+            best = best.min(split.cut(*v, w).len());
+        }
+    }
+
+    for (i, &x) in of_v.iter().enumerate() {
+        let of_x = &around[nodes.iter().position(|&n| n == x).unwrap()];
+
+        for &y in &of_v[i + 1..] {
+            if of_x.binary_search(&y).is_err() {
+                best = best.min(split.cut(x, y).len());
+            }
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(n: usize) -> (UnGraph<(), ()>, Vec<NodeId>) {
+        let mut graph = Graph::new();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+
+        for i in 0..n {
+            graph.add_edge(nodes[i], nodes[(i + 1) % n], ());
+        }
+
+        (graph, nodes)
+    }
+
+    #[test]
+    fn cuts_between_two_nodes() {
+        let (graph, n) = cycle(6);
+
+        // one node off each way round
+        let cut = minimum_vertex_cut(&graph, n[0], n[3]).unwrap();
+        assert_eq!(2, cut.len());
+        assert!(cut.iter().any(|&c| c == n[1] || c == n[2]));
+        assert!(cut.iter().any(|&c| c == n[4] || c == n[5]));
+
+        assert_eq!(None, minimum_vertex_cut(&graph, n[0], n[1]));
+        assert_eq!(None, minimum_vertex_cut(&graph, n[2], n[2]));
+
+        let mut path: DiGraph<(), ()> = Graph::new();
+        let p: Vec<_> = (0..3).map(|_| path.add_node(())).collect();
+        path.add_edge(p[0], p[1], ());
+        path.add_edge(p[1], p[2], ());
+        assert_eq!(Some(vec![p[1]]), minimum_vertex_cut(&path, p[0], p[2]));
+        assert_eq!(Some(vec![]), minimum_vertex_cut(&path, p[2], p[0]));
+    }
+
+    #[test]
+    fn global_vertex_connectivity() {
+        assert_eq!(2, vertex_connectivity(&cycle(7).0));
+
+        let mut complete: UnGraph<(), ()> = Graph::new();
+        let k: Vec<_> = (0..5).map(|_| complete.add_node(())).collect();
+
+        for a in 0..5 {
+            for b in a + 1..5 {
+                complete.add_edge(k[a], k[b], ());
+            }
+        }
+
+        assert_eq!(4, vertex_connectivity(&complete));
+
+        // two triangles sharing node 0 fall apart without it
+        let (mut bowtie, t) = cycle(3);
+        let (x, y) = (bowtie.add_node(()), bowtie.add_node(()));
+
+        for (a, b) in [(t[0], x), (x, y), (y, t[0])] {
+            bowtie.add_edge(a, b, ());
+        }
+
+        assert_eq!(1, vertex_connectivity(&bowtie));
+
+        bowtie.add_node(());
+        assert_eq!(0, vertex_connectivity(&bowtie));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::algo::Measure;
+use crate::graph::{Direction, EdgeId, EdgeType, Graph, NodeId};
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+
+/// Path found by [`constrained_shortest_path`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstrainedPath<K, R> {
+    pub cost: K,
+    pub resource: R,
+    /// Nodes from the source to the target, both included.
+    pub nodes: Vec<NodeId>
+}
+
+struct Label<K, R> {
+    node: NodeId,
+    cost: K,
+    resource: R,
+    pred: Option<usize>,
+    dominated: bool
+}
+
+/// Cheapest path from `source` to `target` whose total resource stays
+/// within `budget`, for edges costing `edge_cost(weight) = (cost,
+/// resource)`, both non-negative. Extends labels in order of cost, keeps
+/// only the labels at each node that no other label beats on both cost
+/// and resource, and drops those that cannot reach the target within
+/// budget even along the least consuming route. The problem is NP-hard, so
+/// the number of labels can grow exponentially in bad cases.
+///
+/// `None` if no path stays within budget.
+pub fn constrained_shortest_path<N, E, Ty, K, R, F>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    target: NodeId,
+    budget: R,
+    mut edge_cost: F
+) -> Option<ConstrainedPath<K, R>>
+where
+    Ty: EdgeType,
+    K: Measure,
+    R: Measure,
+    F: FnMut(&E) -> (K, R)
+{
+    let mut costs = vec![None; graph.edge_bound()];
+
+    for edge in graph.edge_ids() {
+        costs[edge.index()] = Some(edge_cost(graph.edge_weight(edge).unwrap()));
+    }
+
+    let cost = |edge: EdgeId| costs[edge.index()].unwrap();
+
+    // least resource needed from every node to the target, searched backwards
+    let mut least = vec![None; graph.node_bound()];
+    let mut heap = IndexedBinaryHeap::new();
+
+    least[target.index()] = Some(R::default());
+    heap.push(target, R::default());
+
+    while let Some((node, r)) = heap.pop_min() {
+        for edge in graph.edges_directed(node, Direction::Incoming) {
+            let prev = if Ty::is_directed() { edge.source } else { edge.target };
+            let candidate = r + cost(edge.id).1;
+
+            if least[prev.index()].is_none_or(|curr| candidate < curr) {
+                least[prev.index()] = Some(candidate);
+                heap.push(prev, candidate);
+            }
+        }
+    }
+
+    if least[source.index()].is_none_or(|r| r > budget) {
+        return None;
+    }
+
+    let mut labels = vec![Label {
+        node: source,
+        cost: K::default(),
+        resource: R::default(),
+        pred: None,
+        dominated: false
+    }];
+    let mut front = vec![Vec::new(); graph.node_bound()];
+    let mut heap = IndexedBinaryHeap::new();
+
+    front[source.index()].push(0);
+    heap.push(0, K::default());
+
+    while let Some((at, _)) = heap.pop_min() {
+        if labels[at].dominated {
+            continue;
+        }
+
+        let (node, c, r) = (labels[at].node, labels[at].cost, labels[at].resource);
+
+        if node == target {
+            let mut nodes = Vec::new();
+            let mut curr = Some(at);
+
+            while let Some(label) = curr {
+                nodes.push(labels[label].node);
+                curr = labels[label].pred;
+            }
+
+            nodes.reverse();
+            return Some(ConstrainedPath { cost: c, resource: r, nodes });
+        }
+
+        for edge in graph.edges(node) {
+            let (edge_c, edge_r) = cost(edge.id);
+            let (next, c, r) = (edge.target, c + edge_c, r + edge_r);
+
+            if least[next.index()].is_none_or(|least| r + least > budget) {
+                continue;
+            }
+
+            let here = &mut front[next.index()];
+
+            if here.iter().any(|&l| labels[l].cost <= c && labels[l].resource <= r) {
+                continue;
+            }
+
+            here.retain(|&l| {
+                let beaten = c <= labels[l].cost && r <= labels[l].resource;
+                labels[l].dominated |= beaten;
+                !beaten
+            });
+
+            here.push(labels.len());
+            heap.push(labels.len(), c);
+            labels.push(Label { node: next, cost: c, resource: r, pred: Some(at), dominated: false });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn trades_cost_for_resource() {
+        // fast expensive route a-b-d, slow cheap route a-c-d, mixed a-b-c-d
+        let mut graph: DiGraph<(), (u32, u32)> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[0], n[1], (5, 1));
+        graph.add_edge(n[1], n[3], (5, 1));
+        graph.add_edge(n[0], n[2], (1, 5));
+        graph.add_edge(n[2], n[3], (1, 5));
+        graph.add_edge(n[1], n[2], (1, 1));
+
+        let path = |budget| constrained_shortest_path(&graph, n[0], n[3], budget, |&w| w);
+
+        assert_eq!(vec![n[0], n[2], n[3]], path(10).unwrap().nodes);
+        assert_eq!((7, 7), path(7).map(|p| (p.cost, p.resource)).unwrap());
+        assert_eq!(vec![n[0], n[1], n[2], n[3]], path(7).unwrap().nodes);
+        assert_eq!((10, 2), path(6).map(|p| (p.cost, p.resource)).unwrap());
+        assert_eq!(None, path(1));
+    }
+
+    #[test]
+    fn agrees_with_brute_force_on_a_grid() {
+        let mut graph: UnGraph<(), (u32, u32)> = Graph::new();
+        let n: Vec<_> = (0..9).map(|_| graph.add_node(())).collect();
+        let mut weights = (1..).map(|i: u32| (i * 7 % 5 + 1, i * 3 % 4 + 1));
+
+        for i in 0..9 {
+            if i % 3 < 2 {
+                graph.add_edge(n[i], n[i + 1], weights.next().unwrap());
+            }
+            if i < 6 {
+                graph.add_edge(n[i], n[i + 3], weights.next().unwrap());
+            }
+        }
+
+        // every simple path from corner to corner
+        type Weighted = UnGraph<(), (u32, u32)>;
+
+        type Totals = Vec<(u32, u32)>;
+
+        fn paths(graph: &Weighted, at: NodeId, goal: NodeId, seen: &mut Vec<NodeId>, out: &mut Totals) {
+            if at == goal {
+                let mut total = (0, 0);
+                for pair in seen.windows(2) {
+                    let w = graph[graph.find_edge(pair[0], pair[1]).unwrap()];
+                    total = (total.0 + w.0, total.1 + w.1);
+                }
+                out.push(total);
+                return;
+            }
+
+            for next in graph.neighbors(at) {
+                if !seen.contains(&next) {
+                    seen.push(next);
+                    paths(graph, next, goal, seen, out);
+                    seen.pop();
+                }
+            }
+        }
+
+        let mut all = Vec::new();
+        paths(&graph, n[0], n[8], &mut vec![n[0]], &mut all);
+
+        for budget in 0..30 {
+            let best = all.iter().filter(|p| p.1 <= budget).map(|p| p.0).min();
+            let found = constrained_shortest_path(&graph, n[0], n[8], budget, |&w| w);
+
+            assert_eq!(best, found.as_ref().map(|p| p.cost));
+            assert!(found.is_none_or(|p| p.resource <= budget));
+        }
+    }
+}
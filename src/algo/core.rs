@@ -0,0 +1,144 @@
+use crate::algo::undirected_neighbors;
+use crate::attr::NodeMap;
+use crate::graph::{EdgeType, Graph};
+use crate::sample::{induced_subgraph, Sample};
+
+/// Core number of every node: the largest `k` such that the node belongs
+/// to a subgraph in which every node has degree at least `k`. Runs the
+/// bucket algorithm of Batagelj and Zaversnik in `O(n + m)`.
+///
+/// Edge direction is ignored, parallel edges count with their multiplicity
+/// and self loops are not counted.
+pub fn k_core_decomposition<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> NodeMap<usize> {
+    let bound = graph.node_bound();
+    let neighbors = |node| undirected_neighbors(graph, node).filter(move |&other| other != node);
+
+    let mut degree = vec![0; bound];
+
+    for node in graph.node_ids() {
+        degree[node.index()] = neighbors(node).count();
+    }
+
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+    // nodes sorted by current degree, bin[d] being where degree d starts
+    let mut bin = vec![0; max_degree + 1];
+    let mut order = Vec::with_capacity(graph.node_count());
+    let mut pos = vec![0; bound];
+
+    for node in graph.node_ids() {
+        bin[degree[node.index()]] += 1;
+    }
+
+    let mut start = 0;
+
+    for count in bin.iter_mut() {
+        let c = *count;
+        *count = start;
+        start += c;
+    }
+
+    let mut next = bin.clone();
+    order.resize(graph.node_count(), None);
+
+    for node in graph.node_ids() {
+        let d = degree[node.index()];
+        pos[node.index()] = next[d];
+        order[next[d]] = Some(node);
+        next[d] += 1;
+    }
+
+    let mut order: Vec<_> = order.into_iter().map(Option::unwrap).collect();
+
+    for i in 0..order.len() {
+        let node = order[i];
+
+        for other in neighbors(node) {
+            let (u, v) = (other.index(), node.index());
+
+            if degree[u] > degree[v] {
+                // move `other` to the front of its bin, then shrink the bin
+                let first = bin[degree[u]];
+                let swapped = order[first];
+
+                if swapped != other {
+                    order.swap(pos[u], first);
+                    pos[swapped.index()] = pos[u];
+                    pos[u] = first;
+                }
+
+                bin[degree[u]] += 1;
+                degree[u] -= 1;
+            }
+        }
+    }
+
+    let mut cores = NodeMap::with_capacity(bound);
+
+    for node in graph.node_ids() {
+        cores.insert(node, degree[node.index()]);
+    }
+
+    cores
+}
+
+/// Subgraph induced by the nodes with core number at least `k`, with nodes
+/// in index order.
+pub fn k_core<N, E, Ty>(graph: &Graph<N, E, Ty>, k: usize) -> Sample<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    let cores = k_core_decomposition(graph);
+    let nodes: Vec<_> = graph.node_ids().filter(|&node| cores[node] >= k).collect();
+
+    induced_subgraph(graph, &nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn core_numbers_of_clique_with_tail() {
+        // 4-clique 0..4, triangle 4..7 hanging off node 3, path 7 - 8
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..10).map(|_| graph.add_node(())).collect();
+
+        let clique = [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)];
+        let tail = [(3, 4), (4, 5), (5, 6), (6, 4), (6, 7), (7, 8)];
+
+        for (a, b) in clique.into_iter().chain(tail) {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        graph.add_edge(n[8], n[8], ());
+
+        let cores = k_core_decomposition(&graph);
+        let numbers: Vec<_> = n.iter().map(|&node| cores[node]).collect();
+
+        assert_eq!(vec![3, 3, 3, 3, 2, 2, 2, 1, 1, 0], numbers);
+
+        let core = k_core(&graph, 2);
+
+        assert_eq!(&n[..7], &core.origin[..]);
+        assert_eq!(10, core.graph.edge_count());
+    }
+
+    #[test]
+    fn directed_edges_count_both_ways() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+
+        graph.add_edge(n[0], n[1], ());
+        graph.add_edge(n[1], n[2], ());
+        graph.add_edge(n[2], n[0], ());
+
+        let cores = k_core_decomposition(&graph);
+
+        assert!(n.iter().all(|&node| cores[node] == 2));
+        assert_eq!(0, k_core(&graph, 3).graph.node_count());
+    }
+}
@@ -0,0 +1,276 @@
+use crate::algo::components::scc_within;
+use crate::bitset::FixedBitSet;
+use crate::graph::{DiGraph, NodeId};
+
+struct Frame {
+    neighbors: Vec<NodeId>,
+    next: usize,
+    closed: bool
+}
+
+/// Iterator over the simple cycles of a directed graph, created by
+/// [`simple_cycles`]. Each cycle is listed once, as its nodes in path order
+/// without repeating the first.
+pub struct SimpleCycles<'a, N, E> {
+    graph: &'a DiGraph<N, E>,
+    max_len: Option<usize>,
+    self_loops: Vec<NodeId>,
+    components: Vec<Vec<NodeId>>,
+    component: Vec<NodeId>,
+    members: FixedBitSet,
+    blocked: FixedBitSet,
+    block_map: Vec<Vec<NodeId>>,
+    path: Vec<NodeId>,
+    frames: Vec<Frame>
+}
+
+/// Enumerates all simple cycles with Johnson's algorithm, lazily, taking
+/// `O((n + m) (c + 1))` time for `c` cycles overall. Parallel edges do not
+/// produce duplicate cycles.
+pub fn simple_cycles<N, E>(graph: &DiGraph<N, E>) -> SimpleCycles<'_, N, E> {
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let mut members = FixedBitSet::with_capacity(graph.node_bound());
+
+    for node in &nodes {
+        members.insert(node.index());
+    }
+
+    let mut self_loops: Vec<_> = nodes.iter().copied().filter(|&n| graph.find_edge(n, n).is_some()).collect();
+    self_loops.reverse();
+
+    let mut components = scc_within(graph, &nodes, &members);
+    components.retain(|component| component.len() > 1);
+
+    SimpleCycles {
+        graph,
+        max_len: None,
+        self_loops,
+        components,
+        component: Vec::new(),
+        members,
+        blocked: FixedBitSet::with_capacity(graph.node_bound()),
+        block_map: vec![Vec::new(); graph.node_bound()],
+        path: Vec::new(),
+        frames: Vec::new()
+    }
+}
+
+impl<N, E> SimpleCycles<'_, N, E> {
+    /// Only yields cycles of at most `len` nodes. Johnson's blocking does not
+    /// hold under a length bound, so this falls back to plain backtracking,
+    /// which keeps the search short when `len` is small.
+    pub fn max_len(mut self, len: usize) -> Self {
+        self.max_len = Some(len);
+        self
+    }
+
+    fn frame(&self, node: NodeId) -> Frame {
+        let mut neighbors: Vec<_> = self
+            .graph
+            .neighbors(node)
+            .filter(|&next| next != node && self.members.contains(next.index()))
+            .collect();
+
+        neighbors.sort();
+        neighbors.dedup();
+
+        Frame { neighbors, next: 0, closed: false }
+    }
+
+    fn unblock(&mut self, node: NodeId) {
+        let mut stack = vec![node];
+
+        while let Some(node) = stack.pop() {
+            if self.blocked.contains(node.index()) {
+                self.blocked.remove(node.index());
+                stack.append(&mut self.block_map[node.index()]);
+            }
+        }
+    }
+
+    /// Moves on to the next strongly connected component, returning `false`
+    /// once there are none left.
+    fn start_component(&mut self) -> bool {
+        if let Some(&start) = self.component.first() {
+            // all cycles through the start are done, search the rest of the
+            // component without it
+            let rest = self.component.split_off(1);
+
+            self.members.remove(start.index());
+            self.components
+                .extend(scc_within(self.graph, &rest, &self.members).into_iter().filter(|c| c.len() > 1));
+        }
+
+        let Some(component) = self.components.pop() else {
+            return false;
+        };
+
+        self.members.clear();
+        self.blocked.clear();
+
+        for node in &component {
+            self.members.insert(node.index());
+            self.block_map[node.index()].clear();
+        }
+
+        let start = component[0];
+
+        self.component = component;
+        self.path.push(start);
+        self.blocked.insert(start.index());
+
+        let frame = self.frame(start);
+        self.frames.push(frame);
+
+        true
+    }
+}
+
+impl<N, E> Iterator for SimpleCycles<'_, N, E> {
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.max_len == Some(0) {
+            return None;
+        }
+
+        if let Some(node) = self.self_loops.pop() {
+            return Some(vec![node]);
+        }
+
+        loop {
+            if self.frames.is_empty() && !self.start_component() {
+                return None;
+            }
+
+            let start = self.path[0];
+            let frame = self.frames.last_mut().unwrap();
+
+            if let Some(&next) = frame.neighbors.get(frame.next) {
+                frame.next += 1;
+
+                if next == start {
+                    if self.max_len.is_none_or(|len| self.path.len() <= len) {
+                        frame.closed = true;
+                        return Some(self.path.clone());
+                    }
+                } else if !self.blocked.contains(next.index())
+                    && self.max_len.is_none_or(|len| self.path.len() < len)
+                {
+                    self.path.push(next);
+                    self.blocked.insert(next.index());
+
+                    let frame = self.frame(next);
+                    self.frames.push(frame);
+                }
+
+                continue;
+            }
+
+            let frame = self.frames.pop().unwrap();
+            let node = self.path.pop().unwrap();
+
+            if self.max_len.is_some() {
+                self.blocked.remove(node.index());
+            } else if frame.closed {
+                if let Some(parent) = self.frames.last_mut() {
+                    parent.closed = true;
+                }
+
+                self.unblock(node);
+            } else {
+                for next in frame.neighbors {
+                    if !self.block_map[next.index()].contains(&node) {
+                        self.block_map[next.index()].push(node);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::rng::{Rng, SplitMix64};
+
+    /// Rotates each cycle to start at its smallest node, then sorts.
+    fn canonical(cycles: impl Iterator<Item = Vec<NodeId>>) -> Vec<Vec<NodeId>> {
+        let mut cycles: Vec<_> = cycles
+            .map(|mut cycle| {
+                let min = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap();
+                cycle.rotate_left(min);
+                cycle
+            })
+            .collect();
+
+        cycles.sort();
+        cycles
+    }
+
+    fn brute_force(graph: &DiGraph<(), ()>, path: &mut Vec<NodeId>, out: &mut Vec<Vec<NodeId>>) {
+        let last = *path.last().unwrap();
+        let mut next: Vec<_> = graph.neighbors(last).collect();
+
+        next.sort();
+        next.dedup();
+
+        for node in next {
+            if node == path[0] {
+                out.push(path.clone());
+            } else if node > path[0] && !path.contains(&node) {
+                path.push(node);
+                brute_force(graph, path, out);
+                path.pop();
+            }
+        }
+    }
+
+    #[test]
+    fn matches_brute_force_enumeration() {
+        let mut rng = SplitMix64::new(12);
+
+        for _ in 0..20 {
+            let mut graph: DiGraph<(), ()> = Graph::new();
+            let n: Vec<_> = (0..8).map(|_| graph.add_node(())).collect();
+
+            for _ in 0..18 {
+                graph.add_edge(n[rng.below(8)], n[rng.below(8)], ());
+            }
+
+            let mut expected = Vec::new();
+
+            for &node in &n {
+                brute_force(&graph, &mut vec![node], &mut expected);
+            }
+
+            let expected = canonical(expected.into_iter());
+
+            assert_eq!(expected, canonical(simple_cycles(&graph)));
+
+            let short: Vec<_> = expected.iter().filter(|c| c.len() <= 3).cloned().collect();
+
+            assert_eq!(short, canonical(simple_cycles(&graph).max_len(3)));
+        }
+    }
+
+    #[test]
+    fn is_lazy_and_skips_parallel_edges() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+
+        graph.add_edge(n[0], n[1], ());
+        graph.add_edge(n[0], n[1], ());
+        graph.add_edge(n[1], n[0], ());
+        graph.add_edge(n[1], n[2], ());
+        graph.add_edge(n[2], n[0], ());
+        graph.add_edge(n[2], n[2], ());
+
+        let mut cycles = simple_cycles(&graph);
+
+        assert_eq!(Some(vec![n[2]]), cycles.next());
+        assert_eq!(3, 1 + cycles.count());
+        assert_eq!(0, simple_cycles(&graph).max_len(0).count());
+    }
+}
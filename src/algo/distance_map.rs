@@ -0,0 +1,261 @@
+use crate::algo::Measure;
+use crate::bitset::FixedBitSet;
+use crate::graph::{Direction, EdgeId, EdgeType, Graph, NodeId};
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+
+/// Shortest distances from one source that can be repaired after an edge's
+/// cost changes, in the manner of Ramalingam and Reps, instead of being
+/// computed again. Only the nodes whose distance changes are searched from.
+///
+/// Costs are read through `edge_cost` on every call, so after changing a
+/// weight in the graph, pass the same function to
+/// [`edge_decreased`](DistanceMap::edge_decreased) or
+/// [`edge_increased`](DistanceMap::edge_increased). Costs must not be
+/// negative.
+pub struct DistanceMap<K> {
+    source: NodeId,
+    dist: Vec<Option<K>>,
+    /// Predecessor on a shortest path and the edge taken from it.
+    pred: Vec<Option<(NodeId, EdgeId)>>
+}
+
+impl<K: Measure> DistanceMap<K> {
+    pub fn new<N, E, Ty: EdgeType>(
+        graph: &Graph<N, E, Ty>,
+        source: NodeId,
+        mut edge_cost: impl FnMut(&E) -> K
+    ) -> DistanceMap<K> {
+        let mut map = DistanceMap {
+            source,
+            dist: vec![None; graph.node_bound()],
+            pred: vec![None; graph.node_bound()]
+        };
+
+        map.dist[source.index()] = Some(K::default());
+        map.propagate(graph, vec![source], &mut edge_cost);
+        map
+    }
+
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// `None` if `node` is unreachable from the source.
+    pub fn distance(&self, node: NodeId) -> Option<K> {
+        self.dist.get(node.index()).copied().flatten()
+    }
+
+    /// Nodes from the source to `node`, both included.
+    pub fn path_to(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(node)?;
+
+        let mut path: Vec<_> =
+            std::iter::successors(Some(node), |n| self.pred[n.index()].map(|(prev, _)| prev)).collect();
+        path.reverse();
+        Some(path)
+    }
+
+    /// Repairs the distances after the cost of `edge` went down, or after
+    /// it was added. Panics if `edge` is not in the graph.
+    pub fn edge_decreased<N, E, Ty: EdgeType>(
+        &mut self,
+        graph: &Graph<N, E, Ty>,
+        edge: EdgeId,
+        mut edge_cost: impl FnMut(&E) -> K
+    ) {
+        self.grow(graph.node_bound());
+
+        let (a, b) = graph.edge_endpoints(edge).expect("edge not in graph");
+        let cost = edge_cost(&graph[edge]);
+        let mut seeds = Vec::new();
+
+        for (from, to) in [(a, b), (b, a)].into_iter().take(if Ty::is_directed() { 1 } else { 2 }) {
+            let Some(d) = self.dist[from.index()] else { continue };
+            let candidate = d + cost;
+
+            if self.dist[to.index()].is_none_or(|curr| candidate < curr) {
+                self.dist[to.index()] = Some(candidate);
+                self.pred[to.index()] = Some((from, edge));
+                seeds.push(to);
+            }
+        }
+
+        self.propagate(graph, seeds, &mut edge_cost);
+    }
+
+    /// Repairs the distances after the cost of `edge` went up. Only the
+    /// nodes whose shortest path ran through it are searched again, from
+    /// the best way into them from the rest of the graph. Panics if `edge`
+    /// is not in the graph.
+    pub fn edge_increased<N, E, Ty: EdgeType>(
+        &mut self,
+        graph: &Graph<N, E, Ty>,
+        edge: EdgeId,
+        mut edge_cost: impl FnMut(&E) -> K
+    ) {
+        self.grow(graph.node_bound());
+
+        let (a, b) = graph.edge_endpoints(edge).expect("edge not in graph");
+        let mut stack: Vec<_> = [a, b]
+            .into_iter()
+            .filter(|n| self.pred[n.index()].is_some_and(|(_, e)| e == edge))
+            .take(1)
+            .collect();
+
+        // nodes below `edge` in the shortest path tree
+        let mut affected = FixedBitSet::with_capacity(graph.node_bound());
+        let mut subtree = Vec::new();
+
+        while let Some(node) = stack.pop() {
+            if affected.put(node.index()) {
+                continue;
+            }
+
+            subtree.push(node);
+
+            for out in graph.edges(node) {
+                if self.pred[out.target.index()] == Some((node, out.id)) {
+                    stack.push(out.target);
+                }
+            }
+        }
+
+        for &node in &subtree {
+            self.dist[node.index()] = None;
+            self.pred[node.index()] = None;
+        }
+
+        let mut seeds = Vec::new();
+
+        for &node in &subtree {
+            for into in graph.edges_directed(node, Direction::Incoming) {
+                let from = if into.source == node { into.target } else { into.source };
+                let Some(d) = self.dist[from.index()] else { continue };
+                let candidate = d + edge_cost(into.weight);
+
+                if self.dist[node.index()].is_none_or(|curr| candidate < curr) {
+                    self.dist[node.index()] = Some(candidate);
+                    self.pred[node.index()] = Some((from, into.id));
+                }
+            }
+
+            if self.dist[node.index()].is_some() {
+                seeds.push(node);
+            }
+        }
+
+        self.propagate(graph, seeds, &mut edge_cost);
+    }
+
+    /// Makes room for nodes added since the distances were computed.
+    fn grow(&mut self, bound: usize) {
+        if self.dist.len() < bound {
+            self.dist.resize(bound, None);
+            self.pred.resize(bound, None);
+        }
+    }
+
+    /// Dijkstra from `seeds`, whose distances are set, improving on the
+    /// distances already known.
+    fn propagate<N, E, Ty: EdgeType>(
+        &mut self,
+        graph: &Graph<N, E, Ty>,
+        seeds: Vec<NodeId>,
+        edge_cost: &mut impl FnMut(&E) -> K
+    ) {
+        let mut heap = IndexedBinaryHeap::new();
+
+        for seed in seeds {
+            heap.push(seed, self.dist[seed.index()].unwrap());
+        }
+
+        while let Some((node, d)) = heap.pop_min() {
+            for edge in graph.edges(node) {
+                let next = edge.target;
+                let candidate = d + edge_cost(edge.weight);
+
+                if self.dist[next.index()].is_none_or(|curr| candidate < curr) {
+                    self.dist[next.index()] = Some(candidate);
+                    self.pred[next.index()] = Some((node, edge.id));
+                    heap.push(next, candidate);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::dijkstra;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::{Rng, SplitMix64};
+
+    fn churn<Ty: EdgeType>(mut graph: Graph<(), u32, Ty>, rng: &mut SplitMix64) {
+        let nodes: Vec<_> = graph.node_ids().collect();
+        let mut map = DistanceMap::new(&graph, nodes[0], |&w| w);
+
+        for _ in 0..300 {
+            let edges: Vec<_> = graph.edge_ids().collect();
+            let edge = edges[rng.below(edges.len())];
+            let (old, new) = (graph[edge], 1 + rng.below(20) as u32);
+
+            graph[edge] = new;
+
+            if new < old {
+                map.edge_decreased(&graph, edge, |&w| w);
+            } else {
+                map.edge_increased(&graph, edge, |&w| w);
+            }
+
+            let fresh = dijkstra(&graph, nodes[0], |&w| w);
+            assert!(nodes.iter().all(|&n| map.distance(n) == fresh.distance(n)));
+        }
+
+        let far = *nodes.last().unwrap();
+        let path = map.path_to(far).unwrap();
+        assert_eq!((nodes[0], far), (path[0], *path.last().unwrap()));
+    }
+
+    #[test]
+    fn repairs_after_weight_changes() {
+        let mut rng = SplitMix64::new(5);
+
+        let mut directed: DiGraph<(), u32> = Graph::new();
+        let mut undirected: UnGraph<(), u32> = Graph::new();
+        let d: Vec<_> = (0..20).map(|_| directed.add_node(())).collect();
+        let u: Vec<_> = (0..20).map(|_| undirected.add_node(())).collect();
+
+        for i in 0..20 {
+            directed.add_edge(d[i], d[(i + 1) % 20], 10);
+            undirected.add_edge(u[i], u[(i + 1) % 20], 10);
+
+            for _ in 0..2 {
+                let (a, b, w) = (rng.below(20), rng.below(20), 1 + rng.below(20) as u32);
+                directed.add_edge(d[a], d[b], w);
+                undirected.add_edge(u[a], u[b], w);
+            }
+        }
+
+        churn(directed, &mut rng);
+        churn(undirected, &mut rng);
+    }
+
+    #[test]
+    fn handles_added_edges() {
+        let mut graph: DiGraph<(), u32> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+
+        graph.add_edge(n[0], n[1], 5);
+        let mut map = DistanceMap::new(&graph, n[0], |&w| w);
+        assert_eq!(None, map.distance(n[2]));
+
+        let shortcut = graph.add_edge(n[0], n[2], 1);
+        map.edge_decreased(&graph, shortcut, |&w| w);
+        let bridge = graph.add_edge(n[2], n[1], 1);
+        map.edge_decreased(&graph, bridge, |&w| w);
+
+        assert_eq!(Some(2), map.distance(n[1]));
+        assert_eq!(Some(vec![n[0], n[2], n[1]]), map.path_to(n[1]));
+    }
+}
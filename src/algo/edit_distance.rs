@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+
+use crate::algo::min_cost_assignment;
+use crate::graph::{EdgeId, EdgeType, Graph, NodeId};
+
+/// Costs of inserting or deleting a node or an edge, for
+/// [`approx_graph_edit_distance`]. All default to one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EditCosts {
+    pub node_insertion: f64,
+    pub node_deletion: f64,
+    pub edge_insertion: f64,
+    pub edge_deletion: f64
+}
+
+impl Default for EditCosts {
+    fn default() -> Self {
+        EditCosts { node_insertion: 1.0, node_deletion: 1.0, edge_insertion: 1.0, edge_deletion: 1.0 }
+    }
+}
+
+/// Edit path found by [`approx_graph_edit_distance`].
+pub struct GraphEdit {
+    cost: f64,
+    mapping: Vec<(Option<NodeId>, Option<NodeId>)>
+}
+
+impl GraphEdit {
+    /// Total cost of the edit path, an upper bound on the exact distance.
+    pub fn cost(&self) -> f64 {
+        self.cost
+    }
+
+    /// Node operations of the path: `(Some(a), Some(b))` substitutes `b` for
+    /// `a`, `(Some(a), None)` deletes `a` and `(None, Some(b))` inserts `b`.
+    pub fn node_mapping(&self) -> &[(Option<NodeId>, Option<NodeId>)] {
+        &self.mapping
+    }
+}
+
+/// Edge incident to a node, outgoing unless the graph is directed and the
+/// node is the target.
+struct Incident {
+    edge: EdgeId,
+    outgoing: bool
+}
+
+fn incident<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<Vec<Incident>> {
+    let mut incident: Vec<Vec<Incident>> = (0..graph.node_bound()).map(|_| Vec::new()).collect();
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+        incident[a.index()].push(Incident { edge, outgoing: true });
+
+        if a != b {
+            incident[b.index()].push(Incident { edge, outgoing: !Ty::is_directed() });
+        }
+    }
+
+    incident
+}
+
+/// Square cost matrix for matching `p` items against `q`: substitutions
+/// top left, deletions on the diagonal of the top right block, insertions
+/// on the diagonal of the bottom left one, and free dummy pairs bottom
+/// right. Entries off those diagonals are priced out of any optimum.
+fn padded(substitution: Vec<Vec<f64>>, deletion: Vec<f64>, insertion: Vec<f64>) -> Vec<Vec<f64>> {
+    let (p, q) = (deletion.len(), insertion.len());
+    let finite: f64 = substitution.iter().flatten().chain(&deletion).chain(&insertion).map(|c| c.abs()).sum();
+    let forbidden = 1.0 + finite;
+    let mut matrix = vec![vec![forbidden; p + q]; p + q];
+
+    for (i, row) in substitution.into_iter().enumerate() {
+        matrix[i][..q].copy_from_slice(&row);
+        matrix[i][q + i] = deletion[i];
+    }
+
+    for (j, cost) in insertion.into_iter().enumerate() {
+        matrix[p + j][j] = cost;
+        matrix[p + j][q..].fill(0.0);
+    }
+
+    matrix
+}
+
+/// Graph edit distance approximated by the bipartite method of Riesen and
+/// Bunke. Nodes are matched by solving one assignment problem whose costs
+/// combine `node_cost` for substitutions with the cheapest matching of the
+/// edges around both nodes under `edge_cost`; the edit path that this node
+/// matching induces is then costed exactly. Runs in roughly cubic time, a
+/// far cry from the exponential exact search.
+///
+/// Both graphs must have the same directedness.
+pub fn approx_graph_edit_distance<N1, E1, N2, E2, Ty, FN, FE>(
+    first: &Graph<N1, E1, Ty>,
+    second: &Graph<N2, E2, Ty>,
+    costs: &EditCosts,
+    mut node_cost: FN,
+    mut edge_cost: FE
+) -> GraphEdit
+where
+    Ty: EdgeType,
+    FN: FnMut(&N1, &N2) -> f64,
+    FE: FnMut(&E1, &E2) -> f64
+{
+    let left: Vec<_> = first.node_ids().collect();
+    let right: Vec<_> = second.node_ids().collect();
+    let (n, m) = (left.len(), right.len());
+    let first_incident = incident(first);
+    let second_incident = incident(second);
+
+    let edge_weight = |a: &Incident, b: &Incident, edge_cost: &mut FE| {
+        let cost = edge_cost(first.edge_weight(a.edge).unwrap(), second.edge_weight(b.edge).unwrap());
+
+        if a.outgoing == b.outgoing {
+            cost
+        } else {
+            cost.max(costs.edge_deletion + costs.edge_insertion)
+        }
+    };
+
+    // each edge is seen from both endpoints, hence the halving
+    let mut substitution = vec![vec![0.0; m]; n];
+
+    for (i, &a) in left.iter().enumerate() {
+        let around_a = &first_incident[a.index()];
+
+        for (j, &b) in right.iter().enumerate() {
+            let around_b = &second_incident[b.index()];
+            let local = padded(
+                around_a
+                    .iter()
+                    .map(|ea| around_b.iter().map(|eb| edge_weight(ea, eb, &mut edge_cost)).collect())
+                    .collect(),
+                vec![costs.edge_deletion; around_a.len()],
+                vec![costs.edge_insertion; around_b.len()]
+            );
+
+            let node = node_cost(first.node_weight(a).unwrap(), second.node_weight(b).unwrap());
+
+            substitution[i][j] = node + min_cost_assignment(&local).0 / 2.0;
+        }
+    }
+
+    let deletion = left.iter().map(|a| {
+        costs.node_deletion + first_incident[a.index()].len() as f64 * costs.edge_deletion / 2.0
+    });
+    let insertion = right.iter().map(|b| {
+        costs.node_insertion + second_incident[b.index()].len() as f64 * costs.edge_insertion / 2.0
+    });
+
+    let matrix = padded(substitution, deletion.collect(), insertion.collect());
+    let (_, assigned) = min_cost_assignment(&matrix);
+
+    let mut image = vec![None; first.node_bound()];
+    let mut mapping = Vec::with_capacity(n + m);
+    let mut cost = 0.0;
+
+    for (i, &a) in left.iter().enumerate() {
+        if assigned[i] < m {
+            let b = right[assigned[i]];
+
+            image[a.index()] = Some(b);
+            mapping.push((Some(a), Some(b)));
+            cost += node_cost(first.node_weight(a).unwrap(), second.node_weight(b).unwrap());
+        } else {
+            mapping.push((Some(a), None));
+            cost += costs.node_deletion;
+        }
+    }
+
+    for j in 0..m {
+        if assigned[n + j] < m {
+            mapping.push((None, Some(right[j])));
+            cost += costs.node_insertion;
+        }
+    }
+
+    // unused edges of the second graph by endpoints, parallel edges in order
+    let key = |a: NodeId, b: NodeId| {
+        if Ty::is_directed() || a <= b { (a, b) } else { (b, a) }
+    };
+    let mut unused: HashMap<(NodeId, NodeId), Vec<EdgeId>> = HashMap::new();
+
+    for edge in second.edge_ids().collect::<Vec<_>>().into_iter().rev() {
+        let (a, b) = second.edge_endpoints(edge).unwrap();
+        unused.entry(key(a, b)).or_default().push(edge);
+    }
+
+    for edge in first.edge_ids() {
+        let (a, b) = first.edge_endpoints(edge).unwrap();
+        let target = image[a.index()].zip(image[b.index()]);
+
+        match target.and_then(|(a, b)| unused.get_mut(&key(a, b))?.pop()) {
+            Some(other) => {
+                cost += edge_cost(first.edge_weight(edge).unwrap(), second.edge_weight(other).unwrap());
+            },
+            None => cost += costs.edge_deletion
+        }
+    }
+
+    cost += unused.values().map(Vec::len).sum::<usize>() as f64 * costs.edge_insertion;
+
+    GraphEdit { cost, mapping }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+
+    fn molecule(atoms: &str, bonds: &[(usize, usize, u8)]) -> UnGraph<char, u8> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = atoms.chars().map(|c| graph.add_node(c)).collect();
+
+        for &(a, b, order) in bonds {
+            graph.add_edge(n[a], n[b], order);
+        }
+
+        graph
+    }
+
+    fn distance(a: &UnGraph<char, u8>, b: &UnGraph<char, u8>) -> GraphEdit {
+        let label = |x: &char, y: &char| if x == y { 0.0 } else { 1.0 };
+        let order = |x: &u8, y: &u8| if x == y { 0.0 } else { 0.5 };
+
+        approx_graph_edit_distance(a, b, &EditCosts::default(), label, order)
+    }
+
+    #[test]
+    fn identical_and_relabelled_graphs_are_free() {
+        let ethanol = molecule("CCO", &[(0, 1, 1), (1, 2, 1)]);
+        let shuffled = molecule("OCC", &[(2, 1, 1), (1, 0, 1)]);
+        let edit = distance(&ethanol, &shuffled);
+
+        assert_eq!(0.0, edit.cost());
+        assert_eq!(3, edit.node_mapping().len());
+        assert!(edit.node_mapping().iter().all(|&(a, b)| {
+            ethanol.node_weight(a.unwrap()) == shuffled.node_weight(b.unwrap())
+        }));
+    }
+
+    #[test]
+    fn counts_each_kind_of_edit() {
+        let ethanol = molecule("CCO", &[(0, 1, 1), (1, 2, 1)]);
+
+        // substitute a label and a bond order
+        let edit = distance(&ethanol, &molecule("CCN", &[(0, 1, 1), (1, 2, 2)]));
+        assert_eq!(1.5, edit.cost());
+
+        // one node and its edge more
+        let edit = distance(&ethanol, &molecule("CCOC", &[(0, 1, 1), (1, 2, 1), (2, 3, 1)]));
+        assert_eq!(2.0, edit.cost());
+        assert!(edit.node_mapping().contains(&(None, Some(NodeId::new(3)))));
+
+        let empty = molecule("", &[]);
+
+        assert_eq!(5.0, distance(&ethanol, &empty).cost());
+        assert_eq!(5.0, distance(&empty, &ethanol).cost());
+        assert_eq!(0.0, distance(&empty, &empty).cost());
+    }
+}
@@ -0,0 +1,343 @@
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::algo::strongly_connected_components;
+use crate::bitset::FixedBitSet;
+use crate::graph::{DiGraph, Direction, EdgeId, NodeId};
+
+/// Strongly connected components up to this many nodes are solved exactly by
+/// [`feedback_arc_set`].
+pub const EXACT_FEEDBACK_LIMIT: usize = 10;
+
+/// Edges whose removal makes the graph acyclic. Only edges inside strongly
+/// connected components are candidates. Components of at most
+/// [`EXACT_FEEDBACK_LIMIT`] nodes get a minimum set by branch and bound,
+/// larger ones fall back to [`greedy_feedback_arc_set`]'s heuristic. Self
+/// loops are always included. Edges are sorted by index.
+pub fn feedback_arc_set<N, E>(graph: &DiGraph<N, E>) -> Vec<EdgeId> {
+    solve(graph, EXACT_FEEDBACK_LIMIT)
+}
+
+/// Feedback arc set from the ordering heuristic of Eades, Lin and Smyth:
+/// sinks go to the back, sources to the front, and otherwise the node with
+/// the largest out-degree surplus comes next. Runs in `O(m log m)` and
+/// removes at most `m/2 - n/6` edges on graphs without 2-cycles.
+pub fn greedy_feedback_arc_set<N, E>(graph: &DiGraph<N, E>) -> Vec<EdgeId> {
+    solve(graph, 0)
+}
+
+fn solve<N, E>(graph: &DiGraph<N, E>, exact_limit: usize) -> Vec<EdgeId> {
+    let mut members = FixedBitSet::with_capacity(graph.node_bound());
+    let mut pos = vec![0; graph.node_bound()];
+    let mut ids = vec![0; graph.node_bound()];
+
+    for (id, component) in strongly_connected_components(graph).into_iter().enumerate() {
+        for node in &component {
+            ids[node.index()] = id;
+        }
+
+        if component.len() == 1 {
+            continue;
+        }
+
+        for node in &component {
+            members.insert(node.index());
+        }
+
+        let order = if component.len() <= exact_limit {
+            exact_order(graph, &component, &members)
+        } else {
+            eades_order(graph, &component, &members)
+        };
+
+        for (i, node) in order.into_iter().enumerate() {
+            pos[node.index()] = i;
+        }
+
+        for node in &component {
+            members.remove(node.index());
+        }
+    }
+
+    // positions are only comparable within a component, and edges between
+    // components never close a cycle
+    let mut edges: Vec<_> = graph
+        .edge_ids()
+        .filter(|&edge| {
+            let (s, t) = graph.edge_endpoints(edge).unwrap();
+            s == t || (ids[s.index()] == ids[t.index()] && pos[s.index()] > pos[t.index()])
+        })
+        .collect();
+
+    edges.sort();
+    edges
+}
+
+fn eades_order<N, E>(graph: &DiGraph<N, E>, nodes: &[NodeId], members: &FixedBitSet) -> Vec<NodeId> {
+    let inside = |node: NodeId, other: NodeId| other != node && members.contains(other.index());
+    let degree = |node: NodeId, dir| graph.neighbors_directed(node, dir).filter(|&o| inside(node, o)).count();
+
+    let mut out_degree: HashMap<NodeId, usize> = HashMap::new();
+    let mut in_degree: HashMap<NodeId, usize> = HashMap::new();
+    let mut alive = members.clone();
+    let mut heap = BinaryHeap::new();
+    let mut sinks = Vec::new();
+    let mut sources = Vec::new();
+
+    for &node in nodes {
+        let (out, inc) = (degree(node, Direction::Outgoing), degree(node, Direction::Incoming));
+
+        out_degree.insert(node, out);
+        in_degree.insert(node, inc);
+        heap.push((out as isize - inc as isize, std::cmp::Reverse(node)));
+    }
+
+    let mut front = Vec::with_capacity(nodes.len());
+    let mut back = Vec::new();
+    let mut remaining = nodes.len();
+
+    while remaining > 0 {
+        let is_alive = |alive: &FixedBitSet, node: NodeId| alive.contains(node.index());
+
+        let next = if let Some(node) = sinks.pop().filter(|&n| is_alive(&alive, n)) {
+            back.push(node);
+            node
+        } else if let Some(node) = sources.pop().filter(|&n| is_alive(&alive, n)) {
+            front.push(node);
+            node
+        } else if !sinks.is_empty() || !sources.is_empty() {
+            continue;
+        } else {
+            // stale heap entries are skipped by comparing against the
+            // current degrees
+            let Some((delta, std::cmp::Reverse(node))) = heap.pop() else { break };
+
+            if !is_alive(&alive, node) || delta != out_degree[&node] as isize - in_degree[&node] as isize {
+                continue;
+            }
+
+            front.push(node);
+            node
+        };
+
+        alive.remove(next.index());
+        remaining -= 1;
+
+        for (dir, counts) in [(Direction::Incoming, &mut out_degree), (Direction::Outgoing, &mut in_degree)] {
+            for other in graph.neighbors_directed(next, dir) {
+                if other == next || !alive.contains(other.index()) {
+                    continue;
+                }
+
+                *counts.get_mut(&other).unwrap() -= 1;
+            }
+        }
+
+        for dir in [Direction::Incoming, Direction::Outgoing] {
+            for other in graph.neighbors_directed(next, dir) {
+                if other == next || !alive.contains(other.index()) {
+                    continue;
+                }
+
+                let (out, inc) = (out_degree[&other], in_degree[&other]);
+
+                if out == 0 {
+                    sinks.push(other);
+                } else if inc == 0 {
+                    sources.push(other);
+                }
+
+                heap.push((out as isize - inc as isize, std::cmp::Reverse(other)));
+            }
+        }
+    }
+
+    back.reverse();
+    front.extend(back);
+    front
+}
+
+/// Branch and bound over linear orders of the component, minimizing the
+/// number of edges pointing backwards.
+fn exact_order<N, E>(graph: &DiGraph<N, E>, nodes: &[NodeId], members: &FixedBitSet) -> Vec<NodeId> {
+    let k = nodes.len();
+    let local: HashMap<_, _> = nodes.iter().enumerate().map(|(i, &node)| (node, i)).collect();
+    let mut weight = vec![vec![0; k]; k];
+
+    for (i, &node) in nodes.iter().enumerate() {
+        for next in graph.neighbors(node) {
+            if next != node && members.contains(next.index()) {
+                weight[i][local[&next]] += 1;
+            }
+        }
+    }
+
+    let cost = |order: &[usize]| {
+        let mut cost = 0;
+
+        for (a, &u) in order.iter().enumerate() {
+            for &v in &order[..a] {
+                cost += weight[u][v];
+            }
+        }
+
+        cost
+    };
+
+    let greedy: Vec<_> = eades_order(graph, nodes, members).iter().map(|n| local[n]).collect();
+
+    let mut search = BranchAndBound {
+        weight: &weight,
+        best_cost: cost(&greedy),
+        best: greedy,
+        order: Vec::with_capacity(k)
+    };
+
+    search.run((1u32 << k) - 1, 0);
+    search.best.into_iter().map(|i| nodes[i]).collect()
+}
+
+struct BranchAndBound<'a> {
+    weight: &'a [Vec<usize>],
+    best: Vec<usize>,
+    best_cost: usize,
+    order: Vec<usize>
+}
+
+impl BranchAndBound<'_> {
+    /// Every pair of remaining nodes will cost at least its lighter
+    /// direction, whichever way it ends up ordered.
+    fn lower_bound(&self, remaining: u32) -> usize {
+        let nodes: Vec<_> = (0..self.weight.len()).filter(|&i| remaining & (1 << i) != 0).collect();
+        let mut bound = 0;
+
+        for (a, &u) in nodes.iter().enumerate() {
+            for &v in &nodes[a + 1..] {
+                bound += self.weight[u][v].min(self.weight[v][u]);
+            }
+        }
+
+        bound
+    }
+
+    fn run(&mut self, remaining: u32, cost: usize) {
+        if remaining == 0 {
+            if cost < self.best_cost {
+                self.best_cost = cost;
+                self.best = self.order.clone();
+            }
+
+            return;
+        }
+
+        if cost + self.lower_bound(remaining) >= self.best_cost {
+            return;
+        }
+
+        for v in 0..self.weight.len() {
+            if remaining & (1 << v) == 0 {
+                continue;
+            }
+
+            let rest = remaining & !(1 << v);
+
+            // placing `v` next turns every edge from a later node into it
+            // into a backward edge
+            let added: usize = (0..self.weight.len())
+                .filter(|&u| rest & (1 << u) != 0)
+                .map(|u| self.weight[u][v])
+                .sum();
+
+            self.order.push(v);
+            self.run(rest, cost + added);
+            self.order.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::toposort;
+    use crate::graph::Graph;
+    use crate::rng::{Rng, SplitMix64};
+
+    fn without(graph: &DiGraph<(), ()>, removed: &[EdgeId]) -> DiGraph<(), ()> {
+        let mut rest = Graph::new();
+
+        for _ in graph.node_ids() {
+            rest.add_node(());
+        }
+
+        for edge in graph.edge_ids().filter(|e| !removed.contains(e)) {
+            let (s, t) = graph.edge_endpoints(edge).unwrap();
+            rest.add_edge(s, t, ());
+        }
+
+        rest
+    }
+
+    fn random_graph(rng: &mut SplitMix64, nodes: usize, edges: usize) -> DiGraph<(), ()> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..nodes).map(|_| graph.add_node(())).collect();
+
+        for _ in 0..edges {
+            graph.add_edge(n[rng.below(nodes)], n[rng.below(nodes)], ());
+        }
+
+        graph
+    }
+
+    #[test]
+    fn removing_the_set_leaves_a_dag() {
+        let mut rng = SplitMix64::new(2);
+
+        for _ in 0..10 {
+            let graph = random_graph(&mut rng, 30, 80);
+
+            let exact = feedback_arc_set(&graph);
+            let greedy = greedy_feedback_arc_set(&graph);
+
+            assert!(toposort(&without(&graph, &exact)).is_ok());
+            assert!(toposort(&without(&graph, &greedy)).is_ok());
+        }
+    }
+
+    #[test]
+    fn exact_set_is_minimum() {
+        let mut rng = SplitMix64::new(6);
+
+        for _ in 0..10 {
+            let graph = random_graph(&mut rng, 6, 14);
+            let exact = feedback_arc_set(&graph);
+
+            // try every smaller subset of edges
+            let edges: Vec<_> = graph.edge_ids().collect();
+            let smaller = (0u32..1 << edges.len())
+                .filter(|mask| (mask.count_ones() as usize) < exact.len())
+                .any(|mask| {
+                    let removed: Vec<_> = (0..edges.len())
+                        .filter(|&i| mask & (1 << i) != 0)
+                        .map(|i| edges[i])
+                        .collect();
+
+                    toposort(&without(&graph, &removed)).is_ok()
+                });
+
+            assert!(!smaller);
+            assert!(exact.len() <= greedy_feedback_arc_set(&graph).len());
+        }
+
+        let mut cycle: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| cycle.add_node(())).collect();
+
+        cycle.add_edge(n[0], n[1], ());
+        cycle.add_edge(n[1], n[2], ());
+        cycle.add_edge(n[2], n[0], ());
+        let loop_edge = cycle.add_edge(n[1], n[1], ());
+
+        let set = feedback_arc_set(&cycle);
+
+        assert_eq!(2, set.len());
+        assert!(set.contains(&loop_edge));
+    }
+}
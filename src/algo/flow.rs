@@ -0,0 +1,567 @@
+use std::collections::VecDeque;
+
+use crate::algo::Control;
+use crate::attr::EdgeMap;
+use crate::bitset::FixedBitSet;
+use crate::graph::{DiGraph, EdgeId, EdgeType, Graph, NodeId};
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+
+/// Residual network of a graph. Arc `2i` runs along the `i`th edge and arc
+/// `2i + 1` against it, so `arc ^ 1` is the reverse of `arc`. In undirected
+/// graphs both arcs start out with the edge's capacity.
+struct Residual {
+    ids: Vec<EdgeId>,
+    capacity: Vec<i64>,
+    head: Vec<usize>,
+    residual: Vec<i64>,
+    arcs: Vec<Vec<usize>>
+}
+
+impl Residual {
+    fn new<N, E, Ty, C>(graph: &Graph<N, E, Ty>, mut capacity: C) -> Residual
+    where
+        Ty: EdgeType,
+        C: FnMut(&E) -> i64
+    {
+        let mut net = Residual {
+            ids: Vec::new(),
+            capacity: Vec::new(),
+            head: Vec::new(),
+            residual: Vec::new(),
+            arcs: vec![Vec::new(); graph.node_bound()]
+        };
+
+        for edge in graph.edge_ids() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let cap = capacity(graph.edge_weight(edge).unwrap());
+
+            assert!(cap >= 0, "negative edge capacity");
+
+            net.arcs[a.index()].push(net.head.len());
+            net.arcs[b.index()].push(net.head.len() + 1);
+            net.ids.push(edge);
+            net.capacity.push(cap);
+            net.head.extend([b.index(), a.index()]);
+            net.residual.extend([cap, if Ty::is_directed() { 0 } else { cap }]);
+        }
+
+        net
+    }
+
+    fn tail(&self, arc: usize) -> usize {
+        self.head[arc ^ 1]
+    }
+
+    fn push(&mut self, arc: usize, amount: i64) {
+        self.residual[arc] -= amount;
+        self.residual[arc ^ 1] += amount;
+    }
+
+    /// Net flow along every edge, negative when an undirected edge is used
+    /// from its target to its source.
+    fn flows(&self) -> EdgeMap<i64> {
+        let mut flow = EdgeMap::with_capacity(self.ids.iter().map(|e| e.index() + 1).max().unwrap_or(0));
+
+        for (i, &edge) in self.ids.iter().enumerate() {
+            flow.insert(edge, self.capacity[i] - self.residual[2 * i]);
+        }
+
+        flow
+    }
+
+    /// Nodes reachable from `source` over arcs with residual capacity.
+    fn reachable(&self, source: usize) -> FixedBitSet {
+        let mut seen = FixedBitSet::with_capacity(self.arcs.len());
+        let mut stack = vec![source];
+
+        seen.insert(source);
+
+        while let Some(node) = stack.pop() {
+            for &arc in &self.arcs[node] {
+                if self.residual[arc] > 0 && !seen.contains(self.head[arc]) {
+                    seen.insert(self.head[arc]);
+                    stack.push(self.head[arc]);
+                }
+            }
+        }
+
+        seen
+    }
+}
+
+/// Result of [`max_flow`].
+pub struct MaxFlow {
+    flow: EdgeMap<i64>,
+    value: i64,
+    source_side: FixedBitSet
+}
+
+impl MaxFlow {
+    /// Total flow sent from the source to the sink, which is also the
+    /// capacity of a minimum cut.
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+
+    /// Flow on `edge`, zero for edges not in the graph. For undirected
+    /// graphs the sign tells the direction relative to the edge's endpoints.
+    pub fn flow(&self, edge: EdgeId) -> i64 {
+        self.flow.get(edge).copied().unwrap_or(0)
+    }
+
+    /// Whether `node` lies on the source side of the minimum cut closest to
+    /// the source.
+    pub fn is_source_side(&self, node: NodeId) -> bool {
+        self.source_side.contains(node.index())
+    }
+
+    /// Edges crossing the minimum cut.
+    pub fn min_cut<N, E, Ty: EdgeType>(&self, graph: &Graph<N, E, Ty>) -> Vec<EdgeId> {
+        graph
+            .edge_ids()
+            .filter(|&edge| {
+                let (a, b) = graph.edge_endpoints(edge).unwrap();
+                let (a, b) = (self.is_source_side(a), self.is_source_side(b));
+                (a && !b) || (!Ty::is_directed() && b && !a)
+            })
+            .collect()
+    }
+}
+
+/// Maximum flow from `source` to `sink` by Dinic's algorithm. Undirected
+/// edges carry flow either way up to their capacity.
+///
+/// Panics if an edge has negative capacity.
+pub fn max_flow<N, E, Ty, C>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    sink: NodeId,
+    capacity: C
+) -> MaxFlow
+where
+    Ty: EdgeType,
+    C: FnMut(&E) -> i64
+{
+    max_flow_with_control(graph, source, sink, capacity, |_| Control::Continue).unwrap()
+}
+
+/// Same as [`max_flow`], calling `progress(value)` with the flow found so
+/// far between phases, each of which saturates every shortest augmenting
+/// path. `None` if a call returns [`Control::Break`].
+pub fn max_flow_with_control<N, E, Ty, C, P>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    sink: NodeId,
+    capacity: C,
+    mut progress: P
+) -> Option<MaxFlow>
+where
+    Ty: EdgeType,
+    C: FnMut(&E) -> i64,
+    P: FnMut(i64) -> Control
+{
+    let mut net = Residual::new(graph, capacity);
+    let (s, t) = (source.index(), sink.index());
+    let mut value = 0;
+
+    loop {
+        // level graph by breadth-first search over the residual arcs
+        let mut level = vec![usize::MAX; graph.node_bound()];
+        let mut queue = VecDeque::from([s]);
+
+        level[s] = 0;
+
+        while let Some(node) = queue.pop_front() {
+            for &arc in &net.arcs[node] {
+                if net.residual[arc] > 0 && level[net.head[arc]] == usize::MAX {
+                    level[net.head[arc]] = level[node] + 1;
+                    queue.push_back(net.head[arc]);
+                }
+            }
+        }
+
+        if s == t || level[t] == usize::MAX {
+            break;
+        }
+
+        if let Control::Break(()) = progress(value) {
+            return None;
+        }
+
+        // blocking flow, one path at a time, skipping exhausted arcs for good
+        let mut next = vec![0; graph.node_bound()];
+        let mut path = Vec::new();
+        let mut node = s;
+
+        loop {
+            if node == t {
+                let bottleneck = path.iter().map(|&arc| net.residual[arc]).min().unwrap();
+
+                for &arc in &path {
+                    net.push(arc, bottleneck);
+                }
+
+                value += bottleneck;
+                path.clear();
+                node = s;
+                continue;
+            }
+
+            let arcs = &net.arcs[node];
+
+            while next[node] < arcs.len() {
+                let arc = arcs[next[node]];
+
+                if net.residual[arc] > 0 && level[net.head[arc]] == level[node] + 1 {
+                    break;
+                }
+
+                next[node] += 1;
+            }
+
+            if next[node] < arcs.len() {
+                let arc = arcs[next[node]];
+
+                path.push(arc);
+                node = net.head[arc];
+            } else {
+                level[node] = usize::MAX;
+
+                let Some(arc) = path.pop() else {
+                    break;
+                };
+
+                node = net.tail(arc);
+                next[node] += 1;
+            }
+        }
+    }
+
+    Some(MaxFlow { flow: net.flows(), value, source_side: net.reachable(s) })
+}
+
+/// Result of [`min_cost_flow`].
+pub struct MinCostFlow {
+    flow: EdgeMap<i64>,
+    value: i64,
+    cost: i64
+}
+
+impl MinCostFlow {
+    /// Total flow sent from the source to the sink.
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+
+    /// Sum of flow times cost over all edges.
+    pub fn cost(&self) -> i64 {
+        self.cost
+    }
+
+    /// Flow on `edge`, zero for edges not in the graph.
+    pub fn flow(&self, edge: EdgeId) -> i64 {
+        self.flow.get(edge).copied().unwrap_or(0)
+    }
+
+    /// Edges carrying flow, with the amount.
+    pub fn edge_flows(&self) -> impl Iterator<Item = (EdgeId, i64)> + '_ {
+        self.flow.iter().filter(|(_, f)| **f > 0).map(|(e, &f)| (e, f))
+    }
+}
+
+/// Maximum flow from `source` to `sink` of minimum total cost, by successive
+/// shortest augmenting paths. Dijkstra runs on costs reduced by node
+/// potentials, which Bellman-Ford initializes so that negative costs are
+/// allowed.
+///
+/// Panics if an edge has negative capacity or if edges with positive
+/// capacity form a cycle of negative cost reachable from the source.
+pub fn min_cost_flow<N, E, C, W>(
+    graph: &DiGraph<N, E>,
+    source: NodeId,
+    sink: NodeId,
+    capacity: C,
+    mut cost: W
+) -> MinCostFlow
+where
+    C: FnMut(&E) -> i64,
+    W: FnMut(&E) -> i64
+{
+    let bound = graph.node_bound();
+    let mut net = Residual::new(graph, capacity);
+    let arc_cost: Vec<_> = net
+        .ids
+        .iter()
+        .flat_map(|&edge| {
+            let c = cost(graph.edge_weight(edge).unwrap());
+            [c, -c]
+        })
+        .collect();
+
+    let mut potential = vec![0; bound];
+    let mut value = 0;
+    let mut total = 0;
+
+    if source != sink {
+        // Bellman-Ford over the arcs with capacity, from the source only
+        let mut dist = vec![None; bound];
+        dist[source.index()] = Some(0);
+
+        for round in 0..=bound {
+            let mut changed = false;
+
+            for arc in (0..net.head.len()).filter(|&arc| net.residual[arc] > 0) {
+                if let Some(d) = dist[net.tail(arc)] {
+                    let candidate = d + arc_cost[arc];
+
+                    if dist[net.head[arc]].is_none_or(|curr| candidate < curr) {
+                        dist[net.head[arc]] = Some(candidate);
+                        changed = true;
+                    }
+                }
+            }
+
+            if !changed {
+                break;
+            }
+
+            assert!(round < bound, "negative cost cycle");
+        }
+
+        for (p, d) in potential.iter_mut().zip(dist) {
+            *p = d.unwrap_or(0);
+        }
+
+        let mut heap = IndexedBinaryHeap::new();
+
+        loop {
+            let mut dist = vec![None; bound];
+            let mut pred = vec![usize::MAX; bound];
+
+            dist[source.index()] = Some(0);
+            heap.push(source.index(), 0);
+
+            while let Some((node, d)) = heap.pop_min() {
+                for &arc in &net.arcs[node] {
+                    if net.residual[arc] == 0 {
+                        continue;
+                    }
+
+                    let next = net.head[arc];
+                    let candidate = d + arc_cost[arc] + potential[node] - potential[next];
+
+                    if dist[next].is_none_or(|curr| candidate < curr) {
+                        dist[next] = Some(candidate);
+                        pred[next] = arc;
+                        heap.push(next, candidate);
+                    }
+                }
+            }
+
+            if dist[sink.index()].is_none() {
+                break;
+            }
+
+            for (p, d) in potential.iter_mut().zip(&dist) {
+                if let Some(d) = d {
+                    *p += d;
+                }
+            }
+
+            let mut bottleneck = i64::MAX;
+            let mut node = sink.index();
+
+            while node != source.index() {
+                bottleneck = bottleneck.min(net.residual[pred[node]]);
+                node = net.tail(pred[node]);
+            }
+
+            let mut node = sink.index();
+
+            while node != source.index() {
+                let arc = pred[node];
+
+                net.push(arc, bottleneck);
+                total += bottleneck * arc_cost[arc];
+                node = net.tail(arc);
+            }
+
+            value += bottleneck;
+        }
+    }
+
+    MinCostFlow { flow: net.flows(), value, cost: total }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::rng::{Rng, SplitMix64};
+
+    fn conserves(graph: &DiGraph<(), (i64, i64)>, result: &MinCostFlow, source: NodeId, sink: NodeId) {
+        let mut balance = vec![0; graph.node_bound()];
+        let mut cost = 0;
+
+        for edge in graph.edge_ids() {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let &(cap, c) = graph.edge_weight(edge).unwrap();
+            let f = result.flow(edge);
+
+            assert!((0..=cap).contains(&f));
+            balance[a.index()] -= f;
+            balance[b.index()] += f;
+            cost += f * c;
+        }
+
+        for node in graph.node_ids() {
+            let expected = match node {
+                n if n == source => -result.value(),
+                n if n == sink => result.value(),
+                _ => 0
+            };
+
+            assert_eq!(expected, balance[node.index()]);
+        }
+
+        assert_eq!(cost, result.cost());
+    }
+
+    #[test]
+    fn prefers_cheap_paths_at_maximum_value() {
+        // the maximum of four saturates every edge leaving s, one unit detouring b-a
+        let mut graph: DiGraph<(), (i64, i64)> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+
+        for (a, b, cap, cost) in [(0, 1, 2, 1), (1, 3, 3, 1), (0, 2, 2, 5), (2, 3, 1, 5), (2, 1, 1, -3)] {
+            graph.add_edge(n[a], n[b], (cap, cost));
+        }
+
+        let result = min_cost_flow(&graph, n[0], n[3], |w| w.0, |w| w.1);
+
+        conserves(&graph, &result, n[0], n[3]);
+        assert_eq!(4, result.value());
+        assert_eq!(2 + 2 * 5 + 5 - 3 + 3, result.cost());
+        assert_eq!(3, result.flow(graph.find_edge(n[1], n[3]).unwrap()));
+        assert_eq!(0, min_cost_flow(&graph, n[3], n[0], |w| w.0, |w| w.1).value());
+    }
+
+    #[test]
+    fn max_flow_matches_min_cut() {
+        let mut rng = SplitMix64::new(358);
+
+        for _ in 0..100 {
+            let nodes = 2 + rng.below(6);
+            let mut graph: DiGraph<(), (i64, i64)> = Graph::new();
+            let n: Vec<_> = (0..nodes).map(|_| graph.add_node(())).collect();
+
+            for _ in 0..rng.below(16) {
+                graph.add_edge(n[rng.below(nodes)], n[rng.below(nodes)], (rng.below(9) as i64, 0));
+            }
+
+            let result = max_flow(&graph, n[0], n[1], |w| w.0);
+            let min_cost = min_cost_flow(&graph, n[0], n[1], |w| w.0, |w| w.1);
+
+            // every cut separating the endpoints bounds the flow
+            let best = (0..1u32 << nodes)
+                .filter(|set| set & 1 == 1 && set & 2 == 0)
+                .map(|set| {
+                    let inside = |node: NodeId| set >> node.index() & 1 == 1;
+                    graph
+                        .edge_ids()
+                        .filter(|&e| {
+                            let (a, b) = graph.edge_endpoints(e).unwrap();
+                            inside(a) && !inside(b)
+                        })
+                        .map(|e| graph.edge_weight(e).unwrap().0)
+                        .sum::<i64>()
+                })
+                .min()
+                .unwrap();
+
+            let cut: i64 = result.min_cut(&graph).iter().map(|&e| graph.edge_weight(e).unwrap().0).sum();
+
+            assert_eq!(best, result.value());
+            assert_eq!(best, cut);
+            assert_eq!(best, min_cost.value());
+            assert!(result.is_source_side(n[0]) && !result.is_source_side(n[1]));
+
+            let as_min_cost = MinCostFlow { flow: result.flow, value: result.value, cost: 0 };
+            conserves(&graph, &as_min_cost, n[0], n[1]);
+        }
+    }
+
+    #[test]
+    fn reports_progress_between_phases() {
+        // the direct edge saturates in the first phase, the detour in the next
+        let mut graph: DiGraph<(), i64> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[0], n[2], 1);
+        graph.add_edge(n[0], n[1], 2);
+        graph.add_edge(n[1], n[2], 2);
+
+        let mut seen = Vec::new();
+        let result = max_flow_with_control(&graph, n[0], n[2], |&w| w, |value| {
+            seen.push(value);
+            Control::Continue
+        });
+
+        assert_eq!(Some(3), result.map(|flow| flow.value()));
+        assert_eq!(vec![0, 1], seen);
+
+        let stop = |value| if value > 0 { Control::Break(()) } else { Control::Continue };
+        assert!(max_flow_with_control(&graph, n[0], n[2], |&w| w, stop).is_none());
+    }
+
+    #[test]
+    fn solves_assignment_problems() {
+        let mut rng = SplitMix64::new(357);
+
+        for _ in 0..50 {
+            let size = 1 + rng.below(5);
+            let costs: Vec<Vec<i64>> = (0..size)
+                .map(|_| (0..size).map(|_| rng.below(30) as i64 - 10).collect())
+                .collect();
+
+            let mut graph: DiGraph<(), (i64, i64)> = Graph::new();
+            let source = graph.add_node(());
+            let sink = graph.add_node(());
+            let workers: Vec<_> = (0..size).map(|_| graph.add_node(())).collect();
+            let jobs: Vec<_> = (0..size).map(|_| graph.add_node(())).collect();
+
+            for i in 0..size {
+                graph.add_edge(source, workers[i], (1, 0));
+                graph.add_edge(jobs[i], sink, (1, 0));
+
+                for j in 0..size {
+                    graph.add_edge(workers[i], jobs[j], (1, costs[i][j]));
+                }
+            }
+
+            let result = min_cost_flow(&graph, source, sink, |w| w.0, |w| w.1);
+
+            // cheapest permutation by exhaustive search
+            fn best(costs: &[Vec<i64>], row: usize, used: &mut Vec<bool>) -> i64 {
+                if row == costs.len() {
+                    return 0;
+                }
+
+                let mut min = i64::MAX;
+
+                for j in 0..costs.len() {
+                    if !used[j] {
+                        used[j] = true;
+                        min = min.min(costs[row][j] + best(costs, row + 1, used));
+                        used[j] = false;
+                    }
+                }
+
+                min
+            }
+
+            conserves(&graph, &result, source, sink);
+            assert_eq!(size as i64, result.value());
+            assert_eq!(best(&costs, 0, &mut vec![false; size]), result.cost());
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::algo::max_flow;
+use crate::graph::{Graph, NodeId, Undirected};
+
+/// Tree on the nodes of an undirected graph in which the minimum cut
+/// between any two nodes equals the lightest edge on their tree path.
+pub struct GomoryHuTree {
+    nodes: Vec<NodeId>,
+    dense: Vec<usize>,
+    parent: Vec<usize>,
+    weight: Vec<i64>,
+    depth: Vec<usize>
+}
+
+/// Builds the Gomory–Hu tree with Gusfield's method: `n - 1` maximum flow
+/// computations on the original graph, with no contractions. Every tree
+/// edge also splits the nodes the way a minimum cut of the graph does.
+///
+/// Panics if an edge has negative capacity.
+pub fn gomory_hu_tree<N, E, C>(graph: &Graph<N, E, Undirected>, mut capacity: C) -> GomoryHuTree
+where
+    C: FnMut(&E) -> i64
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let mut dense = vec![usize::MAX; graph.node_bound()];
+
+    for (i, node) in nodes.iter().enumerate() {
+        dense[node.index()] = i;
+    }
+
+    let mut parent = vec![0; nodes.len()];
+    let mut weight = vec![0; nodes.len()];
+
+    for s in 1..nodes.len() {
+        let t = parent[s];
+        let flow = max_flow(graph, nodes[s], nodes[t], &mut capacity);
+
+        weight[s] = flow.value();
+
+        for i in 0..nodes.len() {
+            if i != s && parent[i] == t && flow.is_source_side(nodes[i]) {
+                parent[i] = s;
+            }
+        }
+
+        // the cut also separated t from its parent, so s takes t's place
+        if flow.is_source_side(nodes[parent[t]]) {
+            parent[s] = parent[t];
+            parent[t] = s;
+            weight[s] = weight[t];
+            weight[t] = flow.value();
+        }
+    }
+
+    let mut depth = vec![usize::MAX; nodes.len()];
+    let mut path = Vec::new();
+
+    if !nodes.is_empty() {
+        depth[0] = 0;
+    }
+
+    for i in 0..nodes.len() {
+        let mut j = i;
+
+        while depth[j] == usize::MAX {
+            path.push(j);
+            j = parent[j];
+        }
+
+        while let Some(k) = path.pop() {
+            depth[k] = depth[parent[k]] + 1;
+        }
+    }
+
+    GomoryHuTree { nodes, dense, parent, weight, depth }
+}
+
+impl GomoryHuTree {
+    /// Tree edges as `(child, parent, cut value)`.
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId, i64)> + '_ {
+        (1..self.nodes.len()).map(|i| (self.nodes[i], self.nodes[self.parent[i]], self.weight[i]))
+    }
+
+    /// Value of a minimum cut separating `a` from `b`, `None` if either is
+    /// not a node of the graph or they are the same node.
+    pub fn min_cut_value(&self, a: NodeId, b: NodeId) -> Option<i64> {
+        let mut a = self.position(a)?;
+        let mut b = self.position(b)?;
+
+        if a == b {
+            return None;
+        }
+
+        let mut min = i64::MAX;
+
+        while a != b {
+            if self.depth[a] < self.depth[b] {
+                std::mem::swap(&mut a, &mut b);
+            }
+
+            min = min.min(self.weight[a]);
+            a = self.parent[a];
+        }
+
+        Some(min)
+    }
+
+    /// Nodes on `node`'s side of the minimum cut between `node` and its tree
+    /// parent, which is any tree edge's cut in the graph.
+    pub fn cut_side(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        let root = self.position(node)?;
+
+        let side = (0..self.nodes.len()).filter(|&i| {
+            let mut i = i;
+
+            while self.depth[i] > self.depth[root] {
+                i = self.parent[i];
+            }
+
+            i == root
+        });
+
+        Some(side.map(|i| self.nodes[i]).collect())
+    }
+
+    fn position(&self, node: NodeId) -> Option<usize> {
+        let i = *self.dense.get(node.index())?;
+        (i != usize::MAX && self.nodes[i] == node).then_some(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+    use crate::rng::{Rng, SplitMix64};
+
+    #[test]
+    fn answers_all_pairs_min_cuts() {
+        let mut rng = SplitMix64::new(358);
+
+        for _ in 0..50 {
+            let nodes = 2 + rng.below(7);
+            let mut graph: UnGraph<(), i64> = Graph::new();
+            let n: Vec<_> = (0..nodes).map(|_| graph.add_node(())).collect();
+
+            for _ in 0..rng.below(20) {
+                graph.add_edge(n[rng.below(nodes)], n[rng.below(nodes)], rng.below(10) as i64);
+            }
+
+            let tree = gomory_hu_tree(&graph, |&w| w);
+
+            assert_eq!(nodes - 1, tree.edges().count());
+
+            for a in 0..nodes {
+                for b in a + 1..nodes {
+                    let expected = max_flow(&graph, n[a], n[b], |&w| w).value();
+                    assert_eq!(Some(expected), tree.min_cut_value(n[a], n[b]));
+                }
+            }
+
+            // each tree edge's side is a genuine cut of that value in the graph
+            for (child, _, value) in tree.edges() {
+                let side = tree.cut_side(child).unwrap();
+                let crossing: i64 = graph
+                    .edge_ids()
+                    .filter(|&e| {
+                        let (a, b) = graph.edge_endpoints(e).unwrap();
+                        side.contains(&a) != side.contains(&b)
+                    })
+                    .map(|e| graph.edge_weight(e).unwrap())
+                    .sum();
+
+                assert_eq!(value, crossing);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_nodes() {
+        let mut graph: UnGraph<(), i64> = Graph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+
+        graph.add_edge(a, b, 3);
+        graph.remove_node(c);
+
+        let tree = gomory_hu_tree(&graph, |&w| w);
+
+        assert_eq!(Some(3), tree.min_cut_value(a, b));
+        assert_eq!(None, tree.min_cut_value(a, a));
+        assert_eq!(None, tree.min_cut_value(a, c));
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::hash_map::{Entry, HashMap};
+use std::collections::VecDeque;
+use std::hash::Hash;
+
+use crate::algo::Measure;
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+
+/// States seen by a search, numbered in order of discovery, with the state
+/// the search currently reaches each from.
+struct Discovered<S> {
+    states: Vec<S>,
+    ids: HashMap<S, usize>,
+    pred: Vec<Option<usize>>
+}
+
+impl<S: Clone + Eq + Hash> Discovered<S> {
+    fn new(start: S) -> Discovered<S> {
+        Discovered { states: vec![start.clone()], ids: HashMap::from([(start, 0)]), pred: vec![None] }
+    }
+
+    /// Id of `state` and whether it is new.
+    fn insert(&mut self, state: S, pred: usize) -> (usize, bool) {
+        match self.ids.entry(state) {
+            Entry::Occupied(entry) => (*entry.get(), false),
+            Entry::Vacant(entry) => {
+                self.states.push(entry.key().clone());
+                self.pred.push(Some(pred));
+                (*entry.insert(self.states.len() - 1), true)
+            },
+        }
+    }
+
+    /// States from the start to `id`, both included.
+    fn path(&self, id: usize) -> Vec<S> {
+        let mut path = vec![self.states[id].clone()];
+        let mut curr = id;
+
+        while let Some(prev) = self.pred[curr] {
+            path.push(self.states[prev].clone());
+            curr = prev;
+        }
+
+        path.reverse();
+        path
+    }
+}
+
+/// Fewest-steps path from `start` to a state satisfying `goal`, in a graph
+/// given only by `successors(state)`, such as the moves of a puzzle. States
+/// are created as the search reaches them. `None` if no goal is reachable;
+/// the search never ends if infinitely many states are.
+pub fn implicit_bfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>
+{
+    let mut seen = Discovered::new(start);
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(id) = queue.pop_front() {
+        if goal(&seen.states[id]) {
+            return Some(seen.path(id));
+        }
+
+        for next in successors(&seen.states[id]) {
+            if let (next, true) = seen.insert(next, id) {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Same as [`implicit_bfs`], but going deep first, so the path found is not
+/// the shortest.
+pub fn implicit_dfs<S, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut goal: impl FnMut(&S) -> bool
+) -> Option<Vec<S>>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>
+{
+    let mut seen = Discovered::new(start);
+    let mut finished = Vec::new();
+    let mut stack = vec![0];
+
+    while let Some(id) = stack.pop() {
+        if finished.get(id).copied().unwrap_or(false) {
+            continue;
+        }
+
+        if goal(&seen.states[id]) {
+            return Some(seen.path(id));
+        }
+
+        finished.resize(seen.states.len(), false);
+        finished[id] = true;
+
+        let start = stack.len();
+
+        for next in successors(&seen.states[id]) {
+            let (next, _) = seen.insert(next, id);
+
+            if !finished.get(next).copied().unwrap_or(false) {
+                seen.pred[next] = Some(id);
+                stack.push(next);
+            }
+        }
+
+        // reversed so successors are entered in iteration order
+        stack[start..].reverse();
+    }
+
+    None
+}
+
+/// Cheapest path from `start` to a state satisfying `goal` and its cost,
+/// for `successors(state)` yielding each next state with the non-negative
+/// cost of getting there.
+pub fn implicit_dijkstra<S, K, I>(
+    start: S,
+    successors: impl FnMut(&S) -> I,
+    goal: impl FnMut(&S) -> bool
+) -> Option<(K, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    K: Measure,
+    I: IntoIterator<Item = (S, K)>
+{
+    implicit_astar(start, successors, |_| K::default(), goal)
+}
+
+/// Same as [`implicit_dijkstra`], guided by `estimate(state)`, a consistent
+/// lower bound on the cost left to a goal as for [`astar`](crate::algo::astar).
+pub fn implicit_astar<S, K, I>(
+    start: S,
+    mut successors: impl FnMut(&S) -> I,
+    mut estimate: impl FnMut(&S) -> K,
+    mut goal: impl FnMut(&S) -> bool
+) -> Option<(K, Vec<S>)>
+where
+    S: Clone + Eq + Hash,
+    K: Measure,
+    I: IntoIterator<Item = (S, K)>
+{
+    let mut heap = IndexedBinaryHeap::new();
+
+    heap.push(0, estimate(&start));
+
+    let mut seen = Discovered::new(start);
+    let mut dist = vec![Some(K::default())];
+    let mut settled = vec![false];
+
+    while let Some((id, _)) = heap.pop_min() {
+        let d = dist[id].unwrap();
+
+        if goal(&seen.states[id]) {
+            return Some((d, seen.path(id)));
+        }
+
+        settled[id] = true;
+
+        for (next, cost) in successors(&seen.states[id]) {
+            let (next, new) = seen.insert(next, id);
+            let candidate = d + cost;
+
+            if new {
+                dist.push(None);
+                settled.push(false);
+            }
+
+            if settled[next] || dist[next].is_some_and(|curr| candidate >= curr) {
+                continue;
+            }
+
+            dist[next] = Some(candidate);
+            seen.pred[next] = Some(id);
+            heap.push(next, candidate + estimate(&seen.states[next]));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // water jugs of 3 and 5 litres, the goal being 4 litres in the big one
+    fn pours(&(small, big): &(u32, u32)) -> Vec<(u32, u32)> {
+        let into_big = small.min(5 - big);
+        let into_small = big.min(3 - small);
+
+        vec![
+            (3, big),
+            (small, 5),
+            (0, big),
+            (small, 0),
+            (small - into_big, big + into_big),
+            (small + into_small, big - into_small)
+        ]
+    }
+
+    #[test]
+    fn searches_unmaterialized_states() {
+        let goal = |&(_, big): &(u32, u32)| big == 4;
+
+        let shortest = implicit_bfs((0, 0), pours, goal).unwrap();
+        assert_eq!(7, shortest.len());
+        assert_eq!((0, 0), shortest[0]);
+
+        let deep = implicit_dfs((0, 0), pours, goal).unwrap();
+        assert!(deep.len() >= shortest.len());
+        assert!(deep.windows(2).all(|pair| pours(&pair[0]).contains(&pair[1])));
+
+        assert_eq!(None, implicit_bfs((0, 0), pours, |&(small, _)| small > 3));
+    }
+
+    #[test]
+    fn weighted_searches_agree() {
+        // walking the number line in steps of +1 costing 2 or *2 costing 3
+        let steps = |&n: &u32| [(n + 1, 2), (n * 2, 3)].into_iter().filter(|&(m, _)| m <= 100);
+        let goal = |&n: &u32| n == 37;
+
+        let (cost, path) = implicit_dijkstra(1, steps, goal).unwrap();
+        let guided = implicit_astar(1, steps, |&n: &u32| u32::from(n > 37) * 100, goal);
+
+        // 1 -> 2 -> 4 -> 8 -> 9 -> 18 -> 36 -> 37
+        assert_eq!(18, cost);
+        assert_eq!(vec![1, 2, 4, 8, 9, 18, 36, 37], path);
+        assert_eq!(Some((cost, path)), guided);
+    }
+}
@@ -0,0 +1,270 @@
+use std::cmp::Ordering;
+
+use crate::algo::{undirected_neighbors, Measure};
+use crate::graph::{Direction, EdgeType, Graph, NodeId};
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+
+type Label<K> = Vec<(usize, K)>;
+
+/// Distance oracle built by pruned landmark labeling (Akiba, Iwata and
+/// Yoshida, 2013). Every node stores the distances to and from a small set
+/// of hubs such that each shortest path passes through a hub shared by its
+/// endpoints, so a query is a merge of two short sorted lists.
+///
+/// Hubs are taken in order of decreasing degree, which keeps labels small
+/// on the skewed degree distributions of real networks. The oracle is a
+/// snapshot: modifying the graph afterwards invalidates it.
+pub struct PrunedLandmarkLabeling<K> {
+    // sorted by hub rank; `to_hub` is empty for undirected graphs, whose
+    // labels serve both directions
+    from_hub: Vec<Label<K>>,
+    to_hub: Vec<Label<K>>,
+    exact: bool
+}
+
+fn merge_query<K: Measure>(to: &[(usize, K)], from: &[(usize, K)]) -> Option<K> {
+    let (mut i, mut j) = (0, 0);
+    let mut best: Option<K> = None;
+
+    while i < to.len() && j < from.len() {
+        match to[i].0.cmp(&from[j].0) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                let d = to[i].1 + from[j].1;
+
+                if best.is_none_or(|best| d < best) {
+                    best = Some(d);
+                }
+
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    best
+}
+
+impl<K: Measure> PrunedLandmarkLabeling<K> {
+    /// Indexes the whole graph, giving exact distances. Edge costs must be
+    /// non-negative.
+    pub fn new<N, E, Ty, F>(graph: &Graph<N, E, Ty>, edge_cost: F) -> PrunedLandmarkLabeling<K>
+    where
+        Ty: EdgeType,
+        F: FnMut(&E) -> K
+    {
+        PrunedLandmarkLabeling::with_landmarks(graph, usize::MAX, edge_cost)
+    }
+
+    /// Stops after the `count` highest-degree hubs. Indexing is cheaper and
+    /// queries return upper bounds: the length of the shortest path through
+    /// one of those hubs, or `None` if there is none.
+    pub fn with_landmarks<N, E, Ty, F>(
+        graph: &Graph<N, E, Ty>,
+        count: usize,
+        mut edge_cost: F
+    ) -> PrunedLandmarkLabeling<K>
+    where
+        Ty: EdgeType,
+        F: FnMut(&E) -> K
+    {
+        let bound = graph.node_bound();
+        let mut roots: Vec<_> = graph.node_ids().collect();
+
+        roots.sort_by_key(|&node| std::cmp::Reverse(undirected_neighbors(graph, node).count()));
+
+        let mut oracle = PrunedLandmarkLabeling {
+            from_hub: vec![Vec::new(); bound],
+            to_hub: vec![Vec::new(); if Ty::is_directed() { bound } else { 0 }],
+            exact: count >= roots.len()
+        };
+
+        let mut search = Search { dist: vec![None; bound], heap: IndexedBinaryHeap::new() };
+
+        for (rank, &root) in roots.iter().take(count).enumerate() {
+            let reached = search.run(graph, root, Direction::Outgoing, &mut edge_cost, |node, d| {
+                oracle.query(root.index(), node).is_some_and(|best| best <= d)
+            });
+
+            for (node, d) in reached {
+                oracle.from_hub[node].push((rank, d));
+            }
+
+            if !Ty::is_directed() {
+                continue;
+            }
+
+            let reached = search.run(graph, root, Direction::Incoming, &mut edge_cost, |node, d| {
+                oracle.query(node, root.index()).is_some_and(|best| best <= d)
+            });
+
+            for (node, d) in reached {
+                oracle.to_hub[node].push((rank, d));
+            }
+        }
+
+        oracle
+    }
+
+    fn query(&self, source: usize, target: usize) -> Option<K> {
+        let to = if self.to_hub.is_empty() { &self.from_hub } else { &self.to_hub };
+
+        merge_query(to.get(source)?, self.from_hub.get(target)?)
+    }
+
+    /// Distance from `source` to `target`, `None` if there is no path.
+    /// Only an upper bound unless the oracle [`is_exact`](Self::is_exact).
+    pub fn distance(&self, source: NodeId, target: NodeId) -> Option<K> {
+        if source == target {
+            return Some(K::default());
+        }
+
+        self.query(source.index(), target.index())
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// Total number of label entries, a measure of the index size.
+    pub fn label_count(&self) -> usize {
+        self.from_hub.iter().chain(&self.to_hub).map(Vec::len).sum()
+    }
+}
+
+/// Scratch space for the pruned Dijkstra searches, reused across roots.
+struct Search<K> {
+    dist: Vec<Option<K>>,
+    heap: IndexedBinaryHeap<NodeId, K>
+}
+
+impl<K: Measure> Search<K> {
+    /// Nodes settled from `root` with their distances, not expanding nodes
+    /// for which `prune` says existing labels already cover the distance.
+    fn run<N, E, Ty, F, P>(
+        &mut self,
+        graph: &Graph<N, E, Ty>,
+        root: NodeId,
+        dir: Direction,
+        edge_cost: &mut F,
+        prune: P
+    ) -> Vec<(usize, K)>
+    where
+        Ty: EdgeType,
+        F: FnMut(&E) -> K,
+        P: Fn(usize, K) -> bool
+    {
+        let mut reached = Vec::new();
+        let mut touched = vec![root.index()];
+
+        self.dist[root.index()] = Some(K::default());
+        self.heap.push(root, K::default());
+
+        while let Some((node, d)) = self.heap.pop_min() {
+            if prune(node.index(), d) {
+                continue;
+            }
+
+            reached.push((node.index(), d));
+
+            let mut neighbors = graph.neighbors_directed(node, dir);
+
+            while let Some((edge, next)) = neighbors.next_edge() {
+                let candidate = d + edge_cost(graph.edge_weight(edge).unwrap());
+
+                match self.dist[next.index()] {
+                    Some(curr) if candidate >= curr => continue,
+                    None => touched.push(next.index()),
+                    Some(_) => ()
+                }
+
+                self.dist[next.index()] = Some(candidate);
+
+                if self.heap.contains(&next) {
+                    self.heap.decrease_key(&next, candidate);
+                } else {
+                    self.heap.push(next, candidate);
+                }
+            }
+        }
+
+        for i in touched {
+            self.dist[i] = None;
+        }
+
+        reached
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::dijkstra;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::{Rng, SplitMix64};
+
+    fn random_graph<Ty: EdgeType>(rng: &mut SplitMix64) -> Graph<(), u32, Ty> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..40).map(|_| graph.add_node(())).collect();
+
+        for _ in 0..90 {
+            graph.add_edge(n[rng.below(40)], n[rng.below(40)], 1 + rng.below(9) as u32);
+        }
+
+        graph
+    }
+
+    fn check_exact<Ty: EdgeType>(graph: &Graph<(), u32, Ty>) {
+        let oracle = PrunedLandmarkLabeling::new(graph, |&w| w);
+
+        assert!(oracle.is_exact());
+
+        for source in graph.node_ids() {
+            let paths = dijkstra(graph, source, |&w| w);
+
+            for target in graph.node_ids() {
+                assert_eq!(paths.distance(target), oracle.distance(source, target));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_dijkstra_on_random_graphs() {
+        let mut rng = SplitMix64::new(17);
+
+        for _ in 0..5 {
+            check_exact(&random_graph::<crate::Directed>(&mut rng));
+            check_exact(&random_graph::<crate::Undirected>(&mut rng));
+        }
+    }
+
+    #[test]
+    fn landmark_subset_gives_upper_bounds() {
+        let mut rng = SplitMix64::new(4);
+        let graph: UnGraph<(), u32> = random_graph(&mut rng);
+        let oracle = PrunedLandmarkLabeling::with_landmarks(&graph, 3, |&w| w);
+
+        assert!(!oracle.is_exact());
+        assert!(oracle.label_count() < PrunedLandmarkLabeling::new(&graph, |&w| w).label_count());
+
+        for source in graph.node_ids() {
+            let paths = dijkstra(&graph, source, |&w| w);
+
+            for target in graph.node_ids() {
+                if let Some(bound) = oracle.distance(source, target) {
+                    assert!(paths.distance(target).unwrap() <= bound);
+                }
+            }
+        }
+
+        let mut path: DiGraph<(), u32> = Graph::new();
+        let (a, b) = (path.add_node(()), path.add_node(()));
+        path.add_edge(a, b, 5);
+
+        let oracle = PrunedLandmarkLabeling::new(&path, |&w| w);
+
+        assert_eq!(Some(5), oracle.distance(a, b));
+        assert_eq!(None, oracle.distance(b, a));
+    }
+}
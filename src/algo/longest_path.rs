@@ -0,0 +1,96 @@
+use crate::algo::{toposort, Cycle, Measure};
+use crate::attr::NodeMap;
+use crate::graph::{DiGraph, Direction, EdgeRef, NodeId};
+
+/// Longest distance to every node from any node, visiting them in the
+/// topological `order`, with the predecessor on such a path. A node with no
+/// incoming path longer than zero starts one, but ties at zero extend the
+/// path rather than start a new one.
+pub(crate) fn longest_along<N, E, K: Measure>(
+    graph: &DiGraph<N, E>,
+    order: &[NodeId],
+    mut cost: impl FnMut(EdgeRef<'_, E>) -> K
+) -> (Vec<K>, Vec<Option<NodeId>>) {
+    let mut dist = vec![K::default(); graph.node_bound()];
+    let mut pred = vec![None; graph.node_bound()];
+
+    for &node in order {
+        for edge in graph.edges_directed(node, Direction::Incoming) {
+            let candidate = dist[edge.source.index()] + cost(edge);
+            let best = dist[node.index()];
+
+            if candidate > best || (pred[node.index()].is_none() && candidate >= best) {
+                dist[node.index()] = candidate;
+                pred[node.index()] = Some(edge.source);
+            }
+        }
+    }
+
+    (dist, pred)
+}
+
+/// Length of the longest path ending at every node of a DAG, zero where
+/// none is longer than the empty one, with edges costing `edge_cost`.
+/// Fails with a node on a cycle if `graph` has one.
+pub fn dag_longest_distances<N, E, K: Measure>(
+    graph: &DiGraph<N, E>,
+    mut edge_cost: impl FnMut(&E) -> K
+) -> Result<NodeMap<K>, Cycle> {
+    let order = toposort(graph)?;
+    let (dist, _) = longest_along(graph, &order, |edge| edge_cost(edge.weight));
+    let mut distances = NodeMap::with_capacity(graph.node_bound());
+
+    for node in order {
+        distances.insert(node, dist[node.index()]);
+    }
+
+    Ok(distances)
+}
+
+/// Longest path of a DAG and its length, with edges costing `edge_cost`;
+/// empty for an empty graph. Fails with a node on a cycle if `graph` has
+/// one.
+pub fn dag_longest_path<N, E, K: Measure>(
+    graph: &DiGraph<N, E>,
+    mut edge_cost: impl FnMut(&E) -> K
+) -> Result<(K, Vec<NodeId>), Cycle> {
+    let order = toposort(graph)?;
+    let (dist, pred) = longest_along(graph, &order, |edge| edge_cost(edge.weight));
+    let mut end: Option<NodeId> = None;
+
+    for &node in &order {
+        if end.is_none_or(|best| dist[node.index()] > dist[best.index()]) {
+            end = Some(node);
+        }
+    }
+
+    let mut path: Vec<_> = std::iter::successors(end, |n| pred[n.index()]).collect();
+    path.reverse();
+
+    Ok((end.map_or(K::default(), |n| dist[n.index()]), path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    #[test]
+    fn finds_the_heaviest_chain() {
+        let mut graph: DiGraph<(), i32> = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+
+        for (a, b, w) in [(0, 1, 2), (1, 3, 2), (0, 2, 1), (2, 3, 5), (3, 4, -1)] {
+            graph.add_edge(n[a], n[b], w);
+        }
+
+        assert_eq!(Ok((6, vec![n[0], n[2], n[3]])), dag_longest_path(&graph, |&w| w));
+
+        let distances = dag_longest_distances(&graph, |&w| w).unwrap();
+        assert_eq!([0, 2, 1, 6, 5], [0, 1, 2, 3, 4].map(|i| distances[n[i]]));
+
+        graph.add_edge(n[4], n[0], 0);
+        assert!(dag_longest_path(&graph, |&w| w).is_err());
+        assert_eq!(Ok((0, vec![])), dag_longest_path(&DiGraph::<(), i32>::new(), |&w| w));
+    }
+}
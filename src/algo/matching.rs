@@ -0,0 +1,776 @@
+use crate::graph::{EdgeId, Graph, Undirected};
+
+const NONE: usize = usize::MAX;
+
+/// Maximum weight matching in a general undirected graph, by Edmonds'
+/// blossom algorithm with the `O(n³)` refinements of Galil, following Joris
+/// van Rantwijk's well-tested formulation. Weights are integers so that all
+/// dual updates are exact.
+///
+/// With `max_cardinality` set, the result is the heaviest among the
+/// matchings with as many edges as possible. Otherwise edges of negative
+/// weight are never matched. Self loops are ignored. Returns the matched
+/// edges sorted by index.
+pub fn max_weight_matching<N, E, F>(
+    graph: &Graph<N, E, Undirected>,
+    mut weight: F,
+    max_cardinality: bool
+) -> Vec<EdgeId>
+where
+    F: FnMut(&E) -> i64
+{
+    let mut dense = vec![NONE; graph.node_bound()];
+    let mut nodes = 0;
+
+    for node in graph.node_ids() {
+        dense[node.index()] = nodes;
+        nodes += 1;
+    }
+
+    let mut ids = Vec::new();
+    let mut edges = Vec::new();
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+        if a != b {
+            ids.push(edge);
+            edges.push((dense[a.index()], dense[b.index()], weight(graph.edge_weight(edge).unwrap())));
+        }
+    }
+
+    if edges.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matcher = Matcher::new(nodes, edges);
+    matcher.solve(max_cardinality);
+
+    let mut matched: Vec<_> = (0..nodes)
+        .filter_map(|v| {
+            let p = matcher.mate[v];
+            (p != NONE && v < matcher.endpoint[p]).then(|| ids[p / 2])
+        })
+        .collect();
+
+    matched.sort();
+    matched
+}
+
+/// State of the primal-dual search. Vertices are `0..n`, blossoms
+/// `n..2n`; edge `k` has endpoints `2k` and `2k + 1`, so `p ^ 1` is the
+/// opposite endpoint of `p`.
+struct Matcher {
+    n: usize,
+    edges: Vec<(usize, usize, i64)>,
+    endpoint: Vec<usize>,
+    neighbend: Vec<Vec<usize>>,
+    // remote endpoint of the matched edge of every vertex
+    mate: Vec<usize>,
+    // 0 free, 1 S, 2 T; 5 marks blossoms during `scan_blossom`
+    label: Vec<u8>,
+    labelend: Vec<usize>,
+    inblossom: Vec<usize>,
+    blossomparent: Vec<usize>,
+    blossomchilds: Vec<Vec<usize>>,
+    blossombase: Vec<usize>,
+    blossomendps: Vec<Vec<usize>>,
+    bestedge: Vec<usize>,
+    blossombestedges: Vec<Option<Vec<usize>>>,
+    unusedblossoms: Vec<usize>,
+    // twice the dual variables, keeping everything integral
+    dualvar: Vec<i64>,
+    allowedge: Vec<bool>,
+    queue: Vec<usize>
+}
+
+/// Element `j` of a cyclic list, allowing negative `j` to count from the
+/// end.
+fn at(list: &[usize], j: isize) -> usize {
+    list[j.rem_euclid(list.len() as isize) as usize]
+}
+
+impl Matcher {
+    fn new(n: usize, edges: Vec<(usize, usize, i64)>) -> Matcher {
+        let max_weight = edges.iter().map(|e| e.2).max().unwrap_or(0).max(0);
+        let mut endpoint = Vec::with_capacity(2 * edges.len());
+        let mut neighbend = vec![Vec::new(); n];
+
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            endpoint.push(i);
+            endpoint.push(j);
+            neighbend[i].push(2 * k + 1);
+            neighbend[j].push(2 * k);
+        }
+
+        let mut dualvar = vec![max_weight; n];
+        dualvar.resize(2 * n, 0);
+
+        let mut blossombase: Vec<_> = (0..n).collect();
+        blossombase.resize(2 * n, NONE);
+
+        Matcher {
+            n,
+            allowedge: vec![false; edges.len()],
+            edges,
+            endpoint,
+            neighbend,
+            mate: vec![NONE; n],
+            label: vec![0; 2 * n],
+            labelend: vec![NONE; 2 * n],
+            inblossom: (0..n).collect(),
+            blossomparent: vec![NONE; 2 * n],
+            blossomchilds: vec![Vec::new(); 2 * n],
+            blossombase,
+            blossomendps: vec![Vec::new(); 2 * n],
+            bestedge: vec![NONE; 2 * n],
+            blossombestedges: vec![None; 2 * n],
+            unusedblossoms: (n..2 * n).collect(),
+            dualvar,
+            queue: Vec::new()
+        }
+    }
+
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, w) = self.edges[k];
+        self.dualvar[i] + self.dualvar[j] - 2 * w
+    }
+
+    fn leaves(&self, b: usize) -> Vec<usize> {
+        let mut leaves = Vec::new();
+        let mut stack = vec![b];
+
+        while let Some(b) = stack.pop() {
+            if b < self.n {
+                leaves.push(b);
+            } else {
+                stack.extend(self.blossomchilds[b].iter().rev());
+            }
+        }
+
+        leaves
+    }
+
+    fn assign_label(&mut self, w: usize, t: u8, p: usize) {
+        let b = self.inblossom[w];
+
+        self.label[w] = t;
+        self.label[b] = t;
+        self.labelend[w] = p;
+        self.labelend[b] = p;
+        self.bestedge[w] = NONE;
+        self.bestedge[b] = NONE;
+
+        if t == 1 {
+            let leaves = self.leaves(b);
+            self.queue.extend(leaves);
+        } else {
+            let base = self.blossombase[b];
+            let mate = self.mate[base];
+            self.assign_label(self.endpoint[mate], 1, mate ^ 1);
+        }
+    }
+
+    /// Traces back from `v` and `w` to find either a new blossom, returning
+    /// its base, or an augmenting path, returning `NONE`.
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = Vec::new();
+        let mut base = NONE;
+
+        while v != NONE || w != NONE {
+            let mut b = self.inblossom[v];
+
+            if self.label[b] & 4 != 0 {
+                base = self.blossombase[b];
+                break;
+            }
+
+            path.push(b);
+            self.label[b] = 5;
+
+            if self.labelend[b] == NONE {
+                v = NONE;
+            } else {
+                v = self.endpoint[self.labelend[b]];
+                b = self.inblossom[v];
+                v = self.endpoint[self.labelend[b]];
+            }
+
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+
+        for b in path {
+            self.label[b] = 1;
+        }
+
+        base
+    }
+
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.inblossom[base];
+        let mut bv = self.inblossom[v];
+        let mut bw = self.inblossom[w];
+        let b = self.unusedblossoms.pop().unwrap();
+
+        self.blossombase[b] = base;
+        self.blossomparent[b] = NONE;
+        self.blossomparent[bb] = b;
+
+        let mut path = Vec::new();
+        let mut endps = Vec::new();
+
+        while bv != bb {
+            self.blossomparent[bv] = b;
+            path.push(bv);
+            endps.push(self.labelend[bv]);
+            v = self.endpoint[self.labelend[bv]];
+            bv = self.inblossom[v];
+        }
+
+        path.push(bb);
+        path.reverse();
+        endps.reverse();
+        endps.push(2 * k);
+
+        while bw != bb {
+            self.blossomparent[bw] = b;
+            path.push(bw);
+            endps.push(self.labelend[bw] ^ 1);
+            w = self.endpoint[self.labelend[bw]];
+            bw = self.inblossom[w];
+        }
+
+        self.label[b] = 1;
+        self.labelend[b] = self.labelend[bb];
+        self.dualvar[b] = 0;
+
+        for v in self.leaves_of(&path) {
+            if self.label[self.inblossom[v]] == 2 {
+                self.queue.push(v);
+            }
+
+            self.inblossom[v] = b;
+        }
+
+        let mut bestedgeto = vec![NONE; 2 * self.n];
+
+        for &bv in &path {
+            let lists = match self.blossombestedges[bv].take() {
+                Some(list) => vec![list],
+                None => self
+                    .leaves(bv)
+                    .into_iter()
+                    .map(|v| self.neighbend[v].iter().map(|p| p / 2).collect())
+                    .collect()
+            };
+
+            for list in lists {
+                for k in list {
+                    let (mut i, mut j, _) = self.edges[k];
+
+                    if self.inblossom[j] == b {
+                        std::mem::swap(&mut i, &mut j);
+                    }
+
+                    let bj = self.inblossom[j];
+
+                    if bj != b
+                        && self.label[bj] == 1
+                        && (bestedgeto[bj] == NONE || self.slack(k) < self.slack(bestedgeto[bj]))
+                    {
+                        bestedgeto[bj] = k;
+                    }
+                }
+            }
+
+            self.bestedge[bv] = NONE;
+        }
+
+        let best: Vec<_> = bestedgeto.into_iter().filter(|&k| k != NONE).collect();
+
+        self.bestedge[b] = NONE;
+
+        for &k in &best {
+            if self.bestedge[b] == NONE || self.slack(k) < self.slack(self.bestedge[b]) {
+                self.bestedge[b] = k;
+            }
+        }
+
+        self.blossombestedges[b] = Some(best);
+        self.blossomchilds[b] = path;
+        self.blossomendps[b] = endps;
+    }
+
+    fn leaves_of(&self, blossoms: &[usize]) -> Vec<usize> {
+        blossoms.iter().flat_map(|&b| self.leaves(b)).collect()
+    }
+
+    fn expand_blossom(&mut self, b: usize, endstage: bool) {
+        let childs = std::mem::take(&mut self.blossomchilds[b]);
+
+        for &s in &childs {
+            self.blossomparent[s] = NONE;
+
+            if s < self.n {
+                self.inblossom[s] = s;
+            } else if endstage && self.dualvar[s] == 0 {
+                self.expand_blossom(s, endstage);
+            } else {
+                for v in self.leaves(s) {
+                    self.inblossom[v] = s;
+                }
+            }
+        }
+
+        if !endstage && self.label[b] == 2 {
+            // relabel the children along the even side of the cycle, from
+            // the entry point of the T-label back to the base
+            let endps = std::mem::take(&mut self.blossomendps[b]);
+            let entrychild = self.inblossom[self.endpoint[self.labelend[b] ^ 1]];
+            let mut j = childs.iter().position(|&c| c == entrychild).unwrap() as isize;
+
+            let (jstep, endptrick) = if j & 1 != 0 {
+                j -= childs.len() as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+
+            let mut p = self.labelend[b];
+
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                self.label[self.endpoint[at(&endps, j - endptrick) ^ endptrick as usize ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allowedge[at(&endps, j - endptrick) / 2] = true;
+                j += jstep;
+                p = at(&endps, j - endptrick) ^ endptrick as usize;
+                self.allowedge[p / 2] = true;
+                j += jstep;
+            }
+
+            let bv = at(&childs, j);
+
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.labelend[self.endpoint[p ^ 1]] = p;
+            self.labelend[bv] = p;
+            self.bestedge[bv] = NONE;
+            j += jstep;
+
+            while at(&childs, j) != entrychild {
+                let bv = at(&childs, j);
+
+                if self.label[bv] == 1 {
+                    j += jstep;
+                    continue;
+                }
+
+                if let Some(v) = self.leaves(bv).into_iter().find(|&v| self.label[v] != 0) {
+                    self.label[v] = 0;
+                    self.label[self.endpoint[self.mate[self.blossombase[bv]]]] = 0;
+                    self.assign_label(v, 2, self.labelend[v]);
+                }
+
+                j += jstep;
+            }
+        }
+
+        self.label[b] = 0;
+        self.labelend[b] = NONE;
+        self.blossomendps[b].clear();
+        self.blossombase[b] = NONE;
+        self.blossombestedges[b] = None;
+        self.bestedge[b] = NONE;
+        self.unusedblossoms.push(b);
+    }
+
+    /// Swaps matched and unmatched edges along the even path from vertex `v`
+    /// to the base of blossom `b`, making `v` the new base.
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+
+        while self.blossomparent[t] != b {
+            t = self.blossomparent[t];
+        }
+
+        if t >= self.n {
+            self.augment_blossom(t, v);
+        }
+
+        let i = self.blossomchilds[b].iter().position(|&c| c == t).unwrap();
+        let mut j = i as isize;
+        let len = self.blossomchilds[b].len() as isize;
+
+        let (jstep, endptrick) = if i & 1 != 0 {
+            j -= len;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+
+        while j != 0 {
+            j += jstep;
+
+            let t = at(&self.blossomchilds[b], j);
+            let p = at(&self.blossomendps[b], j - endptrick) ^ endptrick as usize;
+
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+
+            j += jstep;
+
+            let t = at(&self.blossomchilds[b], j);
+
+            if t >= self.n {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+
+        self.blossomchilds[b].rotate_left(i);
+        self.blossomendps[b].rotate_left(i);
+        self.blossombase[b] = self.blossombase[self.blossomchilds[b][0]];
+    }
+
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.inblossom[s];
+
+                if bs >= self.n {
+                    self.augment_blossom(bs, s);
+                }
+
+                self.mate[s] = p;
+
+                if self.labelend[bs] == NONE {
+                    break;
+                }
+
+                let t = self.endpoint[self.labelend[bs]];
+                let bt = self.inblossom[t];
+
+                s = self.endpoint[self.labelend[bt]];
+
+                let j = self.endpoint[self.labelend[bt] ^ 1];
+
+                if bt >= self.n {
+                    self.augment_blossom(bt, j);
+                }
+
+                self.mate[j] = self.labelend[bt];
+                p = self.labelend[bt] ^ 1;
+            }
+        }
+    }
+
+    /// Grows alternating trees from the free vertices until a matching edge
+    /// can be augmented, returning `false` when the duals show optimality.
+    fn stage(&mut self, max_cardinality: bool) -> bool {
+        let n = self.n;
+
+        self.label.fill(0);
+        self.bestedge.fill(NONE);
+        self.blossombestedges[n..].fill(None);
+        self.allowedge.fill(false);
+        self.queue.clear();
+
+        for v in 0..n {
+            if self.mate[v] == NONE && self.label[self.inblossom[v]] == 0 {
+                self.assign_label(v, 1, NONE);
+            }
+        }
+
+        loop {
+            while let Some(v) = self.queue.pop() {
+                for i in 0..self.neighbend[v].len() {
+                    let p = self.neighbend[v][i];
+                    let k = p / 2;
+                    let w = self.endpoint[p];
+
+                    if self.inblossom[v] == self.inblossom[w] {
+                        continue;
+                    }
+
+                    let mut kslack = 0;
+
+                    if !self.allowedge[k] {
+                        kslack = self.slack(k);
+
+                        if kslack <= 0 {
+                            self.allowedge[k] = true;
+                        }
+                    }
+
+                    if self.allowedge[k] {
+                        if self.label[self.inblossom[w]] == 0 {
+                            self.assign_label(w, 2, p ^ 1);
+                        } else if self.label[self.inblossom[w]] == 1 {
+                            let base = self.scan_blossom(v, w);
+
+                            if base != NONE {
+                                self.add_blossom(base, k);
+                            } else {
+                                self.augment_matching(k);
+                                return true;
+                            }
+                        } else if self.label[w] == 0 {
+                            self.label[w] = 2;
+                            self.labelend[w] = p ^ 1;
+                        }
+                    } else if self.label[self.inblossom[w]] == 1 {
+                        let b = self.inblossom[v];
+
+                        if self.bestedge[b] == NONE || kslack < self.slack(self.bestedge[b]) {
+                            self.bestedge[b] = k;
+                        }
+                    } else if self.label[w] == 0
+                        && (self.bestedge[w] == NONE || kslack < self.slack(self.bestedge[w]))
+                    {
+                        self.bestedge[w] = k;
+                    }
+                }
+            }
+
+            // no augmenting path with the current duals; find the largest
+            // dual change keeping them feasible
+            let mut delta_type = 0;
+            let mut delta = 0;
+            let mut delta_edge = NONE;
+            let mut delta_blossom = NONE;
+
+            if !max_cardinality {
+                delta_type = 1;
+                delta = self.dualvar[..n].iter().copied().min().unwrap();
+            }
+
+            for v in 0..n {
+                if self.label[self.inblossom[v]] == 0 && self.bestedge[v] != NONE {
+                    let d = self.slack(self.bestedge[v]);
+
+                    if delta_type == 0 || d < delta {
+                        delta = d;
+                        delta_type = 2;
+                        delta_edge = self.bestedge[v];
+                    }
+                }
+            }
+
+            for b in 0..2 * n {
+                if self.blossomparent[b] == NONE && self.label[b] == 1 && self.bestedge[b] != NONE {
+                    let d = self.slack(self.bestedge[b]) / 2;
+
+                    if delta_type == 0 || d < delta {
+                        delta = d;
+                        delta_type = 3;
+                        delta_edge = self.bestedge[b];
+                    }
+                }
+            }
+
+            for b in n..2 * n {
+                if self.blossombase[b] != NONE
+                    && self.blossomparent[b] == NONE
+                    && self.label[b] == 2
+                    && (delta_type == 0 || self.dualvar[b] < delta)
+                {
+                    delta = self.dualvar[b];
+                    delta_type = 4;
+                    delta_blossom = b;
+                }
+            }
+
+            if delta_type == 0 {
+                // only possible with max_cardinality: no further progress
+                delta_type = 1;
+                delta = self.dualvar[..n].iter().copied().min().unwrap().max(0);
+            }
+
+            for v in 0..n {
+                match self.label[self.inblossom[v]] {
+                    1 => self.dualvar[v] -= delta,
+                    2 => self.dualvar[v] += delta,
+                    _ => ()
+                }
+            }
+
+            for b in n..2 * n {
+                if self.blossombase[b] != NONE && self.blossomparent[b] == NONE {
+                    match self.label[b] {
+                        1 => self.dualvar[b] += delta,
+                        2 => self.dualvar[b] -= delta,
+                        _ => ()
+                    }
+                }
+            }
+
+            match delta_type {
+                1 => return false,
+                2 | 3 => {
+                    self.allowedge[delta_edge] = true;
+
+                    let (mut i, j, _) = self.edges[delta_edge];
+
+                    if self.label[self.inblossom[i]] == 0 {
+                        i = j;
+                    }
+
+                    self.queue.push(i);
+                },
+                _ => self.expand_blossom(delta_blossom, false)
+            }
+        }
+    }
+
+    fn solve(&mut self, max_cardinality: bool) {
+        for _ in 0..self.n {
+            if !self.stage(max_cardinality) {
+                break;
+            }
+
+            // expand S-blossoms whose dual dropped to zero before the next stage
+            for b in self.n..2 * self.n {
+                if self.blossomparent[b] == NONE
+                    && self.blossombase[b] != NONE
+                    && self.label[b] == 1
+                    && self.dualvar[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+    use crate::rng::{Rng, SplitMix64};
+
+    /// Best (cardinality, weight) over all matchings, by exhaustive search.
+    fn brute_force(graph: &UnGraph<(), i64>, max_cardinality: bool) -> (usize, i64) {
+        fn go(
+            graph: &UnGraph<(), i64>,
+            edges: &[EdgeId],
+            used: &mut Vec<bool>,
+            key: &dyn Fn(usize, i64) -> (i64, i64)
+        ) -> (usize, i64) {
+            let Some((&edge, rest)) = edges.split_first() else {
+                return (0, 0);
+            };
+
+            let mut best = go(graph, rest, used, key);
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+            if a != b && !used[a.index()] && !used[b.index()] {
+                used[a.index()] = true;
+                used[b.index()] = true;
+
+                let (count, weight) = go(graph, rest, used, key);
+                let with = (count + 1, weight + graph.edge_weight(edge).unwrap());
+
+                if key(with.0, with.1) > key(best.0, best.1) {
+                    best = with;
+                }
+
+                used[a.index()] = false;
+                used[b.index()] = false;
+            }
+
+            best
+        }
+
+        let edges: Vec<_> = graph.edge_ids().collect();
+        let key = |count: usize, weight: i64| (if max_cardinality { count as i64 } else { 0 }, weight);
+
+        go(graph, &edges, &mut vec![false; graph.node_bound()], &key)
+    }
+
+    fn check(graph: &UnGraph<(), i64>, matching: &[EdgeId]) -> (usize, i64) {
+        let mut used = vec![false; graph.node_bound()];
+
+        for &edge in matching {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+            assert_ne!(a, b);
+            assert!(!used[a.index()] && !used[b.index()], "matching shares a node");
+            used[a.index()] = true;
+            used[b.index()] = true;
+        }
+
+        (matching.len(), matching.iter().map(|&e| graph.edge_weight(e).unwrap()).sum())
+    }
+
+    #[test]
+    fn blossom_is_augmented_through() {
+        // a pentagon with a pendant edge; the optimum needs to cross the odd cycle
+        let mut graph: UnGraph<(), i64> = Graph::new();
+        let n: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+
+        for (a, b, w) in [(0, 1, 8), (1, 2, 9), (2, 3, 8), (3, 4, 9), (4, 0, 8), (0, 5, 7)] {
+            graph.add_edge(n[a], n[b], w);
+        }
+
+        let matching = max_weight_matching(&graph, |&w| w, false);
+
+        assert_eq!((3, 25), check(&graph, &matching));
+        assert!(matching.contains(&graph.find_edge(n[0], n[5]).unwrap()));
+
+        let mut graph: UnGraph<(), i64> = Graph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+
+        graph.add_edge(a, b, 1);
+        graph.add_edge(b, c, 5);
+        graph.add_edge(c, c, 9);
+
+        assert_eq!(1, max_weight_matching(&graph, |&w| w, false).len());
+        assert!(max_weight_matching(&graph, |&w| -w, false).is_empty());
+        let matching = max_weight_matching(&graph, |&w| -w, true);
+
+        assert_eq!(1, matching.len());
+        assert_eq!(Some((a, b)), graph.edge_endpoints(matching[0]));
+    }
+
+    #[test]
+    fn matches_brute_force_on_random_graphs() {
+        let mut rng = SplitMix64::new(356);
+
+        for round in 0..300 {
+            let nodes = 2 + rng.below(7);
+            let mut graph: UnGraph<(), i64> = Graph::new();
+            let n: Vec<_> = (0..nodes).map(|_| graph.add_node(())).collect();
+
+            for _ in 0..rng.below(14) {
+                let a = rng.below(nodes);
+                let b = rng.below(nodes);
+                let w = rng.below(20) as i64 - if round % 3 == 0 { 5 } else { 0 };
+
+                graph.add_edge(n[a], n[b], w);
+            }
+
+            for max_cardinality in [false, true] {
+                let matching = max_weight_matching(&graph, |&w| w, max_cardinality);
+                let (count, weight) = check(&graph, &matching);
+                let (best_count, best_weight) = brute_force(&graph, max_cardinality);
+
+                assert_eq!(best_weight, weight, "round {round}");
+
+                if max_cardinality {
+                    assert_eq!(best_count, count, "round {round}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::ops::Add;
+
+use crate::graph::{Direction, EdgeType, Graph, NodeId};
+
+mod assignment;
+mod canonical;
+mod centrality;
+mod components;
+mod connectivity;
+mod constrained;
+mod core;
+mod cycles;
+mod dag_lca;
+mod distance_map;
+mod edit_distance;
+mod feedback;
+mod flow;
+mod gomory_hu;
+mod implicit;
+mod labeling;
+mod longest_path;
+mod matching;
+pub mod optimization;
+mod reachability;
+mod scheduling;
+mod shortest_path;
+mod similarity;
+mod spanning;
+mod subgraph;
+mod toposort;
+mod walks;
+mod weisfeiler_lehman;
+
+pub use assignment::min_cost_assignment;
+pub use canonical::{canonical_form, CanonicalForm};
+pub use centrality::{
+    eigenvector_centrality, harmonic_centrality, harmonic_centrality_sampled, hits, katz_centrality,
+    Centrality, Convergence, HarmonicEstimate, Hits
+};
+pub use components::{connected_components, strongly_connected_components};
+pub use connectivity::{minimum_vertex_cut, vertex_connectivity};
+pub use constrained::{constrained_shortest_path, ConstrainedPath};
+pub use core::{k_core, k_core_decomposition};
+pub use cycles::{simple_cycles, SimpleCycles};
+pub use dag_lca::{all_pairs_lowest_common_ancestor, DagLca};
+pub use distance_map::DistanceMap;
+pub use edit_distance::{approx_graph_edit_distance, EditCosts, GraphEdit};
+pub use feedback::{feedback_arc_set, greedy_feedback_arc_set, EXACT_FEEDBACK_LIMIT};
+pub use flow::{max_flow, max_flow_with_control, min_cost_flow, MaxFlow, MinCostFlow};
+pub use gomory_hu::{gomory_hu_tree, GomoryHuTree};
+pub use implicit::{implicit_astar, implicit_bfs, implicit_dfs, implicit_dijkstra};
+pub use labeling::PrunedLandmarkLabeling;
+pub use longest_path::{dag_longest_distances, dag_longest_path};
+pub use matching::max_weight_matching;
+pub use optimization::{
+    approx_vertex_cover, greedy_independent_set, maximum_independent_set, minimum_vertex_cover
+};
+pub use reachability::ReachabilityIndex;
+pub use scheduling::{critical_path, Schedule};
+pub use shortest_path::{
+    astar, dijkstra, dijkstra_visit, dijkstra_with_heap, time_dependent_dijkstra, try_astar, try_dijkstra,
+    DijkstraWorkspace, ShortestPaths
+};
+pub use similarity::{most_similar, similarity, simrank, SimRank, Similarity};
+pub use spanning::{count_spanning_trees, spanning_trees, SpanningTrees};
+pub use subgraph::{subgraph_monomorphisms, subgraph_monomorphisms_with_control, SubgraphMatch};
+pub use toposort::{toposort, Cycle};
+pub use walks::{count_walks, count_walks_mod, WalkCounts};
+pub use weisfeiler_lehman::{weisfeiler_lehman, weisfeiler_lehman_hash, WeisfeilerLehman};
+
+/// Path lengths accumulated by the shortest-path algorithms, with
+/// `Default::default()` as the zero distance.
+pub trait Measure: Copy + PartialOrd + Add<Output = Self> + Default {}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Default> Measure for T {}
+
+/// Answer of a callback, telling a long-running algorithm or traversal
+/// whether to keep going. `Break` stops it, handing `B` back to the caller,
+/// e.g. a target found. `Prune` skips what lies beyond the node or edge
+/// just reported, where the algorithm has such a notion, and means
+/// `Continue` elsewhere.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Control<B = ()> {
+    Continue,
+    Prune,
+    Break(B)
+}
+
+impl<B> Control<B> {
+    /// The value the algorithm was stopped with, if any.
+    pub fn break_value(self) -> Option<B> {
+        match self {
+            Control::Break(value) => Some(value),
+            _ => None
+        }
+    }
+}
+
+/// Neighbors ignoring edge direction. Directed self loops show up twice.
+pub(crate) fn undirected_neighbors<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    node: NodeId
+) -> impl Iterator<Item = NodeId> + '_ {
+    let outgoing = graph.neighbors_directed(node, Direction::Outgoing);
+    let incoming = graph.neighbors_directed(node, Direction::Incoming).filter(|_| Ty::is_directed());
+
+    outgoing.chain(incoming)
+}
+
+/// Distinct neighbors of `node` other than itself, ignoring direction.
+pub(crate) fn neighborhood<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, node: NodeId) -> Vec<NodeId> {
+    let mut around: Vec<_> = undirected_neighbors(graph, node).filter(|&n| n != node).collect();
+
+    around.sort_unstable();
+    around.dedup();
+    around
+}
@@ -0,0 +1,235 @@
+use crate::algo::undirected_neighbors;
+use crate::bitset::FixedBitSet;
+use crate::graph::{EdgeType, Graph, NodeId};
+
+/// Vertex cover at most twice the minimum size, from the endpoints of a
+/// maximal matching built greedily in edge order. Edge direction is ignored
+/// and a node with a self loop is always in the cover.
+pub fn approx_vertex_cover<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<NodeId> {
+    let mut cover = FixedBitSet::with_capacity(graph.node_bound());
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+        if !cover.contains(a.index()) && !cover.contains(b.index()) {
+            cover.insert(a.index());
+            cover.insert(b.index());
+        }
+    }
+
+    cover.ones().map(NodeId::new).collect()
+}
+
+/// Maximal independent set picking a node of minimum remaining degree
+/// each time, the classic greedy heuristic that is optimal on trees. Nodes
+/// with self loops are never picked.
+pub fn greedy_independent_set<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<NodeId> {
+    let bound = graph.node_bound();
+    let mut alive = FixedBitSet::with_capacity(bound);
+    let mut degree = vec![0; bound];
+
+    for node in graph.node_ids() {
+        if graph.find_edge(node, node).is_none() {
+            alive.insert(node.index());
+        }
+    }
+
+    for node in alive.ones() {
+        degree[node] = undirected_neighbors(graph, NodeId::new(node))
+            .filter(|n| alive.contains(n.index()))
+            .count();
+    }
+
+    let mut set = Vec::new();
+
+    while let Some(pick) = alive.ones().min_by_key(|&n| degree[n]) {
+        set.push(NodeId::new(pick));
+        alive.remove(pick);
+
+        for removed in undirected_neighbors(graph, NodeId::new(pick)) {
+            if !alive.contains(removed.index()) {
+                continue;
+            }
+
+            alive.remove(removed.index());
+
+            for other in undirected_neighbors(graph, removed) {
+                if alive.contains(other.index()) {
+                    degree[other.index()] -= 1;
+                }
+            }
+        }
+    }
+
+    set.sort();
+    set
+}
+
+/// Maximum independent set by branch and bound. Takes exponential time in
+/// the worst case, so it is meant for graphs of up to about a hundred
+/// nodes, or larger sparse ones. Nodes of degree at most one are taken
+/// without branching; otherwise the search branches on a node of maximum
+/// degree and prunes when the remaining candidates cannot beat the best set
+/// found so far.
+pub fn maximum_independent_set<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<NodeId> {
+    let bound = graph.node_bound();
+    let mut adjacency = vec![FixedBitSet::with_capacity(bound); bound];
+    let mut candidates = FixedBitSet::with_capacity(bound);
+
+    for node in graph.node_ids() {
+        for other in undirected_neighbors(graph, node) {
+            adjacency[node.index()].insert(other.index());
+        }
+
+        if !adjacency[node.index()].contains(node.index()) {
+            candidates.insert(node.index());
+        }
+    }
+
+    let mut search = Search { adjacency, current: Vec::new(), best: Vec::new() };
+
+    search.run(candidates);
+
+    let mut set: Vec<_> = search.best.into_iter().map(NodeId::new).collect();
+
+    set.sort();
+    set
+}
+
+/// Minimum vertex cover, the complement of [`maximum_independent_set`]
+/// with the same running time.
+pub fn minimum_vertex_cover<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Vec<NodeId> {
+    let independent = maximum_independent_set(graph);
+
+    graph.node_ids().filter(|node| independent.binary_search(node).is_err()).collect()
+}
+
+struct Search {
+    adjacency: Vec<FixedBitSet>,
+    current: Vec<usize>,
+    best: Vec<usize>
+}
+
+impl Search {
+    fn run(&mut self, mut candidates: FixedBitSet) {
+        let depth = self.current.len();
+
+        loop {
+            let remaining = candidates.count_ones();
+
+            if self.current.len() + remaining <= self.best.len() {
+                break;
+            }
+
+            if remaining == 0 {
+                self.best = self.current.clone();
+                break;
+            }
+
+            let (node, degree) = candidates
+                .ones()
+                .map(|n| (n, self.adjacency[n].intersection_count(&candidates)))
+                .min_by_key(|&(_, d)| d)
+                .unwrap();
+
+            if degree <= 1 {
+                // some maximum set contains a leaf or an isolated node
+                self.take(node, &mut candidates);
+                continue;
+            }
+
+            let (node, _) = candidates
+                .ones()
+                .map(|n| (n, self.adjacency[n].intersection_count(&candidates)))
+                .max_by_key(|&(_, d)| d)
+                .unwrap();
+
+            let mut with = candidates.clone();
+
+            self.take(node, &mut with);
+            self.run(with);
+            self.current.pop();
+            candidates.remove(node);
+        }
+
+        self.current.truncate(depth);
+    }
+
+    fn take(&mut self, node: usize, candidates: &mut FixedBitSet) {
+        self.current.push(node);
+        candidates.remove(node);
+        candidates.difference_with(&self.adjacency[node]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+    use crate::rng::{Rng, SplitMix64};
+
+    fn is_independent(graph: &UnGraph<(), ()>, set: &[NodeId]) -> bool {
+        graph.edge_ids().all(|e| {
+            let (a, b) = graph.edge_endpoints(e).unwrap();
+            !(set.contains(&a) && set.contains(&b))
+        })
+    }
+
+    fn is_cover(graph: &UnGraph<(), ()>, cover: &[NodeId]) -> bool {
+        graph.edge_ids().all(|e| {
+            let (a, b) = graph.edge_endpoints(e).unwrap();
+            cover.contains(&a) || cover.contains(&b)
+        })
+    }
+
+    #[test]
+    fn exact_search_matches_brute_force() {
+        let mut rng = SplitMix64::new(360);
+
+        for _ in 0..100 {
+            let nodes = 1 + rng.below(12);
+            let mut graph: UnGraph<(), ()> = Graph::new();
+            let n: Vec<_> = (0..nodes).map(|_| graph.add_node(())).collect();
+
+            for _ in 0..rng.below(3 * nodes) {
+                graph.add_edge(n[rng.below(nodes)], n[rng.below(nodes)], ());
+            }
+
+            let best = (0..1u32 << nodes)
+                .map(|mask| (0..nodes).filter(|i| mask >> i & 1 == 1).map(|i| n[i]).collect::<Vec<_>>())
+                .filter(|set| is_independent(&graph, set))
+                .map(|set| set.len())
+                .max()
+                .unwrap();
+
+            let exact = maximum_independent_set(&graph);
+            let greedy = greedy_independent_set(&graph);
+            let cover = minimum_vertex_cover(&graph);
+            let approx = approx_vertex_cover(&graph);
+
+            assert!(is_independent(&graph, &exact) && is_independent(&graph, &greedy));
+            assert!(is_cover(&graph, &cover) && is_cover(&graph, &approx));
+            assert_eq!(best, exact.len());
+            assert_eq!(nodes - best, cover.len());
+            assert!(greedy.len() <= best && approx.len() <= 2 * cover.len());
+        }
+    }
+
+    #[test]
+    fn greedy_is_exact_on_paths() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..7).map(|_| graph.add_node(())).collect();
+
+        for pair in n.windows(2) {
+            graph.add_edge(pair[0], pair[1], ());
+        }
+
+        graph.add_edge(n[3], n[3], ());
+
+        let set = greedy_independent_set(&graph);
+
+        assert_eq!(vec![n[0], n[2], n[4], n[6]], set);
+        assert_eq!(set, maximum_independent_set(&graph));
+        assert!(approx_vertex_cover(&graph).contains(&n[3]));
+    }
+}
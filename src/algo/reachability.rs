@@ -0,0 +1,233 @@
+use crate::algo::toposort::{toposort, Cycle};
+use crate::graph::{DiGraph, Direction, NodeId};
+
+const UNREACHABLE: usize = usize::MAX;
+
+/// Reachability index for directed acyclic graphs, based on a greedy
+/// decomposition of the nodes into chains (paths of the DAG). Every node
+/// records, per chain, the earliest position on it that it can reach, so
+/// `can_reach` is a single lookup.
+///
+/// The index takes `O(n * c)` space for `c` chains, which is small for the
+/// narrow, deep DAGs of build and task dependencies. Edges and nodes added
+/// to the graph afterwards can be mirrored with [`insert_edge`] and
+/// [`insert_node`].
+///
+/// [`insert_edge`]: ReachabilityIndex::insert_edge
+/// [`insert_node`]: ReachabilityIndex::insert_node
+pub struct ReachabilityIndex {
+    chain: Vec<usize>,
+    pos: Vec<usize>,
+    chain_lens: Vec<usize>,
+    reach: Vec<Vec<usize>>,
+    preds: Vec<Vec<usize>>
+}
+
+impl ReachabilityIndex {
+    /// Fails if the graph has a cycle.
+    pub fn new<N, E>(graph: &DiGraph<N, E>) -> Result<ReachabilityIndex, Cycle> {
+        let order = toposort(graph)?;
+        let bound = graph.node_bound();
+
+        let mut index = ReachabilityIndex {
+            chain: vec![UNREACHABLE; bound],
+            pos: vec![0; bound],
+            chain_lens: Vec::new(),
+            reach: vec![Vec::new(); bound],
+            preds: vec![Vec::new(); bound]
+        };
+
+        for &start in &order {
+            if index.chain[start.index()] != UNREACHABLE {
+                continue;
+            }
+
+            let chain = index.chain_lens.len();
+            let mut node = Some(start);
+            let mut len = 0;
+
+            while let Some(curr) = node {
+                index.chain[curr.index()] = chain;
+                index.pos[curr.index()] = len;
+                len += 1;
+
+                node = graph
+                    .neighbors_directed(curr, Direction::Outgoing)
+                    .find(|next| index.chain[next.index()] == UNREACHABLE);
+            }
+
+            index.chain_lens.push(len);
+        }
+
+        let chains = index.chain_lens.len();
+
+        for &node in order.iter().rev() {
+            let mut reach = vec![UNREACHABLE; chains];
+            reach[index.chain[node.index()]] = index.pos[node.index()];
+
+            for next in graph.neighbors_directed(node, Direction::Outgoing) {
+                index.preds[next.index()].push(node.index());
+
+                for (r, &other) in reach.iter_mut().zip(&index.reach[next.index()]) {
+                    *r = (*r).min(other);
+                }
+            }
+
+            index.reach[node.index()] = reach;
+        }
+
+        Ok(index)
+    }
+
+    fn reach_of(&self, node: usize, chain: usize) -> usize {
+        self.reach[node].get(chain).copied().unwrap_or(UNREACHABLE)
+    }
+
+    /// Whether there is a path from `source` to `target`. Every node reaches
+    /// itself. Panics if either node is unknown to the index.
+    pub fn can_reach(&self, source: NodeId, target: NodeId) -> bool {
+        let (s, t) = (source.index(), target.index());
+
+        assert!(s < self.reach.len() && t < self.reach.len(), "node not in index");
+
+        self.reach_of(s, self.chain[t]) <= self.pos[t]
+    }
+
+    pub fn chain_count(&self) -> usize {
+        self.chain_lens.len()
+    }
+
+    /// Registers a node added to the graph after the index was built. It
+    /// starts a chain of its own.
+    pub fn insert_node(&mut self, node: NodeId) {
+        let i = node.index();
+
+        if i >= self.reach.len() {
+            self.chain.resize(i + 1, UNREACHABLE);
+            self.pos.resize(i + 1, 0);
+            self.reach.resize(i + 1, Vec::new());
+            self.preds.resize(i + 1, Vec::new());
+        }
+
+        assert!(self.chain[i] == UNREACHABLE, "node already in index");
+
+        let chain = self.chain_lens.len();
+
+        self.chain_lens.push(1);
+        self.chain[i] = chain;
+        self.pos[i] = 0;
+        self.reach[i] = vec![UNREACHABLE; chain + 1];
+        self.reach[i][chain] = 0;
+    }
+
+    /// Registers an edge added to the graph, updating the nodes that can now
+    /// reach further. Fails without changing the index if the edge closes a
+    /// cycle, in which case it should not be added to the graph either.
+    pub fn insert_edge(&mut self, source: NodeId, target: NodeId) -> Result<(), Cycle> {
+        if self.can_reach(target, source) {
+            return Err(Cycle::new(source));
+        }
+
+        let (s, t) = (source.index(), target.index());
+        let chains = self.chain_lens.len();
+        let gained: Vec<_> = (0..chains).map(|c| self.reach_of(t, c)).collect();
+
+        self.preds[t].push(s);
+
+        // ancestors only ever reach at least as far as their descendants,
+        // so the update stops at nodes that gain nothing
+        let mut stack = vec![s];
+
+        while let Some(node) = stack.pop() {
+            let reach = &mut self.reach[node];
+            let mut changed = false;
+
+            reach.resize(chains, UNREACHABLE);
+
+            for (r, &g) in reach.iter_mut().zip(&gained) {
+                if g < *r {
+                    *r = g;
+                    changed = true;
+                }
+            }
+
+            if changed {
+                stack.extend(self.preds[node].iter().copied());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+    use crate::rng::{Rng, SplitMix64};
+    use crate::visit::Bfs;
+
+    fn random_dag(rng: &mut SplitMix64, nodes: usize, edges: usize) -> DiGraph<(), ()> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..nodes).map(|_| graph.add_node(())).collect();
+
+        for _ in 0..edges {
+            let (a, b) = (rng.below(nodes), rng.below(nodes));
+
+            if a < b {
+                graph.add_edge(n[a], n[b], ());
+            }
+        }
+
+        graph
+    }
+
+    fn check(graph: &DiGraph<(), ()>, index: &ReachabilityIndex) {
+        for source in graph.node_ids() {
+            let reachable: Vec<_> = Bfs::new(graph, source).collect();
+
+            for target in graph.node_ids() {
+                assert_eq!(reachable.contains(&target), index.can_reach(source, target));
+            }
+        }
+    }
+
+    #[test]
+    fn agrees_with_traversal() {
+        let mut rng = SplitMix64::new(8);
+
+        for _ in 0..5 {
+            let graph = random_dag(&mut rng, 40, 60);
+            let index = ReachabilityIndex::new(&graph).unwrap();
+
+            assert!(index.chain_count() < 40);
+            check(&graph, &index);
+        }
+    }
+
+    #[test]
+    fn incremental_updates_and_cycle_rejection() {
+        let mut rng = SplitMix64::new(21);
+        let mut graph = random_dag(&mut rng, 30, 20);
+        let mut index = ReachabilityIndex::new(&graph).unwrap();
+
+        for _ in 0..40 {
+            let (a, b) = (NodeId::new(rng.below(30)), NodeId::new(rng.below(30)));
+
+            match index.insert_edge(a, b) {
+                Ok(()) => {
+                    graph.add_edge(a, b, ());
+                },
+                Err(cycle) => assert!(cycle.node_id() == a && index.can_reach(b, a))
+            }
+        }
+
+        let extra = graph.add_node(());
+        index.insert_node(extra);
+        index.insert_edge(NodeId::new(0), extra).unwrap();
+        graph.add_edge(NodeId::new(0), extra, ());
+
+        assert!(crate::algo::toposort(&graph).is_ok());
+        check(&graph, &index);
+    }
+}
@@ -0,0 +1,114 @@
+use std::ops::Sub;
+
+use crate::algo::longest_path::longest_along;
+use crate::algo::Measure;
+use crate::attr::NodeMap;
+use crate::dag::Dag;
+use crate::graph::NodeId;
+
+/// Timing of a project whose tasks are the nodes of a [`Dag`], each one
+/// starting once all its predecessors are done, from [`critical_path`].
+pub struct Schedule<K> {
+    pub earliest_start: NodeMap<K>,
+    /// Latest start that does not delay the project.
+    pub latest_start: NodeMap<K>,
+    /// How much a task can slip without delaying the project.
+    pub slack: NodeMap<K>,
+    /// Time until every task is done.
+    pub length: K,
+    /// Tasks without slack from a first task to a last one, taking the
+    /// project's full length.
+    pub critical_path: Vec<NodeId>
+}
+
+/// Critical path method: earliest and latest start of every task, taking
+/// `duration(node, weight)` time, from a forward pass over the topological
+/// order and a backward one.
+pub fn critical_path<N, E, K>(dag: &Dag<N, E>, mut duration: impl FnMut(NodeId, &N) -> K) -> Schedule<K>
+where
+    K: Measure + Sub<Output = K>
+{
+    let graph = dag.graph();
+    let order = dag.topological_order();
+    let mut length = vec![K::default(); graph.node_bound()];
+
+    for &node in &order {
+        length[node.index()] = duration(node, &graph[node]);
+    }
+
+    // a task starts once the longest chain of tasks before it is done
+    let (earliest, pred) = longest_along(graph, &order, |edge| length[edge.source.index()]);
+    let mut last: Option<(NodeId, K)> = None;
+
+    for &node in &order {
+        let finish = earliest[node.index()] + length[node.index()];
+
+        if last.is_none_or(|(_, end)| finish > end) {
+            last = Some((node, finish));
+        }
+    }
+
+    let project = last.map_or(K::default(), |(_, end)| end);
+    let mut latest = vec![K::default(); graph.node_bound()];
+
+    for &node in order.iter().rev() {
+        let mut finish = project;
+
+        for after in graph.neighbors(node) {
+            if latest[after.index()] < finish {
+                finish = latest[after.index()];
+            }
+        }
+
+        latest[node.index()] = finish - length[node.index()];
+    }
+
+    let mut path: Vec<_> =
+        std::iter::successors(last.map(|(node, _)| node), |n| pred[n.index()]).collect();
+    path.reverse();
+
+    let mut schedule = Schedule {
+        earliest_start: NodeMap::with_capacity(graph.node_bound()),
+        latest_start: NodeMap::with_capacity(graph.node_bound()),
+        slack: NodeMap::with_capacity(graph.node_bound()),
+        length: project,
+        critical_path: path
+    };
+
+    for &node in &order {
+        let (early, late) = (earliest[node.index()], latest[node.index()]);
+
+        schedule.earliest_start.insert(node, early);
+        schedule.latest_start.insert(node, late);
+        schedule.slack.insert(node, late - early);
+    }
+
+    schedule
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_critical_path() {
+        let mut dag = Dag::new();
+        let [a, b, c, d, e] = [3, 2, 2, 4, 1].map(|days| dag.add_node(days));
+
+        for (from, to) in [(a, c), (b, c), (c, d), (b, e), (e, d)] {
+            dag.try_add_edge(from, to, ()).unwrap();
+        }
+
+        let schedule = critical_path(&dag, |_, &days| days);
+        let at = |map: &NodeMap<i32>| [a, b, c, d, e].map(|n| map[n]);
+
+        assert_eq!(9, schedule.length);
+        assert_eq!(vec![a, c, d], schedule.critical_path);
+        assert_eq!([0, 0, 3, 5, 2], at(&schedule.earliest_start));
+        assert_eq!([0, 1, 3, 5, 4], at(&schedule.latest_start));
+        assert_eq!([0, 1, 0, 0, 2], at(&schedule.slack));
+
+        let empty = critical_path(&Dag::<i32, ()>::new(), |_, &days| days);
+        assert_eq!((0, vec![]), (empty.length, empty.critical_path));
+    }
+}
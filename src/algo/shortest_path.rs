@@ -0,0 +1,602 @@
+use crate::algo::{Control, Measure};
+use crate::bitset::FixedBitSet;
+use crate::error::GraphError;
+use crate::graph::{EdgeType, Graph, NodeId};
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+
+pub struct ShortestPaths<K> {
+    source: NodeId,
+    settled: Vec<NodeId>,
+    dist: Vec<Option<K>>,
+    pred: Vec<Option<NodeId>>
+}
+
+impl<K: Copy> ShortestPaths<K> {
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// `None` if `node` is unreachable from the source.
+    pub fn distance(&self, node: NodeId) -> Option<K> {
+        self.dist.get(node.index()).copied().flatten()
+    }
+
+    pub fn predecessor(&self, node: NodeId) -> Option<NodeId> {
+        self.pred.get(node.index()).copied().flatten()
+    }
+
+    /// Nodes from the source to `node`, both included.
+    pub fn path_to(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(node)?;
+
+        let mut path = vec![node];
+        let mut curr = node;
+
+        while let Some(prev) = self.predecessor(curr) {
+            path.push(prev);
+            curr = prev;
+        }
+
+        path.reverse();
+
+        Some(path)
+    }
+
+    /// Reachable nodes with their distances, closest first.
+    pub fn reached(&self) -> impl Iterator<Item = (NodeId, K)> + '_ {
+        self.settled.iter().map(|&node| (node, self.distance(node).unwrap()))
+    }
+}
+
+/// Single-source shortest paths for non-negative edge costs.
+pub fn dijkstra<N, E, Ty, K, F>(graph: &Graph<N, E, Ty>, source: NodeId, edge_cost: F) -> ShortestPaths<K>
+where
+    Ty: EdgeType,
+    K: Measure,
+    F: FnMut(&E) -> K
+{
+    dijkstra_with_heap(graph, source, IndexedBinaryHeap::new(), edge_cost)
+}
+
+/// Same as [`dijkstra`], but failing instead of panicking or returning
+/// wrong distances when `source` is not in the graph or an edge costs less
+/// than zero.
+pub fn try_dijkstra<N, E, Ty, K, F>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    mut edge_cost: F
+) -> Result<ShortestPaths<K>, GraphError>
+where
+    Ty: EdgeType,
+    K: Measure,
+    F: FnMut(&E) -> K
+{
+    graph.check_node(source)?;
+
+    for edge in graph.edge_ids() {
+        let cost = edge_cost(graph.edge_weight(edge).unwrap());
+
+        // NaN counts as negative
+        if cost.partial_cmp(&K::default()).is_none_or(|order| order.is_lt()) {
+            return Err(GraphError::NegativeWeight(edge));
+        }
+    }
+
+    Ok(dijkstra(graph, source, edge_cost))
+}
+
+/// Same as [`dijkstra`], but running on the given priority queue, e.g. a
+/// [`PairingHeap`](crate::PairingHeap). The heap is expected to be empty.
+pub fn dijkstra_with_heap<N, E, Ty, K, H, F>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    heap: H,
+    mut edge_cost: F
+) -> ShortestPaths<K>
+where
+    Ty: EdgeType,
+    K: Measure,
+    H: AddressableHeap<NodeId, K>,
+    F: FnMut(&E) -> K
+{
+    settle(graph, source, K::default(), heap, |weight, d| d + edge_cost(weight))
+}
+
+/// Same as [`dijkstra`], passing every node to `visitor` with its distance
+/// as it is settled, closest first. [`Control::Break`] stops the search,
+/// leaving distances only for the nodes settled so far, and is returned
+/// with them; [`Control::Prune`] does not search on from the node.
+pub fn dijkstra_visit<N, E, Ty, K, F, B>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    mut edge_cost: F,
+    visitor: impl FnMut(NodeId, K) -> Control<B>
+) -> (ShortestPaths<K>, Control<B>)
+where
+    Ty: EdgeType,
+    K: Measure,
+    F: FnMut(&E) -> K
+{
+    let mut paths = ShortestPaths {
+        source,
+        settled: Vec::new(),
+        dist: vec![None; graph.node_bound()],
+        pred: vec![None; graph.node_bound()]
+    };
+    let mut settled = FixedBitSet::with_capacity(graph.node_bound());
+    let mut heap = IndexedBinaryHeap::new();
+    let extend = |weight: &E, d| d + edge_cost(weight);
+    let control = settle_into(graph, K::default(), &mut heap, &mut paths, &mut settled, extend, visitor);
+
+    (paths, control)
+}
+
+/// Earliest arrival times from `source`, leaving at `departure`, when
+/// `travel(weight, time)` is how long an edge takes if entered at `time`,
+/// e.g. waiting for the next scheduled departure plus the ride. The
+/// network must be FIFO: entering an edge later never means leaving it
+/// earlier. [`ShortestPaths::distance`] then gives arrival times rather
+/// than durations.
+pub fn time_dependent_dijkstra<N, E, Ty, T, F>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    departure: T,
+    mut travel: F
+) -> ShortestPaths<T>
+where
+    Ty: EdgeType,
+    T: Measure,
+    F: FnMut(&E, T) -> T
+{
+    settle(graph, source, departure, IndexedBinaryHeap::new(), |weight, t| t + travel(weight, t))
+}
+
+/// Cheapest path from `source` to `target` and its cost, searching towards
+/// the target first as guided by `estimate(node)`, a lower bound on the
+/// cost left from `node`. The estimate must also be consistent: it never
+/// drops by more than an edge's cost along that edge, as straight-line
+/// distances do. `None` if the target is unreachable.
+pub fn astar<N, E, Ty, K, F, H>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    target: NodeId,
+    mut edge_cost: F,
+    mut estimate: H
+) -> Option<(K, Vec<NodeId>)>
+where
+    Ty: EdgeType,
+    K: Measure,
+    F: FnMut(&E) -> K,
+    H: FnMut(NodeId) -> K
+{
+    let mut dist = vec![None; graph.node_bound()];
+    let mut pred = vec![None; graph.node_bound()];
+    let mut settled = FixedBitSet::with_capacity(graph.node_bound());
+    let mut heap = IndexedBinaryHeap::new();
+
+    dist[source.index()] = Some(K::default());
+    heap.push(source, estimate(source));
+
+    while let Some((node, _)) = heap.pop_min() {
+        let d = dist[node.index()].unwrap();
+
+        if node == target {
+            let paths = ShortestPaths { source, settled: Vec::new(), dist, pred };
+            return Some((d, paths.path_to(target).unwrap()));
+        }
+
+        settled.insert(node.index());
+
+        for edge in graph.edges(node) {
+            let next = edge.target;
+            let candidate = d + edge_cost(edge.weight);
+
+            if settled.contains(next.index()) || dist[next.index()].is_some_and(|curr| candidate >= curr) {
+                continue;
+            }
+
+            dist[next.index()] = Some(candidate);
+            pred[next.index()] = Some(node);
+            heap.push(next, candidate + estimate(next));
+        }
+    }
+
+    None
+}
+
+/// Same as [`astar`], but first checking that `estimate` is one it can
+/// rely on, since an estimate that overestimates silently gives paths
+/// that are not the cheapest. Fails with the first edge along which the
+/// estimate drops by more than the edge's cost, with the target if its
+/// own estimate is above zero, and on a bad `source` or a negative cost.
+/// Together these rule out overestimating anywhere. Meant for tests and
+/// debug builds: every edge is checked, not only those searched.
+pub fn try_astar<N, E, Ty, K, F, H>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    target: NodeId,
+    mut edge_cost: F,
+    mut estimate: H
+) -> Result<Option<(K, Vec<NodeId>)>, GraphError>
+where
+    Ty: EdgeType,
+    K: Measure,
+    F: FnMut(&E) -> K,
+    H: FnMut(NodeId) -> K
+{
+    graph.check_node(source)?;
+
+    if graph.check_node(target).is_ok() && estimate(target) > K::default() {
+        return Err(GraphError::InadmissibleHeuristic(target));
+    }
+
+    for node in graph.node_ids() {
+        let here = estimate(node);
+
+        for edge in graph.edges(node) {
+            let cost = edge_cost(edge.weight);
+
+            // NaN counts as negative
+            if cost.partial_cmp(&K::default()).is_none_or(|order| order.is_lt()) {
+                return Err(GraphError::NegativeWeight(edge.id));
+            }
+
+            if here > cost + estimate(edge.target) {
+                return Err(GraphError::InconsistentHeuristic(edge.id));
+            }
+        }
+    }
+
+    Ok(astar(graph, source, target, edge_cost, estimate))
+}
+
+/// Label-setting search where `extend(weight, label)` is the label reached
+/// over an edge, never below `label`.
+fn settle<N, E, Ty, K, H, F>(
+    graph: &Graph<N, E, Ty>,
+    source: NodeId,
+    start: K,
+    mut heap: H,
+    extend: F
+) -> ShortestPaths<K>
+where
+    Ty: EdgeType,
+    K: Measure,
+    H: AddressableHeap<NodeId, K>,
+    F: FnMut(&E, K) -> K
+{
+    let mut paths = ShortestPaths {
+        source,
+        settled: Vec::new(),
+        dist: vec![None; graph.node_bound()],
+        pred: vec![None; graph.node_bound()]
+    };
+    let mut settled = FixedBitSet::with_capacity(graph.node_bound());
+
+    settle_into(graph, start, &mut heap, &mut paths, &mut settled, extend, |_, _| Control::<()>::Continue);
+    paths
+}
+
+/// [`settle`] from `paths.source` into buffers holding no labels and
+/// sized for the graph, leaving the heap empty. Every node settled is
+/// passed to `visit`, as in [`dijkstra_visit`]; after a break, only
+/// settled nodes keep their labels.
+fn settle_into<N, E, Ty, K, H, F, B>(
+    graph: &Graph<N, E, Ty>,
+    start: K,
+    heap: &mut H,
+    paths: &mut ShortestPaths<K>,
+    settled: &mut FixedBitSet,
+    mut extend: F,
+    mut visit: impl FnMut(NodeId, K) -> Control<B>
+) -> Control<B>
+where
+    Ty: EdgeType,
+    K: Measure,
+    H: AddressableHeap<NodeId, K>,
+    F: FnMut(&E, K) -> K
+{
+    let ShortestPaths { source, settled: order, dist, pred } = paths;
+
+    dist[source.index()] = Some(start);
+    heap.push(*source, start);
+
+    while let Some((node, d)) = heap.pop_min() {
+        settled.insert(node.index());
+        order.push(node);
+
+        match visit(node, d) {
+            Control::Continue => {},
+            Control::Prune => continue,
+            Control::Break(value) => {
+                while let Some((left, _)) = heap.pop_min() {
+                    dist[left.index()] = None;
+                    pred[left.index()] = None;
+                }
+
+                return Control::Break(value);
+            }
+        }
+
+        for edge in graph.edges(node) {
+            let next = edge.target;
+
+            if settled.contains(next.index()) {
+                continue;
+            }
+
+            let candidate = extend(edge.weight, d);
+
+            if dist[next.index()].is_some_and(|curr| candidate >= curr) {
+                continue;
+            }
+
+            dist[next.index()] = Some(candidate);
+            pred[next.index()] = Some(node);
+
+            if heap.contains(&next) {
+                heap.decrease_key(&next, candidate);
+            } else {
+                heap.push(next, candidate);
+            }
+        }
+    }
+
+    Control::Continue
+}
+
+/// Heap, visited set and distance arrays for running [`dijkstra`] many
+/// times without allocating once they are large enough. Only the entries
+/// the last run reached are cleared before the next.
+pub struct DijkstraWorkspace<K> {
+    paths: ShortestPaths<K>,
+    settled: FixedBitSet,
+    heap: IndexedBinaryHeap<NodeId, K>
+}
+
+impl<K: Measure> Default for DijkstraWorkspace<K> {
+    fn default() -> Self {
+        DijkstraWorkspace::new()
+    }
+}
+
+impl<K: Measure> DijkstraWorkspace<K> {
+    pub fn new() -> DijkstraWorkspace<K> {
+        DijkstraWorkspace {
+            paths: ShortestPaths {
+                source: NodeId::new(0),
+                settled: Vec::new(),
+                dist: Vec::new(),
+                pred: Vec::new()
+            },
+            settled: FixedBitSet::new(),
+            heap: IndexedBinaryHeap::new()
+        }
+    }
+
+    /// Same as [`dijkstra`], with the result kept in the workspace until
+    /// the next run.
+    pub fn run<N, E, Ty, F>(
+        &mut self,
+        graph: &Graph<N, E, Ty>,
+        source: NodeId,
+        mut edge_cost: F
+    ) -> &ShortestPaths<K>
+    where
+        Ty: EdgeType,
+        F: FnMut(&E) -> K
+    {
+        let paths = &mut self.paths;
+
+        for node in paths.settled.drain(..) {
+            paths.dist[node.index()] = None;
+            paths.pred[node.index()] = None;
+            self.settled.remove(node.index());
+        }
+
+        if paths.dist.len() < graph.node_bound() {
+            paths.dist.resize(graph.node_bound(), None);
+            paths.pred.resize(graph.node_bound(), None);
+            self.settled.grow(graph.node_bound());
+        }
+
+        paths.source = source;
+        let extend = |weight: &E, d| d + edge_cost(weight);
+        let visit = |_, _| Control::<()>::Continue;
+        settle_into(graph, K::default(), &mut self.heap, paths, &mut self.settled, extend, visit);
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::heap::PairingHeap;
+
+    fn sample() -> (DiGraph<(), u32>, Vec<NodeId>) {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+
+        for (a, b, w) in [(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 1), (2, 3, 5), (3, 4, 3)] {
+            graph.add_edge(n[a], n[b], w);
+        }
+
+        (graph, n)
+    }
+
+    #[test]
+    fn dijkstra_finds_shortest_paths() {
+        let (graph, n) = sample();
+
+        let paths = dijkstra(&graph, n[0], |&w| w);
+
+        assert_eq!(Some(0), paths.distance(n[0]));
+        assert_eq!(Some(3), paths.distance(n[1]));
+        assert_eq!(Some(4), paths.distance(n[3]));
+        assert_eq!(Some(7), paths.distance(n[4]));
+        assert_eq!(Some(vec![n[0], n[2], n[1], n[3], n[4]]), paths.path_to(n[4]));
+
+        let back = dijkstra(&graph, n[4], |&w| w);
+
+        assert_eq!(None, back.distance(n[0]));
+        assert_eq!(None, back.path_to(n[0]));
+        assert_eq!(1, back.reached().count());
+    }
+
+    #[test]
+    fn workspace_runs_match_fresh_ones() {
+        let (mut graph, n) = sample();
+        let mut workspace = DijkstraWorkspace::new();
+
+        for _ in 0..2 {
+            for &source in &n {
+                let fresh = dijkstra(&graph, source, |&w| w);
+                let reused = workspace.run(&graph, source, |&w| w);
+
+                assert_eq!(source, reused.source());
+                assert_eq!(fresh.reached().collect::<Vec<_>>(), reused.reached().collect::<Vec<_>>());
+                assert!(n.iter().all(|&node| fresh.path_to(node) == reused.path_to(node)));
+            }
+
+            // grows with the graph
+            let extra = graph.add_node(());
+            graph.add_edge(n[4], extra, 1);
+        }
+    }
+
+    #[test]
+    fn visitor_stops_at_the_target() {
+        let (graph, n) = sample();
+        let full = dijkstra(&graph, n[0], |&w| w);
+
+        let (paths, found) = dijkstra_visit(&graph, n[0], |&w| w, |node, d| {
+            if node == n[3] { Control::Break(d) } else { Control::Continue }
+        });
+
+        assert_eq!(Control::Break(4), found);
+        assert_eq!(full.path_to(n[3]), paths.path_to(n[3]));
+        assert_eq!(None, paths.distance(n[4]));
+
+        let (pruned, done): (_, Control) = dijkstra_visit(&graph, n[0], |&w| w, |node, _| {
+            if node == n[1] { Control::Prune } else { Control::Continue }
+        });
+        assert_eq!((Control::Continue, Some(6)), (done, pruned.distance(n[3])));
+    }
+
+    #[test]
+    fn try_dijkstra_reports_bad_input() {
+        let (mut graph, n) = sample();
+
+        assert_eq!(Some(7), try_dijkstra(&graph, n[0], |&w| w).unwrap().distance(n[4]));
+
+        let gone = graph.add_node(());
+        graph.remove_node(gone);
+        assert_eq!(Some(GraphError::StaleGeneration(gone)), try_dijkstra(&graph, gone, |&w| w).err());
+
+        let unknown = NodeId::new(99);
+        assert_eq!(Some(GraphError::InvalidNodeId(unknown)), try_dijkstra(&graph, unknown, |&w| w).err());
+
+        let edge = graph.add_edge(n[4], n[0], 0);
+        let signed = try_dijkstra(&graph, n[0], |&w| if w == 0 { -1 } else { w as i64 });
+        assert_eq!(Some(GraphError::NegativeWeight(edge)), signed.err());
+    }
+
+    #[test]
+    fn heap_backends_agree() {
+        let (graph, n) = sample();
+
+        let binary = dijkstra(&graph, n[0], |&w| w);
+        let pairing = dijkstra_with_heap(&graph, n[0], PairingHeap::new(), |&w| w);
+
+        for &node in &n {
+            assert_eq!(binary.distance(node), pairing.distance(node));
+        }
+    }
+
+    #[test]
+    fn undirected_edges_work_both_ways() {
+        let mut graph: UnGraph<(), f64> = Graph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+
+        graph.add_edge(b, a, 1.5);
+        graph.add_edge(c, b, 2.0);
+
+        assert_eq!(Some(3.5), dijkstra(&graph, a, |&w| w).distance(c));
+    }
+
+    #[test]
+    fn waits_for_scheduled_departures() {
+        // a bus a-c leaving every 30 minutes, or a walk a-b-c
+        let mut graph: DiGraph<(), Option<u32>> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[0], n[2], Some(30));
+        graph.add_edge(n[0], n[1], None);
+        graph.add_edge(n[1], n[2], None);
+
+        let travel = |&every: &Option<u32>, t: u32| match every {
+            Some(every) => (every - t % every) % every + 5,
+            None => 12
+        };
+
+        let on_time = time_dependent_dijkstra(&graph, n[0], 60, travel);
+        assert_eq!(Some(65), on_time.distance(n[2]));
+        assert_eq!(Some(vec![n[0], n[2]]), on_time.path_to(n[2]));
+
+        let missed = time_dependent_dijkstra(&graph, n[0], 61, travel);
+        assert_eq!(Some(85), missed.distance(n[2]));
+        assert_eq!(Some(vec![n[0], n[1], n[2]]), missed.path_to(n[2]));
+    }
+
+    #[test]
+    fn constant_travel_times_match_dijkstra() {
+        let (graph, n) = sample();
+
+        let plain = dijkstra(&graph, n[0], |&w| w);
+        let timed = time_dependent_dijkstra(&graph, n[0], 100, |&w, _| w);
+
+        for &node in &n {
+            assert_eq!(plain.distance(node).map(|d| d + 100), timed.distance(node));
+        }
+    }
+
+    #[test]
+    fn astar_agrees_with_dijkstra() {
+        let (graph, n) = sample();
+
+        let paths = dijkstra(&graph, n[0], |&w| w);
+        let blind = astar(&graph, n[0], n[4], |&w| w, |_| 0);
+        // d(s, t) - d(s, v) never exceeds d(v, t)
+        let left = |node| paths.distance(n[4]).unwrap() - paths.distance(node).unwrap();
+        let guided = astar(&graph, n[0], n[4], |&w| w, left);
+
+        assert_eq!(Some((7, paths.path_to(n[4]).unwrap())), blind);
+        assert_eq!(blind, guided);
+        assert_eq!(None, astar(&graph, n[4], n[0], |&w| w, |_| 0));
+    }
+
+    #[test]
+    fn try_astar_flags_overestimates() {
+        let (mut graph, n) = sample();
+        let exact = |node| [7, 4, 6, 3, 0][n.iter().position(|&m| m == node).unwrap()];
+
+        let checked = try_astar(&graph, n[0], n[4], |&w| w, exact);
+        assert_eq!(Ok(astar(&graph, n[0], n[4], |&w| w, exact)), checked);
+
+        // too high at n[2], which is never left, so astar misses the path through it
+        let high = |node| if node == n[2] { 10 } else { 0 };
+        assert_eq!(Some(8), astar(&graph, n[0], n[4], |&w| w, high).map(|(d, _)| d));
+
+        let via = graph.edges(n[2]).find(|edge| edge.target == n[1]).unwrap().id;
+        assert_eq!(Err(GraphError::InconsistentHeuristic(via)), try_astar(&graph, n[0], n[4], |&w| w, high));
+        assert_eq!(
+            Err(GraphError::InadmissibleHeuristic(n[4])),
+            try_astar(&graph, n[0], n[4], |&w| w, |_| 1)
+        );
+
+        graph.remove_node(n[3]);
+        assert_eq!(Ok(None), try_astar(&graph, n[0], n[4], |&w| w, |_| 0));
+    }
+}
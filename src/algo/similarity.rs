@@ -0,0 +1,208 @@
+use crate::algo::neighborhood;
+use crate::graph::{Direction, EdgeType, Graph, NodeId};
+
+/// Neighbor-overlap measure used by [`similarity`] and [`most_similar`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Similarity {
+    /// Number of shared neighbors.
+    CommonNeighbors,
+    /// Shared neighbors over the neighbors of either node.
+    Jaccard,
+    /// Shared neighbors weighted by one over the log of their degree, so
+    /// that rare common neighbors count more than hubs.
+    AdamicAdar
+}
+
+/// Score of the pair `a`, `b` under `measure`, ignoring edge direction,
+/// loops and parallel edges. Panics if either node is not in the graph.
+pub fn similarity<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    a: NodeId,
+    b: NodeId,
+    measure: Similarity
+) -> f64 {
+    let of_a = neighborhood(graph, a);
+    let of_b = neighborhood(graph, b);
+    let common = of_a.iter().filter(|n| of_b.binary_search(n).is_ok());
+
+    match measure {
+        Similarity::CommonNeighbors => common.count() as f64,
+        Similarity::Jaccard => {
+            let shared = common.count();
+            let union = of_a.len() + of_b.len() - shared;
+
+            if union == 0 { 0.0 } else { shared as f64 / union as f64 }
+        },
+        Similarity::AdamicAdar => {
+            // a common neighbor has degree at least two, so the log is positive
+            common.map(|&n| 1.0 / (neighborhood(graph, n).len() as f64).ln()).sum()
+        }
+    }
+}
+
+/// The `k` nodes scoring highest against `node` under `measure`, best
+/// first and ties broken by id. Only nodes two hops away share neighbors,
+/// so only those are scored, and nodes scoring zero are left out.
+pub fn most_similar<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    node: NodeId,
+    measure: Similarity,
+    k: usize
+) -> Vec<(NodeId, f64)> {
+    let mut candidates: Vec<_> = neighborhood(graph, node)
+        .into_iter()
+        .flat_map(|n| neighborhood(graph, n))
+        .filter(|&n| n != node)
+        .collect();
+
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let scored = candidates.into_iter().map(|other| (other, similarity(graph, node, other, measure)));
+    top_k(scored.collect(), k)
+}
+
+/// Best `k` of `scored` by decreasing score then id, dropping zeros.
+fn top_k(mut scored: Vec<(NodeId, f64)>, k: usize) -> Vec<(NodeId, f64)> {
+    scored.retain(|&(_, score)| score > 0.0);
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored.truncate(k);
+    scored
+}
+
+/// SimRank scores of every pair of nodes, computed by [`simrank`].
+#[derive(Clone, Debug)]
+pub struct SimRank {
+    nodes: Vec<NodeId>,
+    position: Vec<usize>,
+    scores: Vec<f64>
+}
+
+/// SimRank of all pairs: two nodes are similar when their in-neighbors are,
+/// each step back discounted by `decay`, which should lie in `(0, 1)`.
+/// Undirected graphs use all neighbors. Runs `iterations` rounds in
+/// `O(n² d)` time each and keeps `n²` scores.
+pub fn simrank<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, decay: f64, iterations: usize) -> SimRank {
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let n = nodes.len();
+    let mut position = vec![usize::MAX; graph.node_bound()];
+
+    for (i, node) in nodes.iter().enumerate() {
+        position[node.index()] = i;
+    }
+
+    let incoming: Vec<Vec<usize>> = nodes
+        .iter()
+        .map(|&node| {
+            let mut from: Vec<_> = graph
+                .neighbors_directed(node, Direction::Incoming)
+                .map(|other| position[other.index()])
+                .collect();
+
+            from.sort_unstable();
+            from.dedup();
+            from
+        })
+        .collect();
+
+    let mut scores = vec![0.0; n * n];
+    let mut partial = vec![0.0; n * n];
+
+    for i in 0..n {
+        scores[i * n + i] = 1.0;
+    }
+
+    for _ in 0..iterations {
+        // partial[i][b] sums the scores of i against the in-neighbors of b
+        for i in 0..n {
+            for b in 0..n {
+                partial[i * n + b] = incoming[b].iter().map(|&j| scores[i * n + j]).sum();
+            }
+        }
+
+        for a in 0..n {
+            for b in 0..n {
+                scores[a * n + b] = if a == b {
+                    1.0
+                } else if incoming[a].is_empty() || incoming[b].is_empty() {
+                    0.0
+                } else {
+                    let total: f64 = incoming[a].iter().map(|&i| partial[i * n + b]).sum();
+                    decay * total / (incoming[a].len() * incoming[b].len()) as f64
+                };
+            }
+        }
+    }
+
+    SimRank { nodes, position, scores }
+}
+
+impl SimRank {
+    fn index(&self, node: NodeId) -> usize {
+        let i = self.position.get(node.index()).copied().unwrap_or(usize::MAX);
+        assert!(i < self.nodes.len() && self.nodes[i] == node, "node not in graph");
+        i
+    }
+
+    /// Score of the pair `a`, `b`, one when they are the same node. Panics
+    /// if either was not in the graph.
+    pub fn score(&self, a: NodeId, b: NodeId) -> f64 {
+        self.scores[self.index(a) * self.nodes.len() + self.index(b)]
+    }
+
+    /// The `k` other nodes scoring highest against `node`, best first and
+    /// ties broken by id, leaving out zeros.
+    pub fn most_similar(&self, node: NodeId, k: usize) -> Vec<(NodeId, f64)> {
+        let row = self.index(node) * self.nodes.len();
+        let scored = self.nodes.iter().enumerate().filter(|&(_, &other)| other != node);
+
+        top_k(scored.map(|(i, &other)| (other, self.scores[row + i])).collect(), k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn scores_neighbor_overlap() {
+        // 0 and 1 share 2 and 3; 3 also links 4
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 2), (0, 3), (1, 2), (1, 3), (3, 4), (0, 0)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        assert_eq!(2.0, similarity(&graph, n[0], n[1], Similarity::CommonNeighbors));
+        assert_eq!(1.0, similarity(&graph, n[0], n[1], Similarity::Jaccard));
+        assert_eq!(0.5, similarity(&graph, n[0], n[4], Similarity::Jaccard));
+
+        let adamic_adar = similarity(&graph, n[0], n[1], Similarity::AdamicAdar);
+        assert!((adamic_adar - (1.0 / 2f64.ln() + 1.0 / 3f64.ln())).abs() < 1e-12);
+
+        let top = most_similar(&graph, n[0], Similarity::CommonNeighbors, 5);
+        assert_eq!(vec![(n[1], 2.0), (n[4], 1.0)], top);
+        assert_eq!(1, most_similar(&graph, n[0], Similarity::Jaccard, 1).len());
+    }
+
+    #[test]
+    fn simrank_follows_in_neighbors() {
+        // 0 points at 1 and 2, which are alike; 3 points at 0
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 1), (0, 2), (3, 0)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        let ranks = simrank(&graph, 0.8, 10);
+
+        assert_eq!(1.0, ranks.score(n[1], n[1]));
+        assert_eq!(0.8, ranks.score(n[1], n[2]));
+        assert_eq!(ranks.score(n[2], n[1]), ranks.score(n[1], n[2]));
+        assert_eq!(0.0, ranks.score(n[0], n[1]));
+        assert_eq!(vec![(n[2], 0.8)], ranks.most_similar(n[1], 3));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::graph::{EdgeId, UnGraph};
+
+/// Number of spanning trees, by Kirchhoff's matrix-tree theorem: the
+/// determinant of the Laplacian with one row and column removed, taken
+/// with Bareiss' fraction-free elimination so every step stays an integer.
+/// Parallel edges make distinct trees and self loops are ignored, and a
+/// graph that is not connected, or has no nodes, has none. `None` if a
+/// step of the elimination does not fit in an `i128`, as happens for
+/// counts from about 10^19 on, since steps multiply two of them.
+pub fn count_spanning_trees<N, E>(graph: &UnGraph<N, E>) -> Option<u128> {
+    let positions = positions(graph);
+    let n = graph.node_count();
+
+    if n == 0 {
+        return Some(0);
+    }
+
+    // the Laplacian without the row and column of the last node
+    let m = n - 1;
+    let mut matrix = vec![vec![0i128; m]; m];
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        let (a, b) = (positions[a.index()], positions[b.index()]);
+
+        if a == b {
+            continue;
+        }
+
+        for (i, j) in [(a, b), (b, a)] {
+            if i < m {
+                matrix[i][i] += 1;
+
+                if j < m {
+                    matrix[i][j] -= 1;
+                }
+            }
+        }
+    }
+
+    let mut prev = 1;
+
+    for k in 0..m {
+        if matrix[k][k] == 0 {
+            let Some(pivot) = (k + 1..m).find(|&i| matrix[i][k] != 0) else { return Some(0) };
+            matrix.swap(k, pivot);
+        }
+
+        for i in k + 1..m {
+            for j in k + 1..m {
+                let kept = matrix[i][j].checked_mul(matrix[k][k])?;
+                let removed = matrix[i][k].checked_mul(matrix[k][j])?;
+
+                matrix[i][j] = kept.checked_sub(removed)? / prev;
+            }
+        }
+
+        prev = matrix[k][k];
+    }
+
+    // row swaps only flip the sign, and the count is not negative
+    Some(prev.unsigned_abs())
+}
+
+/// Iterator over the spanning trees of a graph, created by
+/// [`spanning_trees`].
+pub struct SpanningTrees {
+    /// Edges other than self loops, with the positions of their endpoints.
+    edges: Vec<(EdgeId, usize, usize)>,
+    nodes: usize,
+    chosen: Vec<usize>,
+    next: usize,
+    done: bool
+}
+
+/// Enumerates the spanning trees of `graph`, lazily, each as its edges in
+/// the order of [`edge_ids`](crate::Graph::edge_ids). Trees are built by
+/// backtracking over the edges, so this is only meant for small graphs: a
+/// complete graph on `n` nodes has `n^(n - 2)` of them.
+pub fn spanning_trees<N, E>(graph: &UnGraph<N, E>) -> SpanningTrees {
+    let positions = positions(graph);
+    let edges = graph
+        .edge_ids()
+        .filter_map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let (a, b) = (positions[a.index()], positions[b.index()]);
+            (a != b).then_some((edge, a, b))
+        })
+        .collect();
+
+    SpanningTrees {
+        edges,
+        nodes: graph.node_count(),
+        chosen: Vec::new(),
+        next: 0,
+        done: graph.node_count() == 0
+    }
+}
+
+impl SpanningTrees {
+    /// Whether adding edge `candidate` to those chosen closes no cycle.
+    fn keeps_forest(&self, candidate: usize) -> bool {
+        let mut parent: Vec<_> = (0..self.nodes).collect();
+
+        let root = |parent: &mut Vec<usize>, mut node: usize| {
+            while parent[node] != node {
+                parent[node] = parent[parent[node]];
+                node = parent[node];
+            }
+
+            node
+        };
+
+        for &i in &self.chosen {
+            let (_, a, b) = self.edges[i];
+            let (a, b) = (root(&mut parent, a), root(&mut parent, b));
+            parent[a] = b;
+        }
+
+        let (_, a, b) = self.edges[candidate];
+        root(&mut parent, a) != root(&mut parent, b)
+    }
+}
+
+impl Iterator for SpanningTrees {
+    type Item = Vec<EdgeId>;
+
+    fn next(&mut self) -> Option<Vec<EdgeId>> {
+        while !self.done {
+            let need = self.nodes - 1 - self.chosen.len();
+
+            if need == 0 {
+                let tree = self.chosen.iter().map(|&i| self.edges[i].0).collect();
+
+                match self.chosen.pop() {
+                    Some(last) => self.next = last + 1,
+                    None => self.done = true
+                }
+
+                return Some(tree);
+            }
+
+            if self.edges.len() - self.next >= need {
+                if self.keeps_forest(self.next) {
+                    self.chosen.push(self.next);
+                }
+
+                self.next += 1;
+            } else {
+                match self.chosen.pop() {
+                    Some(last) => self.next = last + 1,
+                    None => self.done = true
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Positions `0..node_count` of the nodes, by node index.
+fn positions<N, E>(graph: &UnGraph<N, E>) -> Vec<usize> {
+    let mut positions = vec![usize::MAX; graph.node_bound()];
+
+    for (position, node) in graph.node_ids().enumerate() {
+        positions[node.index()] = position;
+    }
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    fn complete(n: usize) -> UnGraph<(), ()> {
+        let mut graph = Graph::new();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+
+        for i in 0..n {
+            for j in i + 1..n {
+                graph.add_edge(nodes[i], nodes[j], ());
+            }
+        }
+
+        graph
+    }
+
+    #[test]
+    fn counts_by_the_matrix_tree_theorem() {
+        // Cayley's formula
+        for n in 1usize..8 {
+            assert_eq!(Some((n as u128).pow(n.saturating_sub(2) as u32)), count_spanning_trees(&complete(n)));
+        }
+
+        let mut graph = complete(3);
+        let [a, b, c] = [0, 1, 2].map(|i| graph.node_ids().nth(i).unwrap());
+        graph.add_edge(a, b, ());
+        graph.add_edge(c, c, ());
+        assert_eq!(Some(5), count_spanning_trees(&graph));
+
+        graph.remove_node(c);
+        graph.add_node(());
+        assert_eq!(Some(0), count_spanning_trees(&graph));
+        assert_eq!(Some(0), count_spanning_trees(&UnGraph::<(), ()>::new()));
+        assert_eq!(Some(12u128.pow(10)), count_spanning_trees(&complete(12)));
+        assert_eq!(None, count_spanning_trees(&complete(20)));
+    }
+
+    #[test]
+    fn enumerates_every_tree_once() {
+        let graph = complete(5);
+        let mut trees: Vec<_> = spanning_trees(&graph).collect();
+
+        assert_eq!(125, trees.len());
+        assert!(trees.iter().all(|tree| tree.len() == 4));
+        trees.sort();
+        trees.dedup();
+        assert_eq!(125, trees.len());
+
+        let mut single: UnGraph<(), ()> = Graph::new();
+        let node = single.add_node(());
+        single.add_edge(node, node, ());
+        assert_eq!(vec![Vec::<EdgeId>::new()], spanning_trees(&single).collect::<Vec<_>>());
+        assert_eq!(0, spanning_trees(&UnGraph::<(), ()>::new()).count());
+    }
+}
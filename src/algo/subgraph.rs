@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::algo::{undirected_neighbors, Control};
+use crate::attr::{EdgeMap, NodeMap};
+use crate::bitset::FixedBitSet;
+use crate::graph::{Direction, EdgeId, EdgeType, Graph, NodeId};
+
+/// Occurrence of a pattern found by [`subgraph_monomorphisms`], mapping
+/// every pattern node and edge to the target node or edge it matched.
+pub struct SubgraphMatch {
+    pub nodes: NodeMap<NodeId>,
+    pub edges: EdgeMap<EdgeId>
+}
+
+type Matches<'a, A, B> = &'a mut dyn FnMut(&A, &B) -> bool;
+
+struct Search<'a, N1, E1, N2, E2, Ty> {
+    pattern: &'a Graph<N1, E1, Ty>,
+    target: &'a Graph<N2, E2, Ty>,
+    node_match: Matches<'a, N1, N2>,
+    edge_match: Matches<'a, E1, E2>,
+    progress: &'a mut dyn FnMut(usize) -> Control,
+    aborted: bool,
+    order: Vec<NodeId>,
+    nodes: NodeMap<NodeId>,
+    edges: Vec<(EdgeId, EdgeId)>,
+    used_nodes: FixedBitSet,
+    used_edges: FixedBitSet,
+    found: Vec<SubgraphMatch>
+}
+
+impl<N1, E1, N2, E2, Ty: EdgeType> Search<'_, N1, E1, N2, E2, Ty> {
+    fn search(&mut self, depth: usize) {
+        if self.aborted || matches!((self.progress)(self.found.len()), Control::Break(())) {
+            self.aborted = true;
+            return;
+        }
+
+        if depth == self.order.len() {
+            let mut nodes = NodeMap::with_capacity(self.pattern.node_bound());
+            let mut edges = EdgeMap::with_capacity(self.pattern.edge_bound());
+
+            for &p in &self.order {
+                nodes.insert(p, self.nodes[p]);
+            }
+
+            for &(p, t) in &self.edges {
+                edges.insert(p, t);
+            }
+
+            self.found.push(SubgraphMatch { nodes, edges });
+            return;
+        }
+
+        let p = self.order[depth];
+        let anchor = undirected_neighbors(self.pattern, p).find(|&q| self.nodes.contains_key(q));
+
+        let mut candidates: Vec<_> = match anchor {
+            Some(q) => undirected_neighbors(self.target, self.nodes[q]).collect(),
+            None => self.target.node_ids().collect()
+        };
+        candidates.sort_unstable_by_key(|c| c.index());
+        candidates.dedup();
+
+        for c in candidates {
+            if self.aborted {
+                return;
+            }
+
+            if self.used_nodes.contains(c.index()) || !self.feasible(p, c) {
+                continue;
+            }
+
+            self.nodes.insert(p, c);
+            self.used_nodes.insert(c.index());
+            let assigned = self.edges.len();
+
+            if self.assign_edges(p) {
+                self.search(depth + 1);
+            }
+
+            for (_, t) in self.edges.drain(assigned..) {
+                self.used_edges.remove(t.index());
+            }
+
+            self.nodes.remove(p);
+            self.used_nodes.remove(c.index());
+        }
+    }
+
+    fn feasible(&mut self, p: NodeId, c: NodeId) -> bool {
+        let degrees = |dir| {
+            self.pattern.neighbors_directed(p, dir).count() <= self.target.neighbors_directed(c, dir).count()
+        };
+
+        degrees(Direction::Outgoing)
+            && (!Ty::is_directed() || degrees(Direction::Incoming))
+            && (self.node_match)(self.pattern.node_weight(p).unwrap(), self.target.node_weight(c).unwrap())
+    }
+
+    // maps the pattern edges between `p` and the nodes placed before it to
+    // distinct unused target edges by bipartite matching, so parallel edges
+    // with differing weights find an assignment whenever there is one
+    fn assign_edges(&mut self, p: NodeId) -> bool {
+        let mut incident = Vec::new();
+
+        for dir in [Direction::Outgoing, Direction::Incoming] {
+            let mut around = self.pattern.neighbors_directed(p, dir);
+
+            while let Some((edge, other)) = around.next_edge() {
+                if self.nodes.contains_key(other) && !incident.contains(&edge) {
+                    incident.push(edge);
+                }
+            }
+
+            if !Ty::is_directed() {
+                break;
+            }
+        }
+
+        let mut candidates = Vec::with_capacity(incident.len());
+
+        for &edge in &incident {
+            let (source, target) = self.pattern.edge_endpoints(edge).unwrap();
+            let (source, target) = (self.nodes[source], self.nodes[target]);
+            let weight = self.pattern.edge_weight(edge).unwrap();
+            let mut around = self.target.neighbors_directed(source, Direction::Outgoing);
+            let mut images = Vec::new();
+
+            while let Some((candidate, other)) = around.next_edge() {
+                if other == target
+                    && !self.used_edges.contains(candidate.index())
+                    && !images.contains(&candidate)
+                    && (self.edge_match)(weight, self.target.edge_weight(candidate).unwrap())
+                {
+                    images.push(candidate);
+                }
+            }
+
+            candidates.push(images);
+        }
+
+        let mut owner = HashMap::new();
+
+        for i in 0..incident.len() {
+            if !augment(i, &candidates, &mut owner, &mut HashSet::new()) {
+                return false;
+            }
+        }
+
+        for (image, i) in owner {
+            self.used_edges.insert(image.index());
+            self.edges.push((incident[i], image));
+        }
+
+        true
+    }
+}
+
+/// Finds a target edge for pattern edge `i` among its `candidates`, moving
+/// the pattern edges that already own one to another of theirs if need be:
+/// one augmenting path step of Kuhn's matching.
+fn augment(
+    i: usize,
+    candidates: &[Vec<EdgeId>],
+    owner: &mut HashMap<EdgeId, usize>,
+    seen: &mut HashSet<EdgeId>
+) -> bool {
+    for &image in &candidates[i] {
+        if !seen.insert(image) {
+            continue;
+        }
+
+        let prev = owner.get(&image).copied();
+
+        if prev.is_none_or(|k| augment(k, candidates, owner, seen)) {
+            owner.insert(image, i);
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Every occurrence of `pattern` in `target` as a subgraph, not
+/// necessarily induced: distinct pattern nodes and edges map to distinct
+/// target nodes and edges, every pattern edge lands on a target edge
+/// between the images of its endpoints, and weights are compared with
+/// `node_match` and `edge_match`. Backtracks VF2-style over the pattern
+/// nodes, each placed next to an already placed neighbor where possible
+/// and pruned by degree. The pattern edges between two placed nodes are
+/// matched to the target edges between their images as a bipartite
+/// assignment, so each node mapping is reported once, with one assignment
+/// of its parallel edges.
+///
+/// Symmetric patterns are reported once per automorphism.
+pub fn subgraph_monomorphisms<N1, E1, N2, E2, Ty, NM, EM>(
+    pattern: &Graph<N1, E1, Ty>,
+    target: &Graph<N2, E2, Ty>,
+    node_match: NM,
+    edge_match: EM
+) -> Vec<SubgraphMatch>
+where
+    Ty: EdgeType,
+    NM: FnMut(&N1, &N2) -> bool,
+    EM: FnMut(&E1, &E2) -> bool
+{
+    let progress = |_| Control::Continue;
+    subgraph_monomorphisms_with_control(pattern, target, node_match, edge_match, progress).unwrap()
+}
+
+/// Same as [`subgraph_monomorphisms`], calling `progress(found)` with the
+/// number of matches so far at every step of the backtracking. `None` if a
+/// call returns [`Control::Break`].
+pub fn subgraph_monomorphisms_with_control<N1, E1, N2, E2, Ty, NM, EM, P>(
+    pattern: &Graph<N1, E1, Ty>,
+    target: &Graph<N2, E2, Ty>,
+    mut node_match: NM,
+    mut edge_match: EM,
+    mut progress: P
+) -> Option<Vec<SubgraphMatch>>
+where
+    Ty: EdgeType,
+    NM: FnMut(&N1, &N2) -> bool,
+    EM: FnMut(&E1, &E2) -> bool,
+    P: FnMut(usize) -> Control
+{
+    // most constrained first: the node with the most placed neighbors,
+    // then the highest degree
+    let mut order = Vec::with_capacity(pattern.node_count());
+    let mut placed = FixedBitSet::with_capacity(pattern.node_bound());
+    let mut links = vec![0; pattern.node_bound()];
+
+    while order.len() < pattern.node_count() {
+        let next = pattern
+            .node_ids()
+            .filter(|n| !placed.contains(n.index()))
+            .max_by_key(|&n| (links[n.index()], pattern.degree(n), usize::MAX - n.index()))
+            .unwrap();
+
+        placed.insert(next.index());
+        order.push(next);
+
+        for n in undirected_neighbors(pattern, next) {
+            links[n.index()] += 1;
+        }
+    }
+
+    let mut search = Search {
+        pattern,
+        target,
+        node_match: &mut node_match,
+        edge_match: &mut edge_match,
+        progress: &mut progress,
+        aborted: false,
+        order,
+        nodes: NodeMap::with_capacity(pattern.node_bound()),
+        edges: Vec::new(),
+        used_nodes: FixedBitSet::with_capacity(target.node_bound()),
+        used_edges: FixedBitSet::with_capacity(target.edge_bound()),
+        found: Vec::new()
+    };
+
+    search.search(0);
+    (!search.aborted).then_some(search.found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    fn graph<Ty: EdgeType>(labels: &str, edges: &[(usize, usize)]) -> Graph<char, (), Ty> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = labels.chars().map(|c| graph.add_node(c)).collect();
+
+        for &(a, b) in edges {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        graph
+    }
+
+    #[test]
+    fn finds_every_occurrence() {
+        let triangle: UnGraph<_, _> = graph("xxx", &[(0, 1), (1, 2), (2, 0)]);
+        let k4: UnGraph<_, _> = graph("xxxx", &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+
+        // four triangles, six automorphisms each
+        let found = subgraph_monomorphisms(&triangle, &k4, |a, b| a == b, |_, _| true);
+        assert_eq!(24, found.len());
+
+        for m in &found {
+            for edge in triangle.edge_ids() {
+                let (a, b) = triangle.edge_endpoints(edge).unwrap();
+                let (x, y) = k4.edge_endpoints(m.edges[edge]).unwrap();
+                assert!((x, y) == (m.nodes[a], m.nodes[b]) || (y, x) == (m.nodes[a], m.nodes[b]));
+            }
+        }
+
+        // not induced: a path matches inside the triangle
+        let path: UnGraph<_, _> = graph("xxx", &[(0, 1), (1, 2)]);
+        assert_eq!(6, subgraph_monomorphisms(&path, &triangle, |a, b| a == b, |_, _| true).len());
+    }
+
+    #[test]
+    fn respects_labels_and_direction() {
+        let pattern: DiGraph<_, _> = graph("ab", &[(0, 1)]);
+        let target: DiGraph<_, _> = graph("abab", &[(0, 1), (3, 2), (2, 1), (0, 3)]);
+
+        let found = subgraph_monomorphisms(&pattern, &target, |a, b| a == b, |_, _| true);
+        assert_eq!(3, found.len());
+        assert!(found.iter().all(|m| m.edges.len() == 1));
+
+        let doubled: DiGraph<_, _> = graph("ab", &[(0, 1), (0, 1)]);
+        let single: DiGraph<_, _> = graph("ab", &[(0, 1)]);
+        assert!(subgraph_monomorphisms(&doubled, &single, |a, b| a == b, |_, _| true).is_empty());
+        assert_eq!(1, subgraph_monomorphisms(&single, &doubled, |a, b| a == b, |_, _| true).len());
+    }
+
+    #[test]
+    fn matches_parallel_edges_by_weight() {
+        // the first target edge fits both pattern edges, the second only one
+        let mut pattern: DiGraph<(), u32> = Graph::new();
+        let [a, b] = [(), ()].map(|_| pattern.add_node(()));
+        let needs: Vec<_> = [1, 2].map(|w| pattern.add_edge(a, b, w)).into();
+
+        let mut target: DiGraph<(), u32> = Graph::new();
+        let [x, y] = [(), ()].map(|_| target.add_node(()));
+        let offers: Vec<_> = [2, 1].map(|w| target.add_edge(x, y, w)).into();
+
+        let found = subgraph_monomorphisms(&pattern, &target, |_, _| true, |need, offer| need <= offer);
+        assert_eq!(1, found.len());
+        assert_eq!((offers[1], offers[0]), (found[0].edges[needs[0]], found[0].edges[needs[1]]));
+
+        let mut cycle: UnGraph<(), u32> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| cycle.add_node(())).collect();
+        for (i, j, w) in [(0, 1, 3), (1, 0, 1), (1, 2, 1), (2, 0, 1)] {
+            cycle.add_edge(n[i], n[j], w);
+        }
+
+        let mut pair: UnGraph<(), u32> = Graph::new();
+        let [c, d] = [(), ()].map(|_| pair.add_node(()));
+        pair.add_edge(c, d, 1);
+        pair.add_edge(c, d, 2);
+
+        // only the doubled edge between n[0] and n[1] fits, both ways round
+        assert_eq!(2, subgraph_monomorphisms(&pair, &cycle, |_, _| true, |need, offer| need <= offer).len());
+    }
+
+    #[test]
+    fn aborts_when_asked() {
+        let triangle: UnGraph<_, _> = graph("xxx", &[(0, 1), (1, 2), (2, 0)]);
+        let k4: UnGraph<_, _> = graph("xxxx", &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+        let search = |limit| {
+            let progress = |found| if found < limit { Control::Continue } else { Control::Break(()) };
+            subgraph_monomorphisms_with_control(&triangle, &k4, |a, b| a == b, |_, _| true, progress)
+        };
+
+        assert!(search(1).is_none());
+        assert_eq!(Some(24), search(25).map(|found| found.len()));
+    }
+}
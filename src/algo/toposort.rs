@@ -0,0 +1,94 @@
+use crate::graph::{DiGraph, Direction, NodeId};
+
+/// Returned when a graph expected to be acyclic is not.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    node: NodeId
+}
+
+impl Cycle {
+    pub(crate) fn new(node: NodeId) -> Cycle {
+        Cycle { node }
+    }
+
+    /// A node lying on the cycle.
+    pub fn node_id(&self) -> NodeId {
+        self.node
+    }
+}
+
+/// Nodes ordered so that every edge points forward, computed by depth-first
+/// search. Fails with a node on a cycle if there is one.
+pub fn toposort<N, E>(graph: &DiGraph<N, E>) -> Result<Vec<NodeId>, Cycle> {
+    const NEW: u8 = 0;
+    const ACTIVE: u8 = 1;
+    const DONE: u8 = 2;
+
+    let mut state = vec![NEW; graph.node_bound()];
+    let mut order = Vec::with_capacity(graph.node_count());
+    let mut stack = Vec::new();
+
+    for start in graph.node_ids() {
+        if state[start.index()] != NEW {
+            continue;
+        }
+
+        state[start.index()] = ACTIVE;
+        stack.push((start, graph.neighbors_directed(start, Direction::Outgoing)));
+
+        while let Some((node, successors)) = stack.last_mut() {
+            match successors.next() {
+                Some(next) if state[next.index()] == ACTIVE => return Err(Cycle::new(next)),
+                Some(next) if state[next.index()] == NEW => {
+                    state[next.index()] = ACTIVE;
+                    stack.push((next, graph.neighbors_directed(next, Direction::Outgoing)));
+                },
+                Some(_) => (),
+                None => {
+                    state[node.index()] = DONE;
+                    order.push(*node);
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    order.reverse();
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    #[test]
+    fn edges_point_forward() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(5, 2), (5, 0), (4, 0), (4, 1), (2, 3), (3, 1)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        let order = toposort(&graph).unwrap();
+        let mut pos = [0; 6];
+
+        for (i, node) in order.iter().enumerate() {
+            pos[node.index()] = i;
+        }
+
+        assert_eq!(6, order.len());
+        assert!(graph.edge_ids().all(|e| {
+            let (s, t) = graph.edge_endpoints(e).unwrap();
+            pos[s.index()] < pos[t.index()]
+        }));
+
+        graph.add_edge(n[1], n[2], ());
+
+        let cycle = toposort(&graph).unwrap_err();
+
+        assert!([n[1], n[2], n[3]].contains(&cycle.node_id()));
+    }
+}
@@ -0,0 +1,150 @@
+use crate::graph::{EdgeType, Graph, NodeId};
+
+/// Number of walks of one length between every pair of nodes, from
+/// [`count_walks`] or [`count_walks_mod`]. Walks may repeat nodes and
+/// edges, and parallel edges make distinct walks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WalkCounts<T> {
+    bound: usize,
+    counts: Vec<T>
+}
+
+impl<T: Copy + Default> WalkCounts<T> {
+    /// Walks starting at `from` and ending at `to`.
+    pub fn between(&self, from: NodeId, to: NodeId) -> T {
+        if from.index() < self.bound && to.index() < self.bound {
+            self.counts[from.index() * self.bound + to.index()]
+        } else {
+            T::default()
+        }
+    }
+}
+
+/// Walks of exactly `len` edges between all pairs of nodes, by raising the
+/// adjacency matrix to the power `len` with repeated squaring, in
+/// `O(n³ log len)`. Undirected edges can be walked both ways. `None` if a
+/// count, or a product on the way to one, does not fit in a `u128`;
+/// [`count_walks_mod`] works for any size.
+pub fn count_walks<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, len: u32) -> Option<WalkCounts<u128>> {
+    power(graph, len, 1, |acc, a, b| acc.checked_add(a.checked_mul(b)?))
+}
+
+/// Same as [`count_walks`], with every count taken modulo `modulus`, which
+/// never overflows. Panics if `modulus` is zero.
+pub fn count_walks_mod<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    len: u32,
+    modulus: u64
+) -> WalkCounts<u64> {
+    assert!(modulus > 0, "modulus must not be zero");
+
+    let modulus = modulus as u128;
+    let counts = power(graph, len, 1 % modulus, |acc, a, b| Some((acc + a * b % modulus) % modulus));
+    let counts = counts.unwrap();
+
+    WalkCounts { bound: counts.bound, counts: counts.counts.into_iter().map(|c| c as u64).collect() }
+}
+
+/// Adjacency matrix of `graph` to the power `len`, where `mul_add(acc, a,
+/// b)` is `acc + a * b` or `None` on overflow.
+fn power<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    mut len: u32,
+    one: u128,
+    mul_add: impl Fn(u128, u128, u128) -> Option<u128>
+) -> Option<WalkCounts<u128>> {
+    let n = graph.node_bound();
+    let mut base = vec![0; n * n];
+    let mut result = vec![0; n * n];
+
+    for node in graph.node_ids() {
+        result[node.index() * n + node.index()] = one;
+    }
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+        base[a.index() * n + b.index()] = mul_add(base[a.index() * n + b.index()], one, one)?;
+
+        if !Ty::is_directed() && a != b {
+            base[b.index() * n + a.index()] = mul_add(base[b.index() * n + a.index()], one, one)?;
+        }
+    }
+
+    let multiply = |x: &[u128], y: &[u128]| -> Option<Vec<u128>> {
+        let mut product = vec![0; n * n];
+
+        for i in 0..n {
+            for k in (0..n).filter(|&k| x[i * n + k] != 0) {
+                for j in 0..n {
+                    product[i * n + j] = mul_add(product[i * n + j], x[i * n + k], y[k * n + j])?;
+                }
+            }
+        }
+
+        Some(product)
+    };
+
+    while len > 0 {
+        if len & 1 == 1 {
+            result = multiply(&result, &base)?;
+        }
+
+        len >>= 1;
+
+        if len > 0 {
+            base = multiply(&base, &base)?;
+        }
+    }
+
+    Some(WalkCounts { bound: n, counts: result })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn counts_walks_by_length() {
+        let mut cycle: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| cycle.add_node(())).collect();
+
+        for i in 0..4 {
+            cycle.add_edge(n[i], n[(i + 1) % 4], ());
+        }
+
+        let zero = count_walks(&cycle, 0).unwrap();
+        assert_eq!((1, 0), (zero.between(n[0], n[0]), zero.between(n[0], n[1])));
+
+        // closed walks of length 2k on a 4-cycle: 2^(2k-1)
+        let four = count_walks(&cycle, 4).unwrap();
+        assert_eq!((8, 0), (four.between(n[0], n[0]), four.between(n[0], n[1])));
+        assert_eq!(8, four.between(n[0], n[2]));
+
+        let mut path: DiGraph<(), ()> = Graph::new();
+        let [a, b] = [(), ()].map(|_| path.add_node(()));
+        path.add_edge(a, b, ());
+        path.add_edge(a, b, ());
+
+        let one = count_walks(&path, 1).unwrap();
+        assert_eq!((2, 0), (one.between(a, b), one.between(b, a)));
+        assert_eq!(0, count_walks(&path, 2).unwrap().between(a, b));
+    }
+
+    #[test]
+    fn overflow_is_reported_or_reduced() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let node = graph.add_node(());
+
+        for _ in 0..3 {
+            graph.add_edge(node, node, ());
+        }
+
+        // 3^81 does not fit in a u128
+        assert_eq!(Some(3u128.pow(80)), count_walks(&graph, 80).map(|counts| counts.between(node, node)));
+        assert!(count_walks(&graph, 81).is_none());
+        assert_eq!(803, count_walks_mod(&graph, 81, 1000).between(node, node));
+        assert_eq!(0, count_walks_mod(&graph, 5, 1).between(node, node));
+    }
+}
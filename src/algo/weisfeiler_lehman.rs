@@ -0,0 +1,165 @@
+use std::hash::{Hash, Hasher};
+
+use crate::attr::NodeMap;
+use crate::fnv::Fnv;
+use crate::graph::{Direction, EdgeType, Graph, NodeId};
+
+// hashes `head` and each group, length first, as fixed-width integers
+fn hash_labels(head: u64, groups: &[&[u64]]) -> u64 {
+    let mut fnv = Fnv::new();
+
+    fnv.write_u64(head);
+
+    for group in groups {
+        fnv.write_u64(group.len() as u64);
+        group.iter().for_each(|&x| fnv.write_u64(x));
+    }
+
+    fnv.finish()
+}
+
+/// Result of [`weisfeiler_lehman`].
+pub struct WeisfeilerLehman {
+    hash: u64,
+    node_hashes: NodeMap<Vec<u64>>
+}
+
+impl WeisfeilerLehman {
+    /// Hash of the whole graph. Isomorphic graphs always hash alike; graphs
+    /// hashing alike are merely likely to be isomorphic.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Hash of the subtree of depth `i` rooted at `node` for every
+    /// iteration `i`, starting with the hash of the node's own label.
+    pub fn node_hashes(&self, node: NodeId) -> Option<&[u64]> {
+        self.node_hashes.get(node).map(Vec::as_slice)
+    }
+}
+
+/// Weisfeiler–Lehman refinement of node labels: each iteration rehashes
+/// every node's label together with the sorted labels of its neighbors,
+/// with outgoing and incoming neighbors kept apart in directed graphs. The
+/// graph hash combines the label histograms of all iterations. Edge
+/// weights are ignored.
+///
+/// Hashing is FNV-1a over fixed-width little-endian integers, so the
+/// structure hashes alike on every platform; node labels contribute what
+/// their `Hash` impl writes, which is only as stable as that impl.
+pub fn weisfeiler_lehman<N, E, Ty, L, F>(
+    graph: &Graph<N, E, Ty>,
+    iterations: usize,
+    mut node_label: F
+) -> WeisfeilerLehman
+where
+    Ty: EdgeType,
+    L: Hash,
+    F: FnMut(&N) -> L
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let mut labels = vec![0; graph.node_bound()];
+    let mut node_hashes = NodeMap::with_capacity(graph.node_bound());
+    let mut histograms = Vec::with_capacity(iterations + 1);
+
+    for &node in &nodes {
+        let mut fnv = Fnv::new();
+
+        node_label(graph.node_weight(node).unwrap()).hash(&mut fnv);
+        labels[node.index()] = fnv.finish();
+        node_hashes.insert(node, Vec::with_capacity(iterations + 1));
+    }
+
+    for round in 0..=iterations {
+        if round > 0 {
+            let mut next = vec![0; graph.node_bound()];
+
+            for &node in &nodes {
+                let around = |dir| {
+                    let mut around: Vec<_> =
+                        graph.neighbors_directed(node, dir).map(|n| labels[n.index()]).collect();
+                    around.sort_unstable();
+                    around
+                };
+
+                let incoming = if Ty::is_directed() { around(Direction::Incoming) } else { Vec::new() };
+
+                let outgoing = around(Direction::Outgoing);
+                next[node.index()] = hash_labels(labels[node.index()], &[&outgoing, &incoming]);
+            }
+
+            labels = next;
+        }
+
+        let mut histogram: Vec<_> = nodes.iter().map(|n| labels[n.index()]).collect();
+        histogram.sort_unstable();
+        histograms.push(hash_labels(round as u64, &[&histogram]));
+
+        for &node in &nodes {
+            node_hashes[node].push(labels[node.index()]);
+        }
+    }
+
+    WeisfeilerLehman { hash: hash_labels(Ty::is_directed() as u64, &[&histograms]), node_hashes }
+}
+
+/// Hash of an unlabelled graph after `iterations` rounds of
+/// [`weisfeiler_lehman`] refinement, for pre-filtering isomorphism tests
+/// and deduplicating graphs.
+pub fn weisfeiler_lehman_hash<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, iterations: usize) -> u64 {
+    weisfeiler_lehman(graph, iterations, |_| ()).hash()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    fn graph<Ty: EdgeType>(labels: &str, edges: &[(usize, usize)]) -> Graph<char, (), Ty> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = labels.chars().map(|c| graph.add_node(c)).collect();
+
+        for &(a, b) in edges {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        graph
+    }
+
+    #[test]
+    fn isomorphic_graphs_hash_alike() {
+        let a: UnGraph<char, ()> = graph("abcd", &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)]);
+        let b: UnGraph<char, ()> = graph("dcba", &[(3, 1), (2, 1), (0, 1), (3, 2), (0, 3)]);
+        let star: UnGraph<char, ()> = graph("abcd", &[(0, 1), (0, 2), (0, 3)]);
+        let path: UnGraph<char, ()> = graph("abcd", &[(0, 1), (1, 2), (2, 3)]);
+
+        assert_eq!(weisfeiler_lehman_hash(&a, 3), weisfeiler_lehman_hash(&b, 3));
+        assert_ne!(weisfeiler_lehman_hash(&star, 3), weisfeiler_lehman_hash(&path, 3));
+        assert_eq!(weisfeiler_lehman_hash(&star, 0), weisfeiler_lehman_hash(&path, 0));
+
+        let labelled = weisfeiler_lehman(&path, 2, |&c| c == 'a');
+        let flipped: UnGraph<char, ()> = graph("dcba", &[(0, 1), (1, 2), (2, 3)]);
+
+        assert_ne!(labelled.hash(), weisfeiler_lehman(&flipped, 2, |&c| c == 'b').hash());
+        assert_eq!(labelled.hash(), weisfeiler_lehman(&flipped, 2, |&c| c == 'd').hash());
+    }
+
+    #[test]
+    fn node_hashes_tell_apart_roles() {
+        let path: DiGraph<char, ()> = graph("abc", &[(0, 1), (1, 2)]);
+        let reversed: DiGraph<char, ()> = graph("abc", &[(1, 0), (2, 1)]);
+        let result = weisfeiler_lehman(&path, 2, |_| 0);
+        let hashes = |i| result.node_hashes(NodeId::new(i)).unwrap();
+
+        assert_eq!(3, hashes(0).len());
+        assert_eq!(hashes(0)[0], hashes(2)[0]);
+        assert_ne!(hashes(0)[1], hashes(2)[1]);
+        assert_eq!(weisfeiler_lehman_hash(&path, 2), weisfeiler_lehman_hash(&reversed, 2));
+
+        let undirected: UnGraph<char, ()> = graph("abc", &[(0, 1), (1, 2)]);
+        let result = weisfeiler_lehman(&undirected, 2, |_| 0);
+
+        assert_eq!(result.node_hashes(NodeId::new(0)), result.node_hashes(NodeId::new(2)));
+        assert_ne!(weisfeiler_lehman_hash(&path, 2), weisfeiler_lehman_hash(&undirected, 2));
+    }
+}
@@ -0,0 +1,468 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::algo::{connected_components, neighborhood};
+use crate::attr::NodeMap;
+use crate::graph::{Direction, EdgeType, Graph, NodeId};
+use crate::io::JsonValue;
+use crate::rng::{Rng, SplitMix64};
+
+/// Overview of a graph as computed by [`summary`]. Degrees are those of
+/// [`Graph::degree`], so out-degrees for directed graphs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphSummary {
+    pub nodes: usize,
+    pub edges: usize,
+    pub directed: bool,
+    /// Edges over the most edges possible without loops or parallel edges.
+    pub density: f64,
+    pub min_degree: usize,
+    pub max_degree: usize,
+    pub mean_degree: f64,
+    /// Connected components, weakly for directed graphs.
+    pub components: usize,
+    /// Mean local clustering coefficient, ignoring direction.
+    pub clustering: f64,
+    /// Longest shortest path in hops between nodes joined by a path, if
+    /// requested.
+    pub diameter: Option<usize>,
+    /// Whether `diameter` is exact rather than a lower bound.
+    pub diameter_exact: bool
+}
+
+impl GraphSummary {
+    pub fn to_json(&self) -> JsonValue {
+        let diameter = self.diameter.map_or(JsonValue::Null, JsonValue::from);
+
+        JsonValue::Object(vec![
+            ("nodes".to_string(), self.nodes.into()),
+            ("edges".to_string(), self.edges.into()),
+            ("directed".to_string(), self.directed.into()),
+            ("density".to_string(), self.density.into()),
+            ("min_degree".to_string(), self.min_degree.into()),
+            ("max_degree".to_string(), self.max_degree.into()),
+            ("mean_degree".to_string(), self.mean_degree.into()),
+            ("components".to_string(), self.components.into()),
+            ("clustering".to_string(), self.clustering.into()),
+            ("diameter".to_string(), diameter),
+            ("diameter_exact".to_string(), self.diameter_exact.into())
+        ])
+    }
+}
+
+impl fmt::Display for GraphSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "nodes: {}", self.nodes)?;
+        writeln!(f, "edges: {}", self.edges)?;
+        writeln!(f, "directed: {}", self.directed)?;
+        writeln!(f, "density: {:.4}", self.density)?;
+        let (min, max, mean) = (self.min_degree, self.max_degree, self.mean_degree);
+
+        writeln!(f, "degree: min {min}, max {max}, mean {mean:.2}")?;
+        writeln!(f, "components: {}", self.components)?;
+        writeln!(f, "clustering: {:.4}", self.clustering)?;
+
+        match (self.diameter, self.diameter_exact) {
+            (None, _) => Ok(()),
+            (Some(d), true) => writeln!(f, "diameter: {d}"),
+            (Some(d), false) => writeln!(f, "diameter: at least {d}")
+        }
+    }
+}
+
+/// Counts, density, degree statistics, components and clustering of
+/// `graph`, leaving out the diameter.
+pub fn summary<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> GraphSummary {
+    let (n, m) = (graph.node_count(), graph.edge_count());
+    let degrees: Vec<_> = graph.node_ids().map(|node| graph.degree(node)).collect();
+    let pairs = (n * n.saturating_sub(1)) as f64 / if Ty::is_directed() { 1.0 } else { 2.0 };
+    let local: f64 = graph.node_ids().map(|node| clustering_coefficient(graph, node)).sum();
+
+    GraphSummary {
+        nodes: n,
+        edges: m,
+        directed: Ty::is_directed(),
+        density: if pairs > 0.0 { m as f64 / pairs } else { 0.0 },
+        min_degree: degrees.iter().copied().min().unwrap_or(0),
+        max_degree: degrees.iter().copied().max().unwrap_or(0),
+        mean_degree: if n > 0 { degrees.iter().sum::<usize>() as f64 / n as f64 } else { 0.0 },
+        components: connected_components(graph).len(),
+        clustering: if n > 0 { local / n as f64 } else { 0.0 },
+        diameter: None,
+        diameter_exact: false
+    }
+}
+
+/// Same as [`summary`], also bounding the diameter with at most `searches`
+/// breadth-first searches. Each search starts from the node found farthest
+/// by the previous ones, which often meets the diameter early; the value is
+/// exact if every node got searched.
+pub fn summary_with_diameter<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    searches: usize
+) -> GraphSummary {
+    let mut summary = summary(graph);
+    let mut searched = vec![false; graph.node_bound()];
+    let mut diameter = 0;
+    let mut runs = 0;
+    let mut next = graph.node_ids().next();
+
+    while let Some(start) = next.filter(|_| runs < searches) {
+        searched[start.index()] = true;
+        runs += 1;
+
+        let (far, hops) = farthest(graph, start);
+        diameter = diameter.max(hops);
+
+        // an unsearched far end, or else any unsearched node
+        next = Some(far)
+            .filter(|far| !searched[far.index()])
+            .or_else(|| graph.node_ids().find(|node| !searched[node.index()]));
+    }
+
+    summary.diameter = (runs > 0 || next.is_none()).then_some(diameter);
+    summary.diameter_exact = next.is_none();
+    summary
+}
+
+/// Node farthest from `start` in hops and its distance.
+fn farthest<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, start: NodeId) -> (NodeId, usize) {
+    let mut dist = vec![usize::MAX; graph.node_bound()];
+    let mut queue = VecDeque::from([start]);
+    let mut far = (start, 0);
+
+    dist[start.index()] = 0;
+
+    while let Some(node) = queue.pop_front() {
+        if dist[node.index()] > far.1 {
+            far = (node, dist[node.index()]);
+        }
+
+        for next in graph.neighbors(node) {
+            if dist[next.index()] == usize::MAX {
+                dist[next.index()] = dist[node.index()] + 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    far
+}
+
+/// Share of the pairs of neighbors of `node` that are adjacent themselves,
+/// ignoring direction, loops and parallel edges; zero below two neighbors.
+pub fn clustering_coefficient<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, node: NodeId) -> f64 {
+    let around = neighborhood(graph, node);
+    let k = around.len();
+
+    if k < 2 {
+        return 0.0;
+    }
+
+    let links: usize = around
+        .iter()
+        .map(|&a| {
+            let theirs = neighborhood(graph, a);
+            theirs.iter().filter(|b| **b > a && around.binary_search(b).is_ok()).count()
+        })
+        .sum();
+
+    links as f64 / (k * (k - 1) / 2) as f64
+}
+
+/// Pearson correlation between the degrees at the two ends of each edge:
+/// positive when hubs link to hubs, negative when they link to leaves.
+/// Directed graphs pair the source's out-degree with the target's
+/// in-degree; undirected edges count once each way. `None` without edges
+/// or when all those degrees agree.
+pub fn degree_assortativity<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Option<f64> {
+    let mut pairs = Vec::with_capacity(graph.edge_count() * 2);
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+        if Ty::is_directed() {
+            pairs.push((graph.degree(a), graph.neighbors_directed(b, Direction::Incoming).count()));
+        } else {
+            pairs.push((graph.degree(a), graph.degree(b)));
+            pairs.push((graph.degree(b), graph.degree(a)));
+        }
+    }
+
+    let count = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0 as f64).sum::<f64>() / count;
+    let mean_y = pairs.iter().map(|p| p.1 as f64).sum::<f64>() / count;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+
+    for &(x, y) in &pairs {
+        let (dx, dy) = (x as f64 - mean_x, y as f64 - mean_y);
+
+        cov += dx * dy;
+        var_x += dx * dx;
+        var_y += dy * dy;
+    }
+
+    let r = cov / (var_x * var_y).sqrt();
+    r.is_finite().then_some(r)
+}
+
+/// Mean degree of the neighbors of every node, in the sense of
+/// [`Graph::neighbors`] and [`Graph::degree`], so over successors and
+/// their out-degrees for directed graphs. Parallel edges count each time;
+/// nodes without neighbors get zero.
+pub fn average_neighbor_degree<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> NodeMap<f64> {
+    let mut averages = NodeMap::with_capacity(graph.node_bound());
+
+    for node in graph.node_ids() {
+        let (count, total) = graph
+            .neighbors(node)
+            .fold((0, 0), |(count, total), next| (count + 1, total + graph.degree(next)));
+        averages.insert(node, if count > 0 { total as f64 / count as f64 } else { 0.0 });
+    }
+
+    averages
+}
+
+/// Approximate neighborhood function from [`approximate_neighborhood_function`]:
+/// `pairs[t]` estimates how many ordered pairs of nodes, each node paired
+/// with itself included, lie within `t` steps of each other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeighborhoodFunction {
+    pub pairs: Vec<f64>,
+    /// Whether the counters stopped changing, so that the last entry
+    /// counts every pair joined by a path.
+    pub converged: bool
+}
+
+impl NeighborhoodFunction {
+    /// Estimated share of the connected pairs at each distance, from zero.
+    pub fn distance_distribution(&self) -> Vec<f64> {
+        let total = self.pairs.last().copied().unwrap_or(0.0);
+        let mut previous = 0.0;
+
+        self.pairs
+            .iter()
+            .map(|&within| {
+                let share = if total > 0.0 { (within - previous).max(0.0) / total } else { 0.0 };
+                previous = within;
+                share
+            })
+            .collect()
+    }
+
+    /// Mean distance between distinct connected nodes.
+    pub fn average_distance(&self) -> f64 {
+        let distribution = self.distance_distribution();
+        let beyond_zero: f64 = distribution.iter().skip(1).sum();
+        let total: f64 = distribution.iter().enumerate().map(|(t, share)| t as f64 * share).sum();
+
+        if beyond_zero > 0.0 { total / beyond_zero } else { 0.0 }
+    }
+
+    /// Distance within which a share `quantile` of the connected pairs lie,
+    /// interpolated between steps; 0.9 gives the usual effective diameter.
+    pub fn effective_diameter(&self, quantile: f64) -> f64 {
+        let target = quantile * self.pairs.last().copied().unwrap_or(0.0);
+        let Some(t) = self.pairs.iter().position(|&within| within >= target) else {
+            return 0.0;
+        };
+
+        if t == 0 {
+            return 0.0;
+        }
+
+        let (low, high) = (self.pairs[t - 1], self.pairs[t]);
+        (t - 1) as f64 + if high > low { (target - low) / (high - low) } else { 1.0 }
+    }
+}
+
+/// Adds `item` to the HyperLogLog counter held in `registers`.
+fn hll_insert(registers: &mut [u8], bits: u32, item: u64) {
+    let hash = SplitMix64::new(item).next_u64();
+    let register = (hash >> (64 - bits)) as usize;
+    let rank = ((hash << bits) | (1 << (bits - 1))).leading_zeros() + 1;
+
+    registers[register] = registers[register].max(rank as u8);
+}
+
+/// Estimated number of distinct items added to `registers`, with the usual
+/// linear counting correction for small counts.
+fn hll_estimate(registers: &[u8]) -> f64 {
+    let m = registers.len() as f64;
+    let alpha = 0.7213 / (1.0 + 1.079 / m);
+    let sum: f64 = registers.iter().map(|&r| 2f64.powi(-i32::from(r))).sum();
+    let raw = alpha * m * m / sum;
+    let zeros = registers.iter().filter(|&&r| r == 0).count();
+
+    if raw <= 2.5 * m && zeros > 0 { m * (m / zeros as f64).ln() } else { raw }
+}
+
+/// HyperANF (Boldi, Rosa and Vigna): estimates the neighborhood function
+/// with one HyperLogLog counter of `2^precision` registers per node,
+/// merging the counters of neighbors along [`Graph::neighbors`] once per
+/// step for up to `max_steps` steps. Each step is a linear pass over the
+/// edges, against a breadth-first search from every node for the exact
+/// answer. The relative error of each count is about
+/// `1.04 / 2^(precision / 2)`, and memory is `2^precision` bytes a node.
+/// Panics unless `4 <= precision <= 16`.
+pub fn approximate_neighborhood_function<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    precision: u32,
+    max_steps: usize
+) -> NeighborhoodFunction {
+    assert!((4..=16).contains(&precision), "precision must be between 4 and 16");
+
+    let m = 1 << precision;
+    let mut counters = vec![0u8; graph.node_bound() * m];
+
+    for node in graph.node_ids() {
+        let at = node.index() * m;
+        hll_insert(&mut counters[at..at + m], precision, node.index() as u64);
+    }
+
+    let total = |counters: &[u8]| -> f64 {
+        graph.node_ids().map(|node| hll_estimate(&counters[node.index() * m..][..m])).sum()
+    };
+
+    let mut pairs = vec![total(&counters)];
+    let mut converged = false;
+
+    while pairs.len() <= max_steps {
+        let mut next = counters.clone();
+        let mut changed = false;
+
+        for node in graph.node_ids() {
+            for other in graph.neighbors(node) {
+                let (to, from) = (node.index() * m, other.index() * m);
+
+                for j in 0..m {
+                    if counters[from + j] > next[to + j] {
+                        next[to + j] = counters[from + j];
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        if !changed {
+            converged = true;
+            break;
+        }
+
+        counters = next;
+        pairs.push(total(&counters));
+    }
+
+    NeighborhoodFunction { pairs, converged }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    // a triangle with a tail, plus an isolated node
+    fn sample() -> (UnGraph<(), ()>, Vec<NodeId>) {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 1), (1, 2), (2, 0), (2, 3)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        (graph, n)
+    }
+
+    #[test]
+    fn summarizes_counts_and_structure() {
+        let (graph, n) = sample();
+        let summary = summary(&graph);
+
+        assert_eq!((5, 4, 2), (summary.nodes, summary.edges, summary.components));
+        assert_eq!(0.4, summary.density);
+        assert_eq!((0, 3, 1.6), (summary.min_degree, summary.max_degree, summary.mean_degree));
+        assert_eq!(1.0 / 3.0, clustering_coefficient(&graph, n[2]));
+        assert!((summary.clustering - (2.0 + 1.0 / 3.0) / 5.0).abs() < 1e-12);
+        assert_eq!(None, summary.diameter);
+
+        let empty = super::summary(&DiGraph::<(), ()>::new());
+        assert_eq!((0.0, 0.0, 0), (empty.density, empty.clustering, empty.max_degree));
+    }
+
+    #[test]
+    fn bounds_the_diameter() {
+        let (graph, _) = sample();
+
+        let exact = summary_with_diameter(&graph, 10);
+        assert_eq!((Some(2), true), (exact.diameter, exact.diameter_exact));
+
+        let bounded = summary_with_diameter(&graph, 2);
+        assert_eq!((Some(2), false), (bounded.diameter, bounded.diameter_exact));
+        assert!(bounded.to_string().ends_with("diameter: at least 2\n"));
+
+        let json = bounded.to_json();
+        assert_eq!(Some(2.0), json.get("diameter").and_then(JsonValue::as_f64));
+        assert_eq!(Some(false), json.get("diameter_exact").and_then(JsonValue::as_bool));
+        let parsed = JsonValue::parse(&json.to_string()).unwrap();
+        assert_eq!(Some(4.0), parsed.get("edges").and_then(JsonValue::as_f64));
+    }
+
+    #[test]
+    fn measures_degree_correlation() {
+        let mut star: UnGraph<(), ()> = Graph::new();
+        let hub = star.add_node(());
+        let leaves: Vec<_> = (0..4).map(|_| star.add_node(())).collect();
+
+        for &leaf in &leaves {
+            star.add_edge(hub, leaf, ());
+        }
+
+        assert_eq!(Some(-1.0), degree_assortativity(&star));
+
+        let neighbors = average_neighbor_degree(&star);
+        assert_eq!((1.0, 4.0), (neighbors[hub], neighbors[leaves[0]]));
+
+        let mut path: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| path.add_node(())).collect();
+
+        for pair in n.windows(2) {
+            path.add_edge(pair[0], pair[1], ());
+        }
+
+        assert!((degree_assortativity(&path).unwrap() + 0.5).abs() < 1e-12);
+
+        // every edge end has degree 2
+        let mut square: UnGraph<(), ()> = Graph::new();
+        let c: Vec<_> = (0..4).map(|_| square.add_node(())).collect();
+
+        for i in 0..4 {
+            square.add_edge(c[i], c[(i + 1) % 4], ());
+        }
+
+        assert_eq!(None, degree_assortativity(&square));
+        assert_eq!(None, degree_assortativity(&UnGraph::<(), ()>::new()));
+    }
+
+    #[test]
+    fn approximates_the_neighborhood_function() {
+        // a path of 40 nodes: 40 + 2 * (39 + 38 + ... + (40 - t)) pairs within t
+        let mut path: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..40).map(|_| path.add_node(())).collect();
+
+        for pair in n.windows(2) {
+            path.add_edge(pair[0], pair[1], ());
+        }
+
+        let anf = approximate_neighborhood_function(&path, 10, 100);
+        let exact = |t: usize| (40 + 2 * (1..=t).map(|d| 40 - d).sum::<usize>()) as f64;
+
+        assert!(anf.converged);
+        assert_eq!(40, anf.pairs.len());
+        assert!((0..40).all(|t| (anf.pairs[t] - exact(t)).abs() <= 0.05 * exact(t)));
+        assert!((anf.average_distance() - 41.0 / 3.0).abs() < 1.0);
+        assert!((anf.effective_diameter(0.9) - 27.1).abs() < 2.0);
+
+        let short = approximate_neighborhood_function(&path, 4, 3);
+        assert_eq!((4, false), (short.pairs.len(), short.converged));
+    }
+}
@@ -0,0 +1,405 @@
+#[cfg(feature = "proptest")]
+use std::fmt;
+#[cfg(feature = "proptest")]
+use std::marker::PhantomData;
+#[cfg(feature = "proptest")]
+use std::mem;
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "proptest")]
+use proptest::arbitrary::{any, Arbitrary, StrategyFor};
+#[cfg(feature = "proptest")]
+use proptest::prelude::Rng as _;
+#[cfg(feature = "proptest")]
+use proptest::strategy::{NewTree, Strategy, ValueTree};
+#[cfg(feature = "proptest")]
+use proptest::test_runner::{Reason, TestRunner};
+
+use crate::algo::connected_components;
+use crate::bitset::FixedBitSet;
+use crate::graph::{EdgeType, Graph};
+use crate::rng::Rng;
+#[cfg(feature = "proptest")]
+use crate::rng::SplitMix64;
+
+/// Random graphs for property tests, with shrinking toward the smallest
+/// graph that still fails. Sizes are drawn from a range, every pair of
+/// nodes is joined with probability `density`, and graphs can be required
+/// to be connected, which shrinking then keeps them.
+#[derive(Clone, Debug)]
+pub struct GraphStrategy {
+    nodes: RangeInclusive<usize>,
+    density: f64,
+    connected: bool,
+    self_loops: bool
+}
+
+impl Default for GraphStrategy {
+    fn default() -> Self {
+        GraphStrategy { nodes: 0..=16, density: 0.2, connected: false, self_loops: false }
+    }
+}
+
+impl GraphStrategy {
+    pub fn new() -> GraphStrategy {
+        GraphStrategy::default()
+    }
+
+    /// Node counts to draw from. Panics if the range is empty.
+    pub fn nodes(mut self, nodes: RangeInclusive<usize>) -> Self {
+        assert!(!nodes.is_empty(), "empty range");
+        self.nodes = nodes;
+        self
+    }
+
+    /// Chance of an edge between every pair of nodes, each way for
+    /// directed graphs.
+    pub fn density(mut self, density: f64) -> Self {
+        self.density = density;
+        self
+    }
+
+    /// Only yields weakly connected graphs, starting from a random spanning
+    /// tree.
+    pub fn connected(mut self, connected: bool) -> Self {
+        self.connected = connected;
+        self
+    }
+
+    pub fn self_loops(mut self, self_loops: bool) -> Self {
+        self.self_loops = self_loops;
+        self
+    }
+
+    /// Draws a graph with weights from `node` and `edge`.
+    pub fn generate<N, E, Ty, R>(
+        &self,
+        rng: &mut R,
+        mut node: impl FnMut(&mut R) -> N,
+        mut edge: impl FnMut(&mut R) -> E
+    ) -> Graph<N, E, Ty>
+    where
+        Ty: EdgeType,
+        R: Rng
+    {
+        let n = self.nodes.start() + rng.below(self.nodes.end() - self.nodes.start() + 1);
+        let mut graph = Graph::with_capacity(n, 0);
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(node(rng))).collect();
+        let mut joined = FixedBitSet::with_capacity(n * n);
+
+        if self.connected {
+            let mut order = nodes.clone();
+            rng.shuffle(&mut order);
+
+            for i in 1..n {
+                let (a, b) = (order[rng.below(i)], order[i]);
+                let (a, b) = if rng.chance(0.5) { (a, b) } else { (b, a) };
+
+                joined.insert(a.index() * n + b.index());
+                graph.add_edge(a, b, edge(rng));
+            }
+        }
+
+        for a in 0..n {
+            for b in if Ty::is_directed() { 0 } else { a }..n {
+                let taken = joined.contains(a * n + b) || (!Ty::is_directed() && joined.contains(b * n + a));
+
+                if (a != b || self.self_loops) && !taken && rng.chance(self.density) {
+                    graph.add_edge(nodes[a], nodes[b], edge(rng));
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Graphs one step smaller than `graph` that still meet the
+    /// constraints: first with one node and its edges removed, then with
+    /// one edge removed. Ids of the nodes and edges left are kept.
+    pub fn shrink<N, E, Ty>(&self, graph: &Graph<N, E, Ty>) -> Vec<Graph<N, E, Ty>>
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType
+    {
+        let copy = || graph.map(|_, weight| weight.clone(), |_, weight| weight.clone());
+        let mut smaller = Vec::new();
+
+        if graph.node_count() > *self.nodes.start() {
+            for node in graph.node_ids() {
+                let mut candidate = copy();
+                candidate.remove_node(node);
+                smaller.push(candidate);
+            }
+        }
+
+        for edge in graph.edge_ids() {
+            let mut candidate = copy();
+            candidate.remove_edge(edge);
+            smaller.push(candidate);
+        }
+
+        smaller.retain(|candidate| !self.connected || connected_components(candidate).len() <= 1);
+        smaller
+    }
+
+    /// Shrinks `graph` as long as some smaller graph still makes `fails`
+    /// return `true`, taking the first one each step.
+    pub fn minimize<N, E, Ty>(
+        &self,
+        mut graph: Graph<N, E, Ty>,
+        mut fails: impl FnMut(&Graph<N, E, Ty>) -> bool
+    ) -> Graph<N, E, Ty>
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType
+    {
+        while let Some(next) = self.shrink(&graph).into_iter().find(|candidate| fails(candidate)) {
+            graph = next;
+        }
+
+        graph
+    }
+
+    /// This strategy for proptest, with weights drawn from `node` and `edge`.
+    #[cfg(feature = "proptest")]
+    pub fn proptest<NS, ES, Ty>(self, node: NS, edge: ES) -> Graphs<NS, ES, Ty>
+    where
+        NS: Strategy,
+        ES: Strategy,
+        Ty: EdgeType
+    {
+        Graphs { strategy: self, node, edge, ty: PhantomData }
+    }
+
+    /// Checks `property` on `cases` unweighted graphs, returning the first
+    /// counterexample found, minimized, or `None` if it always held.
+    pub fn check<Ty, R>(
+        &self,
+        cases: usize,
+        rng: &mut R,
+        mut property: impl FnMut(&Graph<(), (), Ty>) -> bool
+    ) -> Option<Graph<(), (), Ty>>
+    where
+        Ty: EdgeType,
+        R: Rng
+    {
+        for _ in 0..cases {
+            let graph = self.generate(rng, |_| (), |_| ());
+
+            if !property(&graph) {
+                return Some(self.minimize(graph, |graph| !property(graph)));
+            }
+        }
+
+        None
+    }
+}
+
+/// [`GraphStrategy`] as a proptest [`Strategy`], made by
+/// [`GraphStrategy::proptest`] or, with weights from their [`Arbitrary`]
+/// impls, by `any_with::<Graph<N, E, Ty>>(strategy)`. Weights are not
+/// shrunk, only the graph, as by [`GraphStrategy::shrink`].
+#[cfg(feature = "proptest")]
+pub struct Graphs<NS, ES, Ty> {
+    strategy: GraphStrategy,
+    node: NS,
+    edge: ES,
+    ty: PhantomData<fn() -> Ty>
+}
+
+#[cfg(feature = "proptest")]
+impl<NS: fmt::Debug, ES: fmt::Debug, Ty> fmt::Debug for Graphs<NS, ES, Ty> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Graphs")
+            .field("strategy", &self.strategy)
+            .field("node", &self.node)
+            .field("edge", &self.edge)
+            .finish()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<NS, ES, Ty> Strategy for Graphs<NS, ES, Ty>
+where
+    NS: Strategy,
+    NS::Value: Clone,
+    ES: Strategy,
+    ES::Value: Clone,
+    Ty: EdgeType
+{
+    type Tree = GraphTree<NS::Value, ES::Value, Ty>;
+    type Value = Graph<NS::Value, ES::Value, Ty>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let mut rng = SplitMix64::new(runner.rng().next_u64());
+        let shape: Graph<(), (), Ty> = self.strategy.generate(&mut rng, |_| (), |_| ());
+        let nodes = (0..shape.node_count())
+            .map(|_| Ok(self.node.new_tree(runner)?.current()))
+            .collect::<Result<Vec<_>, Reason>>()?;
+        let edges = (0..shape.edge_count())
+            .map(|_| Ok(self.edge.new_tree(runner)?.current()))
+            .collect::<Result<Vec<_>, Reason>>()?;
+        let (mut nodes, mut edges) = (nodes.into_iter(), edges.into_iter());
+
+        Ok(GraphTree {
+            strategy: self.strategy.clone(),
+            current: shape.map(|_, _| nodes.next().unwrap(), |_, _| edges.next().unwrap()),
+            candidates: None,
+            previous: None,
+            previous_candidates: Vec::new()
+        })
+    }
+}
+
+/// Graph drawn by [`Graphs`], shrinking one node or edge at a time.
+#[cfg(feature = "proptest")]
+pub struct GraphTree<N, E, Ty> {
+    strategy: GraphStrategy,
+    current: Graph<N, E, Ty>,
+    // smaller graphs left to try, the next one last
+    candidates: Option<Vec<Graph<N, E, Ty>>>,
+    // the graph before the last step, for `complicate` to go back to, and
+    // its candidates left
+    previous: Option<Graph<N, E, Ty>>,
+    previous_candidates: Vec<Graph<N, E, Ty>>
+}
+
+#[cfg(feature = "proptest")]
+impl<N, E, Ty> ValueTree for GraphTree<N, E, Ty>
+where
+    N: Clone + fmt::Debug,
+    E: Clone + fmt::Debug,
+    Ty: EdgeType
+{
+    type Value = Graph<N, E, Ty>;
+
+    fn current(&self) -> Graph<N, E, Ty> {
+        self.current.map(|_, weight| weight.clone(), |_, weight| weight.clone())
+    }
+
+    fn simplify(&mut self) -> bool {
+        let mut candidates = self.candidates.take().unwrap_or_else(|| {
+            let mut smaller = self.strategy.shrink(&self.current);
+            smaller.reverse();
+            smaller
+        });
+
+        match candidates.pop() {
+            Some(next) => {
+                self.previous = Some(mem::replace(&mut self.current, next));
+                self.previous_candidates = candidates;
+                true
+            },
+            None => {
+                self.candidates = Some(candidates);
+                false
+            }
+        }
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.previous.take() {
+            Some(graph) => {
+                self.current = graph;
+                self.candidates = Some(mem::take(&mut self.previous_candidates));
+                true
+            },
+            None => false
+        }
+    }
+}
+
+/// Takes a [`GraphStrategy`] as parameters.
+#[cfg(feature = "proptest")]
+impl<N, E, Ty> Arbitrary for Graph<N, E, Ty>
+where
+    N: Arbitrary + Clone,
+    E: Arbitrary + Clone,
+    Ty: EdgeType
+{
+    type Parameters = GraphStrategy;
+    type Strategy = Graphs<StrategyFor<N>, StrategyFor<E>, Ty>;
+
+    fn arbitrary_with(strategy: GraphStrategy) -> Self::Strategy {
+        strategy.proptest(any::<N>(), any::<E>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::SplitMix64;
+
+    #[test]
+    fn generates_within_constraints() {
+        let mut rng = SplitMix64::new(3);
+        let strategy = GraphStrategy::new().nodes(3..=10).density(0.1).connected(true);
+
+        for _ in 0..50 {
+            let graph: UnGraph<(), ()> = strategy.generate(&mut rng, |_| (), |_| ());
+
+            assert!((3..=10).contains(&graph.node_count()));
+            assert_eq!(1, connected_components(&graph).len());
+            assert!(graph.edge_ids().all(|e| {
+                let (a, b) = graph.edge_endpoints(e).unwrap();
+                a != b
+            }));
+        }
+
+        let full: DiGraph<u8, u8> = GraphStrategy::new()
+            .nodes(4..=4)
+            .density(1.0)
+            .self_loops(true)
+            .generate(&mut rng, |_| 1, |_| 2);
+        assert_eq!(16, full.edge_count());
+    }
+
+    #[test]
+    fn shrinks_counterexamples() {
+        let mut rng = SplitMix64::new(8);
+        let strategy = GraphStrategy::new().nodes(0..=12).density(0.4);
+
+        // false as soon as a node has three neighbors
+        let found = strategy
+            .check(100, &mut rng, |graph: &UnGraph<(), ()>| graph.node_ids().all(|n| graph.degree(n) < 3))
+            .unwrap();
+
+        assert_eq!((4, 3), (found.node_count(), found.edge_count()));
+        assert!(strategy.check(20, &mut rng, |graph: &UnGraph<(), ()>| graph.node_count() <= 12).is_none());
+    }
+
+    #[cfg(feature = "proptest")]
+    mod with_proptest {
+        use proptest::prelude::*;
+        use proptest::test_runner::{TestError, TestRunner};
+
+        use super::*;
+
+        proptest! {
+            #[test]
+            fn arbitrary_graphs_meet_constraints(
+                graph in any_with::<UnGraph<u8, ()>>(GraphStrategy::new().nodes(2..=6).connected(true))
+            ) {
+                prop_assert!((2..=6).contains(&graph.node_count()));
+                prop_assert_eq!(1, connected_components(&graph).len());
+            }
+        }
+
+        #[test]
+        fn proptest_shrinks_by_deleting() {
+            let strategy = GraphStrategy::new().nodes(0..=12).density(0.4).proptest(Just(()), Just(()));
+            let result = TestRunner::deterministic().run(&strategy, |graph: UnGraph<(), ()>| {
+                prop_assert!(graph.node_ids().all(|n| graph.degree(n) < 3));
+                Ok(())
+            });
+
+            match result {
+                Err(TestError::Fail(_, found)) => assert_eq!((4, 3), (found.node_count(), found.edge_count())),
+                other => panic!("expected a counterexample, got {other:?}")
+            }
+        }
+    }
+}
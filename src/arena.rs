@@ -0,0 +1,518 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::mem;
+use std::ops::{Index, IndexMut};
+
+enum Slot<T, Ix> {
+    Free(Option<Ix>),
+    Used(T)
+}
+
+impl<T, Ix> Slot<T, Ix> {
+    fn as_free(&self) -> &Option<Ix> {
+        match self {
+            Slot::Free(pos) => pos,
+            _ => unreachable!("expected free slot")
+        }
+    }
+
+    fn as_used(&self) -> &T {
+        match self {
+            Slot::Used(val) => val,
+            _ => unreachable!("expected used slot")
+        }
+    }
+
+    fn as_used_mut(&mut self) -> &mut T {
+        match self {
+            Slot::Used(val) => val,
+            _ => unreachable!("expected used slot")
+        }
+    }
+
+    fn into_used(self) -> T {
+        match self {
+            Slot::Used(val) => val,
+            _ => unreachable!("expected used slot")
+        }
+    }
+
+    fn is_used(&self) -> bool {
+        matches!(self, Slot::Used(_))
+    }
+}
+
+/// Unsigned integer that slot positions are stored as, by the free list of
+/// an [`Arena`] and e.g. the links of a [`LinkedList`](crate::LinkedList).
+/// Narrower types take less memory and hold fewer slots.
+pub trait IndexType: Copy + Eq + Ord + Hash + Debug {
+    /// Panics if `index` does not fit.
+    fn new(index: usize) -> Self;
+
+    fn index(self) -> usize;
+}
+
+macro_rules! index_type {
+    ($($ty:ty),*) => {
+        $(
+            impl IndexType for $ty {
+                fn new(index: usize) -> $ty {
+                    <$ty>::try_from(index).expect(concat!("index exceeds ", stringify!($ty)))
+                }
+
+                fn index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+index_type!(u16, u32, usize);
+
+/// Index type of arenas, lists and graph ids unless one is named: `u32`,
+/// or `usize` with the `usize-indices` feature, for more than `u32::MAX`
+/// nodes or edges.
+#[cfg(not(feature = "usize-indices"))]
+pub type DefaultIx = u32;
+#[cfg(feature = "usize-indices")]
+pub type DefaultIx = usize;
+
+/// Slot occupancy and heap footprint of a container.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Slots holding a value.
+    pub used: usize,
+    /// Slots freed by removals, reused before new ones are allocated.
+    pub free: usize,
+    /// Most free slots in a row.
+    pub longest_free_run: usize,
+    /// Reserved room for slots beyond those.
+    pub spare: usize,
+    /// Heap bytes held, spare room included.
+    pub bytes: usize
+}
+
+impl MemoryUsage {
+    /// Share of the allocated slots that are free, from 0 to 1.
+    pub fn fragmentation(&self) -> f64 {
+        match self.used + self.free {
+            0 => 0.0,
+            slots => self.free as f64 / slots as f64
+        }
+    }
+}
+
+/// Slots of values addressed by position, with positions stored as `Ix`
+/// in the free list, so an arena holds at most as many slots as `Ix` can
+/// count.
+pub struct Arena<T, Ix = DefaultIx> {
+    len: usize,
+    free: Option<Ix>,
+    slots: Vec<Slot<T, Ix>>,
+    // bumped whenever a slot is freed, so stale handles can be detected
+    generations: Vec<u32>
+}
+
+pub struct ArenaIterator<'a, T, Ix = DefaultIx> {
+    slots: std::iter::Enumerate<std::slice::Iter<'a, Slot<T, Ix>>>
+}
+
+impl<'a, T, Ix> Iterator for ArenaIterator<'a, T, Ix> {
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (pos, slot) in self.slots.by_ref() {
+            if let Slot::Used(val) = slot {
+                return Some((pos, val));
+            }
+        }
+
+        None
+    }
+}
+
+/// Draining iterator of an [`Arena`], see [`Arena::drain`].
+pub struct Drain<'a, T, Ix: IndexType = DefaultIx> {
+    arena: &'a mut Arena<T, Ix>,
+    pos: usize
+}
+
+impl<T, Ix: IndexType> Iterator for Drain<'_, T, Ix> {
+    type Item = (usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slots = &mut self.arena.slots;
+
+        while self.pos < slots.len() {
+            let pos = self.pos;
+            let next = (pos + 1 < slots.len()).then(|| Ix::new(pos + 1));
+
+            self.pos += 1;
+
+            if let Slot::Used(val) = mem::replace(&mut slots[pos], Slot::Free(next)) {
+                self.arena.generations[pos] = self.arena.generations[pos].wrapping_add(1);
+                return Some((pos, val));
+            }
+        }
+
+        None
+    }
+}
+
+impl<T, Ix: IndexType> Drop for Drain<'_, T, Ix> {
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+
+        self.arena.len = 0;
+        self.arena.free = (!self.arena.slots.is_empty()).then(|| Ix::new(0));
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Arena {
+            len: 0,
+            free: None,
+            slots: Vec::new(),
+            generations: Vec::new()
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Arena<T> {
+        Arena {
+            len: 0,
+            free: None,
+            slots: Vec::with_capacity(capacity),
+            generations: Vec::with_capacity(capacity)
+        }
+    }
+}
+
+impl<T, Ix: IndexType> Arena<T, Ix> {
+
+    /// Makes room for `additional` more values, counting free slots, so
+    /// that many inserts will not reallocate.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = additional.saturating_sub(self.slots.len() - self.len);
+
+        self.slots.reserve(needed);
+        self.generations.reserve(needed);
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut run = 0;
+        let mut longest_free_run = 0;
+
+        for slot in &self.slots {
+            run = if slot.is_used() { 0 } else { run + 1 };
+            longest_free_run = longest_free_run.max(run);
+        }
+
+        MemoryUsage {
+            used: self.len,
+            free: self.slots.len() - self.len,
+            longest_free_run,
+            spare: self.slots.capacity() - self.slots.len(),
+            bytes: self.slots.capacity() * mem::size_of::<Slot<T, Ix>>()
+                + self.generations.capacity() * mem::size_of::<u32>()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn slot_count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Generation of the slot at `pos`, which changes every time the slot is
+    /// freed. `None` if the slot is not allocated.
+    pub fn generation(&self, pos: usize) -> Option<u32> {
+        self.generations.get(pos).copied().filter(|_| pos < self.slots.len())
+    }
+
+    pub fn contains(&self, pos: usize) -> bool {
+        self.slots.get(pos).is_some_and(Slot::is_used)
+    }
+
+    pub fn get(&self, pos: usize) -> Option<&T> {
+        match self.slots.get(pos) {
+            Some(Slot::Used(val)) => Some(val),
+            _ => None
+        }
+    }
+
+    pub fn get_mut(&mut self, pos: usize) -> Option<&mut T> {
+        match self.slots.get_mut(pos) {
+            Some(Slot::Used(val)) => Some(val),
+            _ => None
+        }
+    }
+
+    pub fn iter(&self) -> ArenaIterator<'_, T, Ix> {
+        ArenaIterator { slots: self.slots.iter().enumerate() }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (usize, &mut T)> + '_ {
+        self.slots.iter_mut().enumerate().filter_map(|(pos, slot)| match slot {
+            Slot::Used(val) => Some((pos, val)),
+            Slot::Free(_) => None
+        })
+    }
+
+    /// Arena with every value mapped through `f` and the same slots, free
+    /// list and generations, so positions and handles carry over.
+    pub fn map<U>(&self, mut f: impl FnMut(usize, &T) -> U) -> Arena<U, Ix> {
+        let slots = self
+            .slots
+            .iter()
+            .enumerate()
+            .map(|(pos, slot)| match slot {
+                Slot::Used(val) => Slot::Used(f(pos, val)),
+                Slot::Free(next) => Slot::Free(*next)
+            })
+            .collect();
+
+        Arena { len: self.len, free: self.free, slots, generations: self.generations.clone() }
+    }
+
+    pub fn insert(&mut self, val: T) -> usize {
+        let slot = Slot::Used(val);
+
+        self.len += 1;
+
+        match self.free {
+            None => {
+                // positions must fit the free list once freed
+                Ix::new(self.slots.len());
+                self.slots.push(slot);
+
+                // slots given up by `compact` keep their generation
+                if self.generations.len() < self.slots.len() {
+                    self.generations.push(0);
+                }
+
+                self.slots.len() - 1
+            },
+            Some(curr) => {
+                let curr = curr.index();
+
+                self.free = *self.slots[curr].as_free();
+                self.slots[curr] = slot;
+                curr
+            }
+        }
+    }
+
+    /// Removes every value, yielding them by position. All slots stay
+    /// allocated and are reused from the first one on; the rest go when
+    /// the iterator is dropped.
+    pub fn drain(&mut self) -> Drain<'_, T, Ix> {
+        Drain { arena: self, pos: 0 }
+    }
+
+    /// Moves every value to the front, keeping their order, and gives up
+    /// the slots left at the end. `moved(old, new)` is called for every
+    /// value that changes position, in order. Slots a value leaves get a
+    /// new generation, so handles to them go stale instead of seeing
+    /// whatever moves in.
+    pub fn compact(&mut self, mut moved: impl FnMut(usize, usize)) {
+        let mut next = 0;
+
+        for pos in 0..self.slots.len() {
+            if !self.slots[pos].is_used() {
+                continue;
+            }
+
+            if pos != next {
+                self.slots.swap(pos, next);
+                self.generations[pos] = self.generations[pos].wrapping_add(1);
+                moved(pos, next);
+            }
+
+            next += 1;
+        }
+
+        self.slots.truncate(self.len);
+        self.free = None;
+    }
+
+    pub fn remove(&mut self, pos: usize) -> Option<T> {
+        if !self.contains(pos) {
+            return None;
+        }
+
+        let slot = mem::replace(
+            &mut self.slots[pos],
+            Slot::Free(self.free)
+        );
+
+        self.free = Some(Ix::new(pos));
+        self.len -= 1;
+        self.generations[pos] = self.generations[pos].wrapping_add(1);
+
+        Some(slot.into_used())
+    }
+}
+
+/// Empty arena with any index type, e.g. `Arena::<T, u16>::default()`.
+impl<T, Ix> Default for Arena<T, Ix> {
+    fn default() -> Self {
+        Arena {
+            len: 0,
+            free: None,
+            slots: Vec::new(),
+            generations: Vec::new()
+        }
+    }
+}
+
+impl<T, Ix, I: IndexType> Index<I> for Arena<T, Ix> {
+    type Output = T;
+
+    fn index(&self, pos: I) -> &T {
+        self.slots[pos.index()].as_used()
+    }
+}
+
+impl<T, Ix, I: IndexType> IndexMut<I> for Arena<T, Ix> {
+    fn index_mut(&mut self, pos: I) -> &mut T {
+        self.slots[pos.index()].as_used_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_get_remove() {
+        let mut arena = Arena::new();
+
+        let a = arena.insert("a");
+        let b = arena.insert("b");
+
+        assert_eq!(2, arena.len());
+        assert_eq!(Some(&"a"), arena.get(a));
+        assert_eq!(Some("b"), arena.remove(b));
+        assert_eq!(None, arena.remove(b));
+        assert_eq!(None, arena.get(b));
+        assert_eq!(1, arena.len());
+    }
+
+    #[test]
+    fn free_slots_are_reused_last_in_first_out() {
+        let mut arena = Arena::new();
+
+        let a = arena.insert(1);
+        let b = arena.insert(2);
+        arena.insert(3);
+
+        arena.remove(a);
+        arena.remove(b);
+
+        assert_eq!(b, arena.insert(4));
+        assert_eq!(a, arena.insert(5));
+        assert_eq!(3, arena.slot_count());
+        assert_eq!(Some(1), arena.generation(a));
+        assert_eq!(Some(0), arena.generation(2));
+        assert_eq!(None, arena.generation(3));
+    }
+
+    #[test]
+    fn iter_skips_free_slots() {
+        let mut arena = Arena::new();
+
+        arena.insert('x');
+        let y = arena.insert('y');
+        arena.insert('z');
+        arena.remove(y);
+
+        let items: Vec<_> = arena.iter().collect();
+
+        assert_eq!(items, vec![(0, &'x'), (2, &'z')]);
+    }
+
+    #[test]
+    fn map_keeps_slots_and_free_list() {
+        let mut arena = Arena::new();
+
+        arena.insert(1);
+        let b = arena.insert(2);
+        arena.insert(3);
+        arena.remove(b);
+
+        let mut mapped = arena.map(|pos, val| pos * 10 + val);
+
+        assert_eq!(vec![(0, &1), (2, &23)], mapped.iter().collect::<Vec<_>>());
+        assert_eq!(Some(1), mapped.generation(b));
+        assert_eq!(b, mapped.insert(0));
+    }
+
+    #[test]
+    fn drain_keeps_slots() {
+        let mut arena = Arena::new();
+
+        for val in 0..4 {
+            arena.insert(val);
+        }
+
+        arena.remove(1);
+        assert_eq!(vec![(0, 0), (2, 2)], arena.drain().take(2).collect::<Vec<_>>());
+
+        assert!(arena.is_empty());
+        assert_eq!((4, 4), (arena.slot_count(), arena.memory_usage().free));
+        assert_eq!([Some(1), Some(1), Some(1), Some(1)], [0, 1, 2, 3].map(|pos| arena.generation(pos)));
+        assert_eq!((0, 1), (arena.insert(7), arena.insert(8)));
+    }
+
+    #[test]
+    fn reserve_counts_free_slots() {
+        let mut arena = Arena::with_capacity(2);
+
+        let a = arena.insert(1u64);
+        arena.insert(2);
+        arena.remove(a);
+        arena.reserve(3);
+
+        let usage = arena.memory_usage();
+
+        assert_eq!((1, 1), (usage.used, usage.free));
+        assert!(usage.spare >= 2);
+        assert!(usage.bytes >= 4 * (mem::size_of::<Slot<u64, DefaultIx>>() + 4));
+    }
+
+    #[test]
+    fn compact_moves_values_to_the_front() {
+        let mut arena = Arena::new();
+
+        for val in 0..6 {
+            arena.insert(val);
+        }
+
+        for pos in [1, 2, 4] {
+            arena.remove(pos);
+        }
+
+        let usage = arena.memory_usage();
+        assert_eq!((3, 2, 0.5), (usage.free, usage.longest_free_run, usage.fragmentation()));
+
+        let mut moves = Vec::new();
+        arena.compact(|old, new| moves.push((old, new)));
+
+        assert_eq!(vec![(3, 1), (5, 2)], moves);
+        assert_eq!(vec![(0, &0), (1, &3), (2, &5)], arena.iter().collect::<Vec<_>>());
+        assert_eq!((3, 0), (arena.slot_count(), arena.memory_usage().longest_free_run));
+        assert_eq!(None, arena.generation(3));
+
+        // slots given up come back with a newer generation than before
+        assert_eq!(3, arena.insert(9));
+        assert_eq!(Some(1), arena.generation(3));
+    }
+}
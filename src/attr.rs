@@ -0,0 +1,198 @@
+use std::ops::{Index, IndexMut};
+
+use crate::graph::{EdgeId, NodeId};
+
+/// Generational handle that a [`SecondaryMap`] can be keyed by.
+pub trait ArenaKey: Copy + Eq {
+    fn index(self) -> usize;
+
+    fn generation(self) -> u32;
+}
+
+impl ArenaKey for NodeId {
+    fn index(self) -> usize {
+        NodeId::index(self)
+    }
+
+    fn generation(self) -> u32 {
+        NodeId::generation(self)
+    }
+}
+
+impl ArenaKey for EdgeId {
+    fn index(self) -> usize {
+        EdgeId::index(self)
+    }
+
+    fn generation(self) -> u32 {
+        EdgeId::generation(self)
+    }
+}
+
+/// Attribute storage living beside a graph rather than inside it, indexed
+/// densely by slot. Each entry remembers the generation of the id it was
+/// stored under, so ids of removed nodes or edges never see the values of
+/// whatever reused their slot.
+pub struct SecondaryMap<K, T> {
+    len: usize,
+    entries: Vec<Option<(K, T)>>
+}
+
+pub type NodeMap<T> = SecondaryMap<NodeId, T>;
+pub type EdgeMap<T> = SecondaryMap<EdgeId, T>;
+
+impl<K: ArenaKey, T> Default for SecondaryMap<K, T> {
+    fn default() -> Self {
+        SecondaryMap::new()
+    }
+}
+
+impl<K: ArenaKey, T> SecondaryMap<K, T> {
+    pub fn new() -> SecondaryMap<K, T> {
+        SecondaryMap { len: 0, entries: Vec::new() }
+    }
+
+    /// Preallocates room for ids with index below `bound`, such as a
+    /// graph's `node_bound()`.
+    pub fn with_capacity(bound: usize) -> SecondaryMap<K, T> {
+        SecondaryMap {
+            len: 0,
+            entries: (0..bound).map(|_| None).collect()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains_key(&self, key: K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Stores `value` under `key` and returns the value previously stored
+    /// under that same key. An entry left in the slot by a different
+    /// generation is dropped.
+    pub fn insert(&mut self, key: K, value: T) -> Option<T> {
+        let index = key.index();
+
+        if index >= self.entries.len() {
+            self.entries.resize_with(index + 1, || None);
+        }
+
+        match self.entries[index].replace((key, value)) {
+            Some((old, value)) if old == key => Some(value),
+            Some(_) => None,
+            None => {
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn get(&self, key: K) -> Option<&T> {
+        match self.entries.get(key.index()) {
+            Some(Some((stored, value))) if *stored == key => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn get_mut(&mut self, key: K) -> Option<&mut T> {
+        match self.entries.get_mut(key.index()) {
+            Some(Some((stored, value))) if *stored == key => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn remove(&mut self, key: K) -> Option<T> {
+        self.get(key)?;
+        self.len -= 1;
+        self.entries[key.index()].take().map(|(_, value)| value)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.len = 0;
+    }
+
+    /// Entries in index order.
+    pub fn iter(&self) -> impl Iterator<Item = (K, &T)> + '_ {
+        self.entries.iter().flatten().map(|(key, value)| (*key, value))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (K, &mut T)> + '_ {
+        self.entries.iter_mut().flatten().map(|(key, value)| (*key, value))
+    }
+}
+
+impl<K: ArenaKey, T> Index<K> for SecondaryMap<K, T> {
+    type Output = T;
+
+    /// Panics if there is no entry for `key`.
+    fn index(&self, key: K) -> &T {
+        self.get(key).expect("no entry for key")
+    }
+}
+
+impl<K: ArenaKey, T> IndexMut<K> for SecondaryMap<K, T> {
+    fn index_mut(&mut self, key: K) -> &mut T {
+        self.get_mut(key).expect("no entry for key")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Graph, UnGraph};
+
+    #[test]
+    fn independent_attribute_maps() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let e = graph.add_edge(a, b, ());
+
+        let mut color = NodeMap::with_capacity(graph.node_bound());
+        let mut label = NodeMap::new();
+        let mut length = EdgeMap::new();
+
+        color.insert(a, "red");
+        color.insert(b, "blue");
+        label.insert(b, String::from("bee"));
+        length.insert(e, 2.5);
+
+        assert_eq!(2, color.len());
+        assert_eq!("blue", color[b]);
+        assert_eq!(None, label.get(a));
+        assert_eq!(Some(&2.5), length.get(e));
+
+        color[a] = "green";
+
+        assert_eq!(Some("green"), color.insert(a, "black"));
+        assert_eq!(vec![(a, &"black"), (b, &"blue")], color.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn stale_ids_do_not_see_new_values() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let mut dist = NodeMap::new();
+
+        let a = graph.add_node(());
+        dist.insert(a, 1);
+
+        graph.remove_node(a);
+        let b = graph.add_node(());
+
+        assert_eq!(a.index(), b.index());
+        assert_eq!(None, dist.get(b));
+        assert_eq!(None, dist.insert(b, 2));
+        assert_eq!(None, dist.get(a));
+        assert_eq!(None, dist.remove(a));
+        assert_eq!(Some(2), dist.remove(b));
+        assert!(dist.is_empty());
+    }
+}
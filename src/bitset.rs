@@ -0,0 +1,188 @@
+const BITS: usize = u64::BITS as usize;
+
+/// Dense set of small integers, one bit per possible member.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FixedBitSet {
+    len: usize,
+    blocks: Vec<u64>
+}
+
+pub struct Ones<'a> {
+    blocks: &'a [u64],
+    block: usize,
+    bits: u64
+}
+
+impl Iterator for Ones<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.bits == 0 {
+            self.block += 1;
+            self.bits = *self.blocks.get(self.block)?;
+        }
+
+        let bit = self.bits.trailing_zeros() as usize;
+
+        self.bits &= self.bits - 1;
+
+        Some(self.block * BITS + bit)
+    }
+}
+
+impl FixedBitSet {
+    pub fn new() -> FixedBitSet {
+        FixedBitSet::default()
+    }
+
+    pub fn with_capacity(bits: usize) -> FixedBitSet {
+        FixedBitSet {
+            len: bits,
+            blocks: vec![0; bits.div_ceil(BITS)]
+        }
+    }
+
+    /// Number of bits the set can hold.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Extends the capacity to at least `bits`; never shrinks.
+    pub fn grow(&mut self, bits: usize) {
+        if bits > self.len {
+            self.len = bits;
+            self.blocks.resize(bits.div_ceil(BITS), 0);
+        }
+    }
+
+    /// Out-of-range bits are reported as unset.
+    pub fn contains(&self, bit: usize) -> bool {
+        bit < self.len && self.blocks[bit / BITS] & (1 << (bit % BITS)) != 0
+    }
+
+    /// Panics if `bit` is out of range.
+    pub fn insert(&mut self, bit: usize) {
+        self.set(bit, true);
+    }
+
+    /// Sets `bit` and returns its previous value. Panics if `bit` is out of
+    /// range.
+    pub fn put(&mut self, bit: usize) -> bool {
+        let prev = self.contains(bit);
+        self.set(bit, true);
+        prev
+    }
+
+    /// Panics if `bit` is out of range.
+    pub fn set(&mut self, bit: usize, enabled: bool) {
+        assert!(bit < self.len, "bit {bit} out of range for set of length {}", self.len);
+
+        let mask = 1 << (bit % BITS);
+
+        if enabled {
+            self.blocks[bit / BITS] |= mask;
+        } else {
+            self.blocks[bit / BITS] &= !mask;
+        }
+    }
+
+    pub fn remove(&mut self, bit: usize) {
+        if bit < self.len {
+            self.set(bit, false);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.fill(0);
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.blocks.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Adds every bit of `other`, growing to its length if needed.
+    pub fn union_with(&mut self, other: &FixedBitSet) {
+        self.grow(other.len);
+
+        for (block, &bits) in self.blocks.iter_mut().zip(&other.blocks) {
+            *block |= bits;
+        }
+    }
+
+    /// Keeps only the bits also set in `other`.
+    pub fn intersect_with(&mut self, other: &FixedBitSet) {
+        for (i, block) in self.blocks.iter_mut().enumerate() {
+            *block &= other.blocks.get(i).copied().unwrap_or(0);
+        }
+    }
+
+    /// Removes every bit set in `other`.
+    pub fn difference_with(&mut self, other: &FixedBitSet) {
+        for (block, &bits) in self.blocks.iter_mut().zip(&other.blocks) {
+            *block &= !bits;
+        }
+    }
+
+    /// Number of bits set in both `self` and `other`.
+    pub fn intersection_count(&self, other: &FixedBitSet) -> usize {
+        self.blocks.iter().zip(&other.blocks).map(|(a, b)| (a & b).count_ones() as usize).sum()
+    }
+
+    pub fn ones(&self) -> Ones<'_> {
+        Ones {
+            blocks: &self.blocks,
+            block: 0,
+            bits: self.blocks.first().copied().unwrap_or(0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_query_bits() {
+        let mut set = FixedBitSet::with_capacity(130);
+
+        assert!(!set.put(0));
+        assert!(set.put(0));
+        set.insert(64);
+        set.insert(129);
+
+        assert!(set.contains(64));
+        assert!(!set.contains(65));
+        assert!(!set.contains(1000));
+        assert_eq!(3, set.count_ones());
+
+        set.remove(64);
+
+        assert_eq!(vec![0, 129], set.ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn grow_keeps_bits() {
+        let mut set = FixedBitSet::with_capacity(3);
+
+        set.insert(2);
+        set.grow(200);
+        set.insert(199);
+
+        assert_eq!(200, set.len());
+        assert_eq!(vec![2, 199], set.ones().collect::<Vec<_>>());
+
+        set.clear();
+
+        assert_eq!(0, set.count_ones());
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_range_panics() {
+        FixedBitSet::with_capacity(8).insert(8);
+    }
+}
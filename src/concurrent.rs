@@ -0,0 +1,393 @@
+use std::collections::HashSet;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
+
+use crate::graph::{Directed, Direction, EdgeId, EdgeType, NodeId};
+use crate::visit::{GraphBase, IntoNeighbors, Visitable};
+
+struct SharedNode<N> {
+    weight: N,
+    // (edge, other endpoint), outgoing then incoming
+    edges: [Vec<(EdgeId, NodeId)>; 2]
+}
+
+struct SharedEdge<E> {
+    weight: E,
+    source: NodeId,
+    target: NodeId
+}
+
+type Shard<T> = RwLock<Vec<Option<T>>>;
+
+// updates never leave a shard half written before they might panic, so a
+// poisoned lock still guards consistent data
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn slot<T>(entries: &mut Vec<Option<T>>, pos: usize) -> &mut Option<T> {
+    if entries.len() <= pos {
+        entries.resize_with(pos + 1, || None);
+    }
+
+    &mut entries[pos]
+}
+
+/// Graph shared between threads. Nodes and edges are spread over shards by
+/// index, each behind its own read-write lock, so reads run concurrently
+/// and writes only contend when they touch the same shard. Adding an edge
+/// locks the shards of its endpoints; removing a node locks every node
+/// shard. Reads see each node or edge either before or after an update,
+/// but a traversal running beside writers is not a consistent snapshot.
+///
+/// Slots are never reused, so ids have generation 0. Weights are returned
+/// by value.
+pub struct ConcurrentGraph<N, E, Ty = Directed> {
+    nodes: Box<[Shard<SharedNode<N>>]>,
+    edges: Box<[Shard<SharedEdge<E>>]>,
+    next_node: AtomicUsize,
+    next_edge: AtomicUsize,
+    node_count: AtomicUsize,
+    edge_count: AtomicUsize,
+    ty: PhantomData<Ty>
+}
+
+impl<N, E, Ty: EdgeType> Default for ConcurrentGraph<N, E, Ty> {
+    fn default() -> Self {
+        ConcurrentGraph::new()
+    }
+}
+
+impl<N, E, Ty: EdgeType> ConcurrentGraph<N, E, Ty> {
+    /// Graph with four shards per available core.
+    pub fn new() -> ConcurrentGraph<N, E, Ty> {
+        ConcurrentGraph::with_shards(4 * thread::available_parallelism().map_or(1, |n| n.get()))
+    }
+
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> ConcurrentGraph<N, E, Ty> {
+        assert!(shards > 0, "need at least one shard");
+
+        ConcurrentGraph {
+            nodes: (0..shards).map(|_| RwLock::new(Vec::new())).collect(),
+            edges: (0..shards).map(|_| RwLock::new(Vec::new())).collect(),
+            next_node: AtomicUsize::new(0),
+            next_edge: AtomicUsize::new(0),
+            node_count: AtomicUsize::new(0),
+            edge_count: AtomicUsize::new(0),
+            ty: PhantomData
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_count.load(Ordering::Acquire)
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count.load(Ordering::Acquire)
+    }
+
+    /// One more than the largest node index handed out so far.
+    pub fn node_bound(&self) -> usize {
+        self.next_node.load(Ordering::Acquire)
+    }
+
+    fn shard(&self, index: usize) -> (usize, usize) {
+        (index % self.nodes.len(), index / self.nodes.len())
+    }
+
+    fn with_node<R>(&self, node: NodeId, f: impl FnOnce(&SharedNode<N>) -> R) -> Option<R> {
+        if node.generation() != 0 {
+            return None;
+        }
+
+        let (shard, pos) = self.shard(node.index());
+        read(&self.nodes[shard]).get(pos)?.as_ref().map(f)
+    }
+
+    fn with_edge<R>(&self, edge: EdgeId, f: impl FnOnce(&SharedEdge<E>) -> R) -> Option<R> {
+        if edge.generation() != 0 {
+            return None;
+        }
+
+        let (shard, pos) = self.shard(edge.index());
+        read(&self.edges[shard]).get(pos)?.as_ref().map(f)
+    }
+
+    pub fn contains_node(&self, node: NodeId) -> bool {
+        self.with_node(node, |_| ()).is_some()
+    }
+
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.with_edge(edge, |_| ()).is_some()
+    }
+
+    pub fn node_weight(&self, node: NodeId) -> Option<N>
+    where
+        N: Clone
+    {
+        self.with_node(node, |n| n.weight.clone())
+    }
+
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<E>
+    where
+        E: Clone
+    {
+        self.with_edge(edge, |e| e.weight.clone())
+    }
+
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.with_edge(edge, |e| (e.source, e.target))
+    }
+
+    /// Replaces the node's weight and returns the old one.
+    pub fn set_node_weight(&self, node: NodeId, weight: N) -> Option<N> {
+        let (shard, pos) = self.shard(node.index());
+        let mut entries = write(&self.nodes[shard]);
+        let entry = entries.get_mut(pos)?.as_mut().filter(|_| node.generation() == 0)?;
+
+        Some(std::mem::replace(&mut entry.weight, weight))
+    }
+
+    /// Nodes alive at the time of the call, in index order.
+    pub fn node_ids(&self) -> Vec<NodeId> {
+        (0..self.node_bound()).map(NodeId::new).filter(|&n| self.contains_node(n)).collect()
+    }
+
+    /// Neighbors at the time of the call, see
+    /// [`Graph::neighbors_directed`](crate::Graph::neighbors_directed).
+    /// Panics if the node is not in the graph.
+    pub fn neighbors_directed(&self, node: NodeId, dir: Direction) -> Vec<NodeId> {
+        self.around(node, dir).expect("node not in graph")
+    }
+
+    fn around(&self, node: NodeId, dir: Direction) -> Option<Vec<NodeId>> {
+        self.with_node(node, |entry| {
+            if Ty::is_directed() {
+                return entry.edges[dir.index()].iter().map(|&(_, n)| n).collect();
+            }
+
+            // as in `Graph`, an undirected self loop shows up once
+            let second = entry.edges[1].iter().filter(|&&(_, n)| n != node);
+            entry.edges[0].iter().chain(second).map(|&(_, n)| n).collect()
+        })
+    }
+
+    pub fn neighbors(&self, node: NodeId) -> Vec<NodeId> {
+        self.neighbors_directed(node, Direction::Outgoing)
+    }
+
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        self.with_node(source, |entry| {
+            let lists = if Ty::is_directed() { &entry.edges[..1] } else { &entry.edges[..] };
+            lists.iter().flatten().find(|&&(_, n)| n == target).map(|&(e, _)| e)
+        })?
+    }
+
+    pub fn add_node(&self, weight: N) -> NodeId {
+        let index = self.next_node.fetch_add(1, Ordering::AcqRel);
+        let (shard, pos) = self.shard(index);
+        let node = SharedNode { weight, edges: [Vec::new(), Vec::new()] };
+
+        *slot(&mut write(&self.nodes[shard]), pos) = Some(node);
+        self.node_count.fetch_add(1, Ordering::AcqRel);
+
+        NodeId::new(index)
+    }
+
+    // write locks on the node shards, always taken in shard order so that
+    // concurrent writers cannot deadlock
+    fn lock_nodes(&self, mut shards: Vec<usize>) -> NodeLocks<'_, N> {
+        shards.sort_unstable();
+        shards.dedup();
+
+        NodeLocks {
+            shards: self.nodes.len(),
+            guards: shards.into_iter().map(|s| (s, write(&self.nodes[s]))).collect()
+        }
+    }
+
+    /// Panics if either endpoint is not in the graph.
+    pub fn add_edge(&self, source: NodeId, target: NodeId, weight: E) -> EdgeId {
+        let mut locks = self.lock_nodes(vec![self.shard(source.index()).0, self.shard(target.index()).0]);
+
+        assert!(locks.get(source).is_some(), "source node not in graph");
+        assert!(locks.get(target).is_some(), "target node not in graph");
+
+        let index = self.next_edge.fetch_add(1, Ordering::AcqRel);
+        let id = EdgeId::new(index);
+        let (shard, pos) = self.shard(index);
+
+        locks.get(source).unwrap().edges[0].push((id, target));
+        locks.get(target).unwrap().edges[1].push((id, source));
+        *slot(&mut write(&self.edges[shard]), pos) = Some(SharedEdge { weight, source, target });
+        self.edge_count.fetch_add(1, Ordering::AcqRel);
+
+        id
+    }
+
+    // takes the edge out of its shard and both adjacency lists
+    fn unlink(&self, locks: &mut NodeLocks<'_, N>, edge: EdgeId) -> Option<E> {
+        let (shard, pos) = self.shard(edge.index());
+        let removed = write(&self.edges[shard]).get_mut(pos)?.take()?;
+
+        for node in [removed.source, removed.target] {
+            if let Some(entry) = locks.get(node) {
+                entry.edges.iter_mut().for_each(|list| list.retain(|&(e, _)| e != edge));
+            }
+        }
+
+        self.edge_count.fetch_sub(1, Ordering::AcqRel);
+        Some(removed.weight)
+    }
+
+    pub fn remove_edge(&self, edge: EdgeId) -> Option<E> {
+        let (source, target) = self.edge_endpoints(edge)?;
+        let mut locks = self.lock_nodes(vec![self.shard(source.index()).0, self.shard(target.index()).0]);
+
+        self.unlink(&mut locks, edge)
+    }
+
+    /// Removes the node and its edges, holding every node shard meanwhile.
+    pub fn remove_node(&self, node: NodeId) -> Option<N> {
+        let mut locks = self.lock_nodes((0..self.nodes.len()).collect());
+        let incident: Vec<_> = locks.get(node)?.edges.iter().flatten().map(|&(e, _)| e).collect();
+
+        for edge in incident {
+            self.unlink(&mut locks, edge);
+        }
+
+        let (shard, pos) = self.shard(node.index());
+        let removed = locks.guards.iter_mut().find(|(s, _)| *s == shard).unwrap().1[pos].take();
+
+        self.node_count.fetch_sub(1, Ordering::AcqRel);
+        removed.map(|n| n.weight)
+    }
+}
+
+type NodeGuard<'a, N> = RwLockWriteGuard<'a, Vec<Option<SharedNode<N>>>>;
+
+struct NodeLocks<'a, N> {
+    shards: usize,
+    guards: Vec<(usize, NodeGuard<'a, N>)>
+}
+
+impl<N> NodeLocks<'_, N> {
+    fn get(&mut self, node: NodeId) -> Option<&mut SharedNode<N>> {
+        if node.generation() != 0 {
+            return None;
+        }
+
+        let (shard, pos) = (node.index() % self.shards, node.index() / self.shards);
+        let (_, guard) = self.guards.iter_mut().find(|(s, _)| *s == shard)?;
+
+        guard.get_mut(pos)?.as_mut()
+    }
+}
+
+impl<N, E, Ty> GraphBase for ConcurrentGraph<N, E, Ty> {
+    type NodeId = NodeId;
+}
+
+/// Traversals treat nodes removed underneath them as having no neighbors.
+impl<N, E, Ty: EdgeType> IntoNeighbors for ConcurrentGraph<N, E, Ty> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.around(node, Direction::Outgoing).unwrap_or_default().into_iter()
+    }
+}
+
+/// Visited sets are hashed, as the graph may grow during a traversal.
+impl<N, E, Ty: EdgeType> Visitable for ConcurrentGraph<N, E, Ty> {
+    type Map = HashSet<NodeId>;
+
+    fn visit_map(&self) -> HashSet<NodeId> {
+        HashSet::new()
+    }
+
+    fn reset_map(&self, map: &mut HashSet<NodeId>) {
+        map.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Undirected;
+    use crate::visit::Bfs;
+
+    #[test]
+    fn behaves_like_graph_on_one_thread() {
+        let graph: ConcurrentGraph<&str, u32, Undirected> = ConcurrentGraph::with_shards(3);
+        let n: Vec<_> = ["a", "b", "c", "d"].into_iter().map(|w| graph.add_node(w)).collect();
+        let ab = graph.add_edge(n[0], n[1], 1);
+        let bc = graph.add_edge(n[1], n[2], 2);
+        graph.add_edge(n[2], n[2], 3);
+        graph.add_edge(n[3], n[0], 4);
+
+        assert_eq!(Some(ab), graph.find_edge(n[1], n[0]));
+        assert_eq!(vec![n[2], n[1]], graph.neighbors(n[2]));
+        assert_eq!(4, Bfs::new(&graph, n[0]).count());
+
+        assert_eq!(Some(2), graph.remove_edge(bc));
+        assert_eq!(None, graph.remove_edge(bc));
+        assert_eq!(Some("a"), graph.remove_node(n[0]));
+        assert_eq!((3, 1), (graph.node_count(), graph.edge_count()));
+        assert!(graph.neighbors(n[1]).is_empty());
+        assert_eq!(Some("b"), graph.set_node_weight(n[1], "z"));
+        assert_eq!(Some("z"), graph.node_weight(n[1]));
+        assert_eq!(vec![n[1], n[2], n[3]], graph.node_ids());
+    }
+
+    #[test]
+    fn readers_run_beside_writers() {
+        let graph: ConcurrentGraph<usize, (), Directed> = ConcurrentGraph::with_shards(4);
+        let hub = graph.add_node(0);
+
+        thread::scope(|scope| {
+            for t in 0..4 {
+                let graph = &graph;
+
+                scope.spawn(move || {
+                    for i in 0..250 {
+                        let node = graph.add_node(t * 1000 + i);
+                        graph.add_edge(hub, node, ());
+                        graph.add_edge(node, hub, ());
+                    }
+                });
+            }
+
+            scope.spawn(|| {
+                while graph.node_count() < 1001 {
+                    // every node reached so far is linked back to the hub
+                    for node in Bfs::new(&graph, hub).skip(1) {
+                        let around = graph.neighbors(node);
+                        assert!(around.is_empty() || around == vec![hub]);
+                    }
+                }
+            });
+        });
+
+        assert_eq!(1001, graph.node_count());
+        assert_eq!(2000, graph.edge_count());
+        assert_eq!(1000, graph.neighbors(hub).len());
+        assert_eq!(1001, Bfs::new(&graph, hub).count());
+
+        let nodes = graph.node_ids();
+
+        thread::scope(|scope| {
+            for chunk in nodes[1..].chunks(250) {
+                let graph = &graph;
+                scope.spawn(move || chunk.iter().for_each(|&n| assert!(graph.remove_node(n).is_some())));
+            }
+        });
+
+        assert_eq!((1, 0), (graph.node_count(), graph.edge_count()));
+        assert!(graph.neighbors(hub).is_empty());
+    }
+}
@@ -0,0 +1,471 @@
+use std::marker::PhantomData;
+
+use crate::bitset::FixedBitSet;
+use crate::graph::{Directed, Direction, EdgeId, EdgeType, Graph, NodeId};
+use crate::visit::{GraphBase, IntoNeighbors, Visitable};
+
+/// Compressed sparse row graph. Edges are numbered in order of their source
+/// and their endpoints and weights are stored as parallel columns, which
+/// keeps whole-graph sweeps over the weights contiguous in memory.
+///
+/// The topology is fixed at construction; only weights can be modified.
+pub struct Csr<N, E, Ty = Directed> {
+    pub(crate) nodes: Vec<N>,
+    pub(crate) out_offsets: Vec<usize>,
+    pub(crate) sources: Vec<usize>,
+    pub(crate) targets: Vec<usize>,
+    pub(crate) in_offsets: Vec<usize>,
+    pub(crate) in_edges: Vec<usize>,
+    pub(crate) weights: Vec<E>,
+    ty: PhantomData<Ty>
+}
+
+pub struct CsrNeighbors<'a> {
+    node: usize,
+    skip_loops: bool,
+    sources: &'a [usize],
+    first: std::slice::Iter<'a, usize>,
+    second: std::slice::Iter<'a, usize>
+}
+
+impl Iterator for CsrNeighbors<'_> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(&target) = self.first.next() {
+            return Some(NodeId::new(target));
+        }
+
+        // in undirected graphs self loops were already reported as outgoing
+        for &edge in self.second.by_ref() {
+            if !self.skip_loops || self.sources[edge] != self.node {
+                return Some(NodeId::new(self.sources[edge]));
+            }
+        }
+
+        None
+    }
+}
+
+fn offsets(count: usize, keys: impl Iterator<Item = usize>) -> Vec<usize> {
+    let mut offsets = vec![0; count + 1];
+
+    for key in keys {
+        offsets[key + 1] += 1;
+    }
+
+    for i in 0..count {
+        offsets[i + 1] += offsets[i];
+    }
+
+    offsets
+}
+
+impl<N, E, Ty: EdgeType> Csr<N, E, Ty> {
+    /// Builds the graph from per-node weights and `(source, target, weight)`
+    /// triples. Edges keep their relative order within each source. Panics if
+    /// an endpoint is out of range.
+    pub fn from_edges<I>(nodes: Vec<N>, edges: I) -> Csr<N, E, Ty>
+    where
+        I: IntoIterator<Item = (NodeId, NodeId, E)>
+    {
+        let edges: Vec<_> = edges.into_iter().collect();
+        let count = nodes.len();
+
+        for &(source, target, _) in &edges {
+            assert!(source.index() < count && target.index() < count, "edge endpoint out of range");
+        }
+
+        let out_offsets = offsets(count, edges.iter().map(|e| e.0.index()));
+
+        let mut next = out_offsets.clone();
+        let mut slots: Vec<Option<(usize, usize, E)>> = (0..edges.len()).map(|_| None).collect();
+
+        for (source, target, weight) in edges {
+            slots[next[source.index()]] = Some((source.index(), target.index(), weight));
+            next[source.index()] += 1;
+        }
+
+        let mut sources = Vec::with_capacity(slots.len());
+        let mut targets = Vec::with_capacity(slots.len());
+        let mut weights = Vec::with_capacity(slots.len());
+
+        for (source, target, weight) in slots.into_iter().map(Option::unwrap) {
+            sources.push(source);
+            targets.push(target);
+            weights.push(weight);
+        }
+
+        Csr::from_columns(nodes, out_offsets, sources, targets, weights)
+    }
+
+    /// Same as [`from_edges`](Csr::from_edges) for edges already sorted by
+    /// source, without buffering them first.
+    pub(crate) fn from_sorted_edges<I>(nodes: Vec<N>, edges: I) -> Csr<N, E, Ty>
+    where
+        I: IntoIterator<Item = (usize, usize, E)>
+    {
+        let count = nodes.len();
+        let mut out_offsets = vec![0; count + 1];
+        let mut sources = Vec::new();
+        let mut targets = Vec::new();
+        let mut weights = Vec::new();
+
+        for (source, target, weight) in edges {
+            assert!(source < count && target < count, "edge endpoint out of range");
+            debug_assert!(sources.last().is_none_or(|&last| last <= source), "edges not sorted");
+
+            out_offsets[source + 1] += 1;
+            sources.push(source);
+            targets.push(target);
+            weights.push(weight);
+        }
+
+        for i in 0..count {
+            out_offsets[i + 1] += out_offsets[i];
+        }
+
+        Csr::from_columns(nodes, out_offsets, sources, targets, weights)
+    }
+
+    fn from_columns(
+        nodes: Vec<N>,
+        out_offsets: Vec<usize>,
+        sources: Vec<usize>,
+        targets: Vec<usize>,
+        weights: Vec<E>
+    ) -> Csr<N, E, Ty> {
+        let in_offsets = offsets(nodes.len(), targets.iter().copied());
+        let mut next = in_offsets.clone();
+        let mut in_edges = vec![0; targets.len()];
+
+        for (edge, &target) in targets.iter().enumerate() {
+            in_edges[next[target]] = edge;
+            next[target] += 1;
+        }
+
+        Csr {
+            nodes,
+            out_offsets,
+            sources,
+            targets,
+            in_offsets,
+            in_edges,
+            weights,
+            ty: PhantomData
+        }
+    }
+
+    /// Copies `graph` into CSR form. Nodes are renumbered densely in index
+    /// order, so ids carry over unchanged if no node was ever removed.
+    pub fn from_graph(graph: &Graph<N, E, Ty>) -> Csr<N, E, Ty>
+    where
+        N: Clone,
+        E: Clone
+    {
+        let mut remap = vec![0; graph.node_bound()];
+        let mut nodes = Vec::with_capacity(graph.node_count());
+
+        for node in graph.node_ids() {
+            remap[node.index()] = nodes.len();
+            nodes.push(graph.node_weight(node).unwrap().clone());
+        }
+
+        let edges = graph.edge_ids().map(|edge| {
+            let (source, target) = graph.edge_endpoints(edge).unwrap();
+            (
+                NodeId::new(remap[source.index()]),
+                NodeId::new(remap[target.index()]),
+                graph.edge_weight(edge).unwrap().clone()
+            )
+        });
+
+        Csr::from_edges(nodes, edges)
+    }
+
+    pub fn is_directed(&self) -> bool {
+        Ty::is_directed()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn node_weight(&self, node: NodeId) -> Option<&N> {
+        self.nodes.get(node.index())
+    }
+
+    pub fn node_weight_mut(&mut self, node: NodeId) -> Option<&mut N> {
+        self.nodes.get_mut(node.index())
+    }
+
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<&E> {
+        self.weights.get(edge.index())
+    }
+
+    pub fn edge_weight_mut(&mut self, edge: EdgeId) -> Option<&mut E> {
+        self.weights.get_mut(edge.index())
+    }
+
+    /// All edge weights, indexed by `EdgeId::index`.
+    pub fn edge_weights(&self) -> &[E] {
+        &self.weights
+    }
+
+    pub fn edge_weights_mut(&mut self) -> &mut [E] {
+        &mut self.weights
+    }
+
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        let source = *self.sources.get(edge.index())?;
+        Some((NodeId::new(source), NodeId::new(self.targets[edge.index()])))
+    }
+
+    /// Ids of the edges leaving `node`, which are always contiguous.
+    pub fn out_edges(&self, node: NodeId) -> impl Iterator<Item = EdgeId> + '_ {
+        (self.out_offsets[node.index()]..self.out_offsets[node.index() + 1]).map(EdgeId::new)
+    }
+
+    pub fn neighbors(&self, node: NodeId) -> CsrNeighbors<'_> {
+        self.neighbors_directed(node, Direction::Outgoing)
+    }
+
+    /// For undirected graphs the direction is ignored.
+    pub fn neighbors_directed(&self, node: NodeId, dir: Direction) -> CsrNeighbors<'_> {
+        let i = node.index();
+        let out = &self.targets[self.out_offsets[i]..self.out_offsets[i + 1]];
+        let incoming = &self.in_edges[self.in_offsets[i]..self.in_offsets[i + 1]];
+
+        let mut neighbors = CsrNeighbors {
+            node: i,
+            skip_loops: !Ty::is_directed(),
+            sources: &self.sources,
+            first: out.iter(),
+            second: incoming.iter()
+        };
+
+        if Ty::is_directed() {
+            match dir {
+                Direction::Outgoing => neighbors.second = [].iter(),
+                Direction::Incoming => neighbors.first = [].iter()
+            }
+        }
+
+        neighbors
+    }
+
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        let outgoing = self
+            .out_edges(source)
+            .find(|e| self.targets[e.index()] == target.index());
+
+        if outgoing.is_some() || Ty::is_directed() {
+            return outgoing;
+        }
+
+        self.in_edges[self.in_offsets[source.index()]..self.in_offsets[source.index() + 1]]
+            .iter()
+            .find(|&&e| self.sources[e] == target.index())
+            .map(|&e| EdgeId::new(e))
+    }
+}
+
+impl<N, E, Ty> GraphBase for Csr<N, E, Ty> {
+    type NodeId = NodeId;
+}
+
+impl<N, E, Ty: EdgeType> IntoNeighbors for Csr<N, E, Ty> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        Csr::neighbors(self, node)
+    }
+}
+
+impl<N, E, Ty: EdgeType> Visitable for Csr<N, E, Ty> {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.node_count())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.node_count());
+    }
+}
+
+/// Directed graph borrowed from CSR arrays it does not own, such as a
+/// memory-mapped file or a buffer from foreign code: the successors of node
+/// `i` are `targets[offsets[i]..offsets[i + 1]]`. Nodes and edges are
+/// numbered by position and carry no weights.
+#[derive(Copy, Clone, Debug)]
+pub struct GraphRef<'a> {
+    offsets: &'a [u32],
+    targets: &'a [u32]
+}
+
+impl<'a> GraphRef<'a> {
+    /// `None` unless `offsets` starts at zero, never decreases and ends at
+    /// the length of `targets`, and every target names a node.
+    pub fn new(offsets: &'a [u32], targets: &'a [u32]) -> Option<GraphRef<'a>> {
+        let nodes = offsets.len().checked_sub(1)?;
+
+        let valid = offsets[0] == 0
+            && offsets.windows(2).all(|pair| pair[0] <= pair[1])
+            && offsets[nodes] as usize == targets.len()
+            && targets.iter().all(|&t| (t as usize) < nodes);
+
+        valid.then_some(GraphRef { offsets, targets })
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.targets.len()
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + use<> {
+        (0..self.node_count()).map(NodeId::new)
+    }
+
+    fn range(&self, node: NodeId) -> std::ops::Range<usize> {
+        self.offsets[node.index()] as usize..self.offsets[node.index() + 1] as usize
+    }
+
+    /// Successors of `node`. Panics if the node is not in the graph.
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + 'a {
+        self.targets[self.range(node)].iter().map(|&t| NodeId::new(t as usize))
+    }
+
+    pub fn out_degree(&self, node: NodeId) -> usize {
+        self.range(node).len()
+    }
+
+    /// First edge from `source` to `target`, by position in `targets`.
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        let range = self.range(source);
+        let start = range.start;
+
+        self.targets[range]
+            .iter()
+            .position(|&t| t as usize == target.index())
+            .map(|i| EdgeId::new(start + i))
+    }
+}
+
+impl GraphBase for GraphRef<'_> {
+    type NodeId = NodeId;
+}
+
+impl IntoNeighbors for GraphRef<'_> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        GraphRef::neighbors(self, node)
+    }
+}
+
+impl Visitable for GraphRef<'_> {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.node_count())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.node_count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::visit::Bfs;
+
+    fn sample() -> DiGraph<char, u32> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = "abcd".chars().map(|c| graph.add_node(c)).collect();
+
+        for (a, b, w) in [(2, 0, 5), (0, 1, 1), (0, 2, 2), (1, 3, 3), (2, 3, 4), (3, 3, 6)] {
+            graph.add_edge(n[a], n[b], w);
+        }
+
+        graph
+    }
+
+    #[test]
+    fn from_graph_preserves_topology() {
+        let graph = sample();
+        let csr = Csr::from_graph(&graph);
+
+        assert_eq!(4, csr.node_count());
+        assert_eq!(6, csr.edge_count());
+        assert_eq!(&[1, 2, 3, 5, 4, 6], csr.edge_weights());
+
+        for node in graph.node_ids() {
+            let expected: Vec<_> = graph.neighbors(node).collect();
+            assert_eq!(expected, csr.neighbors(node).collect::<Vec<_>>());
+
+            let expected: Vec<_> = graph.neighbors_directed(node, Direction::Incoming).collect();
+            assert_eq!(expected, csr.neighbors_directed(node, Direction::Incoming).collect::<Vec<_>>());
+        }
+
+        let e = csr.find_edge(NodeId::new(2), NodeId::new(3)).unwrap();
+
+        assert_eq!(Some(&4), csr.edge_weight(e));
+        assert_eq!(None, csr.find_edge(NodeId::new(3), NodeId::new(2)));
+    }
+
+    #[test]
+    fn edge_weights_mut_updates_in_place() {
+        let mut csr = Csr::from_graph(&sample());
+
+        for w in csr.edge_weights_mut() {
+            *w *= 10;
+        }
+
+        let e = csr.find_edge(NodeId::new(0), NodeId::new(1)).unwrap();
+
+        assert_eq!(Some(&10), csr.edge_weight(e));
+        assert_eq!(Bfs::new(&csr, NodeId::new(0)).count(), 4);
+    }
+
+    #[test]
+    fn undirected_neighbors_include_both_directions() {
+        let n = |i| NodeId::new(i);
+        let csr: Csr<(), (), crate::graph::Undirected> =
+            Csr::from_edges(vec![(); 3], [(n(0), n(1), ()), (n(2), n(1), ()), (n(1), n(1), ())]);
+
+        assert_eq!(vec![n(1), n(0), n(2)], csr.neighbors(n(1)).collect::<Vec<_>>());
+        assert!(csr.find_edge(n(1), n(2)).is_some());
+
+        let graph: UnGraph<(), ()> = Graph::new();
+        assert_eq!(0, Csr::from_graph(&graph).node_count());
+    }
+
+    #[test]
+    fn borrows_external_arrays() {
+        // 0 -> 1, 0 -> 2, 2 -> 3, 3 -> 0, as laid out in a foreign buffer
+        let offsets = [0, 2, 2, 3, 4];
+        let targets = [1, 2, 3, 0];
+        let view = GraphRef::new(&offsets, &targets).unwrap();
+        let n = NodeId::new;
+
+        assert_eq!((4, 4), (view.node_count(), view.edge_count()));
+        assert_eq!(vec![n(1), n(2)], view.neighbors(n(0)).collect::<Vec<_>>());
+        assert_eq!(Some(EdgeId::new(2)), view.find_edge(n(2), n(3)));
+        assert_eq!(None, view.find_edge(n(1), n(0)));
+        assert_eq!(vec![n(3), n(0), n(1), n(2)], Bfs::new(&view, n(3)).collect::<Vec<_>>());
+
+        assert!(GraphRef::new(&[], &[]).is_none());
+        assert!(GraphRef::new(&[0], &[]).is_some());
+        assert!(GraphRef::new(&[0, 2, 1], &[0, 0]).is_none());
+        assert!(GraphRef::new(&[0, 1], &[1]).is_none());
+        assert!(GraphRef::new(&[1, 1], &[0]).is_none());
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashSet;
+
+use crate::algo::{toposort, Cycle};
+use crate::error::GraphError;
+use crate::graph::{DiGraph, Direction, EdgeId, Graph, NodeId};
+
+/// Topological order of a directed graph kept up to date as edges arrive,
+/// by the algorithm of Pearce and Kelly: an edge that points backwards only
+/// reorders the nodes whose positions lie between its ends. The graph is
+/// kept by the caller and passed in on every insertion; [`Dag`] pairs the
+/// two.
+pub struct TopologicalOrder {
+    label: Vec<usize>,
+    next: usize
+}
+
+impl Default for TopologicalOrder {
+    fn default() -> Self {
+        TopologicalOrder::new()
+    }
+}
+
+impl TopologicalOrder {
+    pub fn new() -> TopologicalOrder {
+        TopologicalOrder { label: Vec::new(), next: 0 }
+    }
+
+    /// Order of an existing graph's nodes, failing with a node on a cycle
+    /// if it has one.
+    pub fn of_graph<N, E>(graph: &DiGraph<N, E>) -> Result<TopologicalOrder, Cycle> {
+        let mut order = TopologicalOrder::new();
+
+        for node in toposort(graph)? {
+            order.add_node(node);
+        }
+
+        Ok(order)
+    }
+
+    /// Places a new node last. Every node of the graph must be added before
+    /// edges touching it are inserted.
+    pub fn add_node(&mut self, node: NodeId) {
+        if self.label.len() <= node.index() {
+            self.label.resize(node.index() + 1, usize::MAX);
+        }
+
+        self.label[node.index()] = self.next;
+        self.next += 1;
+    }
+
+    /// Key of `node` in the order: every edge leads to a larger key. Keys
+    /// are not positions, as removed nodes leave gaps. `None` for a node
+    /// never added.
+    pub fn order_of(&self, node: NodeId) -> Option<usize> {
+        self.label.get(node.index()).copied().filter(|&label| label != usize::MAX)
+    }
+
+    /// Reorders for the edge `source -> target`, whether or not it is in
+    /// `graph` yet, returning the nodes that moved in their new order.
+    /// Fails, leaving the order as it was, if `target` reaches `source`
+    /// without that edge, in which case the edge must not be kept. Panics if
+    /// either node was never added.
+    pub fn insert_edge<N, E>(
+        &mut self,
+        graph: &DiGraph<N, E>,
+        source: NodeId,
+        target: NodeId
+    ) -> Result<Vec<NodeId>, Cycle> {
+        let key = |node: NodeId| self.order_of(node).expect("node not in order");
+        let (low, high) = (key(target), key(source));
+
+        if source == target {
+            return Err(Cycle::new(source));
+        }
+
+        if high < low {
+            return Ok(Vec::new());
+        }
+
+        // nodes after `target` that it reaches, and before `source` reaching it
+        let forward = self.affected(graph, target, Direction::Outgoing, |l| l <= high);
+
+        if forward.contains(&source) {
+            return Err(Cycle::new(source));
+        }
+
+        let backward = self.affected(graph, source, Direction::Incoming, |l| l >= low);
+        let mut labels: Vec<_> = forward.iter().chain(&backward).map(|n| self.label[n.index()]).collect();
+        let moved: Vec<_> = backward.into_iter().chain(forward).collect();
+
+        labels.sort_unstable();
+
+        for (node, &label) in moved.iter().zip(&labels) {
+            self.label[node.index()] = label;
+        }
+
+        Ok(moved)
+    }
+
+    /// Nodes of `graph` ordered so that every edge points forward.
+    pub fn sorted<N, E>(&self, graph: &DiGraph<N, E>) -> Vec<NodeId> {
+        let mut nodes: Vec<_> = graph.node_ids().collect();
+        nodes.sort_unstable_by_key(|&n| self.order_of(n));
+        nodes
+    }
+
+    /// Nodes reached from `start` in direction `dir` through nodes whose
+    /// label passes `within`, sorted by label.
+    fn affected<N, E>(
+        &self,
+        graph: &DiGraph<N, E>,
+        start: NodeId,
+        dir: Direction,
+        within: impl Fn(usize) -> bool
+    ) -> Vec<NodeId> {
+        let mut seen = HashSet::from([start]);
+        let mut stack = vec![start];
+        let mut found = Vec::new();
+
+        while let Some(node) = stack.pop() {
+            found.push(node);
+
+            for next in graph.neighbors_directed(node, dir) {
+                if within(self.label[next.index()]) && seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        found.sort_unstable_by_key(|n| self.label[n.index()]);
+        found
+    }
+}
+
+/// Directed graph that stays acyclic: edges that would close a cycle are
+/// refused. A topological order is maintained incrementally, so adding an
+/// edge only costs work around the nodes it reorders.
+pub struct Dag<N, E> {
+    graph: DiGraph<N, E>,
+    order: TopologicalOrder
+}
+
+impl<N, E> Default for Dag<N, E> {
+    fn default() -> Self {
+        Dag::new()
+    }
+}
+
+impl<N, E> Dag<N, E> {
+    pub fn new() -> Dag<N, E> {
+        Dag { graph: Graph::new(), order: TopologicalOrder::new() }
+    }
+
+    /// Takes over `graph`, failing with a node on a cycle if it has one.
+    pub fn from_graph(graph: DiGraph<N, E>) -> Result<Dag<N, E>, Cycle> {
+        let order = TopologicalOrder::of_graph(&graph)?;
+        Ok(Dag { graph, order })
+    }
+
+    pub fn graph(&self) -> &DiGraph<N, E> {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> DiGraph<N, E> {
+        self.graph
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        let node = self.graph.add_node(weight);
+        self.order.add_node(node);
+        node
+    }
+
+    /// Adds `source -> target` unless either node is missing or `target`
+    /// already reaches `source`, which fails with
+    /// [`GraphError::NotADag`] naming `source`.
+    pub fn try_add_edge(&mut self, source: NodeId, target: NodeId, weight: E) -> Result<EdgeId, GraphError> {
+        self.graph.check_node(source)?;
+        self.graph.check_node(target)?;
+        self.order.insert_edge(&self.graph, source, target)?;
+
+        Ok(self.graph.add_edge(source, target, weight))
+    }
+
+    /// Removing edges or nodes keeps the order valid.
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<E> {
+        self.graph.remove_edge(edge)
+    }
+
+    pub fn remove_node(&mut self, node: NodeId) -> Option<N> {
+        self.graph.remove_node(node).map(|removed| removed.weight)
+    }
+
+    pub fn node_weight_mut(&mut self, node: NodeId) -> Option<&mut N> {
+        self.graph.node_weight_mut(node)
+    }
+
+    pub fn edge_weight_mut(&mut self, edge: EdgeId) -> Option<&mut E> {
+        self.graph.edge_weight_mut(edge)
+    }
+
+    /// The order maintained, for [`TopologicalOrder::order_of`].
+    pub fn order(&self) -> &TopologicalOrder {
+        &self.order
+    }
+
+    /// Nodes ordered so that every edge points forward.
+    pub fn topological_order(&self) -> Vec<NodeId> {
+        self.order.sorted(&self.graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Rng, SplitMix64};
+
+    fn points_forward<N, E>(dag: &Dag<N, E>) -> bool {
+        let order = dag.topological_order();
+        let mut rank = vec![0; dag.graph().node_bound()];
+
+        for (i, node) in order.iter().enumerate() {
+            rank[node.index()] = i;
+        }
+
+        dag.graph().edge_ids().all(|edge| {
+            let (a, b) = dag.graph().edge_endpoints(edge).unwrap();
+            rank[a.index()] < rank[b.index()]
+        })
+    }
+
+    #[test]
+    fn refuses_edges_closing_cycles() {
+        let mut dag: Dag<(), ()> = Dag::new();
+        let n: Vec<_> = (0..4).map(|_| dag.add_node(())).collect();
+
+        // added against the initial order, each one forcing a reorder
+        for (a, b) in [(3, 2), (2, 1), (1, 0)] {
+            dag.try_add_edge(n[a], n[b], ()).unwrap();
+        }
+
+        assert_eq!(vec![n[3], n[2], n[1], n[0]], dag.topological_order());
+        assert_eq!(Err(GraphError::NotADag(n[0])), dag.try_add_edge(n[0], n[3], ()));
+        assert_eq!(Err(GraphError::NotADag(n[1])), dag.try_add_edge(n[1], n[1], ()));
+        assert_eq!(3, dag.graph().edge_count());
+
+        let mut cyclic: DiGraph<(), ()> = Graph::new();
+        let a = cyclic.add_node(());
+        cyclic.add_edge(a, a, ());
+        assert!(Dag::from_graph(cyclic).is_err());
+    }
+
+    #[test]
+    fn agrees_with_full_toposort() {
+        let mut rng = SplitMix64::new(11);
+        let mut dag: Dag<(), ()> = Dag::new();
+        let n: Vec<_> = (0..30).map(|_| dag.add_node(())).collect();
+
+        for _ in 0..300 {
+            let (a, b) = (n[rng.below(30)], n[rng.below(30)]);
+            let accepted = dag.try_add_edge(a, b, ()).is_ok();
+
+            let mut copy = dag.graph().map(|_, _| (), |_, _| ());
+            if !accepted {
+                copy.add_edge(a, b, ());
+            }
+
+            assert_eq!(accepted, toposort(&copy).is_ok());
+            assert!(points_forward(&dag));
+        }
+    }
+
+    #[test]
+    fn reports_reorderings() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let mut order = TopologicalOrder::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+
+        n.iter().for_each(|&node| order.add_node(node));
+        graph.add_edge(n[1], n[2], ());
+        order.insert_edge(&graph, n[1], n[2]).unwrap();
+
+        // 3 -> 1 moves 3 ahead of 1 and 2, which still follow each other
+        graph.add_edge(n[3], n[1], ());
+        assert_eq!(vec![n[3], n[1], n[2]], order.insert_edge(&graph, n[3], n[1]).unwrap());
+        assert_eq!(vec![n[0], n[3], n[1], n[2]], order.sorted(&graph));
+        assert!(order.order_of(n[3]) < order.order_of(n[1]));
+
+        assert!(order.insert_edge(&graph, n[2], n[3]).is_err());
+        assert_eq!(vec![n[0], n[3], n[1], n[2]], order.sorted(&graph));
+        assert_eq!(None, order.order_of(NodeId::new(9)));
+    }
+}
@@ -0,0 +1,77 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::algo::Cycle;
+use crate::graph::{EdgeId, NodeId};
+
+/// Failure of a fallible graph operation. The panicking counterparts of
+/// these operations, such as [`Graph::add_edge`](crate::Graph::add_edge)
+/// and indexing, stay available for callers that know their ids are valid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GraphError {
+    /// The id points past every slot the graph ever allocated.
+    InvalidNodeId(NodeId),
+    /// The node was removed, its slot possibly reused by another.
+    StaleGeneration(NodeId),
+    NegativeWeight(EdgeId),
+    /// A search estimate drops by more than the cost of this edge along it.
+    InconsistentHeuristic(EdgeId),
+    /// A search estimate is above zero at this node, the target.
+    InadmissibleHeuristic(NodeId),
+    /// The graph has a cycle through this node.
+    NotADag(NodeId),
+    /// The nodes are already joined by this edge.
+    DuplicateEdge(EdgeId),
+    /// Two nodes were given the same label.
+    DuplicateLabel(NodeId, NodeId)
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::InvalidNodeId(node) => write!(f, "node {} does not exist", node.index()),
+            GraphError::StaleGeneration(node) => {
+                write!(f, "node {} of generation {} was removed", node.index(), node.generation())
+            },
+            GraphError::NegativeWeight(edge) => write!(f, "edge {} has a negative weight", edge.index()),
+            GraphError::InconsistentHeuristic(edge) => {
+                write!(f, "estimate drops by more than the cost of edge {}", edge.index())
+            },
+            GraphError::InadmissibleHeuristic(node) => {
+                write!(f, "estimate at target node {} is above zero", node.index())
+            },
+            GraphError::NotADag(node) => write!(f, "graph has a cycle through node {}", node.index()),
+            GraphError::DuplicateEdge(edge) => write!(f, "nodes are already joined by edge {}", edge.index()),
+            GraphError::DuplicateLabel(a, b) => {
+                write!(f, "nodes {} and {} have the same label", a.index(), b.index())
+            }
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+impl From<Cycle> for GraphError {
+    fn from(cycle: Cycle) -> GraphError {
+        GraphError::NotADag(cycle.node_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::toposort;
+    use crate::graph::{DiGraph, Graph};
+
+    #[test]
+    fn converts_cycles() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let a = graph.add_node(());
+        graph.add_edge(a, a, ());
+
+        let sorted = || -> Result<Vec<NodeId>, GraphError> { Ok(toposort(&graph)?) };
+
+        assert_eq!(Err(GraphError::NotADag(a)), sorted());
+        assert_eq!("graph has a cycle through node 0", sorted().unwrap_err().to_string());
+    }
+}
@@ -0,0 +1,247 @@
+use std::slice;
+
+use crate::algo::{connected_components, try_dijkstra};
+use crate::graph::{DiGraph, EdgeType, Graph, NodeId, UnGraph};
+use crate::visit::Bfs;
+
+/// Graph handed to C as an opaque pointer. Nodes are unweighted, edges
+/// carry a `double`, and nodes are named by the index they were added at,
+/// as the C API cannot remove them.
+pub enum GraphsGraph {
+    Directed(DiGraph<(), f64>),
+    Undirected(UnGraph<(), f64>)
+}
+
+macro_rules! with_graph {
+    ($handle:expr, $graph:ident => $body:expr) => {
+        match $handle {
+            GraphsGraph::Directed($graph) => $body,
+            GraphsGraph::Undirected($graph) => $body
+        }
+    };
+}
+
+fn node<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, index: usize) -> Option<NodeId> {
+    Some(NodeId::new(index)).filter(|&node| graph.contains_node(node))
+}
+
+/// New empty graph, to be released with [`graphs_graph_free`].
+#[unsafe(no_mangle)]
+pub extern "C" fn graphs_graph_new(directed: bool) -> *mut GraphsGraph {
+    let graph = match directed {
+        true => GraphsGraph::Directed(Graph::new()),
+        false => GraphsGraph::Undirected(Graph::new())
+    };
+
+    Box::into_raw(Box::new(graph))
+}
+
+/// # Safety
+///
+/// `graph` must come from [`graphs_graph_new`] and not be used afterwards.
+/// Null is ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_graph_free(graph: *mut GraphsGraph) {
+    if !graph.is_null() {
+        drop(unsafe { Box::from_raw(graph) });
+    }
+}
+
+/// Adds a node and returns its index.
+///
+/// # Safety
+///
+/// `graph` must be a live graph from [`graphs_graph_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_graph_add_node(graph: *mut GraphsGraph) -> usize {
+    with_graph!(unsafe { &mut *graph }, graph => graph.add_node(()).index())
+}
+
+/// Adds an edge and returns `false`, adding nothing, if an endpoint is not
+/// in the graph.
+///
+/// # Safety
+///
+/// `graph` must be a live graph from [`graphs_graph_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_graph_add_edge(
+    graph: *mut GraphsGraph,
+    source: usize,
+    target: usize,
+    weight: f64
+) -> bool {
+    with_graph!(unsafe { &mut *graph }, graph => {
+        match (node(graph, source), node(graph, target)) {
+            (Some(source), Some(target)) => {
+                graph.add_edge(source, target, weight);
+                true
+            },
+            _ => false
+        }
+    })
+}
+
+/// # Safety
+///
+/// `graph` must be a live graph from [`graphs_graph_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_graph_node_count(graph: *const GraphsGraph) -> usize {
+    with_graph!(unsafe { &*graph }, graph => graph.node_count())
+}
+
+/// # Safety
+///
+/// `graph` must be a live graph from [`graphs_graph_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_graph_edge_count(graph: *const GraphsGraph) -> usize {
+    with_graph!(unsafe { &*graph }, graph => graph.edge_count())
+}
+
+/// Writes the nodes reached from `start` in breadth-first order to `order`,
+/// at most `capacity` of them, and returns how many were reached, or
+/// `SIZE_MAX` if `start` is not in the graph.
+///
+/// # Safety
+///
+/// `graph` must be a live graph from [`graphs_graph_new`] and `order` must
+/// point to `capacity` writable values, or may be null if `capacity` is 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_bfs(
+    graph: *const GraphsGraph,
+    start: usize,
+    order: *mut usize,
+    capacity: usize
+) -> usize {
+    let reached: Vec<usize> = with_graph!(unsafe { &*graph }, graph => match node(graph, start) {
+        Some(start) => Bfs::new(graph, start).map(NodeId::index).collect(),
+        None => return usize::MAX
+    });
+
+    if capacity > 0 {
+        let out = unsafe { slice::from_raw_parts_mut(order, capacity) };
+        out.iter_mut().zip(&reached).for_each(|(slot, &node)| *slot = node);
+    }
+
+    reached.len()
+}
+
+/// Writes the distance from `source` to every node to `distances`, indexed
+/// by node, infinity where unreachable. Returns `false`, writing nothing, if
+/// `source` is not in the graph, `len` is below the node count, or an edge
+/// weight is negative or NaN.
+///
+/// # Safety
+///
+/// `graph` must be a live graph from [`graphs_graph_new`] and `distances`
+/// must point to `len` writable values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_dijkstra(
+    graph: *const GraphsGraph,
+    source: usize,
+    distances: *mut f64,
+    len: usize
+) -> bool {
+    let dist: Vec<f64> = with_graph!(unsafe { &*graph }, graph => {
+        let paths = match node(graph, source) {
+            Some(source) if len >= graph.node_count() => try_dijkstra(graph, source, |&w| w),
+            _ => return false
+        };
+
+        match paths {
+            Ok(paths) => graph.node_ids().map(|n| paths.distance(n).unwrap_or(f64::INFINITY)).collect(),
+            Err(_) => return false
+        }
+    });
+
+    let out = unsafe { slice::from_raw_parts_mut(distances, len) };
+    out[..dist.len()].copy_from_slice(&dist);
+    true
+}
+
+/// Writes each node's component number to `labels`, indexed by node, and
+/// returns the number of components, or `SIZE_MAX`, writing nothing, if
+/// `len` is below the node count. Directed edges count in both directions.
+///
+/// # Safety
+///
+/// `graph` must be a live graph from [`graphs_graph_new`] and `labels` must
+/// point to `len` writable values.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn graphs_connected_components(
+    graph: *const GraphsGraph,
+    labels: *mut usize,
+    len: usize
+) -> usize {
+    let components = with_graph!(unsafe { &*graph }, graph => {
+        if len < graph.node_count() {
+            return usize::MAX;
+        }
+
+        connected_components(graph)
+    });
+
+    let out = unsafe { slice::from_raw_parts_mut(labels, len) };
+
+    for (label, component) in components.iter().enumerate() {
+        component.iter().for_each(|node| out[node.index()] = label);
+    }
+
+    components.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = include_str!("../include/graphs_rs.h");
+
+    #[test]
+    fn header_declares_every_function() {
+        let names = [
+            "graphs_graph_new",
+            "graphs_graph_free",
+            "graphs_graph_add_node",
+            "graphs_graph_add_edge",
+            "graphs_graph_node_count",
+            "graphs_graph_edge_count",
+            "graphs_bfs",
+            "graphs_dijkstra",
+            "graphs_connected_components"
+        ];
+
+        let source = include_str!("ffi.rs");
+        let exported = source.matches("extern \"C\" fn ").count();
+
+        assert_eq!(names.len(), exported);
+        assert!(names.iter().all(|name| HEADER.contains(&format!("{name}("))));
+    }
+
+    #[test]
+    fn runs_algorithms_through_raw_pointers() {
+        unsafe {
+            let graph = graphs_graph_new(false);
+            let n: Vec<_> = (0..4).map(|_| graphs_graph_add_node(graph)).collect();
+
+            assert!(graphs_graph_add_edge(graph, n[0], n[1], 1.5));
+            assert!(graphs_graph_add_edge(graph, n[1], n[2], 2.0));
+            assert!(!graphs_graph_add_edge(graph, n[0], 9, 1.0));
+            assert_eq!((4, 2), (graphs_graph_node_count(graph), graphs_graph_edge_count(graph)));
+
+            let mut order = [0; 2];
+            assert_eq!(3, graphs_bfs(graph, n[2], order.as_mut_ptr(), order.len()));
+            assert_eq!([2, 1], order);
+            assert_eq!(usize::MAX, graphs_bfs(graph, 7, std::ptr::null_mut(), 0));
+
+            let mut dist = [0.0; 4];
+            assert!(graphs_dijkstra(graph, n[0], dist.as_mut_ptr(), dist.len()));
+            assert_eq!([0.0, 1.5, 3.5, f64::INFINITY], dist);
+            assert!(!graphs_dijkstra(graph, n[0], dist.as_mut_ptr(), 3));
+
+            let mut labels = [0; 4];
+            assert_eq!(2, graphs_connected_components(graph, labels.as_mut_ptr(), labels.len()));
+            assert!(labels[0] == labels[2] && labels[0] != labels[3]);
+
+            graphs_graph_free(graph);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::hash::Hasher;
+
+/// 64-bit FNV-1a. Integers are fed in as little-endian bytes, `usize` as
+/// eight of them, so results do not depend on the platform.
+pub(crate) struct Fnv(u64);
+
+impl Fnv {
+    pub(crate) fn new() -> Fnv {
+        Fnv(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u16(&mut self, value: u16) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u128(&mut self, value: u128) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::Hash;
+
+    #[test]
+    fn matches_reference_values_on_every_platform() {
+        let hash = |value: &dyn Fn(&mut Fnv)| {
+            let mut fnv = Fnv::new();
+            value(&mut fnv);
+            fnv.finish()
+        };
+
+        // published FNV-1a test vectors
+        assert_eq!(0xcbf29ce484222325, hash(&|_| ()));
+        assert_eq!(0xaf63dc4c8601ec8c, hash(&|f| f.write(b"a")));
+        assert_eq!(0x85944171f73967e8, hash(&|f| f.write(b"foobar")));
+
+        assert_eq!(hash(&|f| f.write(&[7, 0, 0, 0, 0, 0, 0, 0])), hash(&|f| 7usize.hash(f)));
+        assert_eq!(hash(&|f| f.write_u64(3)), hash(&|f| 3i64.hash(f)));
+    }
+}
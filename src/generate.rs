@@ -0,0 +1,160 @@
+use crate::bitset::FixedBitSet;
+use crate::graph::{EdgeId, NodeId, UnGraph};
+use crate::grid::GridGraph;
+use crate::rng::Rng;
+use crate::visit::Bfs;
+
+/// How [`maze`] picks the spanning tree of passages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MazeAlgorithm {
+    /// [`random_dfs_tree`]: long winding corridors with few dead ends.
+    Backtracker,
+    /// [`uniform_spanning_tree`]: every maze equally likely, with many
+    /// short dead ends.
+    Wilson
+}
+
+/// Spanning tree of the nodes reachable from `start`, grown by a
+/// depth-first walk that moves on to a random unvisited neighbor and backs
+/// up at dead ends, as the recursive backtracker does. Returns the tree
+/// edges in the order they were taken.
+pub fn random_dfs_tree<N, E, R: Rng>(graph: &UnGraph<N, E>, start: NodeId, rng: &mut R) -> Vec<EdgeId> {
+    let mut visited = FixedBitSet::with_capacity(graph.node_bound());
+    let mut stack = vec![start];
+    let mut tree = Vec::new();
+
+    visited.insert(start.index());
+
+    while let Some(&node) = stack.last() {
+        let open: Vec<_> = graph.edges(node).filter(|edge| !visited.contains(edge.target.index())).collect();
+
+        if open.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let edge = open[rng.below(open.len())];
+
+        visited.insert(edge.target.index());
+        stack.push(edge.target);
+        tree.push(edge.id);
+    }
+
+    tree
+}
+
+/// Spanning tree of the nodes reachable from `root`, drawn uniformly among
+/// all of them with Wilson's algorithm: random walks from every node not
+/// yet in the tree until they hit it, with their loops erased. Parallel
+/// edges make distinct trees. Returns the tree edges, grouped by the walk
+/// that added them.
+pub fn uniform_spanning_tree<N, E, R: Rng>(graph: &UnGraph<N, E>, root: NodeId, rng: &mut R) -> Vec<EdgeId> {
+    let mut in_tree = FixedBitSet::with_capacity(graph.node_bound());
+    let mut next = vec![None; graph.node_bound()];
+    let mut tree = Vec::new();
+
+    in_tree.insert(root.index());
+
+    for start in Bfs::new(graph, root) {
+        let mut node = start;
+
+        // a node walked through again overwrites its exit, erasing the loop
+        while !in_tree.contains(node.index()) {
+            let edges: Vec<_> = graph.edges(node).collect();
+            let edge = edges[rng.below(edges.len())];
+
+            next[node.index()] = Some(edge);
+            node = edge.target;
+        }
+
+        node = start;
+
+        while !in_tree.contains(node.index()) {
+            let edge = next[node.index()].unwrap();
+
+            in_tree.insert(node.index());
+            tree.push(edge.id);
+            node = edge.target;
+        }
+    }
+
+    tree
+}
+
+/// Perfect maze carved out of `grid`: the same cells, joined only by the
+/// edges of a spanning tree of every open region, so there is exactly one
+/// path between any two cells of a region. Diagonal passages appear only
+/// on grids with [`Connectivity::Eight`](crate::Connectivity::Eight).
+pub fn maze<R: Rng>(grid: &GridGraph, algorithm: MazeAlgorithm, rng: &mut R) -> GridGraph {
+    let graph = grid.graph();
+    let mut spanned = FixedBitSet::with_capacity(graph.node_bound());
+    let mut passages = FixedBitSet::with_capacity(graph.edge_bound());
+
+    for node in graph.node_ids() {
+        if spanned.contains(node.index()) {
+            continue;
+        }
+
+        let tree = match algorithm {
+            MazeAlgorithm::Backtracker => random_dfs_tree(graph, node, rng),
+            MazeAlgorithm::Wilson => uniform_spanning_tree(graph, node, rng)
+        };
+
+        for edge in tree {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+            spanned.insert(a.index());
+            spanned.insert(b.index());
+            passages.insert(edge.index());
+        }
+    }
+
+    grid.retain_edges(|edge| passages.contains(edge.index()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::connected_components;
+    use crate::grid::Connectivity;
+    use crate::rng::SplitMix64;
+
+    #[test]
+    fn mazes_are_spanning_trees() {
+        let mut rng = SplitMix64::new(5);
+        let grid = GridGraph::with_obstacles(9, 7, Connectivity::Four, |x, y| x == 4 && y != 3);
+
+        for algorithm in [MazeAlgorithm::Backtracker, MazeAlgorithm::Wilson] {
+            let carved = maze(&grid, algorithm, &mut rng);
+            let passages = carved.graph();
+
+            assert_eq!(grid.graph().node_count() - 1, passages.edge_count());
+            assert_eq!(1, connected_components(passages).len());
+            assert!(passages.edge_ids().all(|edge| grid.graph().contains_edge(edge)));
+            assert_eq!(grid.node_at(8, 6), carved.node_at(8, 6));
+        }
+
+        let split = GridGraph::with_obstacles(5, 3, Connectivity::Eight, |x, _| x == 2);
+        let carved = maze(&split, MazeAlgorithm::Wilson, &mut rng);
+        assert_eq!((2, 10), (connected_components(carved.graph()).len(), carved.graph().edge_count()));
+    }
+
+    #[test]
+    fn wilson_draws_trees_uniformly() {
+        // a 4-cycle has 4 spanning trees, one without each edge
+        let mut graph: UnGraph<(), ()> = UnGraph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        let edges: Vec<_> = (0..4).map(|i| graph.add_edge(n[i], n[(i + 1) % 4], ())).collect();
+
+        let mut rng = SplitMix64::new(11);
+        let mut missing = [0; 4];
+
+        for _ in 0..4000 {
+            let tree = uniform_spanning_tree(&graph, n[0], &mut rng);
+            missing[edges.iter().position(|edge| !tree.contains(edge)).unwrap()] += 1;
+        }
+
+        assert!(missing.iter().all(|&count| (900..1100).contains(&count)), "{missing:?}");
+        assert_eq!(3, random_dfs_tree(&graph, n[2], &mut rng).len());
+    }
+}
@@ -0,0 +1,1525 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Index, IndexMut};
+use std::slice;
+
+use crate::arena::{Arena, ArenaIterator, DefaultIx, IndexType, MemoryUsage};
+use crate::attr::NodeMap;
+use crate::bitset::FixedBitSet;
+use crate::error::GraphError;
+use crate::fnv::Fnv;
+use crate::parallel;
+#[cfg(feature = "inline-adjacency")]
+use crate::smallvec::SmallVec;
+
+/// Memory report of a [`Graph`], see [`Graph::memory_usage`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphMemory {
+    pub nodes: MemoryUsage,
+    pub edges: MemoryUsage
+}
+
+/// Handle to a node: the index of its slot plus the generation of that
+/// slot, so a handle to a removed node does not silently alias whichever
+/// node reuses the slot. Indices are stored as [`DefaultIx`], so an id
+/// takes eight bytes, or sixteen with the `usize-indices` feature.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId {
+    index: DefaultIx,
+    generation: u32
+}
+
+impl NodeId {
+    /// Id of generation 0, as held by nodes whose slot was never reused and
+    /// by every node of a graph without removals. Panics if `index` does
+    /// not fit in a [`DefaultIx`].
+    pub fn new(index: usize) -> NodeId {
+        NodeId { index: DefaultIx::new(index), generation: 0 }
+    }
+
+    pub(crate) fn with_generation(index: usize, generation: u32) -> NodeId {
+        NodeId { index: DefaultIx::new(index), generation }
+    }
+
+    pub fn index(self) -> usize {
+        self.index.index()
+    }
+
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EdgeId {
+    index: DefaultIx,
+    generation: u32
+}
+
+impl EdgeId {
+    /// Id of generation 0, see [`NodeId::new`].
+    pub fn new(index: usize) -> EdgeId {
+        EdgeId { index: DefaultIx::new(index), generation: 0 }
+    }
+
+    pub(crate) fn with_generation(index: usize, generation: u32) -> EdgeId {
+        EdgeId { index: DefaultIx::new(index), generation }
+    }
+
+    pub fn index(self) -> usize {
+        self.index.index()
+    }
+
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Outgoing,
+    Incoming
+}
+
+impl Direction {
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Outgoing => Direction::Incoming,
+            Direction::Incoming => Direction::Outgoing
+        }
+    }
+
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Direction::Outgoing => 0,
+            Direction::Incoming => 1
+        }
+    }
+}
+
+pub trait EdgeType {
+    fn is_directed() -> bool;
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Directed {}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Undirected {}
+
+impl EdgeType for Directed {
+    fn is_directed() -> bool {
+        true
+    }
+}
+
+impl EdgeType for Undirected {
+    fn is_directed() -> bool {
+        false
+    }
+}
+
+/// Edges at a node in one direction. With the `inline-adjacency` feature,
+/// up to four are kept in the node itself, sparing small-degree nodes a
+/// heap allocation at the cost of a larger node.
+#[cfg(feature = "inline-adjacency")]
+type EdgeList = SmallVec<EdgeId, 4>;
+#[cfg(not(feature = "inline-adjacency"))]
+type EdgeList = Vec<EdgeId>;
+
+struct Node<N> {
+    weight: N,
+    edges: [EdgeList; 2]
+}
+
+struct Edge<E> {
+    weight: E,
+    source: NodeId,
+    target: NodeId
+}
+
+impl<E> Edge<E> {
+    fn other(&self, node: NodeId) -> NodeId {
+        if self.source == node { self.target } else { self.source }
+    }
+
+    // far end as seen from the `dir` list holding the edge
+    fn far(&self, dir: Direction) -> NodeId {
+        match dir {
+            Direction::Outgoing => self.target,
+            Direction::Incoming => self.source
+        }
+    }
+}
+
+/// Id that changed in a [`Graph::compact`], old first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Moved {
+    Node(NodeId, NodeId),
+    Edge(EdgeId, EdgeId)
+}
+
+/// Node taken out of a graph by [`Graph::remove_node`], with the edges
+/// removed along with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemovedNode<N, E> {
+    pub weight: N,
+    pub edges: Vec<(EdgeId, E)>
+}
+
+/// What [`Graph::add_edge`] does when the two nodes are already joined, in
+/// the sense of [`Graph::find_edge`], set per graph with
+/// [`Graph::set_edge_policy`].
+pub enum EdgePolicy<E> {
+    /// Adds a parallel edge, the default.
+    Allow,
+    /// Keeps the existing edge and drops the new weight.
+    Reject,
+    /// Keeps the existing edge, passing its weight and the new one to the
+    /// function, e.g. to add them up.
+    MergeWith(fn(&mut E, E))
+}
+
+impl<E> Clone for EdgePolicy<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EdgePolicy<E> {}
+
+struct AutoCompact {
+    threshold: f64,
+    hook: Box<dyn FnMut(Moved) + Send + Sync>
+}
+
+/// Adjacency-list graph whose nodes and edges live in slot arenas, so ids
+/// stay valid across removals and freed slots are reused.
+pub struct Graph<N, E, Ty = Directed> {
+    nodes: Arena<Node<N>>,
+    edges: Arena<Edge<E>>,
+    // adjacency lists are ordered by the other endpoint, see `freeze`
+    sorted: bool,
+    edge_policy: EdgePolicy<E>,
+    auto_compact: Option<AutoCompact>,
+    ty: PhantomData<Ty>
+}
+
+pub type DiGraph<N, E> = Graph<N, E, Directed>;
+pub type UnGraph<N, E> = Graph<N, E, Undirected>;
+
+pub struct Neighbors<'a, E> {
+    edges: &'a Arena<Edge<E>>,
+    node: NodeId,
+    first: slice::Iter<'a, EdgeId>,
+    second: slice::Iter<'a, EdgeId>
+}
+
+impl<E> Neighbors<'_, E> {
+    pub(crate) fn next_edge(&mut self) -> Option<(EdgeId, NodeId)> {
+        if let Some(&id) = self.first.next() {
+            return Some((id, self.edges[id.index()].other(self.node)));
+        }
+
+        // the second list only exists for undirected graphs, where a self
+        // loop is already reported through the first one
+        for &id in self.second.by_ref() {
+            let edge = &self.edges[id.index()];
+
+            if edge.source != edge.target {
+                return Some((id, edge.other(self.node)));
+            }
+        }
+
+        None
+    }
+}
+
+impl<E> Iterator for Neighbors<'_, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_edge().map(|(_, node)| node)
+    }
+}
+
+/// Edge as seen from one of its endpoints, see [`Graph::edges_directed`].
+pub struct EdgeRef<'a, E> {
+    pub id: EdgeId,
+    pub source: NodeId,
+    pub target: NodeId,
+    pub weight: &'a E
+}
+
+impl<E> Clone for EdgeRef<'_, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for EdgeRef<'_, E> {}
+
+pub struct Edges<'a, E> {
+    neighbors: Neighbors<'a, E>,
+    incoming: bool
+}
+
+impl<'a, E> Iterator for Edges<'a, E> {
+    type Item = EdgeRef<'a, E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, other) = self.neighbors.next_edge()?;
+        let node = self.neighbors.node;
+        let (source, target) = if self.incoming { (other, node) } else { (node, other) };
+
+        Some(EdgeRef { id, source, target, weight: &self.neighbors.edges[id.index()].weight })
+    }
+}
+
+pub struct NodeIds<'a, N> {
+    arena: &'a Arena<Node<N>>,
+    nodes: ArenaIterator<'a, Node<N>>
+}
+
+impl<N> Iterator for NodeIds<'_, N> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes
+            .next()
+            .map(|(pos, _)| NodeId::with_generation(pos, self.arena.generation(pos).unwrap()))
+    }
+}
+
+pub struct EdgeIds<'a, E> {
+    arena: &'a Arena<Edge<E>>,
+    edges: ArenaIterator<'a, Edge<E>>
+}
+
+impl<E> Iterator for EdgeIds<'_, E> {
+    type Item = EdgeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.edges
+            .next()
+            .map(|(pos, _)| EdgeId::with_generation(pos, self.arena.generation(pos).unwrap()))
+    }
+}
+
+/// Compacts `arena`, returning by old position the old and new id of every
+/// value that moved.
+fn remap<T, Id>(arena: &mut Arena<T>, id: impl Fn(usize, u32) -> Id) -> Vec<Option<(Id, Id)>> {
+    // the slots values leave get new generations
+    let before: Vec<_> = (0..arena.slot_count()).map(|pos| arena.generation(pos).unwrap()).collect();
+    let mut moves = Vec::new();
+
+    arena.compact(|old, new| moves.push((old, new)));
+
+    let mut by_old = Vec::new();
+    by_old.resize_with(before.len(), || None);
+
+    for (old, new) in moves {
+        by_old[old] = Some((id(old, before[old]), id(new, arena.generation(new).unwrap())));
+    }
+
+    by_old
+}
+
+impl<N, E, Ty: EdgeType> Default for Graph<N, E, Ty> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+/// Lists nodes as index and weight, and edges as source, target and weight.
+impl<N: fmt::Debug, E: fmt::Debug, Ty: EdgeType> fmt::Debug for Graph<N, E, Ty> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes: Vec<_> = self.node_ids().map(|node| (node.index(), &self[node])).collect();
+        let edges: Vec<_> = self
+            .edge_ids()
+            .map(|edge| {
+                let (source, target) = self.edge_endpoints(edge).unwrap();
+                (source.index(), target.index(), &self[edge])
+            })
+            .collect();
+
+        f.debug_struct("Graph")
+            .field("directed", &Ty::is_directed())
+            .field("nodes", &nodes)
+            .field("edges", &edges)
+            .finish()
+    }
+}
+
+impl<N, E, Ty: EdgeType> Graph<N, E, Ty> {
+    pub fn new() -> Graph<N, E, Ty> {
+        Graph {
+            nodes: Arena::new(),
+            edges: Arena::new(),
+            sorted: false,
+            edge_policy: EdgePolicy::Allow,
+            auto_compact: None,
+            ty: PhantomData
+        }
+    }
+
+    pub fn with_capacity(nodes: usize, edges: usize) -> Graph<N, E, Ty> {
+        Graph {
+            nodes: Arena::with_capacity(nodes),
+            edges: Arena::with_capacity(edges),
+            sorted: false,
+            edge_policy: EdgePolicy::Allow,
+            auto_compact: None,
+            ty: PhantomData
+        }
+    }
+
+    /// Makes room for `additional` more nodes without reallocating.
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    /// Makes room for `additional` more edges without reallocating the edge
+    /// storage; adjacency lists still grow as edges arrive.
+    pub fn reserve_edges(&mut self, additional: usize) {
+        self.edges.reserve(additional);
+    }
+
+    /// Slot occupancy of the node and edge storage. Node bytes include the
+    /// adjacency lists.
+    pub fn memory_usage(&self) -> GraphMemory {
+        let mut nodes = self.nodes.memory_usage();
+
+        for (_, node) in self.nodes.iter() {
+            let lists: usize = node.edges.iter().map(|list| list.capacity()).sum();
+            nodes.bytes += lists * mem::size_of::<EdgeId>();
+        }
+
+        GraphMemory { nodes, edges: self.edges.memory_usage() }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        Ty::is_directed()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Upper bound on `NodeId::index` for live nodes, for sizing dense
+    /// per-node tables.
+    pub fn node_bound(&self) -> usize {
+        self.nodes.slot_count()
+    }
+
+    pub fn edge_bound(&self) -> usize {
+        self.edges.slot_count()
+    }
+
+    fn node(&self, id: NodeId) -> Option<&Node<N>> {
+        if self.nodes.generation(id.index()) != Some(id.generation) {
+            return None;
+        }
+
+        self.nodes.get(id.index())
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> Option<&mut Node<N>> {
+        if self.nodes.generation(id.index()) != Some(id.generation) {
+            return None;
+        }
+
+        self.nodes.get_mut(id.index())
+    }
+
+    fn edge(&self, id: EdgeId) -> Option<&Edge<E>> {
+        if self.edges.generation(id.index()) != Some(id.generation) {
+            return None;
+        }
+
+        self.edges.get(id.index())
+    }
+
+    fn edge_mut(&mut self, id: EdgeId) -> Option<&mut Edge<E>> {
+        if self.edges.generation(id.index()) != Some(id.generation) {
+            return None;
+        }
+
+        self.edges.get_mut(id.index())
+    }
+
+    pub fn contains_node(&self, node: NodeId) -> bool {
+        self.node(node).is_some()
+    }
+
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.edge(edge).is_some()
+    }
+
+    /// Current id of the live node in slot `index`, if any.
+    pub fn node_id_at(&self, index: usize) -> Option<NodeId> {
+        self.nodes.get(index)?;
+        Some(NodeId::with_generation(index, self.nodes.generation(index).unwrap()))
+    }
+
+    /// Current id of the live edge in slot `index`, if any.
+    pub fn edge_id_at(&self, index: usize) -> Option<EdgeId> {
+        self.edges.get(index)?;
+        Some(EdgeId::with_generation(index, self.edges.generation(index).unwrap()))
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        let pos = self.nodes.insert(Node { weight, edges: [EdgeList::new(), EdgeList::new()] });
+        NodeId::with_generation(pos, self.nodes.generation(pos).unwrap())
+    }
+
+    /// Whether `node` is in the graph, and if not, why.
+    pub fn check_node(&self, node: NodeId) -> Result<(), GraphError> {
+        match self.nodes.generation(node.index()) {
+            _ if self.contains_node(node) => Ok(()),
+            Some(_) => Err(GraphError::StaleGeneration(node)),
+            None => Err(GraphError::InvalidNodeId(node))
+        }
+    }
+
+    pub fn edge_policy(&self) -> EdgePolicy<E> {
+        self.edge_policy
+    }
+
+    /// Sets what adding an edge between nodes already joined does from now
+    /// on. Parallel edges added before are kept.
+    pub fn set_edge_policy(&mut self, policy: EdgePolicy<E>) {
+        self.edge_policy = policy;
+    }
+
+    /// Adds an edge, or under an [`EdgePolicy`] other than `Allow`, returns
+    /// the edge already joining the two nodes. Panics if either endpoint is
+    /// not in the graph; see [`try_add_edge`](Graph::try_add_edge).
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, weight: E) -> EdgeId {
+        assert!(self.contains_node(source), "source node not in graph");
+        assert!(self.contains_node(target), "target node not in graph");
+
+        self.add_edge_with_policy(source, target, weight).unwrap_or_else(|existing| existing)
+    }
+
+    /// Same as [`add_edge`](Graph::add_edge), but failing on bad
+    /// endpoints, and with [`GraphError::DuplicateEdge`] under
+    /// [`EdgePolicy::Reject`].
+    pub fn try_add_edge(
+        &mut self,
+        source: NodeId,
+        target: NodeId,
+        weight: E
+    ) -> Result<EdgeId, GraphError> {
+        self.check_node(source)?;
+        self.check_node(target)?;
+
+        match self.add_edge_with_policy(source, target, weight) {
+            Err(existing) if matches!(self.edge_policy, EdgePolicy::Reject) => {
+                Err(GraphError::DuplicateEdge(existing))
+            },
+            added => Ok(added.unwrap_or_else(|existing| existing))
+        }
+    }
+
+    /// The new edge, or the existing one that the policy kept instead.
+    fn add_edge_with_policy(&mut self, source: NodeId, target: NodeId, weight: E) -> Result<EdgeId, EdgeId> {
+        let existing = match self.edge_policy {
+            EdgePolicy::Allow => None,
+            _ => self.find_edge(source, target)
+        };
+
+        match (existing, self.edge_policy) {
+            (Some(edge), EdgePolicy::MergeWith(merge)) => {
+                merge(&mut self.edges[edge.index()].weight, weight);
+                Err(edge)
+            },
+            (Some(edge), _) => Err(edge),
+            (None, _) => Ok(self.insert_edge(source, target, weight))
+        }
+    }
+
+    fn insert_edge(&mut self, source: NodeId, target: NodeId, weight: E) -> EdgeId {
+        let pos = self.edges.insert(Edge { weight, source, target });
+        let id = EdgeId::with_generation(pos, self.edges.generation(pos).unwrap());
+
+        for (node, dir) in [(source, Direction::Outgoing), (target, Direction::Incoming)] {
+            let list = &self.nodes[node.index()].edges[dir.index()];
+
+            // after parallel edges to the same node, as a push would put it
+            let at = if self.sorted {
+                let far = self.edges[pos].far(dir).index();
+                list.partition_point(|&e| self.edges[e.index()].far(dir).index() <= far)
+            } else {
+                list.len()
+            };
+
+            self.nodes[node.index()].edges[dir.index()].insert(at, id);
+        }
+
+        id
+    }
+
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<E> {
+        let weight = self.unlink_edge(edge)?;
+
+        self.compact_if_fragmented();
+        Some(weight)
+    }
+
+    /// Removes `node` along with every edge at it, returned with it.
+    pub fn remove_node(&mut self, node: NodeId) -> Option<RemovedNode<N, E>> {
+        let incident: Vec<EdgeId> = {
+            let entry = self.node(node)?;
+            entry.edges.iter().flatten().copied().collect()
+        };
+
+        // undirected self loops are listed twice, and unlinked once
+        let edges = incident.into_iter().filter_map(|edge| Some((edge, self.unlink_edge(edge)?))).collect();
+        let weight = self.nodes.remove(node.index()).unwrap().weight;
+
+        self.compact_if_fragmented();
+        Some(RemovedNode { weight, edges })
+    }
+
+    /// Removes every node for which `pred(node, weight)` holds, with the
+    /// edges at them, in slot order. Each edge is returned with the first
+    /// of its endpoints removed. Unlike removing the nodes one by one, the
+    /// adjacency lists of the nodes left are cleaned up once each, however
+    /// many of their neighbors go.
+    pub fn remove_nodes_where(
+        &mut self,
+        mut pred: impl FnMut(NodeId, &N) -> bool
+    ) -> Vec<(NodeId, RemovedNode<N, E>)> {
+        let doomed: Vec<_> = self.node_ids().filter(|&node| pred(node, &self[node])).collect();
+        let mut marked = FixedBitSet::with_capacity(self.node_bound());
+        let mut dropped = FixedBitSet::with_capacity(self.edge_bound());
+        let mut touched = Vec::new();
+        let mut removed = Vec::with_capacity(doomed.len());
+
+        doomed.iter().for_each(|node| marked.insert(node.index()));
+
+        for node in doomed {
+            let entry = self.nodes.remove(node.index()).unwrap();
+            let mut edges = Vec::new();
+
+            for &edge in entry.edges.iter().flatten() {
+                if dropped.contains(edge.index()) {
+                    continue;
+                }
+
+                let removed = self.edges.remove(edge.index()).unwrap();
+                let far = removed.other(node);
+
+                if !marked.contains(far.index()) {
+                    touched.push(far);
+                }
+
+                dropped.insert(edge.index());
+                edges.push((edge, removed.weight));
+            }
+
+            removed.push((node, RemovedNode { weight: entry.weight, edges }));
+        }
+
+        touched.sort_unstable();
+        touched.dedup();
+
+        for node in touched {
+            for list in &mut self.nodes[node.index()].edges {
+                list.retain(|edge| !dropped.contains(edge.index()));
+            }
+        }
+
+        self.compact_if_fragmented();
+        removed
+    }
+
+    fn unlink_edge(&mut self, edge: EdgeId) -> Option<E> {
+        self.edge(edge)?;
+
+        let removed = self.edges.remove(edge.index()).unwrap();
+
+        self.nodes[removed.source.index()].edges[Direction::Outgoing.index()].retain(|&e| e != edge);
+        self.nodes[removed.target.index()].edges[Direction::Incoming.index()].retain(|&e| e != edge);
+
+        Some(removed.weight)
+    }
+
+    /// Moves nodes and edges into the lowest slots, keeping their order,
+    /// and gives up the free slots, so [`node_bound`](Graph::node_bound)
+    /// and [`edge_bound`](Graph::edge_bound) drop to the counts. Every id
+    /// that changes is passed to `moved`, nodes first, in slot order; ids
+    /// kept elsewhere, as by a [`NodeMap`](crate::NodeMap), can be updated
+    /// from it by removing the old id and inserting the new one, in that
+    /// order. Old ids go stale.
+    pub fn compact(&mut self, mut moved: impl FnMut(Moved)) {
+        let nodes = remap(&mut self.nodes, NodeId::with_generation);
+        let edges = remap(&mut self.edges, EdgeId::with_generation);
+        let node_at = |id: NodeId| nodes.get(id.index()).copied().flatten().map_or(id, |(_, new)| new);
+        let edge_at = |id: EdgeId| edges.get(id.index()).copied().flatten().map_or(id, |(_, new)| new);
+
+        for (_, edge) in self.edges.iter_mut() {
+            edge.source = node_at(edge.source);
+            edge.target = node_at(edge.target);
+        }
+
+        for (_, node) in self.nodes.iter_mut() {
+            node.edges.iter_mut().flat_map(|list| list.iter_mut()).for_each(|id| *id = edge_at(*id));
+        }
+
+        nodes.into_iter().flatten().for_each(|(old, new)| moved(Moved::Node(old, new)));
+        edges.into_iter().flatten().for_each(|(old, new)| moved(Moved::Edge(old, new)));
+    }
+
+    /// Compacts the graph as soon as a removal leaves more than `threshold`
+    /// of its node or edge slots free, out of what
+    /// [`memory_usage`](Graph::memory_usage) reports, passing the ids that
+    /// change to `hook` as [`compact`](Graph::compact) does. Removals then
+    /// invalidate the ids of other nodes and edges, so ids collected before
+    /// a batch of removals must be mapped through `hook`.
+    pub fn set_auto_compact(&mut self, threshold: f64, hook: impl FnMut(Moved) + Send + Sync + 'static) {
+        self.auto_compact = Some(AutoCompact { threshold, hook: Box::new(hook) });
+        self.compact_if_fragmented();
+    }
+
+    /// Stops compacting on removal, see
+    /// [`set_auto_compact`](Graph::set_auto_compact).
+    pub fn clear_auto_compact(&mut self) {
+        self.auto_compact = None;
+    }
+
+    fn compact_if_fragmented(&mut self) {
+        let Some(mut auto) = self.auto_compact.take() else { return };
+        let usage = [self.nodes.memory_usage(), self.edges.memory_usage()];
+
+        if usage.iter().any(|usage| usage.fragmentation() > auto.threshold) {
+            self.compact(&mut auto.hook);
+        }
+
+        self.auto_compact = Some(auto);
+    }
+
+    /// Removes every edge, yielding their weights. Edge slots and
+    /// adjacency lists keep their room, so a graph rebuilt with as many
+    /// edges does not allocate.
+    pub fn drain_edges(&mut self) -> impl Iterator<Item = E> + '_ {
+        for (_, node) in self.nodes.iter_mut() {
+            node.edges.iter_mut().for_each(|list| list.clear());
+        }
+
+        self.edges.drain().map(|(_, edge)| edge.weight)
+    }
+
+    /// Removes every node and edge, yielding the node weights in slot
+    /// order. Node and edge slots keep their room, and nodes added after
+    /// take the slots from the first one on.
+    pub fn drain_nodes(&mut self) -> impl Iterator<Item = N> + '_ {
+        self.edges.drain();
+        self.nodes.drain().map(|(_, node)| node.weight)
+    }
+
+    pub fn node_weight(&self, node: NodeId) -> Option<&N> {
+        self.node(node).map(|n| &n.weight)
+    }
+
+    pub fn node_weight_mut(&mut self, node: NodeId) -> Option<&mut N> {
+        self.node_mut(node).map(|n| &mut n.weight)
+    }
+
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<&E> {
+        self.edge(edge).map(|e| &e.weight)
+    }
+
+    pub fn edge_weight_mut(&mut self, edge: EdgeId) -> Option<&mut E> {
+        self.edge_mut(edge).map(|e| &mut e.weight)
+    }
+
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edge(edge).map(|e| (e.source, e.target))
+    }
+
+    pub fn node_ids(&self) -> NodeIds<'_, N> {
+        NodeIds { arena: &self.nodes, nodes: self.nodes.iter() }
+    }
+
+    pub fn edge_ids(&self) -> EdgeIds<'_, E> {
+        EdgeIds { arena: &self.edges, edges: self.edges.iter() }
+    }
+
+    /// Successors for directed graphs, all adjacent nodes otherwise. Panics
+    /// if the node is not in the graph.
+    pub fn neighbors(&self, node: NodeId) -> Neighbors<'_, E> {
+        self.neighbors_directed(node, Direction::Outgoing)
+    }
+
+    /// For undirected graphs the direction is ignored.
+    pub fn neighbors_directed(&self, node: NodeId, dir: Direction) -> Neighbors<'_, E> {
+        self.try_neighbors_directed(node, dir).expect("node not in graph")
+    }
+
+    pub fn try_neighbors_directed(
+        &self,
+        node: NodeId,
+        dir: Direction
+    ) -> Result<Neighbors<'_, E>, GraphError> {
+        self.check_node(node)?;
+
+        let edges = &self.nodes[node.index()].edges;
+
+        let (first, second): (&[EdgeId], &[EdgeId]) = if Ty::is_directed() {
+            (&edges[dir.index()], &[])
+        } else {
+            (&edges[0], &edges[1])
+        };
+
+        Ok(Neighbors {
+            edges: &self.edges,
+            node,
+            first: first.iter(),
+            second: second.iter()
+        })
+    }
+
+    /// Edges leaving `node` for directed graphs, all its edges otherwise.
+    /// Panics if the node is not in the graph.
+    pub fn edges(&self, node: NodeId) -> Edges<'_, E> {
+        self.edges_directed(node, Direction::Outgoing)
+    }
+
+    /// Edges in the order [`neighbors_directed`] yields their other ends.
+    /// `source` is `node` and `target` the neighbor, except for incoming
+    /// edges of directed graphs, which keep their direction.
+    ///
+    /// [`neighbors_directed`]: Graph::neighbors_directed
+    pub fn edges_directed(&self, node: NodeId, dir: Direction) -> Edges<'_, E> {
+        Edges {
+            neighbors: self.neighbors_directed(node, dir),
+            incoming: Ty::is_directed() && dir == Direction::Incoming
+        }
+    }
+
+    pub fn degree(&self, node: NodeId) -> usize {
+        self.neighbors(node).count()
+    }
+
+    /// Linear in the degree of `source`, logarithmic after [`freeze`].
+    ///
+    /// [`freeze`]: Graph::freeze
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        let node = self.node(source)?;
+
+        let lookup = |dir: Direction| {
+            let list = &node.edges[dir.index()];
+            let far = |e: &&EdgeId| self.edges[e.index()].far(dir);
+
+            if self.sorted {
+                let at = list.partition_point(|e| far(&e).index() < target.index());
+                let mut run = list[at..].iter().take_while(|e| far(e).index() == target.index());
+                run.find(|e| far(e) == target).copied()
+            } else {
+                list.iter().find(|e| far(e) == target).copied()
+            }
+        };
+
+        let outgoing = lookup(Direction::Outgoing);
+
+        if outgoing.is_some() || Ty::is_directed() {
+            return outgoing;
+        }
+
+        lookup(Direction::Incoming)
+    }
+
+    pub fn has_edge(&self, source: NodeId, target: NodeId) -> bool {
+        self.find_edge(source, target).is_some()
+    }
+
+    /// Position of every node in [`node_ids`](Graph::node_ids) order, from
+    /// zero up, e.g. for rows of a matrix. The positions are the indices
+    /// the nodes would get in a graph built from this one without gaps.
+    pub fn to_index_map(&self) -> NodeMap<usize> {
+        let mut positions = NodeMap::with_capacity(self.node_bound());
+
+        for (i, node) in self.node_ids().enumerate() {
+            positions.insert(node, i);
+        }
+
+        positions
+    }
+
+    /// Sorts every adjacency list by the index of the node at the far end,
+    /// so that [`find_edge`] and [`has_edge`] binary search instead of
+    /// scanning. Edges added later are inserted in order, which costs a
+    /// shift of the list instead of a push. Parallel edges keep their
+    /// order, but neighbors are no longer reported in insertion order.
+    ///
+    /// [`find_edge`]: Graph::find_edge
+    /// [`has_edge`]: Graph::has_edge
+    pub fn freeze(&mut self) {
+        let edges = &self.edges;
+
+        for (_, node) in self.nodes.iter_mut() {
+            for dir in [Direction::Outgoing, Direction::Incoming] {
+                node.edges[dir.index()].sort_by_key(|e| edges[e.index()].far(dir).index());
+            }
+        }
+
+        self.sorted = true;
+    }
+
+    /// `f(node, weight)` for every node, on all available cores, in
+    /// [`node_ids`](Graph::node_ids) order. `f` may read the graph, so a
+    /// frozen graph can be searched from many nodes at once.
+    pub fn par_map_nodes<R: Send>(&self, f: impl Fn(NodeId, &N) -> R + Sync) -> Vec<R>
+    where
+        Self: Sync
+    {
+        let ids: Vec<_> = self.node_ids().collect();
+        parallel::par_map(&ids, |&node| f(node, &self[node]))
+    }
+
+    /// `f` for every edge, on all available cores, in
+    /// [`edge_ids`](Graph::edge_ids) order.
+    pub fn par_map_edges<R: Send>(&self, f: impl Fn(EdgeRef<'_, E>) -> R + Sync) -> Vec<R>
+    where
+        Self: Sync
+    {
+        let ids: Vec<_> = self.edge_ids().collect();
+
+        parallel::par_map(&ids, |&id| {
+            let edge = &self.edges[id.index()];
+            f(EdgeRef { id, source: edge.source, target: edge.target, weight: &edge.weight })
+        })
+    }
+
+    pub fn node_weights(&self) -> impl Iterator<Item = &N> + '_ {
+        self.nodes.iter().map(|(_, n)| &n.weight)
+    }
+
+    pub fn node_weights_mut(&mut self) -> impl Iterator<Item = &mut N> + '_ {
+        self.nodes.iter_mut().map(|(_, n)| &mut n.weight)
+    }
+
+    pub fn edge_weights(&self) -> impl Iterator<Item = &E> + '_ {
+        self.edges.iter().map(|(_, e)| &e.weight)
+    }
+
+    pub fn edge_weights_mut(&mut self) -> impl Iterator<Item = &mut E> + '_ {
+        self.edges.iter_mut().map(|(_, e)| &mut e.weight)
+    }
+
+    /// Graph of the same shape with weights mapped through `node` and
+    /// `edge`. Every id stays valid in the new graph, and stale ones stay
+    /// stale.
+    pub fn map<N2, E2>(
+        &self,
+        mut node: impl FnMut(NodeId, &N) -> N2,
+        mut edge: impl FnMut(EdgeId, &E) -> E2
+    ) -> Graph<N2, E2, Ty> {
+        let nodes = self.nodes.map(|pos, n| Node {
+            weight: node(NodeId::with_generation(pos, self.nodes.generation(pos).unwrap()), &n.weight),
+            edges: n.edges.clone()
+        });
+
+        let edges = self.edges.map(|pos, e| Edge {
+            weight: edge(EdgeId::with_generation(pos, self.edges.generation(pos).unwrap()), &e.weight),
+            source: e.source,
+            target: e.target
+        });
+
+        Graph {
+            nodes,
+            edges,
+            sorted: self.sorted,
+            edge_policy: EdgePolicy::Allow,
+            auto_compact: None,
+            ty: PhantomData
+        }
+    }
+
+    /// Unions `other` into this graph. Nodes with equal `node_key` are the
+    /// same node, whether already here or earlier in `other`, and keep the
+    /// first weight seen; the rest are added. An edge between nodes already
+    /// joined, in the sense of [`find_edge`](Graph::find_edge), is passed
+    /// with the existing weight to `on_conflict`, e.g. to add them up, and
+    /// not added. Returns where every node of `other` ended up.
+    pub fn merge_from<K: Eq + Hash>(
+        &mut self,
+        other: &Graph<N, E, Ty>,
+        mut node_key: impl FnMut(&N) -> K,
+        mut on_conflict: impl FnMut(&mut E, &E)
+    ) -> NodeMap<NodeId>
+    where
+        N: Clone,
+        E: Clone
+    {
+        let mut keyed: HashMap<K, NodeId> = HashMap::with_capacity(self.node_count() + other.node_count());
+
+        for node in self.node_ids() {
+            keyed.entry(node_key(&self[node])).or_insert(node);
+        }
+
+        let mut placed = NodeMap::with_capacity(other.node_bound());
+
+        for node in other.node_ids() {
+            let weight = &other[node];
+            let here = *keyed.entry(node_key(weight)).or_insert_with(|| self.add_node(weight.clone()));
+            placed.insert(node, here);
+        }
+
+        for edge in other.edge_ids() {
+            let (a, b) = other.edge_endpoints(edge).unwrap();
+            let (source, target) = (placed[a], placed[b]);
+
+            match self.find_edge(source, target) {
+                Some(existing) => on_conflict(&mut self[existing], &other[edge]),
+                None => {
+                    self.insert_edge(source, target, other[edge].clone());
+                }
+            }
+        }
+
+        placed
+    }
+
+    /// Hash of the weights of the nodes and of the edges together with the
+    /// weights at their ends, taken as multisets, so it does not depend on
+    /// ids, slot layout or the order things were added in. Graphs equal up
+    /// to renumbering their nodes hash alike. Nodes are only told apart by
+    /// their weights; see [`weisfeiler_lehman_hash`] for unlabelled graphs.
+    ///
+    /// Hashing is FNV-1a, stable across platforms and runs as long as the
+    /// weights' `Hash` impls are.
+    ///
+    /// [`weisfeiler_lehman_hash`]: crate::algo::weisfeiler_lehman_hash
+    pub fn structural_hash(&self) -> u64
+    where
+        N: Hash,
+        E: Hash
+    {
+        fn hash_of(value: impl Hash) -> u64 {
+            let mut fnv = Fnv::new();
+            value.hash(&mut fnv);
+            fnv.finish()
+        }
+
+        let mut nodes: Vec<_> = self.node_ids().map(|node| hash_of(&self[node])).collect();
+        let mut edges: Vec<_> = self
+            .edge_ids()
+            .map(|edge| {
+                let (a, b) = self.edge_endpoints(edge).unwrap();
+                let (mut a, mut b) = (hash_of(&self[a]), hash_of(&self[b]));
+
+                if !Ty::is_directed() && b < a {
+                    mem::swap(&mut a, &mut b);
+                }
+
+                hash_of((a, b, hash_of(&self[edge])))
+            })
+            .collect();
+
+        nodes.sort_unstable();
+        edges.sort_unstable();
+        hash_of((Ty::is_directed(), nodes, edges))
+    }
+
+    /// Entry for the edge from `source` to `target`, the first one if there
+    /// are several, found with a single lookup.
+    pub fn edge_entry(&mut self, source: NodeId, target: NodeId) -> EdgeEntry<'_, N, E, Ty> {
+        let edge = self.find_edge(source, target);
+        EdgeEntry { graph: self, source, target, edge }
+    }
+}
+
+/// Panics if the node is not in the graph.
+impl<N, E, Ty: EdgeType> Index<NodeId> for Graph<N, E, Ty> {
+    type Output = N;
+
+    fn index(&self, node: NodeId) -> &N {
+        self.node_weight(node).expect("node not in graph")
+    }
+}
+
+impl<N, E, Ty: EdgeType> IndexMut<NodeId> for Graph<N, E, Ty> {
+    fn index_mut(&mut self, node: NodeId) -> &mut N {
+        self.node_weight_mut(node).expect("node not in graph")
+    }
+}
+
+/// Panics if the edge is not in the graph.
+impl<N, E, Ty: EdgeType> Index<EdgeId> for Graph<N, E, Ty> {
+    type Output = E;
+
+    fn index(&self, edge: EdgeId) -> &E {
+        self.edge_weight(edge).expect("edge not in graph")
+    }
+}
+
+impl<N, E, Ty: EdgeType> IndexMut<EdgeId> for Graph<N, E, Ty> {
+    fn index_mut(&mut self, edge: EdgeId) -> &mut E {
+        self.edge_weight_mut(edge).expect("edge not in graph")
+    }
+}
+
+/// Edge between two nodes that may not exist yet, from
+/// [`Graph::edge_entry`].
+pub struct EdgeEntry<'a, N, E, Ty> {
+    graph: &'a mut Graph<N, E, Ty>,
+    source: NodeId,
+    target: NodeId,
+    edge: Option<EdgeId>
+}
+
+impl<N, E, Ty: EdgeType> EdgeEntry<'_, N, E, Ty> {
+    /// The edge, if it exists.
+    pub fn id(&self) -> Option<EdgeId> {
+        self.edge
+    }
+
+    /// Updates the weight of an existing edge.
+    pub fn and_modify(self, f: impl FnOnce(&mut E)) -> Self {
+        if let Some(edge) = self.edge {
+            f(&mut self.graph.edges[edge.index()].weight);
+        }
+
+        self
+    }
+
+    /// The existing edge, or a new one weighted `weight`. Panics like
+    /// [`Graph::add_edge`] if an endpoint is not in the graph.
+    pub fn or_insert(self, weight: E) -> EdgeId {
+        self.or_insert_with(|| weight)
+    }
+
+    pub fn or_insert_with(self, weight: impl FnOnce() -> E) -> EdgeId {
+        match self.edge {
+            Some(edge) => edge,
+            None => self.graph.add_edge(self.source, self.target, weight())
+        }
+    }
+
+    pub fn or_default(self) -> EdgeId
+    where
+        E: Default
+    {
+        self.or_insert_with(E::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_and_remove_nodes_and_edges() {
+        let mut graph: DiGraph<&str, u32> = Graph::new();
+
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let c = graph.add_node("c");
+
+        let ab = graph.add_edge(a, b, 1);
+        let bc = graph.add_edge(b, c, 2);
+        graph.add_edge(c, a, 3);
+
+        assert_eq!(3, graph.node_count());
+        assert_eq!(3, graph.edge_count());
+        assert_eq!(Some(ab), graph.find_edge(a, b));
+        assert_eq!(None, graph.find_edge(b, a));
+
+        let removed = RemovedNode { weight: "b", edges: vec![(bc, 2), (ab, 1)] };
+        assert_eq!(Some(removed), graph.remove_node(b));
+        assert_eq!(1, graph.edge_count());
+        assert_eq!(None, graph.edge_weight(ab));
+        assert_eq!(vec![a], graph.neighbors(c).collect::<Vec<_>>());
+        assert_eq!(None, graph.remove_node(b));
+    }
+
+    #[test]
+    fn directed_neighbors_follow_direction() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let c = graph.add_node(());
+
+        graph.add_edge(a, b, ());
+        graph.add_edge(c, b, ());
+
+        assert_eq!(vec![b], graph.neighbors(a).collect::<Vec<_>>());
+        assert_eq!(0, graph.neighbors(b).count());
+        assert_eq!(
+            vec![a, c],
+            graph.neighbors_directed(b, Direction::Incoming).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn undirected_neighbors_ignore_direction() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+
+        graph.add_edge(a, b, ());
+        graph.add_edge(b, b, ());
+
+        assert_eq!(vec![b], graph.neighbors(a).collect::<Vec<_>>());
+        assert_eq!(vec![b, a], graph.neighbors(b).collect::<Vec<_>>());
+        assert!(graph.find_edge(b, a).is_some());
+    }
+
+    #[test]
+    fn stale_ids_are_rejected() {
+        let mut graph: UnGraph<&str, ()> = Graph::new();
+
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let ab = graph.add_edge(a, b, ());
+
+        graph.remove_node(a);
+
+        let c = graph.add_node("c");
+        let cb = graph.add_edge(c, b, ());
+
+        assert_eq!(a.index(), c.index());
+        assert_ne!(a, c);
+        assert_eq!(None, graph.node_weight(a));
+        assert_eq!(Some(&"c"), graph.node_weight(c));
+        assert_eq!(None, graph.remove_edge(ab));
+        assert!(graph.contains_edge(cb));
+        assert_eq!(Some(c), graph.node_id_at(c.index()));
+        assert_eq!(vec![c, b], graph.node_ids().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn edge_entries_insert_or_update() {
+        let mut graph: UnGraph<(), u32> = Graph::new();
+
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+
+        let ab = graph.edge_entry(a, b).and_modify(|w| *w += 1).or_insert(1);
+        assert_eq!(Some(ab), graph.edge_entry(b, a).id());
+
+        assert_eq!(ab, graph.edge_entry(b, a).and_modify(|w| *w += 1).or_insert(1));
+        assert_eq!(Some(&2), graph.edge_weight(ab));
+
+        let bb = graph.edge_entry(b, b).or_default();
+        assert_eq!(Some(&0), graph.edge_weight(bb));
+        assert_eq!(2, graph.edge_count());
+    }
+
+    #[test]
+    fn index_and_map_weights() {
+        let mut graph: DiGraph<&str, u32> = Graph::new();
+
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        let gone = graph.add_node("gone");
+        let ab = graph.add_edge(a, b, 1);
+        graph.add_edge(b, gone, 2);
+        graph.remove_node(gone);
+
+        graph[a] = "x";
+        graph[ab] += 10;
+        graph.edge_weights_mut().for_each(|w| *w *= 2);
+
+        assert_eq!("x", graph[a]);
+        assert_eq!(22, graph[ab]);
+        assert_eq!(vec![&"x", &"b"], graph.node_weights().collect::<Vec<_>>());
+
+        let mapped = graph.map(|id, n| format!("{}{}", n, id.index()), |_, &w| w as f64 / 2.0);
+
+        assert_eq!("x0", mapped[a]);
+        assert_eq!(11.0, mapped[ab]);
+        assert_eq!(Some((a, b)), mapped.edge_endpoints(ab));
+        assert!(!mapped.contains_node(gone));
+        assert_eq!(vec![b], mapped.neighbors(a).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn edges_carry_ids_and_weights() {
+        let mut graph: DiGraph<(), u32> = Graph::new();
+
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        let ab = graph.add_edge(a, b, 5);
+        graph.add_edge(b, b, 7);
+
+        let out: Vec<_> = graph.edges(a).map(|e| (e.id, e.source, e.target, *e.weight)).collect();
+        assert_eq!(vec![(ab, a, b, 5)], out);
+
+        let incoming = graph.edges_directed(b, Direction::Incoming);
+        assert_eq!(vec![(a, 5), (b, 7)], incoming.map(|e| (e.source, *e.weight)).collect::<Vec<_>>());
+
+        let mut undirected: UnGraph<(), u32> = Graph::new();
+        let x = undirected.add_node(());
+        let y = undirected.add_node(());
+        undirected.add_edge(x, y, 1);
+
+        let from_y = undirected.edges_directed(y, Direction::Incoming);
+        assert_eq!(vec![(y, x)], from_y.map(|e| (e.source, e.target)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn frozen_graphs_find_edges_by_binary_search() {
+        let mut graph: UnGraph<(), u32> = Graph::new();
+        let n: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+
+        for (i, &(a, b)) in [(0, 5), (0, 2), (3, 0), (0, 2), (4, 4), (1, 0)].iter().enumerate() {
+            graph.add_edge(n[a], n[b], i as u32);
+        }
+
+        let before: Vec<_> = (0..6).flat_map(|a| (0..6).map(move |b| (a, b))).collect();
+        let found = |graph: &UnGraph<(), u32>| -> Vec<_> {
+            before.iter().map(|&(a, b)| graph.find_edge(n[a], n[b]).map(|e| graph[e])).collect()
+        };
+        let unsorted = found(&graph);
+
+        graph.freeze();
+        assert_eq!(unsorted, found(&graph));
+
+        // edges from the node, then edges to it, each sorted
+        let around = |graph: &UnGraph<(), u32>| graph.neighbors(n[0]).map(|x| x.index()).collect::<Vec<_>>();
+        assert_eq!(vec![2, 2, 5, 1, 3], around(&graph));
+
+        graph.add_edge(n[0], n[3], 6);
+        graph.add_edge(n[0], n[1], 7);
+        assert_eq!(vec![1, 2, 2, 3, 5, 1, 3], around(&graph));
+        assert_eq!(Some(&1), graph.find_edge(n[0], n[2]).map(|e| &graph[e]));
+        assert_eq!(Some(&2), graph.find_edge(n[3], n[0]).map(|e| &graph[e]));
+        assert!(graph.has_edge(n[4], n[4]));
+        assert!(!graph.has_edge(n[1], n[2]));
+    }
+
+    #[test]
+    fn ids_store_default_indices() {
+        let bytes = if cfg!(feature = "usize-indices") { 16 } else { 8 };
+        assert_eq!((bytes, bytes), (mem::size_of::<NodeId>(), mem::size_of::<EdgeId>()));
+        assert_eq!(u32::MAX as usize, NodeId::new(u32::MAX as usize).index());
+
+        #[cfg(feature = "usize-indices")]
+        assert_eq!(1 << 40, EdgeId::new(1 << 40).index());
+    }
+
+    #[test]
+    fn drains_keep_capacity() {
+        let mut graph: DiGraph<u8, u8> = Graph::new();
+        let n: Vec<_> = (0..3).map(|i| graph.add_node(i)).collect();
+
+        graph.add_edge(n[0], n[1], 10);
+        graph.add_edge(n[1], n[2], 20);
+
+        let mut weights: Vec<_> = graph.drain_edges().collect();
+        weights.sort();
+        assert_eq!(vec![10, 20], weights);
+        assert_eq!((3, 0), (graph.node_count(), graph.edge_count()));
+        assert_eq!(0, graph.neighbors(n[0]).count());
+
+        let bytes = graph.memory_usage();
+        graph.add_edge(n[2], n[0], 30);
+        assert_eq!(bytes.edges.bytes, graph.memory_usage().edges.bytes);
+
+        assert_eq!(vec![0, 1, 2], graph.drain_nodes().collect::<Vec<_>>());
+        assert_eq!((0, 0), (graph.node_count(), graph.edge_count()));
+        assert_eq!(None, graph.node_weight(n[0]));
+        assert_eq!(n[0].index(), graph.add_node(5).index());
+        assert_eq!(2, graph.memory_usage().nodes.free);
+    }
+
+    #[test]
+    fn capacity_and_memory_usage() {
+        let mut graph: DiGraph<u8, u8> = Graph::with_capacity(4, 2);
+
+        let a = graph.add_node(0);
+        let b = graph.add_node(1);
+        let ab = graph.add_edge(a, b, 0);
+        graph.remove_edge(ab);
+        graph.reserve_edges(4);
+
+        let usage = graph.memory_usage();
+
+        assert_eq!((2, 0), (usage.nodes.used, usage.nodes.free));
+        assert!(usage.nodes.spare >= 2);
+        assert_eq!((0, 1), (usage.edges.used, usage.edges.free));
+        assert!(usage.edges.free + usage.edges.spare >= 4);
+        assert!(usage.nodes.bytes > 0 && usage.edges.bytes > 0);
+    }
+
+    #[test]
+    fn compaction_reports_moved_ids() {
+        let mut graph: DiGraph<char, u8> = Graph::new();
+        let n: Vec<_> = "abcd".chars().map(|name| graph.add_node(name)).collect();
+        let cd = graph.add_edge(n[2], n[3], 0);
+        graph.add_edge(n[0], n[3], 1);
+
+        let mut names = NodeMap::new();
+        n.iter().for_each(|&node| _ = names.insert(node, graph[node]));
+
+        graph.remove_node(n[1]);
+        names.remove(n[1]);
+        assert_eq!(0.25, graph.memory_usage().nodes.fragmentation());
+
+        let mut moves = Vec::new();
+        graph.compact(|moved| moves.push(moved));
+
+        let (c, d) = (graph.node_id_at(1).unwrap(), graph.node_id_at(2).unwrap());
+        assert_eq!(vec![Moved::Node(n[2], c), Moved::Node(n[3], d)], moves);
+        assert_eq!((3, 0), (graph.node_bound(), graph.memory_usage().nodes.free));
+        assert_eq!((Some(cd), Some('d')), (graph.find_edge(c, d), graph.node_weight(d).copied()));
+        assert!(!graph.contains_node(n[2]) && graph.has_edge(n[0], d));
+
+        for moved in moves {
+            if let Moved::Node(old, new) = moved {
+                let name = names.remove(old).unwrap();
+                names.insert(new, name);
+            }
+        }
+
+        assert!(graph.node_ids().all(|node| names.get(node) == Some(&graph[node])));
+    }
+
+    #[test]
+    fn auto_compaction_past_the_threshold() {
+        use std::sync::{Arc, Mutex};
+
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        let moves = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&moves);
+
+        graph.set_auto_compact(0.4, move |moved| seen.lock().unwrap().push(moved));
+
+        graph.remove_node(n[0]);
+        assert_eq!(4, graph.node_bound());
+        graph.remove_node(n[2]);
+        assert_eq!(2, graph.node_bound());
+        assert_eq!(2, moves.lock().unwrap().len());
+
+        graph.clear_auto_compact();
+        graph.remove_node(graph.node_id_at(0).unwrap());
+        assert_eq!(2, graph.node_bound());
+    }
+
+    #[test]
+    fn batch_removal_reports_each_edge_once() {
+        let mut graph: UnGraph<u32, char> = Graph::new();
+        let n: Vec<_> = (0..5).map(|i| graph.add_node(i)).collect();
+        let e: Vec<_> = [(0, 1, 'a'), (1, 2, 'b'), (2, 3, 'c'), (3, 3, 'd'), (3, 4, 'e')]
+            .into_iter()
+            .map(|(x, y, w)| graph.add_edge(n[x], n[y], w))
+            .collect();
+
+        let removed = graph.remove_nodes_where(|_, &weight| weight % 2 == 1);
+
+        assert_eq!(
+            vec![
+                (n[1], RemovedNode { weight: 1, edges: vec![(e[1], 'b'), (e[0], 'a')] }),
+                (n[3], RemovedNode { weight: 3, edges: vec![(e[3], 'd'), (e[4], 'e'), (e[2], 'c')] })
+            ],
+            removed
+        );
+        assert_eq!((3, 0), (graph.node_count(), graph.edge_count()));
+        assert!([0, 2, 4].iter().all(|&i| graph.degree(n[i]) == 0));
+        assert!(graph.remove_nodes_where(|_, _| false).is_empty());
+    }
+
+    #[test]
+    fn edge_policy_governs_parallel_edges() {
+        let mut graph: UnGraph<(), u32> = Graph::new();
+        let [a, b] = [(), ()].map(|_| graph.add_node(()));
+        let ab = graph.add_edge(a, b, 1);
+
+        graph.set_edge_policy(EdgePolicy::MergeWith(|total, weight| *total += weight));
+        assert_eq!(ab, graph.add_edge(b, a, 2));
+        assert_eq!((1, Some(&3)), (graph.edge_count(), graph.edge_weight(ab)));
+
+        graph.set_edge_policy(EdgePolicy::Reject);
+        assert_eq!(ab, graph.add_edge(a, b, 5));
+        assert_eq!(Err(GraphError::DuplicateEdge(ab)), graph.try_add_edge(a, b, 5));
+        assert_eq!(Some(&3), graph.edge_weight(ab));
+        assert!(graph.try_add_edge(a, a, 7).is_ok());
+
+        graph.set_edge_policy(EdgePolicy::Allow);
+        assert_ne!(ab, graph.add_edge(a, b, 4));
+        assert_eq!(3, graph.edge_count());
+    }
+
+    #[test]
+    fn fallible_calls_explain_bad_ids() {
+        let mut graph: DiGraph<(), ()> = Graph::new();
+        let a = graph.add_node(());
+        let gone = graph.add_node(());
+        graph.remove_node(gone);
+
+        assert_eq!(Ok(()), graph.check_node(a));
+        assert_eq!(Err(GraphError::StaleGeneration(gone)), graph.try_add_edge(a, gone, ()));
+        let unknown = NodeId::new(5);
+        let around = graph.try_neighbors_directed(unknown, Direction::Outgoing);
+        assert_eq!(Some(GraphError::InvalidNodeId(unknown)), around.err());
+
+        let reused = graph.add_node(());
+        assert!(graph.try_add_edge(a, reused, ()).is_ok());
+        assert_eq!(Err(GraphError::StaleGeneration(gone)), graph.check_node(gone));
+    }
+
+    #[test]
+    fn merges_by_node_key() {
+        let mut day1: DiGraph<&str, u32> = Graph::new();
+        let (a, b) = (day1.add_node("a"), day1.add_node("b"));
+        day1.add_edge(a, b, 1);
+
+        let mut day2: DiGraph<&str, u32> = Graph::new();
+        let (b2, c2, a2) = (day2.add_node("b"), day2.add_node("c"), day2.add_node("a"));
+        day2.add_edge(a2, b2, 2);
+        day2.add_edge(b2, c2, 3);
+        day2.add_edge(a2, b2, 4);
+
+        let placed = day1.merge_from(&day2, |&name| name, |total, &more| *total += more);
+
+        assert_eq!((b, a), (placed[b2], placed[a2]));
+        assert_eq!(Some(&"c"), day1.node_weight(placed[c2]));
+        assert_eq!((3, 2), (day1.node_count(), day1.edge_count()));
+        assert_eq!(7, day1[day1.find_edge(a, b).unwrap()]);
+        assert_eq!(3, day1[day1.find_edge(b, placed[c2]).unwrap()]);
+    }
+
+    #[test]
+    fn structural_hash_ignores_layout() {
+        let mut first: DiGraph<&str, u32> = Graph::new();
+        let [a, b, c] = ["a", "b", "c"].map(|name| first.add_node(name));
+        first.add_edge(a, b, 1);
+        first.add_edge(b, c, 2);
+
+        // same graph built in another order, around a removed node
+        let mut second: DiGraph<&str, u32> = Graph::new();
+        let gone = second.add_node("x");
+        let [c2, b2] = ["c", "b"].map(|name| second.add_node(name));
+        second.remove_node(gone);
+        let a2 = second.add_node("a");
+        second.add_edge(b2, c2, 2);
+        let ab = second.add_edge(a2, b2, 1);
+
+        assert_eq!(first.structural_hash(), second.structural_hash());
+
+        second[ab] = 3;
+        assert_ne!(first.structural_hash(), second.structural_hash());
+
+        let mut flipped: UnGraph<&str, ()> = Graph::new();
+        let mut forward: UnGraph<&str, ()> = Graph::new();
+        let [x, y] = ["x", "y"].map(|name| flipped.add_node(name));
+        let [x2, y2] = ["x", "y"].map(|name| forward.add_node(name));
+        flipped.add_edge(y, x, ());
+        forward.add_edge(x2, y2, ());
+        assert_eq!(flipped.structural_hash(), forward.structural_hash());
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::hash_map::{self, HashMap};
+use std::hash::Hash;
+
+use crate::attr::NodeMap;
+use crate::graph::{Directed, EdgeEntry, EdgeId, EdgeType, Graph, NodeId};
+
+/// [`Graph`] whose nodes are also found by a key, such as a name read from
+/// input, so ingestion code can refer to nodes before knowing their ids.
+pub struct GraphMap<K, N, E, Ty = Directed> {
+    graph: Graph<N, E, Ty>,
+    ids: HashMap<K, NodeId>,
+    keys: NodeMap<K>
+}
+
+impl<K: Hash + Eq + Clone, N, E, Ty: EdgeType> Default for GraphMap<K, N, E, Ty> {
+    fn default() -> Self {
+        GraphMap::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone, N, E, Ty: EdgeType> GraphMap<K, N, E, Ty> {
+    pub fn new() -> GraphMap<K, N, E, Ty> {
+        GraphMap { graph: Graph::new(), ids: HashMap::new(), keys: NodeMap::new() }
+    }
+
+    pub fn with_capacity(nodes: usize, edges: usize) -> GraphMap<K, N, E, Ty> {
+        GraphMap {
+            graph: Graph::with_capacity(nodes, edges),
+            ids: HashMap::with_capacity(nodes),
+            keys: NodeMap::with_capacity(nodes)
+        }
+    }
+
+    /// The underlying graph, for running algorithms on.
+    pub fn graph(&self) -> &Graph<N, E, Ty> {
+        &self.graph
+    }
+
+    pub fn node_id(&self, key: &K) -> Option<NodeId> {
+        self.ids.get(key).copied()
+    }
+
+    pub fn key(&self, node: NodeId) -> Option<&K> {
+        self.keys.get(node)
+    }
+
+    /// Entry for the node under `key`, hashing the key once whether or not
+    /// the node exists.
+    pub fn node_entry(&mut self, key: K) -> NodeEntry<'_, K, N, E, Ty> {
+        NodeEntry { entry: self.ids.entry(key), graph: &mut self.graph, keys: &mut self.keys }
+    }
+
+    /// Entry for the edge between the nodes under `source` and `target`.
+    /// Panics if either key has no node.
+    pub fn edge_entry(&mut self, source: &K, target: &K) -> EdgeEntry<'_, N, E, Ty> {
+        let source = self.node_id(source).expect("source key not in graph");
+        let target = self.node_id(target).expect("target key not in graph");
+
+        self.graph.edge_entry(source, target)
+    }
+
+    /// Adds an edge between the nodes under `source` and `target`, as
+    /// [`Graph::add_edge`] does. Panics if either key has no node.
+    pub fn add_edge(&mut self, source: &K, target: &K, weight: E) -> EdgeId {
+        let source = self.node_id(source).expect("source key not in graph");
+        let target = self.node_id(target).expect("target key not in graph");
+
+        self.graph.add_edge(source, target, weight)
+    }
+
+    /// Position of every node in [`node_ids`](Graph::node_ids) order, by
+    /// key; see [`Graph::to_index_map`].
+    pub fn to_index_map(&self) -> HashMap<K, usize> {
+        self.graph.node_ids().enumerate().map(|(i, node)| (self.keys[node].clone(), i)).collect()
+    }
+
+    pub fn node_weight_mut(&mut self, node: NodeId) -> Option<&mut N> {
+        self.graph.node_weight_mut(node)
+    }
+
+    pub fn edge_weight_mut(&mut self, edge: EdgeId) -> Option<&mut E> {
+        self.graph.edge_weight_mut(edge)
+    }
+
+    pub fn remove_node(&mut self, key: &K) -> Option<N> {
+        let node = self.ids.remove(key)?;
+
+        self.keys.remove(node);
+        self.graph.remove_node(node).map(|removed| removed.weight)
+    }
+
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<E> {
+        self.graph.remove_edge(edge)
+    }
+}
+
+/// Node under a key that may not exist yet, from [`GraphMap::node_entry`].
+pub struct NodeEntry<'a, K, N, E, Ty> {
+    entry: hash_map::Entry<'a, K, NodeId>,
+    graph: &'a mut Graph<N, E, Ty>,
+    keys: &'a mut NodeMap<K>
+}
+
+impl<K: Hash + Eq + Clone, N, E, Ty: EdgeType> NodeEntry<'_, K, N, E, Ty> {
+    /// The node, if it exists.
+    pub fn id(&self) -> Option<NodeId> {
+        match &self.entry {
+            hash_map::Entry::Occupied(entry) => Some(*entry.get()),
+            hash_map::Entry::Vacant(_) => None
+        }
+    }
+
+    /// Updates the weight of an existing node.
+    pub fn and_modify(self, f: impl FnOnce(&mut N)) -> Self {
+        if let Some(node) = self.id() {
+            f(self.graph.node_weight_mut(node).unwrap());
+        }
+
+        self
+    }
+
+    /// The existing node, or a new one weighted `weight`.
+    pub fn or_insert(self, weight: N) -> NodeId {
+        self.or_insert_with(|| weight)
+    }
+
+    pub fn or_insert_with(self, weight: impl FnOnce() -> N) -> NodeId {
+        match self.entry {
+            hash_map::Entry::Occupied(entry) => *entry.get(),
+            hash_map::Entry::Vacant(entry) => {
+                let node = self.graph.add_node(weight());
+                self.keys.insert(node, entry.key().clone());
+                *entry.insert(node)
+            },
+        }
+    }
+
+    pub fn or_default(self) -> NodeId
+    where
+        N: Default
+    {
+        self.or_insert_with(N::default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ingests_keyed_edges() {
+        let mut graph: GraphMap<&str, u32, u32> = GraphMap::new();
+
+        for (a, b) in [("x", "y"), ("y", "z"), ("x", "y")] {
+            graph.node_entry(a).and_modify(|seen| *seen += 1).or_insert(1);
+            graph.node_entry(b).and_modify(|seen| *seen += 1).or_insert(1);
+            graph.edge_entry(&a, &b).and_modify(|count| *count += 1).or_insert(1);
+        }
+
+        let (x, y) = (graph.node_id(&"x").unwrap(), graph.node_id(&"y").unwrap());
+
+        assert_eq!(3, graph.graph().node_count());
+        assert_eq!(2, graph.graph().edge_count());
+        assert_eq!(Some(&3), graph.graph().node_weight(y));
+        assert_eq!(Some(&2), graph.graph().edge_weight(graph.graph().find_edge(x, y).unwrap()));
+        assert_eq!(Some(&"x"), graph.key(x));
+        assert_eq!(Some(x), graph.node_entry("x").id());
+    }
+
+    #[test]
+    fn removal_forgets_the_key() {
+        let mut graph: GraphMap<String, (), ()> = GraphMap::new();
+
+        let a = graph.node_entry("a".to_string()).or_default();
+        let b = graph.node_entry("b".to_string()).or_default();
+        graph.edge_entry(&"a".to_string(), &"b".to_string()).or_default();
+
+        assert_eq!(Some(()), graph.remove_node(&"a".to_string()));
+        assert_eq!(None, graph.key(a));
+        assert_eq!(0, graph.graph().edge_count());
+
+        let again = graph.node_entry("a".to_string()).or_default();
+        assert_ne!(a, again);
+        assert_eq!(Some(&"b".to_string()), graph.key(b));
+    }
+}
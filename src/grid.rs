@@ -0,0 +1,188 @@
+use std::f64::consts::SQRT_2;
+
+use crate::graph::{EdgeId, Graph, NodeId, UnGraph};
+
+/// Moves allowed between grid cells.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    /// Up, down, left and right.
+    Four,
+    /// Also diagonally, unless that cuts the corner of a blocked cell.
+    Eight
+}
+
+/// Rectangular grid of cells as an undirected graph, for pathfinding on
+/// game maps. Cells are addressed by `(x, y)`, with `x` below the width;
+/// edges weigh `1.0` straight and `SQRT_2` diagonally, and node weights are
+/// the cell coordinates.
+pub struct GridGraph {
+    graph: UnGraph<(usize, usize), f64>,
+    cells: Vec<Option<NodeId>>,
+    width: usize,
+    height: usize,
+    connectivity: Connectivity
+}
+
+impl GridGraph {
+    /// Grid with every cell open.
+    pub fn new(width: usize, height: usize, connectivity: Connectivity) -> GridGraph {
+        GridGraph::with_obstacles(width, height, connectivity, |_, _| false)
+    }
+
+    /// Grid whose cells with `blocked(x, y)` have no node.
+    pub fn with_obstacles(
+        width: usize,
+        height: usize,
+        connectivity: Connectivity,
+        mut blocked: impl FnMut(usize, usize) -> bool
+    ) -> GridGraph {
+        let mut graph = Graph::with_capacity(width * height, 4 * width * height);
+        let mut cells = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                cells.push((!blocked(x, y)).then(|| graph.add_node((x, y))));
+            }
+        }
+
+        let mut grid = GridGraph { graph, cells, width, height, connectivity };
+
+        for y in 0..height {
+            for x in 0..width {
+                grid.link(x, y);
+            }
+        }
+
+        grid
+    }
+
+    /// Joins the cell to its open neighbors to the right and below.
+    fn link(&mut self, x: usize, y: usize) {
+        let Some(node) = self.node_at(x, y) else {
+            return;
+        };
+
+        let right = self.node_at(x + 1, y);
+        let down = self.node_at(x, y + 1);
+
+        for next in [right, down].into_iter().flatten() {
+            self.graph.add_edge(node, next, 1.0);
+        }
+
+        if self.connectivity == Connectivity::Four || down.is_none() {
+            return;
+        }
+
+        let left = x.checked_sub(1).and_then(|left| self.node_at(left, y));
+        let down_left = x.checked_sub(1).and_then(|left| self.node_at(left, y + 1));
+        let corners = [(right, self.node_at(x + 1, y + 1)), (left, down_left)];
+
+        for (side, next) in corners {
+            if let (Some(_), Some(next)) = (side, next) {
+                self.graph.add_edge(node, next, SQRT_2);
+            }
+        }
+    }
+
+    /// Same grid with only the edges for which `keep` holds, under the same
+    /// ids.
+    pub(crate) fn retain_edges(&self, mut keep: impl FnMut(EdgeId) -> bool) -> GridGraph {
+        let mut graph = self.graph.map(|_, &cell| cell, |_, &weight| weight);
+
+        for edge in self.graph.edge_ids().filter(|&edge| !keep(edge)) {
+            graph.remove_edge(edge);
+        }
+
+        GridGraph {
+            graph,
+            cells: self.cells.clone(),
+            width: self.width,
+            height: self.height,
+            connectivity: self.connectivity
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn graph(&self) -> &UnGraph<(usize, usize), f64> {
+        &self.graph
+    }
+
+    /// `None` outside the grid or on a blocked cell.
+    pub fn node_at(&self, x: usize, y: usize) -> Option<NodeId> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        self.cells[y * self.width + x]
+    }
+
+    pub fn coords_of(&self, node: NodeId) -> Option<(usize, usize)> {
+        self.graph.node_weight(node).copied()
+    }
+
+    /// Length of the shortest path between two cells if nothing were
+    /// blocked, an estimate fit for [`astar`](crate::algo::astar).
+    pub fn distance(&self, a: NodeId, b: NodeId) -> f64 {
+        let ((ax, ay), (bx, by)) = (self.coords_of(a).unwrap(), self.coords_of(b).unwrap());
+        let (dx, dy) = (ax.abs_diff(bx) as f64, ay.abs_diff(by) as f64);
+
+        match self.connectivity {
+            Connectivity::Four => dx + dy,
+            Connectivity::Eight => dx.max(dy) + (SQRT_2 - 1.0) * dx.min(dy)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::astar;
+
+    #[test]
+    fn maps_coordinates_to_nodes() {
+        let grid = GridGraph::with_obstacles(3, 2, Connectivity::Four, |x, y| (x, y) == (1, 0));
+
+        assert_eq!(5, grid.graph().node_count());
+        assert_eq!(4, grid.graph().edge_count());
+        assert_eq!(None, grid.node_at(1, 0));
+        assert_eq!(None, grid.node_at(3, 0));
+
+        let node = grid.node_at(2, 1).unwrap();
+        assert_eq!(Some((2, 1)), grid.coords_of(node));
+        assert_eq!(2, grid.graph().neighbors(node).count());
+    }
+
+    #[test]
+    fn diagonals_do_not_cut_corners() {
+        let open = GridGraph::new(2, 2, Connectivity::Eight);
+        assert_eq!(6, open.graph().edge_count());
+
+        let grid = GridGraph::with_obstacles(2, 2, Connectivity::Eight, |x, y| (x, y) == (1, 0));
+        let (a, b) = (grid.node_at(0, 0).unwrap(), grid.node_at(1, 1).unwrap());
+
+        assert_eq!(2, grid.graph().edge_count());
+        assert_eq!(None, grid.graph().find_edge(a, b));
+    }
+
+    #[test]
+    fn finds_paths_around_walls() {
+        // wall along x = 2 with a gap at the bottom
+        let grid = GridGraph::with_obstacles(5, 5, Connectivity::Eight, |x, y| x == 2 && y < 4);
+        let (start, goal) = (grid.node_at(0, 0).unwrap(), grid.node_at(4, 0).unwrap());
+
+        let estimate = |node| grid.distance(node, goal);
+        let (cost, path) = astar(grid.graph(), start, goal, |&w| w, estimate).unwrap();
+        let cells: Vec<_> = path.iter().map(|&node| grid.coords_of(node).unwrap()).collect();
+
+        assert!((cost - (8.0 + 2.0 * SQRT_2)).abs() < 1e-9);
+        assert_eq!(Some(&(2, 4)), cells.iter().find(|&&(x, _)| x == 2));
+        assert_eq!(4.0, grid.distance(start, goal));
+    }
+}
@@ -0,0 +1,492 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::arena::Arena;
+
+struct HeapEntry<K, P> {
+    key: K,
+    priority: P,
+    pos: usize
+}
+
+/// Min-priority queue whose entries can be found again by key, as needed
+/// by label-setting searches such as Dijkstra.
+pub trait AddressableHeap<K, P>: Default {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains(&self, key: &K) -> bool;
+
+    fn priority(&self, key: &K) -> Option<&P>;
+
+    /// Queues `key`, or moves it to `priority` if it is already queued, in
+    /// which case the previous priority is returned.
+    fn push(&mut self, key: K, priority: P) -> Option<P>;
+
+    /// Lowers the priority of a queued key. Returns `false`, leaving the heap
+    /// untouched, if the key is not queued or `priority` is not lower.
+    fn decrease_key(&mut self, key: &K, priority: P) -> bool;
+
+    fn pop_min(&mut self) -> Option<(K, P)>;
+}
+
+fn is_lower<P: PartialOrd>(a: &P, b: &P) -> bool {
+    a.partial_cmp(b) == Some(Ordering::Less)
+}
+
+/// Binary min-heap addressed by key, so the priority of a queued key can be
+/// lowered in O(log n). Entries live in an arena and the heap itself only
+/// shuffles slot indices around.
+pub struct IndexedBinaryHeap<K, P> {
+    heap: Vec<usize>,
+    entries: Arena<HeapEntry<K, P>>,
+    lookup: HashMap<K, usize>
+}
+
+impl<K: Clone + Eq + Hash, P: PartialOrd> Default for IndexedBinaryHeap<K, P> {
+    fn default() -> Self {
+        IndexedBinaryHeap::new()
+    }
+}
+
+impl<K: Clone + Eq + Hash, P: PartialOrd> IndexedBinaryHeap<K, P> {
+    pub fn new() -> IndexedBinaryHeap<K, P> {
+        IndexedBinaryHeap {
+            heap: Vec::new(),
+            entries: Arena::new(),
+            lookup: HashMap::new()
+        }
+    }
+
+    pub fn peek_min(&self) -> Option<(&K, &P)> {
+        self.heap.first().map(|&slot| {
+            let entry = &self.entries[slot];
+            (&entry.key, &entry.priority)
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.heap.clear();
+        self.entries = Arena::new();
+        self.lookup.clear();
+    }
+
+    fn less(&self, a: usize, b: usize) -> bool {
+        is_lower(&self.entries[self.heap[a]].priority, &self.entries[self.heap[b]].priority)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.entries[self.heap[a]].pos = a;
+        self.entries[self.heap[b]].pos = b;
+    }
+
+    fn sift_up(&mut self, mut pos: usize) {
+        while pos > 0 {
+            let parent = (pos - 1) / 2;
+
+            if !self.less(pos, parent) {
+                break;
+            }
+
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: usize) {
+        loop {
+            let left = 2 * pos + 1;
+            let right = left + 1;
+            let mut smallest = pos;
+
+            if left < self.heap.len() && self.less(left, smallest) {
+                smallest = left;
+            }
+
+            if right < self.heap.len() && self.less(right, smallest) {
+                smallest = right;
+            }
+
+            if smallest == pos {
+                break;
+            }
+
+            self.swap(pos, smallest);
+            pos = smallest;
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, P: PartialOrd> AddressableHeap<K, P> for IndexedBinaryHeap<K, P> {
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.lookup.contains_key(key)
+    }
+
+    fn priority(&self, key: &K) -> Option<&P> {
+        self.lookup.get(key).map(|&slot| &self.entries[slot].priority)
+    }
+
+    fn push(&mut self, key: K, priority: P) -> Option<P> {
+        if let Some(&slot) = self.lookup.get(&key) {
+            let old = std::mem::replace(&mut self.entries[slot].priority, priority);
+            let pos = self.entries[slot].pos;
+
+            if is_lower(&self.entries[slot].priority, &old) {
+                self.sift_up(pos);
+            } else {
+                self.sift_down(pos);
+            }
+
+            return Some(old);
+        }
+
+        let pos = self.heap.len();
+        let slot = self.entries.insert(HeapEntry { key: key.clone(), priority, pos });
+
+        self.heap.push(slot);
+        self.lookup.insert(key, slot);
+        self.sift_up(pos);
+
+        None
+    }
+
+    fn decrease_key(&mut self, key: &K, priority: P) -> bool {
+        let Some(&slot) = self.lookup.get(key) else {
+            return false;
+        };
+
+        if !is_lower(&priority, &self.entries[slot].priority) {
+            return false;
+        }
+
+        self.entries[slot].priority = priority;
+        self.sift_up(self.entries[slot].pos);
+
+        true
+    }
+
+    fn pop_min(&mut self) -> Option<(K, P)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let last = self.heap.len() - 1;
+
+        self.swap(0, last);
+
+        let slot = self.heap.pop().unwrap();
+        let entry = self.entries.remove(slot).expect("expected used slot");
+
+        self.lookup.remove(&entry.key);
+
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+
+        Some((entry.key, entry.priority))
+    }
+}
+
+struct PairingNode<K, P> {
+    key: K,
+    priority: P,
+    child: Option<usize>,
+    sibling: Option<usize>,
+    // parent for a leftmost child, left sibling otherwise
+    prev: Option<usize>
+}
+
+/// Pairing heap with O(1) push and amortised sub-logarithmic decrease-key,
+/// which tends to beat the binary heap on decrease-key heavy workloads.
+pub struct PairingHeap<K, P> {
+    root: Option<usize>,
+    nodes: Arena<PairingNode<K, P>>,
+    lookup: HashMap<K, usize>
+}
+
+impl<K: Clone + Eq + Hash, P: PartialOrd> Default for PairingHeap<K, P> {
+    fn default() -> Self {
+        PairingHeap::new()
+    }
+}
+
+impl<K: Clone + Eq + Hash, P: PartialOrd> PairingHeap<K, P> {
+    pub fn new() -> PairingHeap<K, P> {
+        PairingHeap {
+            root: None,
+            nodes: Arena::new(),
+            lookup: HashMap::new()
+        }
+    }
+
+    pub fn peek_min(&self) -> Option<(&K, &P)> {
+        self.root.map(|pos| {
+            let node = &self.nodes[pos];
+            (&node.key, &node.priority)
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.nodes = Arena::new();
+        self.lookup.clear();
+    }
+
+    fn meld(&mut self, a: usize, b: usize) -> usize {
+        let (parent, child) = if is_lower(&self.nodes[b].priority, &self.nodes[a].priority) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+
+        let first = self.nodes[parent].child;
+
+        self.nodes[child].sibling = first;
+        self.nodes[child].prev = Some(parent);
+
+        if let Some(first) = first {
+            self.nodes[first].prev = Some(child);
+        }
+
+        self.nodes[parent].child = Some(child);
+
+        parent
+    }
+
+    fn merge_pairs(&mut self, first: Option<usize>) -> Option<usize> {
+        let mut pairs = Vec::new();
+        let mut curr = first;
+
+        while let Some(a) = curr {
+            let b = self.nodes[a].sibling;
+            curr = b.and_then(|b| self.nodes[b].sibling);
+
+            self.nodes[a].sibling = None;
+            self.nodes[a].prev = None;
+
+            match b {
+                None => pairs.push(a),
+                Some(b) => {
+                    self.nodes[b].sibling = None;
+                    self.nodes[b].prev = None;
+                    pairs.push(self.meld(a, b));
+                }
+            }
+        }
+
+        let mut root = pairs.pop();
+
+        while let Some(next) = pairs.pop() {
+            root = Some(self.meld(next, root.unwrap()));
+        }
+
+        root
+    }
+
+    fn cut(&mut self, pos: usize) {
+        let prev = self.nodes[pos].prev.expect("expected non-root node");
+        let sibling = self.nodes[pos].sibling;
+
+        if self.nodes[prev].child == Some(pos) {
+            self.nodes[prev].child = sibling;
+        } else {
+            self.nodes[prev].sibling = sibling;
+        }
+
+        if let Some(sibling) = sibling {
+            self.nodes[sibling].prev = Some(prev);
+        }
+
+        self.nodes[pos].sibling = None;
+        self.nodes[pos].prev = None;
+    }
+
+    fn reinsert(&mut self, pos: usize) {
+        self.root = Some(match self.root {
+            None => pos,
+            Some(root) => self.meld(root, pos)
+        });
+    }
+}
+
+impl<K: Clone + Eq + Hash, P: PartialOrd> AddressableHeap<K, P> for PairingHeap<K, P> {
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        self.lookup.contains_key(key)
+    }
+
+    fn priority(&self, key: &K) -> Option<&P> {
+        self.lookup.get(key).map(|&pos| &self.nodes[pos].priority)
+    }
+
+    fn push(&mut self, key: K, priority: P) -> Option<P> {
+        if let Some(&pos) = self.lookup.get(&key) {
+            let old = std::mem::replace(&mut self.nodes[pos].priority, priority);
+
+            if self.root != Some(pos) {
+                self.cut(pos);
+            } else {
+                self.root = None;
+            }
+
+            if !is_lower(&self.nodes[pos].priority, &old) {
+                // children may now be smaller than their parent
+                let children = self.nodes[pos].child.take();
+
+                if let Some(merged) = self.merge_pairs(children) {
+                    self.reinsert(merged);
+                }
+            }
+
+            self.reinsert(pos);
+
+            return Some(old);
+        }
+
+        let pos = self.nodes.insert(PairingNode {
+            key: key.clone(),
+            priority,
+            child: None,
+            sibling: None,
+            prev: None
+        });
+
+        self.lookup.insert(key, pos);
+        self.reinsert(pos);
+
+        None
+    }
+
+    fn decrease_key(&mut self, key: &K, priority: P) -> bool {
+        let Some(&pos) = self.lookup.get(key) else {
+            return false;
+        };
+
+        if !is_lower(&priority, &self.nodes[pos].priority) {
+            return false;
+        }
+
+        self.nodes[pos].priority = priority;
+
+        if self.root != Some(pos) {
+            self.cut(pos);
+            self.reinsert(pos);
+        }
+
+        true
+    }
+
+    fn pop_min(&mut self) -> Option<(K, P)> {
+        let root = self.root?;
+        let children = self.nodes[root].child;
+
+        self.root = self.merge_pairs(children);
+
+        let node = self.nodes.remove(root).expect("expected used slot");
+
+        self.lookup.remove(&node.key);
+
+        Some((node.key, node.priority))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pops_in_priority_order() {
+        let mut heap = IndexedBinaryHeap::new();
+
+        for (key, priority) in [('a', 5), ('b', 1), ('c', 4), ('d', 2), ('e', 3)] {
+            heap.push(key, priority);
+        }
+
+        let order: Vec<_> = std::iter::from_fn(|| heap.pop_min()).collect();
+
+        assert_eq!(order, vec![('b', 1), ('d', 2), ('e', 3), ('c', 4), ('a', 5)]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn decrease_key_reorders() {
+        let mut heap = IndexedBinaryHeap::new();
+
+        heap.push(1, 10.0);
+        heap.push(2, 20.0);
+        heap.push(3, 30.0);
+
+        assert!(heap.decrease_key(&3, 5.0));
+        assert!(!heap.decrease_key(&2, 25.0));
+        assert!(!heap.decrease_key(&4, 1.0));
+
+        assert_eq!(Some((&3, &5.0)), heap.peek_min());
+        assert_eq!(Some(&20.0), heap.priority(&2));
+    }
+
+    #[test]
+    fn push_existing_key_updates_priority() {
+        let mut heap = IndexedBinaryHeap::new();
+
+        heap.push("x", 1);
+        heap.push("y", 2);
+
+        assert_eq!(Some(1), heap.push("x", 3));
+        assert_eq!(2, heap.len());
+        assert_eq!(Some(("y", 2)), heap.pop_min());
+        assert_eq!(Some(("x", 3)), heap.pop_min());
+        assert_eq!(None, heap.pop_min());
+    }
+
+    #[test]
+    fn pairing_heap_matches_binary_heap() {
+        let mut binary = IndexedBinaryHeap::new();
+        let mut pairing = PairingHeap::new();
+
+        let mut state = 12345u64;
+        let mut next = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            state >> 33
+        };
+
+        for round in 0..500 {
+            let key = next() % 64;
+            // ties are broken by key so both heaps agree on the minimum
+            let priority = (next() % 1000, key);
+
+            match round % 4 {
+                0 | 1 => {
+                    assert_eq!(binary.push(key, priority), pairing.push(key, priority));
+                }
+                2 => {
+                    assert_eq!(
+                        binary.decrease_key(&key, priority),
+                        pairing.decrease_key(&key, priority)
+                    );
+                }
+                _ => {
+                    let popped = pairing.pop_min();
+                    assert_eq!(binary.pop_min(), popped);
+                    if let Some((key, _)) = popped {
+                        assert!(!pairing.contains(&key));
+                    }
+                }
+            }
+
+            assert_eq!(binary.len(), pairing.len());
+            assert_eq!(binary.peek_min(), pairing.peek_min());
+        }
+    }
+}
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::ops::{Index, IndexMut};
+
+use crate::arena::Arena;
+use crate::graph::{EdgeId, Graph, NodeId, UnGraph};
+
+struct HyperNode<N> {
+    weight: N,
+    edges: Vec<EdgeId>
+}
+
+struct HyperEdge<E> {
+    weight: E,
+    members: Vec<NodeId>
+}
+
+/// Node of the incidence graph of a [`Hypergraph`], naming the node or
+/// hyperedge it stands for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Incidence {
+    Node(NodeId),
+    Edge(EdgeId)
+}
+
+/// Graph whose edges join any number of nodes, as authors share a paper.
+/// Ids behave as in [`Graph`]; existing algorithms run on its
+/// [`incidence_graph`] or [`clique_expansion`].
+///
+/// [`incidence_graph`]: Hypergraph::incidence_graph
+/// [`clique_expansion`]: Hypergraph::clique_expansion
+pub struct Hypergraph<N, E> {
+    nodes: Arena<HyperNode<N>>,
+    edges: Arena<HyperEdge<E>>
+}
+
+impl<N, E> Default for Hypergraph<N, E> {
+    fn default() -> Self {
+        Hypergraph::new()
+    }
+}
+
+impl<N, E> Hypergraph<N, E> {
+    pub fn new() -> Hypergraph<N, E> {
+        Hypergraph { nodes: Arena::new(), edges: Arena::new() }
+    }
+
+    pub fn with_capacity(nodes: usize, edges: usize) -> Hypergraph<N, E> {
+        Hypergraph { nodes: Arena::with_capacity(nodes), edges: Arena::with_capacity(edges) }
+    }
+
+    pub fn reserve_nodes(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    pub fn reserve_edges(&mut self, additional: usize) {
+        self.edges.reserve(additional);
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    fn node(&self, id: NodeId) -> Option<&HyperNode<N>> {
+        if self.nodes.generation(id.index()) != Some(id.generation()) {
+            return None;
+        }
+
+        self.nodes.get(id.index())
+    }
+
+    fn edge(&self, id: EdgeId) -> Option<&HyperEdge<E>> {
+        if self.edges.generation(id.index()) != Some(id.generation()) {
+            return None;
+        }
+
+        self.edges.get(id.index())
+    }
+
+    pub fn contains_node(&self, node: NodeId) -> bool {
+        self.node(node).is_some()
+    }
+
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.edge(edge).is_some()
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        let pos = self.nodes.insert(HyperNode { weight, edges: Vec::new() });
+        NodeId::with_generation(pos, self.nodes.generation(pos).unwrap())
+    }
+
+    /// Adds a hyperedge joining `members`, each taken once. Panics if a
+    /// member is not in the graph.
+    pub fn add_edge(&mut self, members: impl IntoIterator<Item = NodeId>, weight: E) -> EdgeId {
+        let mut unique = Vec::new();
+
+        for node in members {
+            assert!(self.contains_node(node), "member node not in graph");
+
+            if !unique.contains(&node) {
+                unique.push(node);
+            }
+        }
+
+        let pos = self.edges.insert(HyperEdge { weight, members: Vec::new() });
+        let id = EdgeId::with_generation(pos, self.edges.generation(pos).unwrap());
+
+        for &node in &unique {
+            self.nodes[node.index()].edges.push(id);
+        }
+
+        self.edges[pos].members = unique;
+        id
+    }
+
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<E> {
+        self.edge(edge)?;
+
+        let removed = self.edges.remove(edge.index()).unwrap();
+
+        for node in removed.members {
+            self.nodes[node.index()].edges.retain(|&e| e != edge);
+        }
+
+        Some(removed.weight)
+    }
+
+    /// Removes the node from every hyperedge it belongs to. Hyperedges left
+    /// with no members stay in the graph.
+    pub fn remove_node(&mut self, node: NodeId) -> Option<N> {
+        self.node(node)?;
+
+        let removed = self.nodes.remove(node.index()).unwrap();
+
+        for edge in removed.edges {
+            self.edges[edge.index()].members.retain(|&n| n != node);
+        }
+
+        Some(removed.weight)
+    }
+
+    pub fn node_weight(&self, node: NodeId) -> Option<&N> {
+        self.node(node).map(|n| &n.weight)
+    }
+
+    pub fn node_weight_mut(&mut self, node: NodeId) -> Option<&mut N> {
+        self.node(node)?;
+        self.nodes.get_mut(node.index()).map(|n| &mut n.weight)
+    }
+
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<&E> {
+        self.edge(edge).map(|e| &e.weight)
+    }
+
+    pub fn edge_weight_mut(&mut self, edge: EdgeId) -> Option<&mut E> {
+        self.edge(edge)?;
+        self.edges.get_mut(edge.index()).map(|e| &mut e.weight)
+    }
+
+    /// Members of the hyperedge in the order they were given.
+    pub fn members(&self, edge: EdgeId) -> Option<&[NodeId]> {
+        self.edge(edge).map(|e| e.members.as_slice())
+    }
+
+    /// Hyperedges the node belongs to.
+    pub fn incident_edges(&self, node: NodeId) -> Option<&[EdgeId]> {
+        self.node(node).map(|n| n.edges.as_slice())
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes.iter().map(|(pos, _)| NodeId::with_generation(pos, self.nodes.generation(pos).unwrap()))
+    }
+
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        self.edges.iter().map(|(pos, _)| EdgeId::with_generation(pos, self.edges.generation(pos).unwrap()))
+    }
+
+    /// Nodes sharing at least one hyperedge with `node`, each once and in
+    /// id order. Panics if the node is not in the graph.
+    pub fn neighbors(&self, node: NodeId) -> Vec<NodeId> {
+        let mut around: Vec<_> = self
+            .node(node)
+            .expect("node not in graph")
+            .edges
+            .iter()
+            .flat_map(|&e| self.edges[e.index()].members.iter().copied())
+            .filter(|&n| n != node)
+            .collect();
+
+        around.sort_unstable();
+        around.dedup();
+        around
+    }
+
+    /// Bipartite graph with one node per node and per hyperedge, and an
+    /// edge from each hyperedge to each of its members. Nodes come first,
+    /// in id order, then hyperedges.
+    pub fn incidence_graph(&self) -> UnGraph<Incidence, ()> {
+        let mut graph = Graph::new();
+        let mut remap = vec![None; self.nodes.slot_count()];
+
+        for node in self.node_ids() {
+            remap[node.index()] = Some(graph.add_node(Incidence::Node(node)));
+        }
+
+        for edge in self.edge_ids() {
+            let id = graph.add_node(Incidence::Edge(edge));
+
+            for &node in &self.edges[edge.index()].members {
+                graph.add_edge(id, remap[node.index()].unwrap(), ());
+            }
+        }
+
+        graph
+    }
+
+    /// Graph joining every two nodes that share a hyperedge, weighted with
+    /// how many they share, so co-authors are linked by their number of
+    /// joint papers. Node weights are the hypergraph's node ids.
+    pub fn clique_expansion(&self) -> UnGraph<NodeId, usize> {
+        let mut graph = Graph::new();
+        let mut remap = vec![None; self.nodes.slot_count()];
+        let mut shared = HashMap::new();
+
+        for node in self.node_ids() {
+            remap[node.index()] = Some(graph.add_node(node));
+        }
+
+        for (_, edge) in self.edges.iter() {
+            for (i, &a) in edge.members.iter().enumerate() {
+                for &b in &edge.members[i + 1..] {
+                    *shared.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let mut pairs: Vec<_> = shared.into_iter().collect();
+        pairs.sort_unstable();
+
+        for ((a, b), count) in pairs {
+            graph.add_edge(remap[a.index()].unwrap(), remap[b.index()].unwrap(), count);
+        }
+
+        graph
+    }
+}
+
+/// Panics if the node is not in the graph.
+impl<N, E> Index<NodeId> for Hypergraph<N, E> {
+    type Output = N;
+
+    fn index(&self, node: NodeId) -> &N {
+        self.node_weight(node).expect("node not in graph")
+    }
+}
+
+impl<N, E> IndexMut<NodeId> for Hypergraph<N, E> {
+    fn index_mut(&mut self, node: NodeId) -> &mut N {
+        self.node_weight_mut(node).expect("node not in graph")
+    }
+}
+
+/// Panics if the edge is not in the graph.
+impl<N, E> Index<EdgeId> for Hypergraph<N, E> {
+    type Output = E;
+
+    fn index(&self, edge: EdgeId) -> &E {
+        self.edge_weight(edge).expect("edge not in graph")
+    }
+}
+
+impl<N, E> IndexMut<EdgeId> for Hypergraph<N, E> {
+    fn index_mut(&mut self, edge: EdgeId) -> &mut E {
+        self.edge_weight_mut(edge).expect("edge not in graph")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::connected_components;
+
+    fn papers() -> (Hypergraph<&'static str, u32>, Vec<NodeId>, Vec<EdgeId>) {
+        let mut graph = Hypergraph::new();
+        let n: Vec<_> = ["ada", "bob", "cy", "dee", "eve"].into_iter().map(|a| graph.add_node(a)).collect();
+        let e = vec![
+            graph.add_edge([n[0], n[1], n[2]], 2019),
+            graph.add_edge([n[0], n[1]], 2021),
+            graph.add_edge([n[3], n[4], n[3]], 2022)
+        ];
+
+        (graph, n, e)
+    }
+
+    #[test]
+    fn stores_and_removes_hyperedges() {
+        let (mut graph, n, e) = papers();
+
+        graph[n[4]] = "eva";
+        assert_eq!(("eva", 2022), (graph[n[4]], graph[e[2]]));
+        assert_eq!(Some(&[n[3], n[4]][..]), graph.members(e[2]));
+        assert_eq!(vec![n[1], n[2]], graph.neighbors(n[0]));
+        assert_eq!(2, graph.incident_edges(n[1]).unwrap().len());
+
+        assert_eq!(Some("bob"), graph.remove_node(n[1]));
+        assert_eq!(Some(&[n[0], n[2]][..]), graph.members(e[0]));
+        assert_eq!(Some(&[n[0]][..]), graph.members(e[1]));
+
+        assert_eq!(Some(2022), graph.remove_edge(e[2]));
+        assert!(graph.incident_edges(n[3]).unwrap().is_empty());
+        assert_eq!(2, graph.edge_count());
+    }
+
+    #[test]
+    fn projects_to_ordinary_graphs() {
+        let (graph, n, _) = papers();
+
+        let incidence = graph.incidence_graph();
+        assert_eq!(8, incidence.node_count());
+        assert_eq!(7, incidence.edge_count());
+        assert_eq!(2, connected_components(&incidence).len());
+
+        let cliques = graph.clique_expansion();
+        let node = |id| cliques.node_ids().find(|&x| cliques.node_weight(x) == Some(&id)).unwrap();
+        let ab = cliques.find_edge(node(n[0]), node(n[1])).unwrap();
+
+        assert_eq!(4, cliques.edge_count());
+        assert_eq!(Some(&2), cliques.edge_weight(ab));
+        assert_eq!(Some(&1), cliques.edge_weight(cliques.find_edge(node(n[3]), node(n[4])).unwrap()));
+    }
+}
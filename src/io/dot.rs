@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::graph::{EdgeType, Graph};
+use crate::io::ParseError;
+
+/// Attribute list of a DOT node or edge statement, in source order.
+pub type DotAttributes = Vec<(String, String)>;
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn render_attributes(out: &mut String, attributes: &[(String, String)]) {
+    if attributes.is_empty() {
+        return;
+    }
+
+    let rendered: Vec<_> = attributes.iter().map(|(k, v)| format!("{}={}", quote(k), quote(v))).collect();
+
+    write!(out, " [{}]", rendered.join(", ")).unwrap();
+}
+
+/// Renders the graph in Graphviz DOT. Nodes are identified by their index
+/// and get the attributes returned by `node_attributes`, e.g. a `label`.
+pub fn to_dot<N, E, Ty, FN, FE>(
+    graph: &Graph<N, E, Ty>,
+    mut node_attributes: FN,
+    mut edge_attributes: FE
+) -> String
+where
+    Ty: EdgeType,
+    FN: FnMut(&N) -> DotAttributes,
+    FE: FnMut(&E) -> DotAttributes
+{
+    let (keyword, op) = if graph.is_directed() { ("digraph", "->") } else { ("graph", "--") };
+    let mut out = format!("{keyword} {{\n");
+
+    for node in graph.node_ids() {
+        write!(out, "    {}", node.index()).unwrap();
+        render_attributes(&mut out, &node_attributes(graph.node_weight(node).unwrap()));
+        out.push_str(";\n");
+    }
+
+    for edge in graph.edge_ids() {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+
+        write!(out, "    {} {op} {}", source.index(), target.index()).unwrap();
+        render_attributes(&mut out, &edge_attributes(graph.edge_weight(edge).unwrap()));
+        out.push_str(";\n");
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+const PUNCTUATION: [&str; 8] = ["{", "}", "[", "]", ";", ",", "=", ":"];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Id(String),
+    Punct(&'static str)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line_start = true;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c == '\n' {
+            line_start = true;
+            i += 1;
+            continue;
+        }
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let at_line_start = std::mem::replace(&mut line_start, false);
+
+        if (c == '#' && at_line_start) || (c == '/' && chars.get(i + 1) == Some(&'/')) {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+
+            if i >= chars.len() {
+                return Err(ParseError::new("unterminated comment"));
+            }
+
+            i += 2;
+        } else if c == '"' {
+            let mut value = String::new();
+            i += 1;
+
+            loop {
+                match chars.get(i) {
+                    None => return Err(ParseError::new("unterminated string")),
+                    Some('"') => break,
+                    Some('\\') if matches!(chars.get(i + 1), Some('"' | '\\')) => {
+                        value.push(chars[i + 1]);
+                        i += 2;
+                    },
+                    Some('\\') if chars.get(i + 1) == Some(&'\n') => i += 2,
+                    Some(&c) => {
+                        value.push(c);
+                        i += 1;
+                    }
+                }
+            }
+
+            tokens.push(Token::Id(value));
+            i += 1;
+        } else if c == '<' {
+            let start = i;
+            let mut depth = 0;
+
+            loop {
+                match chars.get(i) {
+                    None => return Err(ParseError::new("unterminated HTML string")),
+                    Some('<') => depth += 1,
+                    Some('>') => depth -= 1,
+                    _ => ()
+                }
+
+                i += 1;
+
+                if depth == 0 {
+                    break;
+                }
+            }
+
+            tokens.push(Token::Id(chars[start + 1..i - 1].iter().collect()));
+        } else if c == '-' && matches!(chars.get(i + 1), Some('>' | '-')) {
+            tokens.push(Token::Punct(if chars[i + 1] == '>' { "->" } else { "--" }));
+            i += 2;
+        } else if let Some(punct) = PUNCTUATION.into_iter().find(|p| p.starts_with(c)) {
+            tokens.push(Token::Punct(punct));
+            i += 1;
+        } else if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {
+            let start = i;
+            i += 1;
+
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+
+            tokens.push(Token::Id(chars[start..i].iter().collect()));
+        } else {
+            return Err(ParseError::new(format!("unexpected character {c:?}")));
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let found = self.peek_punct(punct);
+        self.pos += found as usize;
+        found
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<(), ParseError> {
+        if self.eat_punct(punct) { Ok(()) } else { Err(self.unexpected(&format!("'{punct}'"))) }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Id(id)) if id.eq_ignore_ascii_case(keyword))
+    }
+
+    fn id(&mut self) -> Result<String, ParseError> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Id(id)) => {
+                self.pos += 1;
+                Ok(id.clone())
+            },
+            _ => Err(self.unexpected("an identifier"))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(Token::Id(id)) => ParseError::new(format!("expected {expected}, found \"{id}\"")),
+            Some(Token::Punct(p)) => ParseError::new(format!("expected {expected}, found '{p}'")),
+            None => ParseError::new(format!("expected {expected}, found end of input"))
+        }
+    }
+
+    fn attributes(&mut self, into: &mut DotAttributes) -> Result<(), ParseError> {
+        while self.eat_punct("[") {
+            while !self.eat_punct("]") {
+                let key = self.id()?;
+                let value = if self.eat_punct("=") { self.id()? } else { "true".to_string() };
+
+                set_attribute(into, key, value);
+
+                if !self.eat_punct(",") {
+                    self.eat_punct(";");
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Node id, discarding any port.
+    fn node_id(&mut self) -> Result<String, ParseError> {
+        let id = self.id()?;
+
+        while self.eat_punct(":") {
+            self.id()?;
+        }
+
+        Ok(id)
+    }
+}
+
+fn set_attribute(attributes: &mut DotAttributes, key: String, value: String) {
+    match attributes.iter_mut().find(|(k, _)| *k == key) {
+        Some(entry) => entry.1 = value,
+        None => attributes.push((key, value))
+    }
+}
+
+/// Builds a graph from a DOT document. Nodes are created in order of first
+/// mention and the closures receive each node's id and attributes and each
+/// edge's attributes, with `node [...]` and `edge [...]` defaults applied.
+/// Graph attributes are ignored and subgraphs are not supported.
+pub fn from_dot<N, E, Ty, FN, FE>(
+    input: &str,
+    mut node_weight: FN,
+    mut edge_weight: FE
+) -> Result<Graph<N, E, Ty>, ParseError>
+where
+    Ty: EdgeType,
+    FN: FnMut(&str, &[(String, String)]) -> N,
+    FE: FnMut(&[(String, String)]) -> E
+{
+    let mut parser = Parser { tokens: tokenize(input)?, pos: 0 };
+
+    if parser.peek_keyword("strict") {
+        parser.pos += 1;
+    }
+
+    let directed = if parser.peek_keyword("digraph") {
+        true
+    } else if parser.peek_keyword("graph") {
+        false
+    } else {
+        return Err(parser.unexpected("\"graph\" or \"digraph\""));
+    };
+
+    if directed != Ty::is_directed() {
+        return Err(ParseError::new(format!(
+            "document is {}directed but the graph type is not",
+            if directed { "" } else { "un" }
+        )));
+    }
+
+    parser.pos += 1;
+
+    if !parser.peek_punct("{") {
+        parser.id()?;
+    }
+
+    parser.expect_punct("{")?;
+
+    let op = if directed { "->" } else { "--" };
+    let mut node_defaults = DotAttributes::new();
+    let mut edge_defaults = DotAttributes::new();
+    let mut nodes: Vec<(String, DotAttributes)> = Vec::new();
+    let mut ids = HashMap::new();
+    let mut edges = Vec::new();
+
+    let mut node = |nodes: &mut Vec<(String, DotAttributes)>, defaults: &DotAttributes, id: String| {
+        *ids.entry(id.clone()).or_insert_with(|| {
+            nodes.push((id, defaults.clone()));
+            nodes.len() - 1
+        })
+    };
+
+    while !parser.eat_punct("}") {
+        if parser.peek().is_none() {
+            return Err(parser.unexpected("'}'"));
+        }
+
+        if parser.peek_keyword("subgraph") || parser.peek_punct("{") {
+            return Err(ParseError::new("subgraphs are not supported"));
+        }
+
+        if parser.peek_keyword("graph") || parser.peek_keyword("node") || parser.peek_keyword("edge") {
+            let keyword = parser.id()?.to_ascii_lowercase();
+
+            match keyword.as_str() {
+                "node" => parser.attributes(&mut node_defaults)?,
+                "edge" => parser.attributes(&mut edge_defaults)?,
+                _ => parser.attributes(&mut DotAttributes::new())?
+            }
+        } else {
+            let first = parser.node_id()?;
+
+            if parser.eat_punct("=") {
+                parser.id()?;
+            } else {
+                let mut chain = vec![node(&mut nodes, &node_defaults, first)];
+
+                while parser.peek_punct("->") || parser.peek_punct("--") {
+                    if !parser.eat_punct(op) {
+                        return Err(ParseError::new(format!(
+                            "edge operator does not match {}",
+                            if directed { "digraph" } else { "graph" }
+                        )));
+                    }
+
+                    if parser.peek_keyword("subgraph") || parser.peek_punct("{") {
+                        return Err(ParseError::new("subgraphs are not supported"));
+                    }
+
+                    let next = parser.node_id()?;
+                    chain.push(node(&mut nodes, &node_defaults, next));
+                }
+
+                if chain.len() == 1 {
+                    parser.attributes(&mut nodes[chain[0]].1)?;
+                } else {
+                    let mut attributes = edge_defaults.clone();
+                    parser.attributes(&mut attributes)?;
+
+                    for pair in chain.windows(2) {
+                        edges.push((pair[0], pair[1], attributes.clone()));
+                    }
+                }
+            }
+        }
+
+        parser.eat_punct(";");
+    }
+
+    if parser.peek().is_some() {
+        return Err(parser.unexpected("end of input"));
+    }
+
+    let mut graph = Graph::new();
+    let ids: Vec<_> = nodes
+        .iter()
+        .map(|(id, attributes)| graph.add_node(node_weight(id, attributes)))
+        .collect();
+
+    for (source, target, attributes) in edges {
+        graph.add_edge(ids[source], ids[target], edge_weight(&attributes));
+    }
+
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, NodeId, UnGraph};
+
+    fn label(id: &str, attributes: &[(String, String)]) -> String {
+        match attributes.iter().find(|(k, _)| k == "label") {
+            Some((_, label)) => label.clone(),
+            None => id.to_string()
+        }
+    }
+
+    fn weight(attributes: &[(String, String)]) -> Option<String> {
+        attributes.iter().find(|(k, _)| k == "weight").map(|(_, v)| v.clone())
+    }
+
+    #[test]
+    fn parses_chains_defaults_and_comments() {
+        let input = r#"
+            # preprocessor line
+            strict digraph "deps" {
+                rankdir = LR; // layout hint
+                node [shape=box]
+                a -> b -> c [weight=2, color="red"];
+                /* block
+                   comment */
+                c:port -> a
+                edge [weight=5]
+                d; b -> d; "quoted \"id\"" [label=<<b>x</b>>]
+            }
+        "#;
+
+        let graph: DiGraph<String, Option<String>> = from_dot(input, label, weight).unwrap();
+        let names: Vec<_> = graph.node_ids().map(|n| graph.node_weight(n).unwrap().clone()).collect();
+
+        assert_eq!(vec!["a", "b", "c", "d", "<b>x</b>"], names);
+        assert_eq!(4, graph.edge_count());
+
+        let weights: Vec<_> = graph.edge_ids().map(|e| graph.edge_weight(e).unwrap().clone()).collect();
+
+        assert_eq!(
+            vec![Some("2".to_string()), Some("2".to_string()), None, Some("5".to_string())],
+            weights
+        );
+    }
+
+    #[test]
+    fn round_trips_through_to_dot() {
+        let mut graph: UnGraph<String, u32> = Graph::new();
+        let a = graph.add_node("say \"hi\"".to_string());
+        let b = graph.add_node("b".to_string());
+
+        graph.add_edge(a, b, 7);
+
+        let dot = to_dot(
+            &graph,
+            |n| vec![("label".to_string(), n.clone())],
+            |w| vec![("weight".to_string(), w.to_string())]
+        );
+
+        assert!(dot.starts_with("graph {\n    0 [\"label\"=\"say \\\"hi\\\"\"];"));
+
+        let parsed: UnGraph<String, Option<String>> = from_dot(&dot, label, weight).unwrap();
+
+        assert_eq!(Some(&"say \"hi\"".to_string()), parsed.node_weight(NodeId::new(0)));
+        assert_eq!(Some(&Some("7".to_string())), parsed.edge_weight(crate::EdgeId::new(0)));
+    }
+
+    #[test]
+    fn rejects_mismatched_operators_and_subgraphs() {
+        let parse = |input: &str| from_dot::<(), (), crate::Directed, _, _>(input, |_, _| (), |_| ()).err();
+
+        let mismatch = parse("graph { a }").unwrap();
+
+        assert_eq!("document is undirected but the graph type is not", mismatch.message());
+        assert_eq!("edge operator does not match digraph", parse("digraph { a -- b }").unwrap().message());
+        assert_eq!("subgraphs are not supported", parse("digraph { a -> { b c } }").unwrap().message());
+        assert_eq!("expected '}', found end of input", parse("digraph { a").unwrap().message());
+    }
+}
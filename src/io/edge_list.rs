@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, BufRead, Write};
+
+use crate::graph::{EdgeType, Graph};
+
+/// Calls `f` with the line number and fields of every record, skipping
+/// blank lines and comments starting with `#` or `%`.
+pub(crate) fn for_each_record<R, F>(reader: R, mut f: F) -> io::Result<()>
+where
+    R: BufRead,
+    F: FnMut(usize, &[&str]) -> io::Result<()>
+{
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+            continue;
+        }
+
+        let fields: Vec<_> = line.split_whitespace().collect();
+
+        f(number + 1, &fields)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn invalid_line(number: usize, message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("line {number}: {message}"))
+}
+
+/// Reads `source target [weight]` lines into a graph whose nodes are
+/// weighted with their labels, in order of first appearance. Missing
+/// weights default to `1.0`.
+pub fn read_edge_list<Ty: EdgeType, R: BufRead>(reader: R) -> io::Result<Graph<String, f64, Ty>> {
+    let mut graph = Graph::new();
+    let mut ids = HashMap::new();
+
+    for_each_record(reader, |number, fields| {
+        let (source, target, weight) = match *fields {
+            [source, target] => (source, target, 1.0),
+            [source, target, weight] => match weight.parse() {
+                Ok(weight) => (source, target, weight),
+                Err(_) => return Err(invalid_line(number, "weight is not a number"))
+            },
+            _ => return Err(invalid_line(number, "expected `source target [weight]`"))
+        };
+
+        let mut node = |label: &str| {
+            *ids.entry(label.to_string())
+                .or_insert_with(|| graph.add_node(label.to_string()))
+        };
+
+        let (source, target) = (node(source), node(target));
+        graph.add_edge(source, target, weight);
+
+        Ok(())
+    })?;
+
+    Ok(graph)
+}
+
+/// Writes one `source target weight` line per edge, using the `Display`
+/// form of the node and edge weights. Isolated nodes are not represented.
+pub fn write_edge_list<N, E, Ty, W>(graph: &Graph<N, E, Ty>, mut writer: W) -> io::Result<()>
+where
+    N: Display,
+    E: Display,
+    Ty: EdgeType,
+    W: Write
+{
+    for edge in graph.edge_ids() {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+
+        writeln!(
+            writer,
+            "{} {} {}",
+            graph.node_weight(source).unwrap(),
+            graph.node_weight(target).unwrap(),
+            graph.edge_weight(edge).unwrap()
+        )?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, NodeId, UnGraph};
+
+    #[test]
+    fn read_and_write_round_trip() {
+        let input = "# header\na b 2.5\nb c\n\nc a 0\n";
+        let graph: DiGraph<String, f64> = read_edge_list(input.as_bytes()).unwrap();
+
+        assert_eq!(3, graph.node_count());
+        assert_eq!(Some(&"b".to_string()), graph.node_weight(NodeId::new(1)));
+
+        let mut out = Vec::new();
+        write_edge_list(&graph, &mut out).unwrap();
+
+        assert_eq!("a b 2.5\nb c 1\nc a 0\n", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn bad_weight_reports_line() {
+        let result: io::Result<UnGraph<String, f64>> = read_edge_list("a b\na c x\n".as_bytes());
+        let Err(err) = result else { panic!("expected an error") };
+
+        assert_eq!("line 2: weight is not a number", err.to_string());
+    }
+}
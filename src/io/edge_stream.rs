@@ -0,0 +1,290 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::csr::Csr;
+use crate::graph::{Directed, EdgeType};
+use crate::io::edge_list::{for_each_record, invalid_line};
+
+const DEFAULT_MEMORY_LIMIT: usize = 1 << 24;
+
+static RUN_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+struct Run {
+    reader: BufReader<File>
+}
+
+impl Run {
+    fn next_edge(&mut self) -> io::Result<Option<(usize, usize)>> {
+        let mut buf = [0; 16];
+
+        match self.reader.read_exact(&mut buf) {
+            Ok(()) => Ok(Some((
+                u64::from_le_bytes(buf[..8].try_into().unwrap()) as usize,
+                u64::from_le_bytes(buf[8..].try_into().unwrap()) as usize
+            ))),
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+}
+
+/// Builds a graph from an edge list that need not fit in memory. Edges are
+/// buffered up to a limit, then sorted, deduplicated and spilled to a
+/// temporary file; `finish` merges the spilled runs into the final
+/// [`Csr`]. Only the finished graph itself has to fit in memory. On
+/// `wasm32-unknown-unknown`, which has no filesystem, exceeding the memory
+/// limit is an error.
+///
+/// Nodes are the integers `0..=max_id` seen in the input. For undirected
+/// graphs `u v` and `v u` count as the same edge.
+pub struct EdgeStreamBuilder<Ty = Directed> {
+    memory_limit: usize,
+    temp_dir: Option<PathBuf>,
+    node_count: usize,
+    buffer: Vec<(usize, usize)>,
+    runs: Vec<PathBuf>,
+    ty: PhantomData<Ty>
+}
+
+impl<Ty: EdgeType> Default for EdgeStreamBuilder<Ty> {
+    fn default() -> Self {
+        EdgeStreamBuilder::new()
+    }
+}
+
+impl<Ty: EdgeType> EdgeStreamBuilder<Ty> {
+    pub fn new() -> EdgeStreamBuilder<Ty> {
+        EdgeStreamBuilder {
+            memory_limit: DEFAULT_MEMORY_LIMIT,
+            temp_dir: None,
+            node_count: 0,
+            buffer: Vec::new(),
+            runs: Vec::new(),
+            ty: PhantomData
+        }
+    }
+
+    /// Number of edges buffered before a run is spilled to disk.
+    pub fn memory_limit(mut self, edges: usize) -> Self {
+        self.memory_limit = edges.max(1);
+        self
+    }
+
+    /// Directory for spilled runs, the system temporary directory by default.
+    pub fn temp_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = Some(dir.into());
+        self
+    }
+
+    /// Makes sure the graph has at least `count` nodes, including ones
+    /// without edges.
+    pub fn min_node_count(mut self, count: usize) -> Self {
+        self.node_count = self.node_count.max(count);
+        self
+    }
+
+    pub fn spilled_runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Fails with `InvalidInput` for the node id `usize::MAX`, which would
+    /// need one node more than can be counted.
+    pub fn push_edge(&mut self, source: usize, target: usize) -> io::Result<()> {
+        let Some(count) = source.max(target).checked_add(1) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "node id too large"));
+        };
+
+        let edge = if Ty::is_directed() {
+            (source, target)
+        } else {
+            (source.min(target), source.max(target))
+        };
+
+        self.node_count = self.node_count.max(count);
+        self.buffer.push(edge);
+
+        if self.buffer.len() >= self.memory_limit {
+            self.spill()?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads whitespace separated `source target` pairs, one per line,
+    /// ignoring any further columns, blank lines and lines starting with `#`
+    /// or `%`. Returns the number of edges read.
+    pub fn read_edges<R: BufRead>(&mut self, reader: R) -> io::Result<usize> {
+        let mut count = 0;
+
+        for_each_record(reader, |number, fields| {
+            let (Some(Ok(source)), Some(Ok(target))) = (
+                fields.first().map(|f| f.parse::<usize>()),
+                fields.get(1).map(|f| f.parse::<usize>())
+            ) else {
+                return Err(invalid_line(number, "expected two node ids"));
+            };
+
+            if source.max(target) == usize::MAX {
+                return Err(invalid_line(number, "node id too large"));
+            }
+
+            count += 1;
+            self.push_edge(source, target)
+        })?;
+
+        Ok(count)
+    }
+
+    fn sort_buffer(&mut self) {
+        self.buffer.sort_unstable();
+        self.buffer.dedup();
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        // browsers and other hosts without a filesystem, where asking for
+        // the temporary directory or process id would panic
+        if cfg!(all(target_family = "wasm", target_os = "unknown")) {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, "no filesystem to spill edges to"));
+        }
+
+        self.sort_buffer();
+
+        let dir = self.temp_dir.get_or_insert_with(std::env::temp_dir);
+        let path = dir.join(format!(
+            "graphs-rs-run-{}-{}.bin",
+            std::process::id(),
+            RUN_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut out = BufWriter::new(File::create(&path)?);
+
+        self.runs.push(path);
+
+        for &(source, target) in &self.buffer {
+            out.write_all(&(source as u64).to_le_bytes())?;
+            out.write_all(&(target as u64).to_le_bytes())?;
+        }
+
+        out.flush()?;
+        self.buffer.clear();
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<Csr<(), (), Ty>> {
+        let nodes = vec![(); self.node_count];
+
+        if self.runs.is_empty() {
+            self.sort_buffer();
+            let edges = self.buffer.drain(..).map(|(s, t)| (s, t, ()));
+            return Ok(Csr::from_sorted_edges(nodes, edges));
+        }
+
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+
+        let mut runs = Vec::with_capacity(self.runs.len());
+
+        for path in &self.runs {
+            runs.push(Run { reader: BufReader::new(File::open(path)?) });
+        }
+
+        let mut heap = BinaryHeap::new();
+
+        for (i, run) in runs.iter_mut().enumerate() {
+            if let Some(edge) = run.next_edge()? {
+                heap.push(Reverse((edge, i)));
+            }
+        }
+
+        let mut merged = Vec::new();
+
+        while let Some(Reverse((edge, i))) = heap.pop() {
+            if merged.last() != Some(&edge) {
+                merged.push(edge);
+            }
+
+            if let Some(next) = runs[i].next_edge()? {
+                heap.push(Reverse((next, i)));
+            }
+        }
+
+        Ok(Csr::from_sorted_edges(nodes, merged.into_iter().map(|(s, t)| (s, t, ()))))
+    }
+}
+
+impl<Ty> Drop for EdgeStreamBuilder<Ty> {
+    fn drop(&mut self) {
+        for path in &self.runs {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{NodeId, Undirected};
+
+    const INPUT: &str = "# comment\n0 1\n1 2 0.5\n\n0 1\n% another\n2 0\n1 2\n4 4\n";
+
+    fn neighbors<Ty: EdgeType>(csr: &Csr<(), (), Ty>, node: usize) -> Vec<usize> {
+        csr.neighbors(NodeId::new(node)).map(NodeId::index).collect()
+    }
+
+    #[test]
+    fn deduplicates_in_memory() {
+        let mut builder: EdgeStreamBuilder = EdgeStreamBuilder::new();
+
+        assert_eq!(6, builder.read_edges(INPUT.as_bytes()).unwrap());
+        assert_eq!(0, builder.spilled_runs());
+
+        let csr = builder.finish().unwrap();
+
+        assert_eq!(5, csr.node_count());
+        assert_eq!(4, csr.edge_count());
+        assert_eq!(vec![1], neighbors(&csr, 0));
+        assert_eq!(vec![4], neighbors(&csr, 4));
+    }
+
+    #[test]
+    fn spilled_runs_merge_to_same_graph() {
+        let mut builder = EdgeStreamBuilder::<Undirected>::new().memory_limit(2).min_node_count(7);
+
+        builder.read_edges(INPUT.as_bytes()).unwrap();
+
+        let runs: Vec<_> = builder.runs.clone();
+
+        assert_eq!(3, builder.spilled_runs());
+        assert!(runs.iter().all(|path| path.exists()));
+
+        let csr = builder.finish().unwrap();
+
+        assert!(runs.iter().all(|path| !path.exists()));
+        assert_eq!(7, csr.node_count());
+        assert_eq!(4, csr.edge_count());
+        assert_eq!(vec![1, 2], neighbors(&csr, 0));
+        assert_eq!(vec![2, 0], neighbors(&csr, 1));
+    }
+
+    #[test]
+    fn malformed_line_is_rejected() {
+        let mut builder: EdgeStreamBuilder = EdgeStreamBuilder::new();
+
+        let err = builder.read_edges("0 1\n2 x\n".as_bytes()).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(err.to_string().starts_with("line 2"));
+
+        let err = builder.read_edges("18446744073709551615 0\n".as_bytes()).unwrap_err();
+
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(io::ErrorKind::InvalidInput, builder.push_edge(0, usize::MAX).unwrap_err().kind());
+    }
+}
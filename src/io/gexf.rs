@@ -0,0 +1,427 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use crate::graph::{EdgeId, EdgeType, Graph, NodeId};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AttrType {
+    Integer,
+    Double,
+    Boolean,
+    String
+}
+
+impl AttrType {
+    fn name(self) -> &'static str {
+        match self {
+            AttrType::Integer => "long",
+            AttrType::Double => "double",
+            AttrType::Boolean => "boolean",
+            AttrType::String => "string"
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttrValue {
+    Integer(i64),
+    Double(f64),
+    Boolean(bool),
+    String(String)
+}
+
+impl AttrValue {
+    fn render(&self) -> String {
+        match self {
+            AttrValue::Integer(v) => v.to_string(),
+            AttrValue::Double(v) => v.to_string(),
+            AttrValue::Boolean(v) => v.to_string(),
+            AttrValue::String(v) => escape(v)
+        }
+    }
+}
+
+/// Half-open time interval `[start, end)`; no end means it never closes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimeInterval {
+    pub start: f64,
+    pub end: Option<f64>
+}
+
+impl TimeInterval {
+    pub fn new(start: f64, end: f64) -> TimeInterval {
+        TimeInterval { start, end: Some(end) }
+    }
+
+    pub fn starting_at(start: f64) -> TimeInterval {
+        TimeInterval { start, end: None }
+    }
+
+    fn render(&self) -> String {
+        match self.end {
+            Some(end) => format!(" start=\"{}\" end=\"{end}\"", self.start),
+            None => format!(" start=\"{}\"", self.start)
+        }
+    }
+}
+
+type ValueFn<'a, I, T> = Box<dyn Fn(I, &T) -> AttrValue + 'a>;
+type TimedValueFn<'a, I, T> = Box<dyn Fn(I, &T) -> Vec<(TimeInterval, AttrValue)> + 'a>;
+type SpellFn<'a, I, T> = Box<dyn Fn(I, &T) -> Vec<TimeInterval> + 'a>;
+type LabelFn<'a, N> = Box<dyn Fn(NodeId, &N) -> String + 'a>;
+type WeightFn<'a, E> = Box<dyn Fn(EdgeId, &E) -> f64 + 'a>;
+
+enum Values<'a, I, T> {
+    Static(ValueFn<'a, I, T>),
+    Dynamic(TimedValueFn<'a, I, T>)
+}
+
+struct Attribute<'a, I, T> {
+    title: String,
+    ty: AttrType,
+    values: Values<'a, I, T>
+}
+
+struct Class<'a, I, T> {
+    attributes: Vec<Attribute<'a, I, T>>,
+    spells: Option<SpellFn<'a, I, T>>
+}
+
+impl<I: Copy, T> Class<'_, I, T> {
+    fn new() -> Self {
+        Class { attributes: Vec::new(), spells: None }
+    }
+
+    fn is_dynamic(&self) -> bool {
+        self.spells.is_some() || self.attributes.iter().any(|a| matches!(a.values, Values::Dynamic(_)))
+    }
+
+    fn write_declarations(&self, class: &str, out: &mut String) {
+        if self.attributes.is_empty() {
+            return;
+        }
+
+        let mode = if self.is_dynamic() { "dynamic" } else { "static" };
+        let _ = writeln!(out, "    <attributes class=\"{class}\" mode=\"{mode}\">");
+
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "      <attribute id=\"{i}\" title=\"{}\" type=\"{}\"/>",
+                escape(&attribute.title),
+                attribute.ty.name()
+            );
+        }
+
+        out.push_str("    </attributes>\n");
+    }
+
+    // attvalues and spells of one element, or nothing if it has neither
+    fn write_children(&self, id: I, weight: &T, out: &mut String) {
+        let spells = self.spells.as_ref().map(|f| f(id, weight)).unwrap_or_default();
+
+        if self.attributes.is_empty() && spells.is_empty() {
+            return;
+        }
+
+        if !self.attributes.is_empty() {
+            out.push_str("        <attvalues>\n");
+
+            for (i, attribute) in self.attributes.iter().enumerate() {
+                let values = match &attribute.values {
+                    Values::Static(f) => vec![(None, f(id, weight))],
+                    Values::Dynamic(f) => f(id, weight).into_iter().map(|(t, v)| (Some(t), v)).collect()
+                };
+
+                for (interval, value) in values {
+                    let _ = writeln!(
+                        out,
+                        "          <attvalue for=\"{i}\" value=\"{}\"{}/>",
+                        value.render(),
+                        interval.map(|t| t.render()).unwrap_or_default()
+                    );
+                }
+            }
+
+            out.push_str("        </attvalues>\n");
+        }
+
+        if !spells.is_empty() {
+            out.push_str("        <spells>\n");
+
+            for spell in spells {
+                let _ = writeln!(out, "          <spell{}/>", spell.render());
+            }
+
+            out.push_str("        </spells>\n");
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c)
+        }
+    }
+
+    escaped
+}
+
+/// GEXF 1.3 export for Gephi. Attribute and time information is pulled
+/// from closures over each node or edge, so it can come from the weights as
+/// well as from side tables such as a [`NodeMap`](crate::NodeMap). As soon
+/// as anything is time dependent the graph is written in dynamic mode with
+/// `double` time stamps.
+pub struct GexfWriter<'a, N, E, Ty> {
+    graph: &'a Graph<N, E, Ty>,
+    node_label: Option<LabelFn<'a, N>>,
+    edge_weight: Option<WeightFn<'a, E>>,
+    nodes: Class<'a, NodeId, N>,
+    edges: Class<'a, EdgeId, E>
+}
+
+impl<'a, N, E, Ty: EdgeType> GexfWriter<'a, N, E, Ty> {
+    pub fn new(graph: &'a Graph<N, E, Ty>) -> GexfWriter<'a, N, E, Ty> {
+        GexfWriter {
+            graph,
+            node_label: None,
+            edge_weight: None,
+            nodes: Class::new(),
+            edges: Class::new()
+        }
+    }
+
+    pub fn node_label(mut self, label: impl Fn(NodeId, &N) -> String + 'a) -> Self {
+        self.node_label = Some(Box::new(label));
+        self
+    }
+
+    pub fn edge_weight(mut self, weight: impl Fn(EdgeId, &E) -> f64 + 'a) -> Self {
+        self.edge_weight = Some(Box::new(weight));
+        self
+    }
+
+    pub fn node_attribute(
+        mut self,
+        title: &str,
+        ty: AttrType,
+        value: impl Fn(NodeId, &N) -> AttrValue + 'a
+    ) -> Self {
+        self.nodes.attributes.push(Attribute {
+            title: title.to_string(),
+            ty,
+            values: Values::Static(Box::new(value))
+        });
+        self
+    }
+
+    /// Attribute taking a different value in each of the returned intervals.
+    pub fn dynamic_node_attribute(
+        mut self,
+        title: &str,
+        ty: AttrType,
+        values: impl Fn(NodeId, &N) -> Vec<(TimeInterval, AttrValue)> + 'a
+    ) -> Self {
+        self.nodes.attributes.push(Attribute {
+            title: title.to_string(),
+            ty,
+            values: Values::Dynamic(Box::new(values))
+        });
+        self
+    }
+
+    pub fn edge_attribute(
+        mut self,
+        title: &str,
+        ty: AttrType,
+        value: impl Fn(EdgeId, &E) -> AttrValue + 'a
+    ) -> Self {
+        self.edges.attributes.push(Attribute {
+            title: title.to_string(),
+            ty,
+            values: Values::Static(Box::new(value))
+        });
+        self
+    }
+
+    /// Attribute taking a different value in each of the returned intervals.
+    pub fn dynamic_edge_attribute(
+        mut self,
+        title: &str,
+        ty: AttrType,
+        values: impl Fn(EdgeId, &E) -> Vec<(TimeInterval, AttrValue)> + 'a
+    ) -> Self {
+        self.edges.attributes.push(Attribute {
+            title: title.to_string(),
+            ty,
+            values: Values::Dynamic(Box::new(values))
+        });
+        self
+    }
+
+    /// Intervals during which each node exists. An empty list leaves the
+    /// node present at all times.
+    pub fn node_spells(mut self, spells: impl Fn(NodeId, &N) -> Vec<TimeInterval> + 'a) -> Self {
+        self.nodes.spells = Some(Box::new(spells));
+        self
+    }
+
+    /// Intervals during which each edge exists. An empty list leaves the
+    /// edge present at all times.
+    pub fn edge_spells(mut self, spells: impl Fn(EdgeId, &E) -> Vec<TimeInterval> + 'a) -> Self {
+        self.edges.spells = Some(Box::new(spells));
+        self
+    }
+
+    pub fn render(&self) -> String {
+        let graph = self.graph;
+        let dynamic = self.nodes.is_dynamic() || self.edges.is_dynamic();
+        let mut out = String::new();
+
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        let _ = writeln!(
+            out,
+            "  <graph defaultedgetype=\"{}\" mode=\"{}\"{}>",
+            if graph.is_directed() { "directed" } else { "undirected" },
+            if dynamic { "dynamic" } else { "static" },
+            if dynamic { " timeformat=\"double\"" } else { "" }
+        );
+
+        self.nodes.write_declarations("node", &mut out);
+        self.edges.write_declarations("edge", &mut out);
+
+        out.push_str("    <nodes>\n");
+
+        for node in graph.node_ids() {
+            let weight = graph.node_weight(node).unwrap();
+            let label = self
+                .node_label
+                .as_ref()
+                .map(|f| format!(" label=\"{}\"", escape(&f(node, weight))))
+                .unwrap_or_default();
+
+            let mut children = String::new();
+            self.nodes.write_children(node, weight, &mut children);
+
+            if children.is_empty() {
+                let _ = writeln!(out, "      <node id=\"{}\"{label}/>", node.index());
+            } else {
+                let _ = writeln!(out, "      <node id=\"{}\"{label}>", node.index());
+                out.push_str(&children);
+                out.push_str("      </node>\n");
+            }
+        }
+
+        out.push_str("    </nodes>\n    <edges>\n");
+
+        for edge in graph.edge_ids() {
+            let (source, target) = graph.edge_endpoints(edge).unwrap();
+            let weight = graph.edge_weight(edge).unwrap();
+            let value = self
+                .edge_weight
+                .as_ref()
+                .map(|f| format!(" weight=\"{}\"", f(edge, weight)))
+                .unwrap_or_default();
+
+            let head = format!(
+                "      <edge id=\"{}\" source=\"{}\" target=\"{}\"{value}",
+                edge.index(),
+                source.index(),
+                target.index()
+            );
+
+            let mut children = String::new();
+            self.edges.write_children(edge, weight, &mut children);
+
+            if children.is_empty() {
+                let _ = writeln!(out, "{head}/>");
+            } else {
+                let _ = writeln!(out, "{head}>");
+                out.push_str(&children);
+                out.push_str("      </edge>\n");
+            }
+        }
+
+        out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+
+        out
+    }
+
+    pub fn write<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(self.render().as_bytes())
+    }
+}
+
+/// Plain export without attributes, labelling nodes with their weights.
+pub fn to_gexf<N: ToString, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> String {
+    GexfWriter::new(graph).node_label(|_, n| n.to_string()).render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attr::NodeMap;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn static_export() {
+        let mut graph: UnGraph<&str, f64> = Graph::new();
+        let a = graph.add_node("a & b");
+        let b = graph.add_node("<c>");
+        graph.add_edge(a, b, 0.5);
+
+        let xml = GexfWriter::new(&graph)
+            .node_label(|_, n| n.to_string())
+            .edge_weight(|_, &w| w)
+            .render();
+
+        assert!(xml.contains("<graph defaultedgetype=\"undirected\" mode=\"static\">"));
+        assert!(xml.contains("<node id=\"0\" label=\"a &amp; b\"/>"));
+        assert!(xml.contains("<node id=\"1\" label=\"&lt;c&gt;\"/>"));
+        assert!(xml.contains("<edge id=\"0\" source=\"0\" target=\"1\" weight=\"0.5\"/>"));
+        assert!(!xml.contains("<attributes"));
+        assert!(to_gexf(&graph).contains("<edge id=\"0\" source=\"0\" target=\"1\"/>"));
+    }
+
+    #[test]
+    fn dynamic_export_with_attributes() {
+        let mut graph: DiGraph<u32, &str> = Graph::new();
+        let a = graph.add_node(1);
+        let b = graph.add_node(2);
+        graph.add_edge(a, b, "knows");
+
+        let mut joined = NodeMap::new();
+        joined.insert(a, 0.0);
+        joined.insert(b, 2.5);
+
+        let xml = GexfWriter::new(&graph)
+            .node_attribute("score", AttrType::Integer, |_, &n| AttrValue::Integer(n as i64))
+            .dynamic_node_attribute("active", AttrType::Boolean, |_, _| {
+                vec![
+                    (TimeInterval::new(0.0, 1.0), AttrValue::Boolean(true)),
+                    (TimeInterval::starting_at(1.0), AttrValue::Boolean(false))
+                ]
+            })
+            .node_spells(|id, _| vec![TimeInterval::starting_at(joined[id])])
+            .edge_attribute("kind", AttrType::String, |_, &e| AttrValue::String(e.to_string()))
+            .render();
+
+        assert!(xml.contains("mode=\"dynamic\" timeformat=\"double\""));
+        assert!(xml.contains("<attributes class=\"node\" mode=\"dynamic\">"));
+        assert!(xml.contains("<attribute id=\"0\" title=\"score\" type=\"long\"/>"));
+        assert!(xml.contains("<attribute id=\"0\" title=\"kind\" type=\"string\"/>"));
+        assert!(xml.contains("<attvalue for=\"0\" value=\"2\"/>"));
+        assert!(xml.contains("<attvalue for=\"1\" value=\"true\" start=\"0\" end=\"1\"/>"));
+        assert!(xml.contains("<spell start=\"2.5\"/>"));
+        assert!(xml.contains("<attvalue for=\"0\" value=\"knows\"/>"));
+    }
+}
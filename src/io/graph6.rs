@@ -0,0 +1,379 @@
+use std::io::{self, BufRead};
+
+use crate::graph::{Graph, NodeId, UnGraph};
+use crate::io::ParseError;
+
+const GRAPH6_HEADER: &str = ">>graph6<<";
+const SPARSE6_HEADER: &str = ">>sparse6<<";
+
+fn encode_size(n: usize, out: &mut Vec<u8>) {
+    if n <= 62 {
+        out.push(n as u8 + 63);
+    } else if n <= 258047 {
+        out.push(126);
+        out.extend((0..3).rev().map(|i| ((n >> (6 * i)) & 63) as u8 + 63));
+    } else {
+        out.extend([126, 126]);
+        out.extend((0..6).rev().map(|i| ((n >> (6 * i)) & 63) as u8 + 63));
+    }
+}
+
+fn decode_size(bytes: &[u8]) -> Result<(usize, &[u8]), ParseError> {
+    let group = |bytes: &[u8], len: usize| -> Result<usize, ParseError> {
+        match bytes.get(..len) {
+            Some(digits) => Ok(digits.iter().fold(0, |n, &d| (n << 6) | (d - 63) as usize)),
+            None => Err(ParseError::new("truncated node count"))
+        }
+    };
+
+    match bytes {
+        [] => Err(ParseError::new("missing node count")),
+        [126, 126, rest @ ..] => Ok((group(rest, 6)?, &rest[6..])),
+        [126, rest @ ..] => Ok((group(rest, 3)?, &rest[3..])),
+        [b, rest @ ..] => Ok(((b - 63) as usize, rest))
+    }
+}
+
+fn encode_bits(bits: &[bool], out: &mut Vec<u8>) {
+    for chunk in bits.chunks(6) {
+        let value = chunk
+            .iter()
+            .chain(std::iter::repeat(&false))
+            .take(6)
+            .fold(0, |acc, &bit| (acc << 1) | bit as u8);
+        out.push(value + 63);
+    }
+}
+
+fn decode_bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|&b| (0..6).rev().map(move |i| (b - 63) >> i & 1 == 1))
+        .collect()
+}
+
+fn validate(line: &str) -> Result<&[u8], ParseError> {
+    let bytes = line.as_bytes();
+
+    match bytes.iter().position(|b| !(63..=126).contains(b)) {
+        Some(pos) => Err(ParseError::new(format!("invalid character at byte {pos}"))),
+        None => Ok(bytes)
+    }
+}
+
+// dense renumbering of the live nodes, in index order
+fn dense_ids<N, E>(graph: &UnGraph<N, E>) -> Vec<usize> {
+    let mut dense = vec![0; graph.node_bound()];
+
+    for (i, node) in graph.node_ids().enumerate() {
+        dense[node.index()] = i;
+    }
+
+    dense
+}
+
+fn empty_graph(n: usize) -> UnGraph<(), ()> {
+    let mut graph = Graph::new();
+
+    for _ in 0..n {
+        graph.add_node(());
+    }
+
+    graph
+}
+
+/// Encodes the graph in graph6, which only describes simple graphs: weights
+/// and self loops are dropped and parallel edges collapse into one. Nodes
+/// are numbered in index order.
+pub fn to_graph6<N, E>(graph: &UnGraph<N, E>) -> String {
+    let n = graph.node_count();
+    let dense = dense_ids(graph);
+
+    // edges as (column, row) of the upper triangle, in bit order
+    let mut edges: Vec<(usize, usize)> = graph
+        .edge_ids()
+        .map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let (a, b) = (dense[a.index()], dense[b.index()]);
+            (a.max(b), a.min(b))
+        })
+        .filter(|&(j, i)| i != j)
+        .collect();
+
+    edges.sort_unstable();
+    edges.dedup();
+
+    let mut edges = edges.into_iter().peekable();
+    let bits: Vec<bool> = (1..n)
+        .flat_map(|j| (0..j).map(move |i| (j, i)))
+        .map(|pair| edges.next_if_eq(&pair).is_some())
+        .collect();
+
+    let mut out = Vec::new();
+
+    encode_size(n, &mut out);
+    encode_bits(&bits, &mut out);
+
+    String::from_utf8(out).unwrap()
+}
+
+/// Parses a single graph6 string, with or without the `>>graph6<<` header.
+pub fn from_graph6(input: &str) -> Result<UnGraph<(), ()>, ParseError> {
+    let line = input.trim();
+    let line = line.strip_prefix(GRAPH6_HEADER).unwrap_or(line);
+
+    let (n, data) = decode_size(validate(line)?)?;
+    // checked, as the node count comes straight from the input
+    let Some(needed) = n.checked_mul(n.saturating_sub(1)).map(|pairs| pairs / 2) else {
+        return Err(ParseError::new(format!("node count {n} is too large")));
+    };
+
+    if data.len() != needed.div_ceil(6) {
+        return Err(ParseError::new(format!(
+            "expected {} data bytes for {n} nodes, found {}",
+            needed.div_ceil(6),
+            data.len()
+        )));
+    }
+
+    let bits = decode_bits(data);
+    let mut graph = empty_graph(n);
+    let mut pos = 0;
+
+    for j in 1..n {
+        for i in 0..j {
+            if bits[pos] {
+                graph.add_edge(NodeId::new(i), NodeId::new(j), ());
+            }
+            pos += 1;
+        }
+    }
+
+    Ok(graph)
+}
+
+fn sparse6_width(n: usize) -> usize {
+    let mut k = 1;
+
+    while 1 << k < n {
+        k += 1;
+    }
+
+    k
+}
+
+fn push_value(bits: &mut Vec<bool>, value: usize, width: usize) {
+    bits.extend((0..width).rev().map(|i| value >> i & 1 == 1));
+}
+
+/// Encodes the graph in sparse6, which keeps self loops and parallel edges
+/// but not weights. Nodes are numbered in index order.
+pub fn to_sparse6<N, E>(graph: &UnGraph<N, E>) -> String {
+    let n = graph.node_count();
+    let k = sparse6_width(n);
+    let dense = dense_ids(graph);
+
+    let mut edges: Vec<(usize, usize)> = graph
+        .edge_ids()
+        .map(|edge| {
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            let (a, b) = (dense[a.index()], dense[b.index()]);
+            (a.max(b), a.min(b))
+        })
+        .collect();
+
+    edges.sort();
+
+    let mut bits = Vec::new();
+    let mut curr = 0;
+
+    for (v, u) in edges {
+        if v == curr {
+            bits.push(false);
+            push_value(&mut bits, u, k);
+        } else if v == curr + 1 {
+            curr += 1;
+            bits.push(true);
+            push_value(&mut bits, u, k);
+        } else {
+            curr = v;
+            bits.push(true);
+            push_value(&mut bits, v, k);
+            bits.push(false);
+            push_value(&mut bits, u, k);
+        }
+    }
+
+    let padding = (6 - bits.len() % 6) % 6;
+
+    // padding with ones would read back as an edge to n - 1 in this case
+    if k < 6 && n == 1 << k && padding >= k && curr < n - 1 {
+        bits.push(false);
+    }
+
+    let padding = (6 - bits.len() % 6) % 6;
+    bits.extend(std::iter::repeat_n(true, padding));
+
+    let mut out = vec![b':'];
+
+    encode_size(n, &mut out);
+    encode_bits(&bits, &mut out);
+
+    String::from_utf8(out).unwrap()
+}
+
+/// Parses a single sparse6 string, with or without the `>>sparse6<<`
+/// header.
+pub fn from_sparse6(input: &str) -> Result<UnGraph<(), ()>, ParseError> {
+    let line = input.trim();
+    let line = line.strip_prefix(SPARSE6_HEADER).unwrap_or(line);
+
+    let Some(line) = line.strip_prefix(':') else {
+        return Err(ParseError::new("sparse6 data must start with ':'"));
+    };
+
+    let (n, data) = decode_size(validate(line)?)?;
+    let k = sparse6_width(n);
+    let bits = decode_bits(data);
+
+    let read = |pos: usize| bits[pos..pos + k].iter().fold(0, |acc, &bit| (acc << 1) | bit as usize);
+
+    let mut graph = empty_graph(n);
+    let mut pos = 0;
+    let mut v = 0;
+
+    while pos + 1 + k <= bits.len() {
+        let b = bits[pos];
+        let x = read(pos + 1);
+
+        pos += 1 + k;
+
+        if b {
+            v += 1;
+        }
+
+        // trailing padding may decode as an out of range value
+        if x >= n || v >= n {
+            break;
+        }
+
+        if x > v {
+            v = x;
+        } else {
+            graph.add_edge(NodeId::new(x), NodeId::new(v), ());
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Reads one graph per line, accepting graph6 and sparse6 lines alike, as
+/// found in nauty output and published graph collections. Blank lines are
+/// skipped; malformed lines fail with `InvalidData`.
+pub fn read_graph6<R: BufRead>(reader: R) -> io::Result<Vec<UnGraph<(), ()>>> {
+    let mut graphs = Vec::new();
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let parsed = if line.starts_with(':') || line.starts_with(SPARSE6_HEADER) {
+            from_sparse6(line)
+        } else {
+            from_graph6(line)
+        };
+
+        let graph = parsed.map_err(|err| {
+            let err = ParseError::new(format!("line {}: {}", number + 1, err));
+            io::Error::new(io::ErrorKind::InvalidData, err)
+        })?;
+
+        graphs.push(graph);
+    }
+
+    Ok(graphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(graph: &UnGraph<(), ()>) -> Vec<(usize, usize)> {
+        let mut edges: Vec<_> = graph
+            .edge_ids()
+            .map(|e| {
+                let (a, b) = graph.edge_endpoints(e).unwrap();
+                (a.index().min(b.index()), a.index().max(b.index()))
+            })
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    fn petersen() -> UnGraph<(), ()> {
+        let mut graph = empty_graph(10);
+
+        for i in 0..5 {
+            graph.add_edge(NodeId::new(i), NodeId::new((i + 1) % 5), ());
+            graph.add_edge(NodeId::new(i), NodeId::new(i + 5), ());
+            graph.add_edge(NodeId::new(i + 5), NodeId::new((i + 2) % 5 + 5), ());
+        }
+
+        graph
+    }
+
+    #[test]
+    fn graph6_known_encodings() {
+        // reference strings as produced by nauty's geng and networkx
+        assert_eq!("IheA@GUAo", to_graph6(&petersen()));
+        assert_eq!("?", to_graph6(&empty_graph(0)));
+
+        let graph = from_graph6(">>graph6<<IheA@GUAo\n").unwrap();
+
+        assert_eq!(10, graph.node_count());
+        assert_eq!(edges(&petersen()), edges(&graph));
+        assert!(from_graph6("Ih").is_err());
+        assert!(from_graph6("I he").is_err());
+        assert!(from_graph6("~~~~~~~~").is_err());
+        assert!(from_graph6("~~~~~~~~~~").is_err());
+    }
+
+    #[test]
+    fn sparse6_round_trip_with_loops_and_multi_edges() {
+        let mut graph = petersen();
+
+        graph.add_edge(NodeId::new(3), NodeId::new(3), ());
+        graph.add_edge(NodeId::new(0), NodeId::new(1), ());
+
+        let encoded = to_sparse6(&graph);
+        let decoded = from_sparse6(&encoded).unwrap();
+
+        assert!(encoded.starts_with(':'));
+        assert_eq!(10, decoded.node_count());
+        assert_eq!(edges(&graph), edges(&decoded));
+
+        // the example from the format description
+        let example = from_sparse6(":Fa@x^").unwrap();
+        assert_eq!(vec![(0, 1), (0, 2), (1, 2), (5, 6)], edges(&example));
+    }
+
+    #[test]
+    fn read_mixed_collection() {
+        let input = ">>graph6<<A_\n\n:Fa@x^\nBw\n";
+        let graphs = read_graph6(input.as_bytes()).unwrap();
+
+        assert_eq!(3, graphs.len());
+        assert_eq!(vec![(0, 1)], edges(&graphs[0]));
+        assert_eq!(3, graphs[2].edge_count());
+
+        let Err(err) = read_graph6("A_\n!!\n".as_bytes()) else {
+            panic!("expected malformed line to be rejected");
+        };
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(err.to_string().starts_with("line 2"));
+    }
+}
@@ -0,0 +1,389 @@
+use std::fmt;
+
+use crate::io::ParseError;
+
+/// Minimal JSON document model used by the JSON based formats.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Members in document order.
+    Object(Vec<(String, JsonValue)>)
+}
+
+impl JsonValue {
+    pub fn parse(input: &str) -> Result<JsonValue, ParseError> {
+        let mut parser = Parser { chars: input.char_indices().peekable(), input, depth: 0 };
+
+        let value = parser.value()?;
+
+        parser.skip_whitespace();
+
+        match parser.chars.peek() {
+            None => Ok(value),
+            Some(&(pos, _)) => Err(ParseError::new(format!("trailing characters at byte {pos}")))
+        }
+    }
+
+    /// Member of an object, `None` for other values.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(n) => Some(*n),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            JsonValue::Array(items) => Some(items),
+            _ => None
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&[(String, JsonValue)]> {
+        match self {
+            JsonValue::Object(members) => Some(members),
+            _ => None
+        }
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Number(value)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(value: i64) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value as f64)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{c}")?
+        }
+    }
+
+    f.write_str("\"")
+}
+
+/// Compact serialization. Non-finite numbers have no JSON form and are
+/// written as `null`.
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("null"),
+            JsonValue::Bool(b) => write!(f, "{b}"),
+            JsonValue::Number(n) if n.is_finite() => write!(f, "{n}"),
+            JsonValue::Number(_) => f.write_str("null"),
+            JsonValue::String(s) => write_string(f, s),
+            JsonValue::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{item}")?;
+                }
+                f.write_str("]")
+            },
+            JsonValue::Object(members) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Deepest nesting of arrays and objects accepted, so that hostile input
+/// fails to parse instead of overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    depth: usize
+}
+
+impl Parser<'_> {
+    fn error<T>(&mut self, expected: &str) -> Result<T, ParseError> {
+        match self.chars.peek() {
+            Some(&(pos, c)) => {
+                Err(ParseError::new(format!("expected {expected} at byte {pos}, found '{c}'")))
+            },
+            None => Err(ParseError::new(format!("expected {expected}, found end of input")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|(_, c)| c.is_ascii_whitespace()).is_some() {}
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        self.chars.next_if(|&(_, c)| c == expected).is_some()
+    }
+
+    fn keyword(&mut self, word: &str, value: JsonValue) -> Result<JsonValue, ParseError> {
+        for expected in word.chars() {
+            if !self.eat(expected) {
+                return self.error(word);
+            }
+        }
+
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<JsonValue, ParseError> {
+        self.skip_whitespace();
+
+        match self.chars.peek().map(|&(_, c)| c) {
+            Some('n') => self.keyword("null", JsonValue::Null),
+            Some('t') => self.keyword("true", JsonValue::Bool(true)),
+            Some('f') => self.keyword("false", JsonValue::Bool(false)),
+            Some('"') => self.string().map(JsonValue::String),
+            Some(c @ ('[' | '{')) => {
+                if self.depth == MAX_DEPTH {
+                    let pos = self.chars.peek().unwrap().0;
+                    return Err(ParseError::new(format!("nesting deeper than {MAX_DEPTH} at byte {pos}")));
+                }
+
+                self.depth += 1;
+
+                let value = if c == '[' { self.array() } else { self.object() };
+
+                self.depth -= 1;
+                value
+            },
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            _ => self.error("value")
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, ParseError> {
+        let start = self.chars.peek().unwrap().0;
+        let mut end = start;
+
+        while let Some((pos, c)) = self.chars.next_if(|&(_, c)| c.is_ascii_digit() || "+-.eE".contains(c)) {
+            end = pos + c.len_utf8();
+        }
+
+        match self.input[start..end].parse() {
+            Ok(n) => Ok(JsonValue::Number(n)),
+            Err(_) => Err(ParseError::new(format!("invalid number at byte {start}")))
+        }
+    }
+
+    fn hex(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            match self.chars.next().and_then(|(_, c)| c.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return Err(ParseError::new("invalid unicode escape"))
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        if !self.eat('"') {
+            return self.error("string");
+        }
+
+        let mut out = String::new();
+
+        loop {
+            match self.chars.next() {
+                None => return Err(ParseError::new("unterminated string")),
+                Some((_, '"')) => return Ok(out),
+                Some((_, '\\')) => {
+                    let escaped = match self.chars.next() {
+                        Some((_, '"')) => '"',
+                        Some((_, '\\')) => '\\',
+                        Some((_, '/')) => '/',
+                        Some((_, 'b')) => '\u{8}',
+                        Some((_, 'f')) => '\u{c}',
+                        Some((_, 'n')) => '\n',
+                        Some((_, 'r')) => '\r',
+                        Some((_, 't')) => '\t',
+                        Some((_, 'u')) => {
+                            let mut code = self.hex()?;
+
+                            if (0xd800..0xdc00).contains(&code) && self.eat('\\') && self.eat('u') {
+                                let low = self.hex()?;
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        },
+                        _ => return Err(ParseError::new("invalid escape sequence"))
+                    };
+                    out.push(escaped);
+                },
+                Some((_, c)) => out.push(c)
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, ParseError> {
+        self.eat('[');
+        self.skip_whitespace();
+
+        let mut items = Vec::new();
+
+        if self.eat(']') {
+            return Ok(JsonValue::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+
+            if self.eat(']') {
+                return Ok(JsonValue::Array(items));
+            }
+
+            if !self.eat(',') {
+                return self.error("',' or ']'");
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<JsonValue, ParseError> {
+        self.eat('{');
+        self.skip_whitespace();
+
+        let mut members = Vec::new();
+
+        if self.eat('}') {
+            return Ok(JsonValue::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            let key = self.string()?;
+
+            self.skip_whitespace();
+
+            if !self.eat(':') {
+                return self.error("':'");
+            }
+
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+
+            if self.eat('}') {
+                return Ok(JsonValue::Object(members));
+            }
+
+            if !self.eat(',') {
+                return self.error("',' or '}'");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_serialize_round_trip() {
+        let input = r#" {"a": [1, 2.5, -3e2], "b": {"c": null, "d": true}, "e": "x\"é\n"} "#;
+        let value = JsonValue::parse(input).unwrap();
+
+        assert_eq!(Some(-300.0), value.get("a").unwrap().as_array().unwrap()[2].as_f64());
+        assert_eq!(Some(true), value.get("b").unwrap().get("d").unwrap().as_bool());
+        assert_eq!(Some("x\"é\n"), value.get("e").unwrap().as_str());
+
+        let text = value.to_string();
+
+        assert_eq!(r#"{"a":[1,2.5,-300],"b":{"c":null,"d":true},"e":"x\"é\n"}"#, text);
+        assert_eq!(value, JsonValue::parse(&text).unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_documents() {
+        for input in ["", "[1,", "{\"a\" 1}", "tru", "[1] 2", "\"abc", "{1: 2}"] {
+            assert!(JsonValue::parse(input).is_err(), "{input:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(JsonValue::parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(JsonValue::parse(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(JsonValue::parse(&"[".repeat(200_000)).is_err());
+        assert!(JsonValue::parse(&"{\"a\":".repeat(200_000)).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use std::error::Error;
+use std::fmt;
+
+mod dot;
+mod edge_list;
+mod edge_stream;
+mod gexf;
+mod graph6;
+mod json;
+mod node_link;
+mod snapshot;
+
+pub use dot::{from_dot, to_dot, DotAttributes};
+pub use edge_list::{read_edge_list, write_edge_list};
+pub use edge_stream::EdgeStreamBuilder;
+pub use gexf::{to_gexf, AttrType, AttrValue, GexfWriter, TimeInterval};
+pub use graph6::{from_graph6, from_sparse6, read_graph6, to_graph6, to_sparse6};
+pub use json::JsonValue;
+pub use node_link::{from_json_node_link, to_json_node_link, NodeLinkKeys};
+pub use snapshot::{read_snapshot, write_snapshot, SnapshotValue, SnapshotView};
+
+/// Malformed input handed to one of the format readers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    message: String
+}
+
+impl ParseError {
+    pub(crate) fn new(message: impl Into<String>) -> ParseError {
+        ParseError { message: message.into() }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ParseError {}
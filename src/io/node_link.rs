@@ -0,0 +1,252 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::graph::{EdgeType, Graph, NodeId};
+use crate::io::{JsonValue, ParseError};
+
+/// Member names of the node-link document. The defaults match networkx's
+/// `node_link_data` and d3's force layout examples.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeLinkKeys {
+    pub nodes: String,
+    pub links: String,
+    pub id: String,
+    pub source: String,
+    pub target: String
+}
+
+impl Default for NodeLinkKeys {
+    fn default() -> Self {
+        NodeLinkKeys {
+            nodes: "nodes".to_string(),
+            links: "links".to_string(),
+            id: "id".to_string(),
+            source: "source".to_string(),
+            target: "target".to_string()
+        }
+    }
+}
+
+/// Serializes the graph as `{"directed", "multigraph", "graph", "nodes",
+/// "links"}`. The closures supply extra members for each node and link
+/// object; nodes are identified by their index unless the node members
+/// contain the id key themselves. The graph counts as a multigraph when
+/// two edges join the same pair of nodes.
+pub fn to_json_node_link<N, E, Ty, FN, FE>(
+    graph: &Graph<N, E, Ty>,
+    keys: &NodeLinkKeys,
+    mut node_data: FN,
+    mut edge_data: FE
+) -> String
+where
+    Ty: EdgeType,
+    FN: FnMut(&N) -> Vec<(String, JsonValue)>,
+    FE: FnMut(&E) -> Vec<(String, JsonValue)>
+{
+    let mut ids = HashMap::new();
+
+    let nodes = graph
+        .node_ids()
+        .map(|node| {
+            let data = node_data(graph.node_weight(node).unwrap());
+
+            let id = match data.iter().find(|(k, _)| *k == keys.id) {
+                Some((_, id)) => id.clone(),
+                None => JsonValue::from(node.index())
+            };
+
+            let mut members = vec![(keys.id.clone(), id.clone())];
+            members.extend(data.into_iter().filter(|(k, _)| *k != keys.id));
+
+            ids.insert(node, id);
+
+            JsonValue::Object(members)
+        })
+        .collect();
+
+    let links = graph
+        .edge_ids()
+        .map(|edge| {
+            let (source, target) = graph.edge_endpoints(edge).unwrap();
+
+            let mut members = vec![
+                (keys.source.clone(), ids[&source].clone()),
+                (keys.target.clone(), ids[&target].clone())
+            ];
+            members.extend(edge_data(graph.edge_weight(edge).unwrap()));
+
+            JsonValue::Object(members)
+        })
+        .collect();
+
+    let mut pairs = HashSet::new();
+    let multigraph = !graph.edge_ids().all(|edge| {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+        let undirected = !Ty::is_directed() && target < source;
+        pairs.insert(if undirected { (target, source) } else { (source, target) })
+    });
+
+    JsonValue::Object(vec![
+        ("directed".to_string(), JsonValue::Bool(graph.is_directed())),
+        ("multigraph".to_string(), JsonValue::Bool(multigraph)),
+        ("graph".to_string(), JsonValue::Object(Vec::new())),
+        (keys.nodes.clone(), JsonValue::Array(nodes)),
+        (keys.links.clone(), JsonValue::Array(links))
+    ])
+    .to_string()
+}
+
+/// Builds a graph from a node-link document. The closures receive the full
+/// node and link objects to derive weights from. A `"directed"` flag
+/// contradicting the requested graph type is an error, as are links to
+/// unknown ids and duplicate node ids.
+pub fn from_json_node_link<N, E, Ty, FN, FE>(
+    input: &str,
+    keys: &NodeLinkKeys,
+    mut node_weight: FN,
+    mut edge_weight: FE
+) -> Result<Graph<N, E, Ty>, ParseError>
+where
+    Ty: EdgeType,
+    FN: FnMut(&JsonValue) -> N,
+    FE: FnMut(&JsonValue) -> E
+{
+    let document = JsonValue::parse(input)?;
+
+    if let Some(directed) = document.get("directed").and_then(JsonValue::as_bool)
+        && directed != Ty::is_directed()
+    {
+        return Err(ParseError::new(format!(
+            "document is {}directed but the graph type is not",
+            if directed { "" } else { "un" }
+        )));
+    }
+
+    let list = |key: &str| match document.get(key).map(JsonValue::as_array) {
+        Some(Some(items)) => Ok(items),
+        Some(None) => Err(ParseError::new(format!("\"{key}\" is not an array"))),
+        None => Err(ParseError::new(format!("missing \"{key}\"")))
+    };
+
+    let mut graph = Graph::new();
+    let mut ids: HashMap<String, NodeId> = HashMap::new();
+
+    for node in list(&keys.nodes)? {
+        let Some(id) = node.get(&keys.id) else {
+            return Err(ParseError::new(format!("node without \"{}\"", keys.id)));
+        };
+
+        let created = graph.add_node(node_weight(node));
+
+        if ids.insert(id.to_string(), created).is_some() {
+            return Err(ParseError::new(format!("duplicate node id {id}")));
+        }
+    }
+
+    for link in list(&keys.links)? {
+        let endpoint = |key: &str| match link.get(key) {
+            Some(id) => ids
+                .get(&id.to_string())
+                .copied()
+                .ok_or_else(|| ParseError::new(format!("link to unknown node {id}"))),
+            None => Err(ParseError::new(format!("link without \"{key}\"")))
+        };
+
+        let (source, target) = (endpoint(&keys.source)?, endpoint(&keys.target)?);
+
+        graph.add_edge(source, target, edge_weight(link));
+    }
+
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn default_keys_match_networkx() {
+        let mut graph: DiGraph<&str, f64> = Graph::new();
+        let a = graph.add_node("alice");
+        let b = graph.add_node("bob");
+        graph.add_edge(a, b, 2.0);
+
+        let json = to_json_node_link(
+            &graph,
+            &NodeLinkKeys::default(),
+            |&name| vec![("id".to_string(), JsonValue::from(name))],
+            |&w| vec![("weight".to_string(), JsonValue::from(w))]
+        );
+
+        assert_eq!(
+            r#"{"directed":true,"multigraph":false,"graph":{},"nodes":[{"id":"alice"},{"id":"bob"}],"links":[{"source":"alice","target":"bob","weight":2}]}"#,
+            json
+        );
+
+        let back: DiGraph<String, f64> = from_json_node_link(
+            &json,
+            &NodeLinkKeys::default(),
+            |n| n.get("id").unwrap().as_str().unwrap().to_string(),
+            |l| l.get("weight").and_then(JsonValue::as_f64).unwrap_or(1.0)
+        )
+        .unwrap();
+
+        let e = back.edge_ids().next().unwrap();
+        let (s, t) = back.edge_endpoints(e).unwrap();
+
+        assert_eq!("alice", back.node_weight(s).unwrap());
+        assert_eq!("bob", back.node_weight(t).unwrap());
+        assert_eq!(Some(&2.0), back.edge_weight(e));
+    }
+
+    #[test]
+    fn custom_keys_and_index_ids() {
+        let keys = NodeLinkKeys {
+            links: "edges".to_string(),
+            id: "key".to_string(),
+            ..NodeLinkKeys::default()
+        };
+
+        let mut graph: UnGraph<u8, ()> = Graph::new();
+        let a = graph.add_node(7);
+        let b = graph.add_node(8);
+        graph.add_edge(b, a, ());
+
+        let json = to_json_node_link(
+            &graph,
+            &keys,
+            |&n| vec![("size".to_string(), JsonValue::from(n as i64))],
+            |_| vec![]
+        );
+
+        assert!(json.contains(r#""nodes":[{"key":0,"size":7},{"key":1,"size":8}]"#));
+        assert!(json.contains(r#""edges":[{"source":1,"target":0}]"#));
+        assert!(json.contains(r#""multigraph":false"#));
+
+        let size = |n: &JsonValue| n.get("size").unwrap().as_f64().unwrap();
+        let back: UnGraph<f64, ()> = from_json_node_link(&json, &keys, size, |_| ()).unwrap();
+
+        assert_eq!(2, back.node_count());
+        assert_eq!(1, back.edge_count());
+
+        graph.add_edge(a, b, ());
+
+        let json = to_json_node_link(&graph, &keys, |_| vec![], |_| vec![]);
+
+        assert!(json.contains(r#""multigraph":true"#));
+    }
+
+    #[test]
+    fn rejects_inconsistent_documents() {
+        let keys = NodeLinkKeys::default();
+        let parse = |json: &str| {
+            from_json_node_link::<(), (), crate::graph::Directed, _, _>(json, &keys, |_| (), |_| ())
+        };
+
+        assert!(parse(r#"{"directed":false,"nodes":[],"links":[]}"#).is_err());
+        assert!(parse(r#"{"nodes":[{"id":1}],"links":[{"source":1,"target":2}]}"#).is_err());
+        assert!(parse(r#"{"nodes":[{"id":1},{"id":1}],"links":[]}"#).is_err());
+        assert!(parse(r#"{"nodes":[{"id":1}]}"#).is_err());
+        assert!(parse(r#"{"nodes":[{"id":1},{"id":"1"}],"links":[]}"#).is_ok());
+    }
+}
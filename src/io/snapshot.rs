@@ -0,0 +1,486 @@
+use std::hash::Hasher;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+
+use crate::bitset::FixedBitSet;
+use crate::csr::Csr;
+use crate::fnv::Fnv;
+use crate::graph::{EdgeId, EdgeType, NodeId};
+use crate::io::ParseError;
+use crate::visit::{GraphBase, IntoNeighbors, Visitable};
+
+const MAGIC: &[u8; 8] = b"GRSNAPv\0";
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 40;
+const CHECKSUM_LEN: usize = 8;
+
+/// Fixed-size value that can be stored in a snapshot as little-endian bytes.
+pub trait SnapshotValue: Sized {
+    const SIZE: usize;
+
+    fn write_le(&self, out: &mut Vec<u8>);
+
+    /// `bytes` is exactly `SIZE` long.
+    fn read_le(bytes: &[u8]) -> Self;
+}
+
+impl SnapshotValue for () {
+    const SIZE: usize = 0;
+
+    fn write_le(&self, _: &mut Vec<u8>) {}
+
+    fn read_le(_: &[u8]) -> Self {}
+}
+
+macro_rules! snapshot_value {
+    ($($ty:ty),*) => {
+        $(
+            impl SnapshotValue for $ty {
+                const SIZE: usize = size_of::<$ty>();
+
+                fn write_le(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn read_le(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+snapshot_value!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+struct Sink<W> {
+    out: W,
+    checksum: Fnv,
+    written: usize,
+    buffer: Vec<u8>
+}
+
+impl<W: Write> Sink<W> {
+    fn flush_buffer(&mut self) -> io::Result<()> {
+        self.checksum.write(&self.buffer);
+        self.out.write_all(&self.buffer)?;
+        self.written += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+
+    fn push(&mut self, write: impl FnOnce(&mut Vec<u8>)) -> io::Result<()> {
+        write(&mut self.buffer);
+
+        if self.buffer.len() >= 1 << 16 {
+            self.flush_buffer()?;
+        }
+
+        Ok(())
+    }
+
+    fn align(&mut self) -> io::Result<()> {
+        let len = self.written + self.buffer.len();
+        self.push(|buf| buf.resize(buf.len() + (8 - len % 8) % 8, 0))
+    }
+}
+
+fn padded(len: usize) -> usize {
+    len.div_ceil(8) * 8
+}
+
+/// Writes `csr` in the versioned snapshot format: a header, then the
+/// outgoing offsets and targets, the incoming offsets and sources, and the
+/// node and edge weights, each section little-endian and 8-byte aligned,
+/// followed by an FNV-1a checksum of everything before it.
+///
+/// Offsets are stored as `u64` and node indices as `u32`, so graphs with
+/// more than `u32::MAX` nodes are rejected.
+pub fn write_snapshot<N, E, Ty, W>(csr: &Csr<N, E, Ty>, out: W) -> io::Result<()>
+where
+    N: SnapshotValue,
+    E: SnapshotValue,
+    Ty: EdgeType,
+    W: Write
+{
+    if csr.node_count() > u32::MAX as usize {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "too many nodes for a snapshot"));
+    }
+
+    let mut sink = Sink { out, checksum: Fnv::new(), written: 0, buffer: Vec::new() };
+
+    sink.push(|buf| {
+        buf.extend_from_slice(MAGIC);
+        buf.extend_from_slice(&VERSION.to_le_bytes());
+        buf.extend_from_slice(&(Ty::is_directed() as u32).to_le_bytes());
+        buf.extend_from_slice(&(csr.node_count() as u64).to_le_bytes());
+        buf.extend_from_slice(&(csr.edge_count() as u64).to_le_bytes());
+        buf.extend_from_slice(&(N::SIZE as u32).to_le_bytes());
+        buf.extend_from_slice(&(E::SIZE as u32).to_le_bytes());
+    })?;
+
+    for &offset in &csr.out_offsets {
+        sink.push(|buf| buf.extend_from_slice(&(offset as u64).to_le_bytes()))?;
+    }
+
+    for &target in &csr.targets {
+        sink.push(|buf| buf.extend_from_slice(&(target as u32).to_le_bytes()))?;
+    }
+
+    sink.align()?;
+
+    for &offset in &csr.in_offsets {
+        sink.push(|buf| buf.extend_from_slice(&(offset as u64).to_le_bytes()))?;
+    }
+
+    for &edge in &csr.in_edges {
+        sink.push(|buf| buf.extend_from_slice(&(csr.sources[edge] as u32).to_le_bytes()))?;
+    }
+
+    sink.align()?;
+
+    for weight in &csr.nodes {
+        sink.push(|buf| weight.write_le(buf))?;
+    }
+
+    sink.align()?;
+
+    for weight in &csr.weights {
+        sink.push(|buf| weight.write_le(buf))?;
+    }
+
+    sink.align()?;
+    sink.flush_buffer()?;
+
+    let checksum = sink.checksum.finish();
+
+    sink.out.write_all(&checksum.to_le_bytes())
+}
+
+#[derive(Copy, Clone)]
+struct Layout {
+    directed: bool,
+    nodes: usize,
+    edges: usize,
+    out_offsets: usize,
+    targets: usize,
+    in_offsets: usize,
+    sources: usize,
+    node_weights: usize,
+    edge_weights: usize,
+    end: usize
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap())
+}
+
+fn read_layout<N: SnapshotValue, E: SnapshotValue>(bytes: &[u8], verify: bool) -> Result<Layout, ParseError> {
+    if bytes.len() < HEADER_LEN + CHECKSUM_LEN || &bytes[..8] != MAGIC {
+        return Err(ParseError::new("not a graph snapshot"));
+    }
+
+    let version = u32_at(bytes, 8);
+
+    if version != VERSION {
+        return Err(ParseError::new(format!("unsupported snapshot version {version}")));
+    }
+
+    let node_size = u32_at(bytes, 32) as usize;
+    let edge_size = u32_at(bytes, 36) as usize;
+
+    if node_size != N::SIZE || edge_size != E::SIZE {
+        return Err(ParseError::new(format!(
+            "snapshot holds {node_size} and {edge_size} byte weights, expected {} and {}",
+            N::SIZE,
+            E::SIZE
+        )));
+    }
+
+    let nodes = usize::try_from(u64_at(bytes, 16)).map_err(|_| ParseError::new("node count too large"))?;
+    let edges = usize::try_from(u64_at(bytes, 24)).map_err(|_| ParseError::new("edge count too large"))?;
+
+    // every node and edge takes at least four bytes, which also keeps the
+    // layout arithmetic below from overflowing
+    if nodes > bytes.len() / 4 || edges > bytes.len() / 4 {
+        return Err(ParseError::new("snapshot counts exceed its length"));
+    }
+
+    let index_bytes = 8 * (nodes + 1) + padded(4 * edges);
+    let out_offsets = HEADER_LEN;
+    let in_offsets = out_offsets + index_bytes;
+    let node_weights = in_offsets + index_bytes;
+    let edge_weights = node_weights + padded(nodes * node_size);
+    let end = edge_weights + padded(edges * edge_size);
+
+    if bytes.len() != end + CHECKSUM_LEN {
+        return Err(ParseError::new(format!(
+            "snapshot should be {} bytes long, found {}",
+            end + CHECKSUM_LEN,
+            bytes.len()
+        )));
+    }
+
+    if verify {
+        let mut checksum = Fnv::new();
+        checksum.write(&bytes[..end]);
+
+        if checksum.finish() != u64_at(bytes, end) {
+            return Err(ParseError::new("snapshot checksum mismatch"));
+        }
+    }
+
+    let layout = Layout {
+        directed: u32_at(bytes, 12) & 1 == 1,
+        nodes,
+        edges,
+        out_offsets,
+        targets: out_offsets + 8 * (nodes + 1),
+        in_offsets,
+        sources: in_offsets + 8 * (nodes + 1),
+        node_weights,
+        edge_weights,
+        end
+    };
+
+    for (offsets, column) in [(layout.out_offsets, layout.targets), (layout.in_offsets, layout.sources)] {
+        let mut prev = 0;
+
+        for i in 0..=nodes {
+            let offset = u64_at(bytes, offsets + 8 * i);
+
+            if offset < prev || offset > edges as u64 || (i == nodes && offset != edges as u64) {
+                return Err(ParseError::new("corrupt offsets in snapshot"));
+            }
+
+            prev = offset;
+        }
+
+        if (0..edges).any(|e| u32_at(bytes, column + 4 * e) as usize >= nodes) {
+            return Err(ParseError::new("node index out of range in snapshot"));
+        }
+    }
+
+    Ok(layout)
+}
+
+/// Loads a snapshot into an owned graph, verifying its checksum first.
+pub fn read_snapshot<N, E, Ty>(bytes: &[u8]) -> Result<Csr<N, E, Ty>, ParseError>
+where
+    N: SnapshotValue,
+    E: SnapshotValue,
+    Ty: EdgeType
+{
+    let view: SnapshotView<'_, N, E> = SnapshotView::new(bytes)?;
+
+    if view.is_directed() != Ty::is_directed() {
+        return Err(ParseError::new("snapshot directedness does not match the graph type"));
+    }
+
+    let nodes = (0..view.node_count()).map(|i| view.node_weight(NodeId::new(i))).collect();
+
+    let edges = (0..view.node_count()).flat_map(|i| {
+        let source = NodeId::new(i);
+        view.out_edges(source).map(move |(edge, target)| (source, target, view.edge_weight(edge)))
+    });
+
+    Ok(Csr::from_edges(nodes, edges))
+}
+
+/// Read-only graph backed directly by the bytes of a snapshot, e.g. a
+/// memory-mapped file, so opening it costs no parsing or copying beyond
+/// the validation pass. Values are decoded on access.
+pub struct SnapshotView<'a, N, E> {
+    bytes: &'a [u8],
+    layout: Layout,
+    weights: PhantomData<(N, E)>
+}
+
+impl<N, E> Clone for SnapshotView<'_, N, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N, E> Copy for SnapshotView<'_, N, E> {}
+
+impl<'a, N: SnapshotValue, E: SnapshotValue> SnapshotView<'a, N, E> {
+    /// Validates the header, offsets and checksum.
+    pub fn new(bytes: &'a [u8]) -> Result<SnapshotView<'a, N, E>, ParseError> {
+        Ok(SnapshotView { bytes, layout: read_layout::<N, E>(bytes, true)?, weights: PhantomData })
+    }
+
+    /// Like [`new`](SnapshotView::new) but skips the checksum, which is the
+    /// only step that reads every byte of the snapshot.
+    pub fn without_checksum(bytes: &'a [u8]) -> Result<SnapshotView<'a, N, E>, ParseError> {
+        Ok(SnapshotView { bytes, layout: read_layout::<N, E>(bytes, false)?, weights: PhantomData })
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.layout.directed
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.layout.nodes
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.layout.edges
+    }
+
+    fn range(&self, offsets: usize, node: NodeId) -> std::ops::Range<usize> {
+        let start = u64_at(self.bytes, offsets + 8 * node.index()) as usize;
+        let end = u64_at(self.bytes, offsets + 8 * (node.index() + 1)) as usize;
+        start..end
+    }
+
+    /// Panics if `node` is out of range.
+    pub fn node_weight(&self, node: NodeId) -> N {
+        assert!(node.index() < self.layout.nodes, "node out of range");
+        let pos = self.layout.node_weights + node.index() * N::SIZE;
+        N::read_le(&self.bytes[pos..pos + N::SIZE])
+    }
+
+    /// Panics if `edge` is out of range.
+    pub fn edge_weight(&self, edge: EdgeId) -> E {
+        assert!(edge.index() < self.layout.edges, "edge out of range");
+        let pos = self.layout.edge_weights + edge.index() * E::SIZE;
+        E::read_le(&self.bytes[pos..pos + E::SIZE])
+    }
+
+    /// Outgoing edges with their targets. Edge ids match those of the
+    /// [`Csr`] the snapshot was written from.
+    pub fn out_edges(&self, node: NodeId) -> impl Iterator<Item = (EdgeId, NodeId)> + 'a {
+        let view = *self;
+        self.range(self.layout.out_offsets, node).map(move |e| {
+            (EdgeId::new(e), NodeId::new(u32_at(view.bytes, view.layout.targets + 4 * e) as usize))
+        })
+    }
+
+    /// Successors for directed snapshots, all adjacent nodes otherwise.
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + 'a {
+        let view = *self;
+        let incoming = if self.layout.directed { 0..0 } else { self.range(self.layout.in_offsets, node) };
+
+        self.out_edges(node).map(|(_, target)| target).chain(
+            incoming
+                .map(move |i| NodeId::new(u32_at(view.bytes, view.layout.sources + 4 * i) as usize))
+                .filter(move |&source| source != node)
+        )
+    }
+
+    pub fn predecessors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + 'a {
+        let view = *self;
+        self.range(self.layout.in_offsets, node)
+            .map(move |i| NodeId::new(u32_at(view.bytes, view.layout.sources + 4 * i) as usize))
+    }
+
+    /// Size of the snapshot in bytes, checksum included.
+    pub fn byte_len(&self) -> usize {
+        self.layout.end + CHECKSUM_LEN
+    }
+}
+
+impl<N, E> GraphBase for SnapshotView<'_, N, E> {
+    type NodeId = NodeId;
+}
+
+impl<N: SnapshotValue, E: SnapshotValue> IntoNeighbors for SnapshotView<'_, N, E> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        SnapshotView::neighbors(self, node)
+    }
+}
+
+impl<N: SnapshotValue, E: SnapshotValue> Visitable for SnapshotView<'_, N, E> {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.node_count())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.node_count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Directed, Undirected};
+    use crate::visit::Bfs;
+
+    fn sample() -> Csr<u16, f64> {
+        let n = NodeId::new;
+        Csr::from_edges(
+            vec![10, 20, 30, 40],
+            [(n(0), n(1), 0.5), (n(0), n(2), 1.5), (n(2), n(3), 2.5), (n(3), n(0), 3.5)]
+        )
+    }
+
+    fn bytes_of<N: SnapshotValue, E: SnapshotValue, Ty: EdgeType>(csr: &Csr<N, E, Ty>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_snapshot(csr, &mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn round_trip_through_bytes() {
+        let csr = sample();
+        let bytes = bytes_of(&csr);
+
+        assert_eq!(0, bytes.len() % 8);
+        assert_eq!(&MAGIC[..], &bytes[..8]);
+
+        let loaded: Csr<u16, f64, Directed> = read_snapshot(&bytes).unwrap();
+
+        assert_eq!(csr.edge_weights(), loaded.edge_weights());
+        for i in 0..4 {
+            let node = NodeId::new(i);
+            assert_eq!(csr.node_weight(node), loaded.node_weight(node));
+            assert_eq!(csr.neighbors(node).collect::<Vec<_>>(), loaded.neighbors(node).collect::<Vec<_>>());
+        }
+
+        assert!(read_snapshot::<u16, f64, Undirected>(&bytes).is_err());
+        assert!(read_snapshot::<u32, f64, Directed>(&bytes).is_err());
+    }
+
+    #[test]
+    fn view_reads_in_place() {
+        let bytes = bytes_of(&sample());
+        let view: SnapshotView<'_, u16, f64> = SnapshotView::new(&bytes).unwrap();
+
+        assert_eq!(4, view.node_count());
+        assert_eq!(4, view.edge_count());
+        assert_eq!(30, view.node_weight(NodeId::new(2)));
+        assert_eq!(vec![NodeId::new(3)], view.predecessors(NodeId::new(0)).collect::<Vec<_>>());
+        assert_eq!(4, Bfs::new(&view, NodeId::new(0)).count());
+
+        let edges: Vec<_> = view.out_edges(NodeId::new(0)).map(|(e, _)| view.edge_weight(e)).collect();
+        assert_eq!(vec![0.5, 1.5], edges);
+
+        let n = NodeId::new;
+        let undirected: Csr<(), (), Undirected> =
+            Csr::from_edges(vec![(); 3], [(n(0), n(1), ()), (n(1), n(1), ())]);
+        let bytes = bytes_of(&undirected);
+        let view: SnapshotView<'_, (), ()> = SnapshotView::new(&bytes).unwrap();
+
+        assert_eq!(vec![n(1), n(0)], view.neighbors(n(1)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let mut bytes = bytes_of(&sample());
+        let last = bytes.len() - 9;
+
+        bytes[last] ^= 1;
+
+        assert!(SnapshotView::<u16, f64>::new(&bytes).is_err());
+        assert!(SnapshotView::<u16, f64>::without_checksum(&bytes).is_ok());
+        assert!(SnapshotView::<u16, f64>::new(&bytes[..bytes.len() - 1]).is_err());
+        assert!(SnapshotView::<u16, f64>::new(b"not a snapshot at all, clearly").is_err());
+    }
+}
@@ -0,0 +1,170 @@
+use crate::attr::NodeMap;
+use crate::graph::{EdgeType, Graph, NodeId};
+use crate::rng::Rng;
+
+/// Nodes evenly spaced on the unit circle around the origin, in node
+/// order, starting at the top and going clockwise with `y` pointing down,
+/// as on screen.
+pub fn circular_layout<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> NodeMap<(f64, f64)> {
+    let count = graph.node_count() as f64;
+    let mut layout = NodeMap::with_capacity(graph.node_bound());
+
+    for (i, node) in graph.node_ids().enumerate() {
+        let angle = std::f64::consts::TAU * i as f64 / count - std::f64::consts::FRAC_PI_2;
+        layout.insert(node, (angle.cos(), angle.sin()));
+    }
+
+    layout
+}
+
+/// Force-directed layout after Fruchterman and Reingold: edges pull their
+/// endpoints together, every pair of nodes pushes apart, and the nodes move
+/// from random starting points by steps that shrink to nothing over
+/// `iterations` rounds. Directions, weights and self loops are ignored.
+/// The result is centered on the origin and scaled to fit `[-1, 1]` on both
+/// axes. Each round takes time quadratic in the node count.
+pub fn spring_layout<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    iterations: usize,
+    rng: &mut R
+) -> NodeMap<(f64, f64)>
+where
+    Ty: EdgeType,
+    R: Rng
+{
+    let nodes: Vec<NodeId> = graph.node_ids().collect();
+    let mut slot = vec![0; graph.node_bound()];
+
+    for (i, node) in nodes.iter().enumerate() {
+        slot[node.index()] = i;
+    }
+
+    let edges: Vec<(usize, usize)> = graph
+        .edge_ids()
+        .map(|edge| graph.edge_endpoints(edge).unwrap())
+        .filter(|(a, b)| a != b)
+        .map(|(a, b)| (slot[a.index()], slot[b.index()]))
+        .collect();
+
+    let mut position: Vec<(f64, f64)> = nodes.iter().map(|_| (rng.next_f64(), rng.next_f64())).collect();
+    let mut shift = vec![(0.0, 0.0); nodes.len()];
+    // ideal edge length for nodes spread over the unit square
+    let k = (1.0 / nodes.len().max(1) as f64).sqrt();
+
+    for round in 0..iterations {
+        let temperature = 0.1 * (1.0 - round as f64 / iterations as f64);
+        shift.fill((0.0, 0.0));
+
+        for i in 0..nodes.len() {
+            for j in i + 1..nodes.len() {
+                let (dx, dy, distance) = apart(position[i], position[j]);
+                let push = k * k / distance;
+
+                shift[i].0 += dx / distance * push;
+                shift[i].1 += dy / distance * push;
+                shift[j].0 -= dx / distance * push;
+                shift[j].1 -= dy / distance * push;
+            }
+        }
+
+        for &(a, b) in &edges {
+            let (dx, dy, distance) = apart(position[a], position[b]);
+            let pull = distance * distance / k;
+
+            shift[a].0 -= dx / distance * pull;
+            shift[a].1 -= dy / distance * pull;
+            shift[b].0 += dx / distance * pull;
+            shift[b].1 += dy / distance * pull;
+        }
+
+        for (at, &(dx, dy)) in position.iter_mut().zip(&shift) {
+            let length = dx.hypot(dy);
+
+            if length > 0.0 {
+                let step = length.min(temperature);
+                at.0 += dx / length * step;
+                at.1 += dy / length * step;
+            }
+        }
+    }
+
+    rescale(&mut position);
+
+    let mut layout = NodeMap::with_capacity(graph.node_bound());
+
+    for (node, at) in nodes.into_iter().zip(position) {
+        layout.insert(node, at);
+    }
+
+    layout
+}
+
+/// Offset from `b` to `a` and its length, kept off zero so that nodes
+/// meeting by chance still push apart.
+fn apart(a: (f64, f64), b: (f64, f64)) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    (dx, dy, dx.hypot(dy).max(1e-3))
+}
+
+/// Centers `position` on the origin and scales it into `[-1, 1]`.
+fn rescale(position: &mut [(f64, f64)]) {
+    let count = position.len().max(1) as f64;
+    let center = position.iter().fold((0.0, 0.0), |sum, at| (sum.0 + at.0 / count, sum.1 + at.1 / count));
+    let extent = position
+        .iter()
+        .map(|at| (at.0 - center.0).abs().max((at.1 - center.1).abs()))
+        .fold(0.0, f64::max);
+    let scale = if extent > 0.0 { 1.0 / extent } else { 0.0 };
+
+    for at in position {
+        *at = ((at.0 - center.0) * scale, (at.1 - center.1) * scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+    use crate::rng::SplitMix64;
+
+    #[test]
+    fn circular_layout_starts_at_the_top() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        graph.remove_node(n[1]);
+
+        let layout = circular_layout(&graph);
+        let expected = [(n[0], (0.0, -1.0)), (n[2], (0.866, 0.5)), (n[3], (-0.866, 0.5))];
+
+        assert_eq!(3, layout.len());
+
+        for (node, (x, y)) in expected {
+            let at = layout.get(node).unwrap();
+            assert!((at.0 - x).abs() < 1e-3 && (at.1 - y).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn spring_layout_keeps_neighbors_close() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..8).map(|_| graph.add_node(())).collect();
+
+        for i in 0..7 {
+            graph.add_edge(n[i], n[i + 1], ());
+        }
+
+        let layout = spring_layout(&graph, 200, &mut SplitMix64::new(7));
+        let distance = |a: usize, b: usize| {
+            let (p, q) = (layout.get(n[a]).unwrap(), layout.get(n[b]).unwrap());
+            (p.0 - q.0).hypot(p.1 - q.1)
+        };
+
+        assert!(layout.iter().all(|(_, at)| at.0.abs() <= 1.0 + 1e-9 && at.1.abs() <= 1.0 + 1e-9));
+        assert!((0..7).all(|i| distance(i, i + 1) < distance(0, 7)));
+        assert!(distance(0, 7) > 1.0);
+
+        let mut single: UnGraph<(), ()> = Graph::new();
+        let alone = single.add_node(());
+        assert_eq!(Some(&(0.0, 0.0)), spring_layout(&single, 10, &mut SplitMix64::new(7)).get(alone));
+    }
+}
@@ -0,0 +1,66 @@
+#[macro_use]
+mod macros;
+
+mod arena;
+mod attr;
+mod bitset;
+mod concurrent;
+mod csr;
+mod dag;
+mod error;
+mod fnv;
+mod graph;
+mod graphmap;
+mod grid;
+mod heap;
+mod hypergraph;
+mod list;
+mod multiplex;
+mod parallel;
+mod persistent;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "inline-adjacency")]
+mod smallvec;
+mod temporal;
+
+pub mod algo;
+pub mod analysis;
+pub mod arbitrary;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod generate;
+pub mod io;
+pub mod layout;
+pub mod link_prediction;
+pub mod query;
+pub mod rng;
+pub mod rewrite;
+pub mod sample;
+pub mod sparsify;
+pub mod transform;
+pub mod tree;
+pub mod visit;
+pub mod walk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use arena::{Arena, DefaultIx, IndexType, MemoryUsage};
+pub use attr::{ArenaKey, EdgeMap, NodeMap, SecondaryMap};
+pub use bitset::FixedBitSet;
+pub use concurrent::ConcurrentGraph;
+pub use csr::{Csr, CsrNeighbors, GraphRef};
+pub use dag::{Dag, TopologicalOrder};
+pub use error::GraphError;
+pub use graph::{
+    DiGraph, Directed, Direction, EdgeEntry, EdgeId, EdgeIds, EdgePolicy, EdgeRef, EdgeType, Edges, Graph,
+    GraphMemory, Moved, Neighbors, NodeId, NodeIds, RemovedNode, UnGraph, Undirected
+};
+pub use graphmap::{GraphMap, NodeEntry};
+pub use grid::{Connectivity, GridGraph};
+pub use heap::{AddressableHeap, IndexedBinaryHeap, PairingHeap};
+pub use hypergraph::{Hypergraph, Incidence};
+pub use list::LinkedList;
+pub use multiplex::{LayerView, MultiplexGraph};
+pub use persistent::ImGraph;
+pub use temporal::{Interval, TemporalGraph, TemporalPaths, TemporalView};
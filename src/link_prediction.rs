@@ -0,0 +1,249 @@
+use std::collections::HashSet;
+
+use crate::algo::{neighborhood, similarity, Similarity};
+use crate::graph::{EdgeType, Graph, NodeId};
+use crate::rng::Rng;
+
+/// Graph with some edges held out, from [`split_edges`]. Ids in `train`
+/// are those of the original graph.
+pub struct EdgeSplit<N, E, Ty> {
+    pub train: Graph<N, E, Ty>,
+    pub test: Vec<(NodeId, NodeId)>
+}
+
+/// Scores of a predictor against held-out edges, from [`evaluate`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LinkEvaluation {
+    /// Chance that a held-out edge outscores a random non-edge, ties
+    /// counting half. `None` without held-out edges or non-edges.
+    pub auc: Option<f64>,
+    /// Share of the `k` best-scored non-edges of the training graph that
+    /// are held-out edges.
+    pub precision_at_k: f64
+}
+
+/// The pair with the lower id first, as pairs are compared unordered.
+fn unordered(a: NodeId, b: NodeId) -> (NodeId, NodeId) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Moves a random `test_fraction` of the edges, rounded, out of a copy of
+/// `graph`, returning their endpoints.
+pub fn split_edges<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    test_fraction: f64,
+    rng: &mut R
+) -> EdgeSplit<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    R: Rng
+{
+    let mut train = graph.map(|_, n| n.clone(), |_, e| e.clone());
+    let mut edges: Vec<_> = graph.edge_ids().collect();
+    let count = ((edges.len() as f64 * test_fraction).round() as usize).min(edges.len());
+
+    rng.shuffle(&mut edges);
+
+    let test = edges[..count]
+        .iter()
+        .map(|&edge| {
+            let ends = train.edge_endpoints(edge).unwrap();
+            train.remove_edge(edge);
+            ends
+        })
+        .collect();
+
+    EdgeSplit { train, test }
+}
+
+/// Up to `count` distinct unordered pairs of nodes drawn at random with no
+/// edge between them either way. Gives up after `100 * count` draws, so a
+/// dense graph may yield fewer.
+pub fn sample_non_edges<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    count: usize,
+    rng: &mut R
+) -> Vec<(NodeId, NodeId)>
+where
+    Ty: EdgeType,
+    R: Rng
+{
+    let nodes: Vec<_> = graph.node_ids().collect();
+    let mut seen = HashSet::new();
+    let mut pairs = Vec::with_capacity(count);
+
+    if nodes.len() < 2 {
+        return pairs;
+    }
+
+    for _ in 0..count.saturating_mul(100) {
+        if pairs.len() == count {
+            break;
+        }
+
+        let (a, b) = (nodes[rng.below(nodes.len())], nodes[rng.below(nodes.len())]);
+
+        if a != b && !graph.has_edge(a, b) && !graph.has_edge(b, a) && seen.insert(unordered(a, b)) {
+            pairs.push(unordered(a, b));
+        }
+    }
+
+    pairs
+}
+
+/// Every unordered pair of non-adjacent nodes with a positive score under
+/// `measure`, best first and ties broken by the pair. Only nodes two hops
+/// apart share neighbors, so only those are scored.
+pub fn score_non_edges<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    measure: Similarity
+) -> Vec<(NodeId, NodeId, f64)> {
+    let mut scored = Vec::new();
+
+    for a in graph.node_ids() {
+        let around = neighborhood(graph, a);
+        let mut candidates: Vec<_> = around
+            .iter()
+            .flat_map(|&n| neighborhood(graph, n))
+            .filter(|&b| b > a && around.binary_search(&b).is_err())
+            .collect();
+
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        for b in candidates {
+            let score = similarity(graph, a, b, measure);
+
+            if score > 0.0 {
+                scored.push((a, b, score));
+            }
+        }
+    }
+
+    scored.sort_by(|x, y| y.2.total_cmp(&x.2).then((x.0, x.1).cmp(&(y.0, y.1))));
+    scored
+}
+
+/// Chance that a score from `positives` beats one from `negatives`, ties
+/// counting half. `None` if either is empty.
+pub fn auc(positives: &[f64], negatives: &[f64]) -> Option<f64> {
+    if positives.is_empty() || negatives.is_empty() {
+        return None;
+    }
+
+    let mut negatives = negatives.to_vec();
+    negatives.sort_by(f64::total_cmp);
+
+    let wins: f64 = positives
+        .iter()
+        .map(|&score| {
+            let below = negatives.partition_point(|&n| n < score);
+            let tied = negatives[below..].partition_point(|&n| n <= score);
+            below as f64 + tied as f64 / 2.0
+        })
+        .sum();
+
+    Some(wins / (positives.len() * negatives.len()) as f64)
+}
+
+/// Share of the first `k` pairs of `ranked` found in `relevant`, either
+/// way round, out of `k`. Zero when `k` is.
+pub fn precision_at_k(ranked: &[(NodeId, NodeId, f64)], relevant: &[(NodeId, NodeId)], k: usize) -> f64 {
+    if k == 0 {
+        return 0.0;
+    }
+
+    let relevant: HashSet<_> = relevant.iter().map(|&(a, b)| unordered(a, b)).collect();
+    let hits = ranked.iter().take(k).filter(|&&(a, b, _)| relevant.contains(&unordered(a, b))).count();
+
+    hits as f64 / k as f64
+}
+
+/// Holds out `test_fraction` of the edges, scores pairs on what is left
+/// with `measure` and reports how well the held-out edges are recovered,
+/// against as many sampled non-edges of the full graph.
+pub fn evaluate<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    test_fraction: f64,
+    measure: Similarity,
+    k: usize,
+    rng: &mut R
+) -> LinkEvaluation
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    R: Rng
+{
+    let split = split_edges(graph, test_fraction, rng);
+    let negatives = sample_non_edges(graph, split.test.len(), rng);
+    let score = |pairs: &[(NodeId, NodeId)]| -> Vec<f64> {
+        pairs.iter().map(|&(a, b)| similarity(&split.train, a, b, measure)).collect()
+    };
+
+    let ranked = score_non_edges(&split.train, measure);
+
+    LinkEvaluation {
+        auc: auc(&score(&split.test), &score(&negatives)),
+        precision_at_k: precision_at_k(&ranked, &split.test, k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::UnGraph;
+    use crate::rng::SplitMix64;
+
+    // two 5-cliques joined by a single edge
+    fn cliques() -> UnGraph<(), ()> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..10).map(|_| graph.add_node(())).collect();
+
+        for a in 0..10 {
+            for b in a + 1..10 {
+                if a / 5 == b / 5 {
+                    graph.add_edge(n[a], n[b], ());
+                }
+            }
+        }
+
+        graph.add_edge(n[4], n[5], ());
+        graph
+    }
+
+    #[test]
+    fn splits_and_samples_pairs() {
+        let graph = cliques();
+        let mut rng = SplitMix64::new(7);
+        let split = split_edges(&graph, 0.25, &mut rng);
+
+        assert_eq!(5, split.test.len());
+        assert_eq!(16, split.train.edge_count());
+        assert!(split.test.iter().all(|&(a, b)| graph.has_edge(a, b) && !split.train.has_edge(a, b)));
+
+        let negatives = sample_non_edges(&graph, 10, &mut rng);
+        assert_eq!(10, negatives.len());
+        assert!(negatives.iter().all(|&(a, b)| a < b && !graph.has_edge(a, b)));
+    }
+
+    #[test]
+    fn measures_ranking_quality() {
+        assert_eq!(Some(0.875), auc(&[3.0, 1.0], &[0.0, 1.0]));
+        assert_eq!(None, auc(&[], &[1.0]));
+
+        let graph = cliques();
+        let ranked = score_non_edges(&graph, Similarity::CommonNeighbors);
+        let n: Vec<_> = graph.node_ids().collect();
+
+        // every cross pair two hops apart shares exactly one of the bridge ends
+        assert_eq!(8, ranked.len());
+        assert_eq!((n[0], n[5], 1.0), ranked[0]);
+        assert_eq!(0.5, precision_at_k(&ranked, &[(n[5], n[1])], 2));
+
+        let evaluation = evaluate(&graph, 0.2, Similarity::Jaccard, 3, &mut SplitMix64::new(1));
+        assert!(evaluation.auc.unwrap() > 0.5);
+    }
+}
@@ -0,0 +1,644 @@
+use std::cmp::Ordering;
+
+use crate::arena::{Arena, DefaultIx, IndexType, MemoryUsage};
+
+struct LinkedListNode<T, Ix> {
+    prev: Option<Ix>,
+    next: Option<Ix>,
+    val: T
+}
+
+/// Doubly linked list whose nodes live in an [`Arena`], linked by
+/// positions stored as `Ix`, in the links and the arena's free list. The
+/// default [`DefaultIx`] holds up to `u32::MAX` values; `u16` makes slots
+/// smaller still and `usize` lifts the limit.
+pub struct LinkedList<T, Ix = DefaultIx> {
+    head: Option<Ix>,
+    tail: Option<Ix>,
+    nodes: Arena<LinkedListNode<T, Ix>, Ix>
+}
+
+pub struct LinkedListIterator<'a, T, Ix = DefaultIx> {
+    list: &'a LinkedList<T, Ix>,
+    curr: Option<Ix>
+}
+
+impl<'a, T, Ix: IndexType> LinkedListIterator<'a, T, Ix> {
+    fn new(list: &'a LinkedList<T, Ix>) -> LinkedListIterator<'a, T, Ix> {
+        LinkedListIterator { list, curr: list.head }
+    }
+}
+
+impl<'a, T, Ix: IndexType> Iterator for LinkedListIterator<'a, T, Ix> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pos = self.curr?;
+
+        let node = &self.list.nodes[pos];
+
+        self.curr = node.next;
+
+        Some(&node.val)
+    }
+}
+
+impl<'a, T, Ix: IndexType> IntoIterator for &'a LinkedList<T, Ix> {
+    type Item = &'a T;
+    type IntoIter = LinkedListIterator<'a, T, Ix>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Draining iterator of a [`LinkedList`], see [`LinkedList::drain`].
+pub struct LinkedListDrain<'a, T, Ix: IndexType = DefaultIx> {
+    list: &'a mut LinkedList<T, Ix>
+}
+
+impl<T, Ix: IndexType> Iterator for LinkedListDrain<'_, T, Ix> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.remove_first()
+    }
+}
+
+impl<T, Ix: IndexType> Drop for LinkedListDrain<'_, T, Ix> {
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
+
+/// Empty list with any index type, e.g. `LinkedList::<T, u16>::default()`.
+impl<T, Ix: IndexType> Default for LinkedList<T, Ix> {
+    fn default() -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            nodes: Arena::default()
+        }
+    }
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> LinkedList<T> {
+        LinkedList {
+            head: None,
+            tail: None,
+            nodes: Arena::new()
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> LinkedList<T> {
+        LinkedList {
+            head: None,
+            tail: None,
+            nodes: Arena::with_capacity(capacity)
+        }
+    }
+}
+
+impl<T, Ix: IndexType> LinkedList<T, Ix> {
+    /// Makes room for `additional` more values without reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        self.nodes.reserve(additional);
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.nodes.memory_usage()
+    }
+
+    pub fn iter(&self) -> LinkedListIterator<'_, T, Ix> {
+        LinkedListIterator::new(self)
+    }
+
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn get_first(&self) -> Option<&T> {
+        self.head.map(|pos| &self.nodes[pos].val)
+    }
+
+    pub fn get_last(&self) -> Option<&T> {
+        self.tail.map(|pos| &self.nodes[pos].val)
+    }
+
+    pub fn add_first(&mut self, val: T) {
+        let node = LinkedListNode {
+            prev: None,
+            next: self.head,
+            val
+        };
+
+        let new_head = Ix::new(self.nodes.insert(node));
+
+        match self.head {
+            None => {
+                self.head = Some(new_head);
+                self.tail = Some(new_head);
+            },
+            Some(old_head) => {
+                self.nodes[old_head].prev = Some(new_head);
+                self.head = Some(new_head);
+            }
+        }
+    }
+
+    pub fn add_last(&mut self, val: T) {
+        let node = LinkedListNode {
+            prev: self.tail,
+            next: None,
+            val
+        };
+
+        let new_tail = Ix::new(self.nodes.insert(node));
+
+        match self.tail {
+            None => {
+                self.head = Some(new_tail);
+                self.tail = Some(new_tail);
+            },
+            Some(old_tail) => {
+                self.nodes[old_tail].next = Some(new_tail);
+                self.tail = Some(new_tail);
+            }
+        }
+    }
+
+    /// Removes every value, yielding them front to back while the slots
+    /// stay allocated for the next values added. Values not taken are
+    /// dropped with the iterator.
+    pub fn drain(&mut self) -> LinkedListDrain<'_, T, Ix> {
+        LinkedListDrain { list: self }
+    }
+
+    pub fn remove_first(&mut self) -> Option<T> {
+        self.head.map(|pos| {
+            self.head = self.nodes[pos].next;
+
+            match self.head {
+                None => {
+                    self.tail = None;
+                }
+                Some(new_head) => {
+                    self.nodes[new_head].prev = None;
+                }
+            }
+
+            self.nodes.remove(pos.index()).expect("expected used slot").val
+        })
+    }
+
+    pub fn remove_last(&mut self) -> Option<T> {
+        self.tail.map(|pos| {
+            self.tail = self.nodes[pos].prev;
+
+            match self.tail {
+                None => {
+                    self.head = None;
+                }
+                Some(new_tail) => {
+                    self.nodes[new_tail].next = None;
+                }
+            }
+
+            self.nodes.remove(pos.index()).expect("expected used slot").val
+        })
+    }
+
+    /// Sorts the list stably by relinking its nodes with a bottom-up merge
+    /// sort, moving no values.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        // runs[i] holds a sorted run of 2^i nodes, older than the runs below
+        let mut runs: Vec<Option<Ix>> = Vec::new();
+        let mut curr = self.head;
+
+        while let Some(pos) = curr {
+            curr = self.nodes[pos].next;
+            self.nodes[pos].next = None;
+
+            let mut carry = Some(pos);
+            let mut i = 0;
+
+            while let Some(Some(run)) = runs.get(i).copied() {
+                carry = self.merge_runs(Some(run), carry, &mut compare);
+                runs[i] = None;
+                i += 1;
+            }
+
+            if i == runs.len() {
+                runs.push(None);
+            }
+
+            runs[i] = carry;
+        }
+
+        let head = runs
+            .into_iter()
+            .fold(None, |newer, older| self.merge_runs(older, newer, &mut compare));
+        self.relink(head);
+    }
+
+    pub fn sort(&mut self)
+    where
+        T: Ord
+    {
+        self.sort_by(T::cmp);
+    }
+
+    /// Merges `other` into this list, both sorted by `compare`, keeping
+    /// this list's values first among equals. The values of `other` move
+    /// into this list's slots; the merge itself only relinks.
+    pub fn merge_by(&mut self, mut other: LinkedList<T, Ix>, mut compare: impl FnMut(&T, &T) -> Ordering) {
+        let mut chain = None;
+        let mut last: Option<Ix> = None;
+
+        while let Some(val) = other.remove_first() {
+            let pos = Ix::new(self.nodes.insert(LinkedListNode { prev: None, next: None, val }));
+
+            match last {
+                None => chain = Some(pos),
+                Some(last) => self.nodes[last].next = Some(pos)
+            }
+
+            last = Some(pos);
+        }
+
+        let head = self.merge_runs(self.head, chain, &mut compare);
+        self.relink(head);
+    }
+
+    pub fn merge(&mut self, other: LinkedList<T, Ix>)
+    where
+        T: Ord
+    {
+        self.merge_by(other, T::cmp);
+    }
+
+    /// Removes every value for which `same_bucket(value, kept)` holds, `kept`
+    /// being the last value before it that stayed, so runs of equal values
+    /// shrink to their first.
+    pub fn dedup_by(&mut self, mut same_bucket: impl FnMut(&T, &T) -> bool) {
+        let Some(mut kept) = self.head else {
+            return;
+        };
+
+        while let Some(pos) = self.nodes[kept].next {
+            if !same_bucket(&self.nodes[pos].val, &self.nodes[kept].val) {
+                kept = pos;
+                continue;
+            }
+
+            let next = self.nodes[pos].next;
+
+            self.nodes[kept].next = next;
+
+            match next {
+                None => self.tail = Some(kept),
+                Some(next) => self.nodes[next].prev = Some(kept)
+            }
+
+            self.nodes.remove(pos.index());
+        }
+    }
+
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq
+    {
+        self.dedup_by(|a, b| a == b);
+    }
+
+    /// Splits off the values from index `at` on into a new list. They move
+    /// to the new list's own slots; this list just drops its link to them.
+    /// Panics if `at` is past the end.
+    pub fn split_off(&mut self, at: usize) -> LinkedList<T, Ix> {
+        assert!(at <= self.size(), "split index out of bounds");
+
+        let mut tail = LinkedList::default();
+        tail.reserve(self.size() - at);
+
+        if at == self.size() {
+            return tail;
+        }
+
+        let first = self.position(at);
+        let mut curr = Some(first);
+
+        self.tail = self.nodes[first].prev;
+
+        match self.tail {
+            None => self.head = None,
+            Some(last) => self.nodes[last].next = None
+        }
+
+        while let Some(pos) = curr {
+            let node = self.nodes.remove(pos.index()).unwrap();
+            curr = node.next;
+            tail.add_last(node.val);
+        }
+
+        tail
+    }
+
+    /// Moves the first `n` values, modulo the length, to the back by
+    /// relinking the ends.
+    pub fn rotate_left(&mut self, n: usize) {
+        let len = self.size();
+
+        if len == 0 || n.is_multiple_of(len) {
+            return;
+        }
+
+        let (head, tail) = (self.head.unwrap(), self.tail.unwrap());
+        let first = self.position(n % len);
+        let last = self.nodes[first].prev.unwrap();
+
+        self.nodes[tail].next = Some(head);
+        self.nodes[head].prev = Some(tail);
+        self.nodes[first].prev = None;
+        self.nodes[last].next = None;
+        self.head = Some(first);
+        self.tail = Some(last);
+    }
+
+    /// Moves the last `n` values, modulo the length, to the front.
+    pub fn rotate_right(&mut self, n: usize) {
+        let len = self.size();
+
+        if len > 0 {
+            self.rotate_left(len - n % len);
+        }
+    }
+
+    /// Slot of the value at `index`, walking from the nearer end.
+    fn position(&self, index: usize) -> Ix {
+        let len = self.size();
+
+        if index < len / 2 {
+            let mut pos = self.head.unwrap();
+            (0..index).for_each(|_| pos = self.nodes[pos].next.unwrap());
+            pos
+        } else {
+            let mut pos = self.tail.unwrap();
+            (index + 1..len).for_each(|_| pos = self.nodes[pos].prev.unwrap());
+            pos
+        }
+    }
+
+    /// Merges two sorted chains linked by `next` only, taking from `a` on
+    /// ties, and returns the head of the result.
+    fn merge_runs(
+        &mut self,
+        mut a: Option<Ix>,
+        mut b: Option<Ix>,
+        compare: &mut impl FnMut(&T, &T) -> Ordering
+    ) -> Option<Ix> {
+        let mut head = None;
+        let mut last: Option<Ix> = None;
+
+        loop {
+            let b_first = match (a, b) {
+                (Some(x), Some(y)) => compare(&self.nodes[x].val, &self.nodes[y].val) == Ordering::Greater,
+                (a, _) => a.is_none()
+            };
+
+            let pos = match (a, b) {
+                (_, Some(y)) if b_first => {
+                    b = self.nodes[y].next;
+                    y
+                },
+                (Some(x), _) => {
+                    a = self.nodes[x].next;
+                    x
+                },
+                _ => return head
+            };
+
+            match last {
+                None => head = Some(pos),
+                Some(last) => self.nodes[last].next = Some(pos)
+            }
+
+            last = Some(pos);
+        }
+    }
+
+    /// Makes the chain starting at `head` the list, restoring `prev` links
+    /// and the tail.
+    fn relink(&mut self, head: Option<Ix>) {
+        let mut prev = None;
+        let mut curr = head;
+
+        while let Some(pos) = curr {
+            self.nodes[pos].prev = prev;
+            prev = Some(pos);
+            curr = self.nodes[pos].next;
+        }
+
+        self.head = head;
+        self.tail = prev;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{Rng, SplitMix64};
+
+    #[test]
+    fn empty_list_behaviour() {
+        let mut list: LinkedList<i32> = LinkedList::new();
+
+        assert!(list.is_empty());
+        assert_eq!(0, list.size());
+        assert_eq!(None, list.remove_first());
+        assert_eq!(None, list.remove_last());
+    }
+
+    #[test]
+    fn add_and_remove_first_last() {
+        let mut list = LinkedList::new();
+
+        list.add_last(1);
+        list.add_last(2);
+        list.add_first(0);
+
+        let mut it = list.iter();
+
+        assert_eq!(it.next(), Some(&0));
+        assert_eq!(it.next(), Some(&1));
+        assert_eq!(it.next(), Some(&2));
+        assert_eq!(it.next(), None);
+
+        assert_eq!(Some(0), list.remove_first());
+        assert_eq!(Some(2), list.remove_last());
+        assert_eq!(Some(1), list.remove_first());
+        assert_eq!(None, list.remove_first());
+    }
+
+    #[test]
+    fn free_slot_reuse() {
+        let mut list = LinkedList::new();
+
+        list.add_last(10);
+        list.add_last(20);
+        list.add_last(30);
+
+        assert_eq!(Some(10), list.remove_first());
+        assert_eq!(Some(20), list.remove_first());
+
+        list.add_last(40);
+        list.add_first(0);
+
+        let vals: Vec<_> = list.iter().copied().collect();
+
+        assert_eq!(vals, vec![0, 30, 40]);
+    }
+
+    #[test]
+    fn preallocated_lists_report_their_slots() {
+        let mut list = LinkedList::with_capacity(4);
+
+        list.add_last(1);
+        list.add_last(2);
+        list.remove_first();
+        list.reserve(8);
+
+        let usage = list.memory_usage();
+
+        assert_eq!((1, 1), (usage.used, usage.free));
+        assert!(usage.used + usage.free + usage.spare >= 9);
+    }
+
+    #[test]
+    fn drain_keeps_slots() {
+        let mut list = LinkedList::with_capacity(3);
+
+        for val in [1, 2, 3] {
+            list.add_last(val);
+        }
+
+        assert_eq!(vec![1, 2], list.drain().take(2).collect::<Vec<_>>());
+        assert!(list.is_empty());
+        assert_eq!((0, 3), (list.memory_usage().used, list.memory_usage().free));
+
+        list.add_first(4);
+        assert_eq!(vec![4], list.drain().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn narrow_index_types_shrink_slots() {
+        let mut list: LinkedList<u16, u16> = LinkedList::default();
+
+        for val in [3, 1, 2] {
+            list.add_last(val);
+        }
+
+        list.sort();
+        assert_eq!(vec![1, 2, 3], list.iter().copied().collect::<Vec<_>>());
+        assert_eq!(vec![3], list.split_off(2).iter().copied().collect::<Vec<_>>());
+
+        // bytes per stored slot, generation included
+        fn slot_bytes<Ix: IndexType>() -> usize {
+            let mut list: LinkedList<u8, Ix> = LinkedList::default();
+            (0..100).for_each(|val| list.add_last(val));
+
+            let usage = list.memory_usage();
+            usage.bytes / (usage.used + usage.free + usage.spare)
+        }
+
+        let (narrow, default, wide) = (slot_bytes::<u16>(), slot_bytes::<u32>(), slot_bytes::<usize>());
+        assert!(narrow < default && default < wide, "{narrow} {default} {wide}");
+
+        // the value, two four-byte links and the generation
+        assert!(narrow <= 16);
+    }
+
+    #[test]
+    fn sorts_stably_by_relinking() {
+        let mut rng = SplitMix64::new(382);
+
+        for len in 0..40 {
+            let mut list = LinkedList::new();
+            let vals: Vec<_> = (0..len).map(|i| (rng.below(5), i)).collect();
+            vals.iter().for_each(|&v| list.add_last(v));
+
+            list.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let mut expected = vals.clone();
+            expected.sort_by_key(|v| v.0);
+
+            assert_eq!(expected, list.iter().copied().collect::<Vec<_>>());
+            assert_eq!(expected.last(), list.get_last());
+
+            list.add_first((9, 0));
+            assert_eq!(Some((9, 0)), list.remove_first());
+        }
+    }
+
+    #[test]
+    fn merges_and_dedups() {
+        let mut a = LinkedList::new();
+        let mut b = LinkedList::new();
+        [1, 3, 3, 7].into_iter().for_each(|v| a.add_last(v));
+        [2, 3, 8].into_iter().for_each(|v| b.add_last(v));
+
+        a.merge(b);
+        assert_eq!(vec![1, 2, 3, 3, 3, 7, 8], a.iter().copied().collect::<Vec<_>>());
+
+        a.dedup();
+        assert_eq!(vec![1, 2, 3, 7, 8], a.iter().copied().collect::<Vec<_>>());
+        assert_eq!(5, a.size());
+        assert_eq!(Some(8), a.remove_last());
+
+        a.dedup_by(|later, kept| later - kept < 3);
+        assert_eq!(vec![1, 7], a.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&7), a.get_last());
+    }
+
+    #[test]
+    fn splits_and_rotates() {
+        let mut list = LinkedList::new();
+        (0..6).for_each(|v| list.add_last(v));
+
+        let tail = list.split_off(4);
+        assert_eq!(vec![4, 5], tail.iter().copied().collect::<Vec<_>>());
+        assert_eq!((4, Some(&3)), (list.size(), list.get_last()));
+        assert!(list.split_off(4).is_empty());
+
+        list.rotate_left(5);
+        assert_eq!(vec![1, 2, 3, 0], list.iter().copied().collect::<Vec<_>>());
+        list.rotate_right(3);
+        assert_eq!(vec![2, 3, 0, 1], list.iter().copied().collect::<Vec<_>>());
+        assert_eq!((Some(&2), Some(&1)), (list.get_first(), list.get_last()));
+
+        let all = list.split_off(0);
+        assert!(list.is_empty() && list.get_last().is_none());
+        assert_eq!(4, all.size());
+    }
+
+    #[test]
+    fn rotation_serves_round_robin() {
+        let mut queue = LinkedList::new();
+        ["a", "b", "c"].into_iter().for_each(|task| queue.add_last(task));
+
+        let mut served = Vec::new();
+
+        for _ in 0..5 {
+            served.push(*queue.get_first().unwrap());
+            queue.rotate_left(1);
+        }
+
+        assert_eq!(vec!["a", "b", "c", "a", "b"], served);
+        assert_eq!(Some("c"), queue.remove_first());
+        assert_eq!(Some(&"b"), queue.get_last());
+    }
+}
@@ -0,0 +1,97 @@
+/// Declares a small [`DiGraph`](crate::DiGraph) inline, as in
+/// `digraph! { a -> b: 3, b -> c: 1, a -> c: 7, d }`. Nodes are weighted
+/// with their names and added in order of first mention; edges without a
+/// weight get `()`. Evaluates to the graph and a `HashMap` from node names
+/// to ids.
+#[macro_export]
+macro_rules! digraph {
+    ($($body:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut graph: $crate::DiGraph<&'static str, _> = $crate::Graph::new();
+        #[allow(unused_mut)]
+        let mut nodes = ::std::collections::HashMap::<&'static str, $crate::NodeId>::new();
+        $crate::__graph_entries!(@directed graph, nodes; $($body)*);
+        (graph, nodes)
+    }};
+}
+
+/// Same as [`digraph!`] for an [`UnGraph`](crate::UnGraph), with edges
+/// written `a -- b: 3`.
+#[macro_export]
+macro_rules! graph {
+    ($($body:tt)*) => {{
+        #[allow(unused_mut)]
+        let mut graph: $crate::UnGraph<&'static str, _> = $crate::Graph::new();
+        #[allow(unused_mut)]
+        let mut nodes = ::std::collections::HashMap::<&'static str, $crate::NodeId>::new();
+        $crate::__graph_entries!(@undirected graph, nodes; $($body)*);
+        (graph, nodes)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __graph_entries {
+    (@$kind:ident $graph:ident, $nodes:ident; ) => {};
+    (@directed $graph:ident, $nodes:ident; $a:ident -> $b:ident : $w:expr $(, $($rest:tt)*)?) => {
+        $crate::__graph_edge!($graph, $nodes; $a, $b, $w);
+        $crate::__graph_entries!(@directed $graph, $nodes; $($($rest)*)?);
+    };
+    (@directed $graph:ident, $nodes:ident; $a:ident -> $b:ident $(, $($rest:tt)*)?) => {
+        $crate::__graph_edge!($graph, $nodes; $a, $b, ());
+        $crate::__graph_entries!(@directed $graph, $nodes; $($($rest)*)?);
+    };
+    (@undirected $graph:ident, $nodes:ident; $a:ident -- $b:ident : $w:expr $(, $($rest:tt)*)?) => {
+        $crate::__graph_edge!($graph, $nodes; $a, $b, $w);
+        $crate::__graph_entries!(@undirected $graph, $nodes; $($($rest)*)?);
+    };
+    (@undirected $graph:ident, $nodes:ident; $a:ident -- $b:ident $(, $($rest:tt)*)?) => {
+        $crate::__graph_edge!($graph, $nodes; $a, $b, ());
+        $crate::__graph_entries!(@undirected $graph, $nodes; $($($rest)*)?);
+    };
+    (@$kind:ident $graph:ident, $nodes:ident; $a:ident $(, $($rest:tt)*)?) => {
+        let _ = $crate::__graph_node!($graph, $nodes; $a);
+        $crate::__graph_entries!(@$kind $graph, $nodes; $($($rest)*)?);
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __graph_edge {
+    ($graph:ident, $nodes:ident; $a:ident, $b:ident, $w:expr) => {{
+        let a = $crate::__graph_node!($graph, $nodes; $a);
+        let b = $crate::__graph_node!($graph, $nodes; $b);
+        $graph.add_edge(a, b, $w);
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __graph_node {
+    ($graph:ident, $nodes:ident; $name:ident) => {
+        *$nodes.entry(stringify!($name)).or_insert_with(|| $graph.add_node(stringify!($name)))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algo::dijkstra;
+
+    #[test]
+    fn declares_graphs_inline() {
+        let (graph, n) = digraph! { a -> b: 3, b -> c: 1, a -> c: 7, d };
+
+        assert_eq!((4, 3), (graph.node_count(), graph.edge_count()));
+        assert_eq!("c", graph[n["c"]]);
+        assert_eq!(Some(4), dijkstra(&graph, n["a"], |&w| w).distance(n["c"]));
+        assert!(!graph.has_edge(n["c"], n["a"]));
+
+        let (ring, m) = graph! { x -- y, y -- z, z -- x, };
+        assert_eq!(3, ring.edge_count());
+        assert!(ring.has_edge(m["x"], m["z"]) && ring.has_edge(m["z"], m["x"]));
+
+        let (empty, none) = digraph! {};
+        let _: &crate::DiGraph<&str, ()> = &empty;
+        assert!(none.is_empty());
+    }
+}
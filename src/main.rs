@@ -1,254 +1,370 @@
-use std::mem;
-
-enum Slot<T> {
-    Free(Option<usize>),
-    Used(T)
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use graphs_rs::algo::{connected_components, dijkstra};
+use graphs_rs::io::{
+    from_dot, from_json_node_link, read_edge_list, to_dot, to_json_node_link, write_edge_list, JsonValue,
+    NodeLinkKeys
+};
+use graphs_rs::layout::circular_layout;
+use graphs_rs::{Directed, Direction, EdgeType, Graph, NodeId, Undirected};
+
+const USAGE: &str = "\
+usage: graphs <command> [options]
+
+commands:
+  convert <input> <output>            convert between edge list, DOT and JSON node-link
+  stats <input>                       print degree, component and diameter statistics
+  shortest-path <input> <from> <to>   print a minimum weight path between two nodes
+  render <input> <output>             draw the graph as SVG on a circular layout
+
+options:
+  --from <format>   input format: edges, dot or json (default: by file extension)
+  --to <format>     output format for convert (default: by file extension)
+  --undirected      read edge lists as undirected graphs
+
+Use - to read from stdin or write to stdout. Edge lists hold one
+`source target [weight]` line per edge.";
+
+/// Nodes are weighted with their names, edges with their (default 1) weights.
+type CliGraph<Ty> = Graph<String, f64, Ty>;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Format {
+    Edges,
+    Dot,
+    Json
 }
 
-impl<T> Slot<T> {
-    fn as_free(&self) -> &Option<usize> {
-        match self {
-            Slot::Free(pos) => pos,
-            _ => panic!("expected free slot")
+impl Format {
+    fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "edges" | "edgelist" | "txt" | "el" => Ok(Format::Edges),
+            "dot" | "gv" => Ok(Format::Dot),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format \"{name}\""))
         }
     }
 
-    fn as_free_mut(&mut self) -> &mut Option<usize> {
-        match self {
-            Slot::Free(pos) => pos,
-            _ => panic!("expected free slot")
+    fn guess(path: &str, explicit: Option<Format>) -> Result<Format, String> {
+        if let Some(format) = explicit {
+            return Ok(format);
         }
-    }
 
-    fn into_free(self) -> Option<usize> {
-        match self {
-            Slot::Free(pos) => pos,
-            _ => panic!("expected free slot")
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => Format::parse(ext),
+            None => Err(format!("cannot tell the format of \"{path}\", pass --from or --to"))
         }
     }
+}
 
-    fn as_used(&self) -> &T {
-        match self {
-            Slot::Used(val) => val,
-            _ => panic!("expected used slot")
-        }
-    }
+struct Options {
+    positional: Vec<String>,
+    from: Option<Format>,
+    to: Option<Format>,
+    undirected: bool
+}
 
-    fn as_used_mut(&mut self) -> &mut T {
-        match self {
-            Slot::Used(val) => val,
-            _ => panic!("expected used slot")
-        }
-    }
+fn parse_options(args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options { positional: Vec::new(), from: None, to: None, undirected: false };
+    let mut args = args.peekable();
 
-    fn into_used(self) -> T {
-        match self {
-            Slot::Used(val) => val,
-            _ => panic!("expected used slot")
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" | "--to" => {
+                let value = args.next().ok_or_else(|| format!("{arg} needs a value"))?;
+                let format = Some(Format::parse(&value)?);
+
+                if arg == "--from" {
+                    options.from = format;
+                } else {
+                    options.to = format;
+                }
+            },
+            "--undirected" => options.undirected = true,
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ => options.positional.push(arg)
         }
     }
+
+    Ok(options)
 }
 
-struct LinkedListNode<T> {
-    prev: Option<usize>,
-    next: Option<usize>,
-    val: T
+fn read_input(path: &str) -> Result<String, String> {
+    let mut text = String::new();
+
+    let result = if path == "-" {
+        io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        File::open(path).and_then(|file| BufReader::new(file).read_to_string(&mut text).map(|_| ()))
+    };
+
+    result.map(|()| text).map_err(|err| format!("{path}: {err}"))
 }
 
-struct LinkedList<T> {
-    size: usize,
-    head: Option<usize>,
-    tail: Option<usize>,
-    free: Option<usize>,
-    slots: Vec<Slot<LinkedListNode<T>>>
+fn write_output(path: &str, contents: &[u8]) -> Result<(), String> {
+    let result = if path == "-" { io::stdout().write_all(contents) } else { fs::write(path, contents) };
+
+    result.map_err(|err| format!("{path}: {err}"))
 }
 
-struct LinkedListIterator<'a, T> {
-    list: &'a LinkedList<T>,
-    curr: Option<usize>
+fn attribute<'a>(attributes: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attributes.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
 }
 
-impl<'a, T> LinkedListIterator<'a, T> {
-    fn new(list: &'a LinkedList<T>) -> LinkedListIterator<'a, T> {
-        LinkedListIterator { list, curr: list.head }
+fn json_name(node: &JsonValue) -> String {
+    match node.get("name").or_else(|| node.get("label")).or_else(|| node.get("id")) {
+        Some(JsonValue::String(name)) => name.clone(),
+        Some(other) => other.to_string(),
+        None => String::new()
     }
 }
 
-impl<'a, T> Iterator for LinkedListIterator<'a, T> {
-    type Item = &'a T;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        let Some(pos) = self.curr else {
-            return None;
-        };
-
-        let node = self.list.slots[pos].as_used();
-
-        self.curr = node.next;
+/// Whether a DOT or JSON document declares itself directed; edge lists
+/// carry no such flag.
+fn declares_directed(format: Format, text: &str) -> Option<bool> {
+    match format {
+        Format::Edges => None,
+        Format::Dot => {
+            let header = text.split('{').next()?.to_ascii_lowercase();
+            Some(header.split_whitespace().any(|word| word == "digraph"))
+        },
+        Format::Json => JsonValue::parse(text).ok()?.get("directed")?.as_bool()
+    }
+}
 
-        Some(&node.val)
+/// Directedness to read the input with: as declared by the document, else
+/// as requested on the command line, directed by default.
+fn is_directed(format: Format, text: &str, undirected: bool) -> Result<bool, String> {
+    match declares_directed(format, text) {
+        Some(true) if undirected => Err("--undirected given for a directed document".to_string()),
+        Some(directed) => Ok(directed),
+        None => Ok(!undirected)
     }
 }
 
-impl<'a, T> IntoIterator for &'a LinkedList<T> {
-    type Item = &'a T;
-    type IntoIter = LinkedListIterator<'a, T>;
+fn parse_graph<Ty: EdgeType>(format: Format, text: &str) -> Result<CliGraph<Ty>, String> {
+    match format {
+        Format::Edges => read_edge_list(text.as_bytes()).map_err(|err| err.to_string()),
+        Format::Dot => from_dot(
+            text,
+            |id, attributes| attribute(attributes, "label").unwrap_or(id).to_string(),
+            |attributes| attribute(attributes, "weight").and_then(|w| w.parse().ok()).unwrap_or(1.0)
+        )
+        .map_err(|err| err.to_string()),
+        Format::Json => from_json_node_link(text, &NodeLinkKeys::default(), json_name, |link| {
+            link.get("weight").and_then(JsonValue::as_f64).unwrap_or(1.0)
+        })
+        .map_err(|err| err.to_string())
+    }
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
+fn render_graph<Ty: EdgeType>(graph: &CliGraph<Ty>, format: Format) -> Vec<u8> {
+    match format {
+        Format::Edges => {
+            let mut out = Vec::new();
+            write_edge_list(graph, &mut out).unwrap();
+            out
+        },
+        Format::Dot => to_dot(
+            graph,
+            |name| vec![("label".to_string(), name.clone())],
+            |weight| vec![("weight".to_string(), weight.to_string())]
+        )
+        .into_bytes(),
+        Format::Json => to_json_node_link(
+            graph,
+            &NodeLinkKeys::default(),
+            |name| vec![("id".to_string(), JsonValue::from(name.as_str()))],
+            |&weight| vec![("weight".to_string(), JsonValue::from(weight))]
+        )
+        .into_bytes()
     }
 }
 
-impl<T> LinkedList<T> {
-    pub fn new() -> LinkedList<T> {
-        LinkedList {
-            size: 0,
-            head: None,
-            tail: None,
-            free: None,
-            slots: Vec::new()
+fn find_node<Ty: EdgeType>(graph: &CliGraph<Ty>, name: &str) -> Result<NodeId, String> {
+    graph
+        .node_ids()
+        .find(|&node| graph.node_weight(node).unwrap() == name)
+        .ok_or_else(|| format!("no node named \"{name}\""))
+}
+
+/// Longest shortest path in hops, over all pairs connected by a path.
+fn hop_diameter<Ty: EdgeType>(graph: &CliGraph<Ty>) -> usize {
+    let mut diameter = 0;
+    let mut dist = vec![usize::MAX; graph.node_bound()];
+    let mut queue = VecDeque::new();
+
+    for start in graph.node_ids() {
+        dist.iter_mut().for_each(|d| *d = usize::MAX);
+        dist[start.index()] = 0;
+        queue.push_back(start);
+
+        while let Some(node) = queue.pop_front() {
+            diameter = diameter.max(dist[node.index()]);
+
+            for next in graph.neighbors(node) {
+                if dist[next.index()] == usize::MAX {
+                    dist[next.index()] = dist[node.index()] + 1;
+                    queue.push_back(next);
+                }
+            }
         }
     }
 
-    pub fn iter(&self) -> LinkedListIterator<'_, T> {
-        LinkedListIterator::new(self)
-    }
+    diameter
+}
 
-    pub fn size(&self) -> usize {
-        return self.size
-    }
+fn stats<Ty: EdgeType>(graph: &CliGraph<Ty>) -> String {
+    let mut out = String::new();
+    let degrees: Vec<_> = graph.node_ids().map(|node| graph.degree(node)).collect();
+    let components = connected_components(graph);
 
-    pub fn is_empty(&self) -> bool {
-        return self.head.is_none();
-    }
+    writeln!(out, "nodes: {}", graph.node_count()).unwrap();
+    writeln!(out, "edges: {}", graph.edge_count()).unwrap();
+    writeln!(out, "directed: {}", graph.is_directed()).unwrap();
+
+    if let (Some(min), Some(max)) = (degrees.iter().min(), degrees.iter().max()) {
+        let mean = degrees.iter().sum::<usize>() as f64 / degrees.len() as f64;
+        let kind = if graph.is_directed() { "out-degree" } else { "degree" };
 
-    pub fn get_first(&self) -> Option<&T> {
-        self.head.map(|pos| &self.slots[pos].as_used().val)
+        writeln!(out, "{kind}: min {min}, max {max}, mean {mean:.2}").unwrap();
     }
 
-    pub fn get_last(&self) -> Option<&T> {
-        self.tail.map(|pos| &self.slots[pos].as_used().val)
+    if graph.is_directed() {
+        let sinks = graph
+            .node_ids()
+            .filter(|&node| graph.neighbors_directed(node, Direction::Outgoing).next().is_none())
+            .count();
+        let sources = graph
+            .node_ids()
+            .filter(|&node| graph.neighbors_directed(node, Direction::Incoming).next().is_none())
+            .count();
+
+        writeln!(out, "sources: {sources}, sinks: {sinks}").unwrap();
     }
 
-    pub fn add_first(&mut self, val: T) {
-        let node = LinkedListNode {
-            prev: None,
-            next: self.head,
-            val
-        };
+    let largest = components.iter().map(Vec::len).max().unwrap_or(0);
 
-        let new_head = self.insert(node);
+    writeln!(out, "components: {} (largest has {largest} nodes)", components.len()).unwrap();
+    writeln!(out, "diameter: {}", hop_diameter(graph)).unwrap();
 
-        match self.head {
-            None => {
-                self.head = Some(new_head);
-                self.tail = Some(new_head);
-            },
-            Some(old_head) => {
-                self.slots[old_head].as_used_mut().prev = Some(new_head);
-                self.head = Some(new_head);
-            }
-        }
-    }
+    out
+}
 
-    pub fn add_last(&mut self, val: T) {
-        let node = LinkedListNode {
-            prev: self.tail,
-            next: None,
-            val
-        };
+fn shortest_path<Ty: EdgeType>(graph: &CliGraph<Ty>, from: &str, to: &str) -> Result<String, String> {
+    let (source, target) = (find_node(graph, from)?, find_node(graph, to)?);
 
-        let new_tail = self.insert(node);
+    let mut weights = graph.edge_ids().map(|edge| *graph.edge_weight(edge).unwrap());
 
-        match self.tail {
-            None => {
-                self.head = Some(new_tail);
-                self.tail = Some(new_tail);
-            },
-            Some(old_tail) => {
-                self.slots[old_tail].as_used_mut().next = Some(new_tail);
-                self.tail = Some(new_tail);
-            }
-        }
+    if weights.any(|weight| weight < 0.0 || weight.is_nan()) {
+        return Err("shortest-path needs non-negative edge weights".to_string());
     }
 
-    pub fn remove_first(&mut self) -> Option<T> {
-        self.head.map(|pos| {
-            self.head = self.slots[pos].as_used().next;
+    let paths = dijkstra(graph, source, |&weight| weight);
 
-            match self.head {
-                None => {
-                    self.tail = None;
-                }
-                Some(new_head) => {
-                    self.slots[new_head].as_used_mut().prev = None;
-                }
-            }
+    let Some(path) = paths.path_to(target) else {
+        return Err(format!("\"{to}\" is not reachable from \"{from}\""));
+    };
 
-            let slot = mem::replace(
-                &mut self.slots[pos],
-                Slot::Free(self.free)
-            );
+    let names: Vec<_> = path.iter().map(|&node| graph.node_weight(node).unwrap().as_str()).collect();
 
-            self.free = Some(pos);
-            self.size -= 1;
+    Ok(format!("{}\ndistance: {}\n", names.join(" -> "), paths.distance(target).unwrap()))
+}
 
-            slot.into_used().val
-        })
-    }
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
 
-    pub fn remove_last(&mut self) -> Option<T> {
-        self.tail.map(|pos| {
-            self.tail = self.slots[pos].as_used().prev;
+/// SVG drawing with the nodes evenly spaced on a circle.
+fn svg<Ty: EdgeType>(graph: &CliGraph<Ty>) -> String {
+    let count = graph.node_count().max(1) as f64;
+    let radius = (40.0 * count / std::f64::consts::PI).max(120.0);
+    let size = 2.0 * radius + 120.0;
+    let mut position = circular_layout(graph);
 
-            match self.tail {
-                None => {
-                    self.head = None;
-                }
-                Some(new_tail) => {
-                    self.slots[new_tail].as_used_mut().next = None;
-                }
-            }
+    for (_, (x, y)) in position.iter_mut() {
+        (*x, *y) = (size / 2.0 + radius * *x, size / 2.0 + radius * *y);
+    }
 
-            let slot = mem::replace(
-                &mut self.slots[pos],
-                Slot::Free(self.free)
-            );
+    let mut out = String::new();
+    let marker = if graph.is_directed() { " marker-end=\"url(#arrow)\"" } else { "" };
 
-            self.free = Some(pos);
-            self.size -= 1;
+    writeln!(out, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size:.0}" height="{size:.0}">"#).unwrap();
+    out.push_str(r#"  <defs><marker id="arrow" viewBox="0 0 10 10" refX="23" refY="5" markerWidth="6" "#);
+    out.push_str("markerHeight=\"6\" orient=\"auto\"><path d=\"M0,0 L10,5 L0,10 z\"/></marker></defs>\n");
 
-            slot.into_used().val
-        })
+    for edge in graph.edge_ids() {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+        let ((x1, y1), (x2, y2)) = (position[source], position[target]);
+
+        writeln!(
+            out,
+            "  <line x1=\"{x1:.1}\" y1=\"{y1:.1}\" x2=\"{x2:.1}\" y2=\"{y2:.1}\" stroke=\"#888\"{marker}/>"
+        )
+        .unwrap();
     }
 
-    fn insert(&mut self, node: LinkedListNode<T>) -> usize {
-        let slot = Slot::Used(node);
+    for node in graph.node_ids() {
+        let (x, y) = position[node];
+        let name = escape_xml(graph.node_weight(node).unwrap());
 
-        self.size += 1;
+        writeln!(out, r##"  <circle cx="{x:.1}" cy="{y:.1}" r="14" fill="#fff" stroke="#333"/>"##).unwrap();
+        writeln!(out, r#"  <text x="{x:.1}" y="{y:.1}" text-anchor="middle" dy=".35em">{name}</text>"#).unwrap();
+    }
 
-        match self.free {
-            None => {
-                self.slots.push(slot);
-                self.slots.len() - 1
-            },
-            Some(curr) => {
-                self.free = *self.slots[curr].as_free();
-                self.slots[curr] = slot;
-                curr
-            }
-        }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn run_with<Ty: EdgeType>(
+    command: &str,
+    options: &Options,
+    format: Format,
+    text: &str
+) -> Result<(), String> {
+    let graph: CliGraph<Ty> = parse_graph(format, text)?;
+    let args = &options.positional;
+
+    match (command, args.len()) {
+        ("convert", 3) => write_output(&args[2], &render_graph(&graph, Format::guess(&args[2], options.to)?)),
+        ("stats", 2) => write_output("-", stats(&graph).as_bytes()),
+        ("shortest-path", 4) => write_output("-", shortest_path(&graph, &args[2], &args[3])?.as_bytes()),
+        ("render", 3) => write_output(&args[2], svg(&graph).as_bytes()),
+        _ => Err(USAGE.to_string())
     }
 }
 
-fn main() {
-    let mut list: LinkedList<&str> = LinkedList::new();
+fn run(options: Options) -> Result<(), String> {
+    let (Some(command), Some(input)) = (options.positional.first(), options.positional.get(1)) else {
+        return Err(USAGE.to_string());
+    };
+
+    if !["convert", "stats", "shortest-path", "render"].contains(&command.as_str()) {
+        return Err(format!("unknown command \"{command}\"\n\n{USAGE}"));
+    }
+
+    let format = Format::guess(input, options.from)?;
+    let text = read_input(input)?;
 
-    list.add_last("Hello");
-    list.add_last("World");
+    if is_directed(format, &text, options.undirected)? {
+        run_with::<Directed>(command, &options, format, &text)
+    } else {
+        run_with::<Undirected>(command, &options, format, &text)
+    }
+}
 
-    for item in &list {
-        println!("{item}");
+fn main() -> ExitCode {
+    match parse_options(std::env::args().skip(1)).and_then(run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("{message}");
+            ExitCode::FAILURE
+        }
     }
 }
 
@@ -256,53 +372,94 @@ fn main() {
 mod tests {
     use super::*;
 
-    #[test]
-    fn empty_list_behaviour() {
-        let mut list: LinkedList<i32> = LinkedList::new();
+    const TRIANGLE: &str = "a b 1\nb c 2.5\na c 4\n";
 
-        assert!(list.is_empty());
-        assert_eq!(0, list.size());
-        assert_eq!(None, list.remove_first());
-        assert_eq!(None, list.remove_last());
+    fn triangle<Ty: EdgeType>() -> CliGraph<Ty> {
+        parse_graph(Format::Edges, TRIANGLE).unwrap()
+    }
+
+    fn options(args: &[&str]) -> Result<Options, String> {
+        parse_options(args.iter().map(|arg| arg.to_string()))
     }
 
     #[test]
-    fn add_and_remove_first_last() {
-        let mut list = LinkedList::new();
+    fn options_and_formats() {
+        let parsed = options(&["convert", "in.txt", "out", "--to", "dot", "--undirected"]).unwrap();
+
+        assert_eq!(vec!["convert", "in.txt", "out"], parsed.positional);
+        assert!(parsed.to == Some(Format::Dot) && parsed.from.is_none() && parsed.undirected);
+        assert!(options(&["--from"]).is_err());
+        assert!(options(&["--from", "xml"]).is_err());
+        assert!(options(&["--verbose"]).is_err());
+
+        assert!(Format::guess("graph.gv", None) == Ok(Format::Dot));
+        assert!(Format::guess("graph.json", None) == Ok(Format::Json));
+        assert!(Format::guess("-", Some(Format::Edges)) == Ok(Format::Edges));
+        assert!(Format::guess("-", None).is_err());
+        assert!(Format::guess("graph.xml", None).is_err());
+    }
 
-        list.add_last(1);
-        list.add_last(2);
-        list.add_first(0);
+    #[test]
+    fn documents_decide_directedness() {
+        assert_eq!(Ok(true), is_directed(Format::Dot, "digraph { a -> b }", false));
+        assert_eq!(Ok(false), is_directed(Format::Dot, "strict graph { a -- b }", false));
+        assert!(is_directed(Format::Dot, "digraph { a -> b }", true).is_err());
+        assert_eq!(Ok(false), is_directed(Format::Json, r#"{"directed": false}"#, true));
+        assert_eq!(Ok(true), is_directed(Format::Edges, TRIANGLE, false));
+        assert_eq!(Ok(false), is_directed(Format::Edges, TRIANGLE, true));
+    }
 
-        let mut it = list.iter();
+    #[test]
+    fn conversions_round_trip() {
+        let graph: CliGraph<Directed> = triangle();
 
-        assert_eq!(it.next(), Some(&0));
-        assert_eq!(it.next(), Some(&1));
-        assert_eq!(it.next(), Some(&2));
-        assert_eq!(it.next(), None);
+        for format in [Format::Edges, Format::Dot, Format::Json] {
+            let text = String::from_utf8(render_graph(&graph, format)).unwrap();
+            let back: CliGraph<Directed> = parse_graph(format, &text).unwrap();
 
-        assert_eq!(Some(0), list.remove_first());
-        assert_eq!(Some(2), list.remove_last());
-        assert_eq!(Some(1), list.remove_first());
-        assert_eq!(None, list.remove_first());
+            assert_eq!(Ok(true), is_directed(format, &text, false));
+            assert_eq!(3, back.node_count());
+            assert_eq!(TRIANGLE, String::from_utf8(render_graph(&back, Format::Edges)).unwrap());
+        }
     }
 
     #[test]
-    fn free_slot_reuse() {
-        let mut list = LinkedList::new();
+    fn stats_and_shortest_paths() {
+        let directed: CliGraph<Directed> = triangle();
+        let report = stats(&directed);
+
+        assert!(report.contains("nodes: 3\nedges: 3\ndirected: true\n"));
+        assert!(report.contains("out-degree: min 0, max 2, mean 1.00"));
+        assert!(report.contains("sources: 1, sinks: 1"));
+        assert!(report.contains("components: 1 (largest has 3 nodes)"));
+        assert!(report.contains("diameter: 1"));
 
-        list.add_last(10);
-        list.add_last(20);
-        list.add_last(30);
+        assert_eq!(Ok("a -> b -> c\ndistance: 3.5\n".to_string()), shortest_path(&directed, "a", "c"));
+        assert!(shortest_path(&directed, "c", "a").is_err());
+        assert!(shortest_path(&directed, "a", "z").is_err());
 
-        assert_eq!(Some(10), list.remove_first());
-        assert_eq!(Some(20), list.remove_first());
+        let undirected: CliGraph<Undirected> = triangle();
 
-        list.add_last(40);
-        list.add_first(0);
+        assert!(stats(&undirected).contains("degree: min 2, max 2, mean 2.00"));
+        assert!(stats(&undirected).contains("diameter: 1"));
+        assert_eq!(Ok("c -> b -> a\ndistance: 3.5\n".to_string()), shortest_path(&undirected, "c", "a"));
 
-        let vals: Vec<_> = list.iter().copied().collect();
+        let negative: CliGraph<Directed> = parse_graph(Format::Edges, "a b -1\n").unwrap();
+        assert!(shortest_path(&negative, "a", "b").is_err());
+    }
 
-        assert_eq!(vals, vec![0, 30, 40]);
+    #[test]
+    fn svg_draws_every_node_and_edge() {
+        let graph: CliGraph<Directed> = parse_graph(Format::Edges, "a <b> 1\n<b> c&d 1\n").unwrap();
+        let drawing = svg(&graph);
+
+        assert!(drawing.starts_with("<svg") && drawing.ends_with("</svg>\n"));
+        assert_eq!(2, drawing.matches("<line").count());
+        assert_eq!(3, drawing.matches("<circle").count());
+        assert_eq!(2, drawing.matches("marker-end").count());
+        assert!(drawing.contains(">&lt;b&gt;</text>") && drawing.contains(">c&amp;d</text>"));
+
+        let undirected: CliGraph<Undirected> = Graph::new();
+        assert!(!svg(&undirected).contains("marker-end="));
     }
 }
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::bitset::FixedBitSet;
+use crate::graph::{Directed, EdgeId, EdgeType, Graph, NodeId, NodeIds};
+use crate::visit::{GraphBase, IntoNeighbors, Visitable};
+
+struct LayeredEdge<E, R> {
+    weight: E,
+    relation: R
+}
+
+/// Graph whose edges each belong to a relation, such as the kinds of link
+/// of a knowledge graph or the layers of a multiplex network. Every
+/// relation keeps its own adjacency lists, so the neighbors over one
+/// relation are found without looking at the edges of the others. Views
+/// restricted to some relations are taken with [`layers`].
+///
+/// [`layers`]: MultiplexGraph::layers
+pub struct MultiplexGraph<N, E, R, Ty = Directed> {
+    graph: Graph<N, LayeredEdge<E, R>, Ty>,
+    /// Edges leaving every node, by relation and node index; edges of
+    /// undirected graphs are listed at both ends.
+    adjacency: HashMap<R, Vec<Vec<EdgeId>>>
+}
+
+impl<N, E, R: Copy + Eq + Hash, Ty: EdgeType> Default for MultiplexGraph<N, E, R, Ty> {
+    fn default() -> Self {
+        MultiplexGraph::new()
+    }
+}
+
+impl<N, E, R: Copy + Eq + Hash, Ty: EdgeType> MultiplexGraph<N, E, R, Ty> {
+    pub fn new() -> MultiplexGraph<N, E, R, Ty> {
+        MultiplexGraph { graph: Graph::new(), adjacency: HashMap::new() }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        self.graph.add_node(weight)
+    }
+
+    /// Adds an edge of `relation`. Panics if either endpoint is not in the
+    /// graph.
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, relation: R, weight: E) -> EdgeId {
+        let edge = self.graph.add_edge(source, target, LayeredEdge { weight, relation });
+        let lists = self.adjacency.entry(relation).or_default();
+        let ends = if Ty::is_directed() || source == target { &[source][..] } else { &[source, target] };
+
+        for end in ends {
+            if lists.len() <= end.index() {
+                lists.resize_with(end.index() + 1, Vec::new);
+            }
+
+            lists[end.index()].push(edge);
+        }
+
+        edge
+    }
+
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<(R, E)> {
+        let (source, target) = self.graph.edge_endpoints(edge)?;
+        let removed = self.graph.remove_edge(edge).unwrap();
+        let lists = self.adjacency.get_mut(&removed.relation).unwrap();
+
+        for end in [source, target] {
+            if let Some(list) = lists.get_mut(end.index()) {
+                list.retain(|&e| e != edge);
+            }
+        }
+
+        Some((removed.relation, removed.weight))
+    }
+
+    pub fn remove_node(&mut self, node: NodeId) -> Option<N> {
+        self.graph.node_weight(node)?;
+
+        let incident: Vec<_> = self
+            .graph
+            .edge_ids()
+            .filter(|&edge| {
+                let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+                a == node || b == node
+            })
+            .collect();
+
+        for edge in incident {
+            self.remove_edge(edge);
+        }
+
+        self.graph.remove_node(node).map(|removed| removed.weight)
+    }
+
+    pub fn node_weight(&self, node: NodeId) -> Option<&N> {
+        self.graph.node_weight(node)
+    }
+
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<&E> {
+        self.graph.edge_weight(edge).map(|edge| &edge.weight)
+    }
+
+    pub fn edge_relation(&self, edge: EdgeId) -> Option<R> {
+        self.graph.edge_weight(edge).map(|edge| edge.relation)
+    }
+
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.graph.edge_endpoints(edge)
+    }
+
+    pub fn node_ids(&self) -> NodeIds<'_, N> {
+        self.graph.node_ids()
+    }
+
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        self.graph.edge_ids()
+    }
+
+    /// Relations with at least one edge added so far, in no given order.
+    pub fn relations(&self) -> impl Iterator<Item = R> + '_ {
+        self.adjacency.keys().copied()
+    }
+
+    /// Edges of `relation` leaving `node`, all of them at `node` for
+    /// undirected graphs, with the node at their other end.
+    pub fn edges_in(&self, node: NodeId, relation: R) -> impl Iterator<Item = (EdgeId, NodeId)> + '_ {
+        let list = self.adjacency.get(&relation).and_then(|lists| lists.get(node.index()));
+
+        list.into_iter().flatten().map(move |&edge| {
+            let (a, b) = self.graph.edge_endpoints(edge).unwrap();
+            (edge, if a == node { b } else { a })
+        })
+    }
+
+    /// Successors of `node` over edges of `relation`, all neighbors over
+    /// them for undirected graphs.
+    pub fn neighbors_in(&self, node: NodeId, relation: R) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges_in(node, relation).map(|(_, next)| next)
+    }
+
+    /// View of the graph holding only the edges of `relations`.
+    pub fn layers<'a>(&'a self, relations: &'a [R]) -> LayerView<'a, N, E, R, Ty> {
+        LayerView { graph: self, relations }
+    }
+
+    /// Copy of the graph holding only the edges of `relation`, with the
+    /// same node and edge ids.
+    pub fn layer(&self, relation: R) -> Graph<N, E, Ty>
+    where
+        N: Clone,
+        E: Clone
+    {
+        let mut layer = self.graph.map(|_, weight| weight.clone(), |_, edge| edge.weight.clone());
+
+        for edge in self.graph.edge_ids() {
+            if self.graph[edge].relation != relation {
+                layer.remove_edge(edge);
+            }
+        }
+
+        layer
+    }
+}
+
+/// View of a [`MultiplexGraph`] holding only the edges of some relations.
+/// All nodes are part of every view.
+pub struct LayerView<'a, N, E, R, Ty = Directed> {
+    graph: &'a MultiplexGraph<N, E, R, Ty>,
+    relations: &'a [R]
+}
+
+impl<N, E, R, Ty> Clone for LayerView<'_, N, E, R, Ty> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N, E, R, Ty> Copy for LayerView<'_, N, E, R, Ty> {}
+
+impl<'a, N, E, R: Copy + Eq + Hash, Ty: EdgeType> LayerView<'a, N, E, R, Ty> {
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.graph.edge_relation(edge).is_some_and(|relation| self.relations.contains(&relation))
+    }
+
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + 'a {
+        let view = *self;
+        self.graph.edge_ids().filter(move |&edge| view.contains_edge(edge))
+    }
+
+    /// Neighbors over the edges in view, relation by relation.
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + 'a {
+        let graph = self.graph;
+        self.relations.iter().flat_map(move |&relation| graph.neighbors_in(node, relation))
+    }
+}
+
+impl<N, E, R, Ty> GraphBase for LayerView<'_, N, E, R, Ty> {
+    type NodeId = NodeId;
+}
+
+impl<N, E, R: Copy + Eq + Hash, Ty: EdgeType> IntoNeighbors for LayerView<'_, N, E, R, Ty> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        LayerView::neighbors(self, node)
+    }
+}
+
+impl<N, E, R: Copy + Eq + Hash, Ty: EdgeType> Visitable for LayerView<'_, N, E, R, Ty> {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.graph.graph.node_bound())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.graph.graph.node_bound());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Undirected;
+    use crate::visit::Bfs;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+    enum Rel {
+        Follows,
+        Blocks
+    }
+
+    #[test]
+    fn iterates_neighbors_per_relation() {
+        let mut graph: MultiplexGraph<&str, (), Rel> = MultiplexGraph::new();
+        let [a, b, c] = ["a", "b", "c"].map(|name| graph.add_node(name));
+
+        graph.add_edge(a, b, Rel::Follows, ());
+        graph.add_edge(b, c, Rel::Follows, ());
+        let blocks = graph.add_edge(a, c, Rel::Blocks, ());
+
+        assert_eq!(vec![b], graph.neighbors_in(a, Rel::Follows).collect::<Vec<_>>());
+        assert_eq!(vec![c], graph.neighbors_in(a, Rel::Blocks).collect::<Vec<_>>());
+        assert_eq!(Some(Rel::Blocks), graph.edge_relation(blocks));
+
+        let follows = graph.layers(&[Rel::Follows]);
+        assert_eq!(vec![a, b, c], Bfs::new(&follows, a).collect::<Vec<_>>());
+        assert_eq!(2, follows.edge_ids().count());
+        assert_eq!(1, Bfs::new(&graph.layers(&[Rel::Blocks]), b).count());
+        assert_eq!(2, graph.layer(Rel::Follows).edge_count());
+
+        assert_eq!(Some((Rel::Blocks, ())), graph.remove_edge(blocks));
+        assert_eq!(0, graph.neighbors_in(a, Rel::Blocks).count());
+    }
+
+    #[test]
+    fn undirected_layers_list_both_ends() {
+        let mut graph: MultiplexGraph<(), u32, Rel, Undirected> = MultiplexGraph::new();
+        let [a, b, c] = [(), (), ()].map(|_| graph.add_node(()));
+
+        graph.add_edge(a, b, Rel::Follows, 1);
+        graph.add_edge(b, c, Rel::Blocks, 2);
+        graph.add_edge(c, c, Rel::Blocks, 3);
+
+        assert_eq!(vec![a], graph.neighbors_in(b, Rel::Follows).collect::<Vec<_>>());
+        assert_eq!(vec![b, c], graph.neighbors_in(c, Rel::Blocks).collect::<Vec<_>>());
+
+        graph.remove_node(b);
+        assert_eq!((2, 1), (graph.node_count(), graph.edge_count()));
+        assert_eq!(vec![c], graph.neighbors_in(c, Rel::Blocks).collect::<Vec<_>>());
+    }
+}
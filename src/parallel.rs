@@ -0,0 +1,73 @@
+use std::thread;
+
+/// `f` applied to every item on scoped threads, one contiguous chunk per
+/// available core, results in input order.
+pub(crate) fn par_map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+
+    // also where threads cannot be spawned at all, as on wasm32
+    if threads == 1 || items.len() < 2 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk = items.len().div_ceil(threads);
+    let f = &f;
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = items
+            .chunks(chunk)
+            .map(|part| scope.spawn(move || part.iter().map(f).collect::<Vec<_>>()))
+            .collect();
+
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::dijkstra;
+    use crate::arena::Arena;
+    use crate::csr::Csr;
+    use crate::graph::{DiGraph, Graph, UnGraph};
+    use crate::list::LinkedList;
+
+    fn shareable<T: Send + Sync>() {}
+
+    #[test]
+    fn containers_cross_threads() {
+        shareable::<LinkedList<String>>();
+        shareable::<Arena<String>>();
+        shareable::<DiGraph<String, f64>>();
+        shareable::<UnGraph<(), ()>>();
+        shareable::<Csr<String, f64>>();
+        shareable::<crate::ImGraph<String, f64>>();
+        shareable::<crate::ConcurrentGraph<String, f64>>();
+
+        assert_eq!(vec![2, 4, 6], par_map(&[1, 2, 3], |&x| x * 2));
+        assert!(par_map(&[] as &[u8], |&x| x).is_empty());
+    }
+
+    #[test]
+    fn frozen_graph_is_searched_from_every_node() {
+        let mut graph: DiGraph<(), u32> = Graph::new();
+        let n: Vec<_> = (0..50).map(|_| graph.add_node(())).collect();
+
+        for i in 0..50 {
+            graph.add_edge(n[i], n[(i * 7 + 3) % 50], 1 + i as u32 % 4);
+            graph.add_edge(n[i], n[(i + 1) % 50], 5);
+        }
+
+        graph.freeze();
+
+        let reach = |node| dijkstra(&graph, node, |&w| w).reached().map(|(_, d)| d).sum::<u32>();
+        let parallel = graph.par_map_nodes(|node, _| reach(node));
+        let sequential: Vec<_> = graph.node_ids().map(reach).collect();
+
+        assert_eq!(sequential, parallel);
+
+        let heavy = graph.par_map_edges(|edge| (edge.source, *edge.weight > 3));
+        assert_eq!(100, heavy.len());
+        assert_eq!(graph.edge_ids().count(), heavy.iter().filter(|h| h.0.index() < 50).count());
+    }
+}
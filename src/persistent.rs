@@ -0,0 +1,474 @@
+use std::marker::PhantomData;
+use std::ops::Index;
+use std::sync::Arc;
+
+use crate::bitset::FixedBitSet;
+use crate::graph::{Directed, Direction, EdgeId, EdgeType, NodeId};
+use crate::visit::{GraphBase, IntoNeighbors, Visitable};
+
+const BITS: usize = 5;
+const MASK: usize = (1 << BITS) - 1;
+
+enum Chunk<T> {
+    Leaf(Vec<T>),
+    Branch(Vec<Arc<Chunk<T>>>)
+}
+
+// 32-way trie; updates copy the path from the root to one leaf and share
+// everything else with the old version
+struct PersistentVec<T> {
+    root: Arc<Chunk<T>>,
+    len: usize,
+    shift: usize
+}
+
+impl<T> Clone for PersistentVec<T> {
+    fn clone(&self) -> Self {
+        PersistentVec { root: Arc::clone(&self.root), len: self.len, shift: self.shift }
+    }
+}
+
+impl<T: Clone> PersistentVec<T> {
+    fn new() -> PersistentVec<T> {
+        PersistentVec { root: Arc::new(Chunk::Leaf(Vec::new())), len: 0, shift: 0 }
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let mut chunk = &*self.root;
+        let mut shift = self.shift;
+
+        loop {
+            match chunk {
+                Chunk::Leaf(items) => return items.get(index & MASK),
+                Chunk::Branch(children) => {
+                    chunk = &children[(index >> shift) & MASK];
+                    shift -= BITS;
+                },
+            }
+        }
+    }
+
+    /// Panics if `index` is out of bounds.
+    fn set(&self, index: usize, value: T) -> PersistentVec<T> {
+        assert!(index < self.len, "index out of bounds");
+
+        fn set<T: Clone>(chunk: &Chunk<T>, shift: usize, index: usize, value: T) -> Chunk<T> {
+            match chunk {
+                Chunk::Leaf(items) => {
+                    let mut items = items.clone();
+                    items[index & MASK] = value;
+                    Chunk::Leaf(items)
+                },
+                Chunk::Branch(children) => {
+                    let mut children = children.clone();
+                    let i = (index >> shift) & MASK;
+                    children[i] = Arc::new(set(&children[i], shift - BITS, index, value));
+                    Chunk::Branch(children)
+                },
+            }
+        }
+
+        PersistentVec { root: Arc::new(set(&self.root, self.shift, index, value)), ..self.clone() }
+    }
+
+    fn push(&self, value: T) -> PersistentVec<T> {
+        fn path<T>(shift: usize, value: T) -> Chunk<T> {
+            if shift == 0 {
+                Chunk::Leaf(vec![value])
+            } else {
+                Chunk::Branch(vec![Arc::new(path(shift - BITS, value))])
+            }
+        }
+
+        fn push<T: Clone>(chunk: &Chunk<T>, shift: usize, index: usize, value: T) -> Chunk<T> {
+            match chunk {
+                Chunk::Leaf(items) => {
+                    let mut items = items.clone();
+                    items.push(value);
+                    Chunk::Leaf(items)
+                },
+                Chunk::Branch(children) => {
+                    let mut children = children.clone();
+                    let i = (index >> shift) & MASK;
+
+                    if i < children.len() {
+                        children[i] = Arc::new(push(&children[i], shift - BITS, index, value));
+                    } else {
+                        children.push(Arc::new(path(shift - BITS, value)));
+                    }
+
+                    Chunk::Branch(children)
+                },
+            }
+        }
+
+        let (root, shift) = if self.len == 1 << (self.shift + BITS) {
+            let grown = Chunk::Branch(vec![Arc::clone(&self.root), Arc::new(path(self.shift, value))]);
+            (grown, self.shift + BITS)
+        } else {
+            (push(&self.root, self.shift, self.len, value), self.shift)
+        };
+
+        PersistentVec { root: Arc::new(root), len: self.len + 1, shift }
+    }
+}
+
+struct ImNode<N> {
+    weight: Arc<N>,
+    edges: [Vec<EdgeId>; 2]
+}
+
+struct ImEdge<E> {
+    weight: E,
+    source: NodeId,
+    target: NodeId
+}
+
+type Slot<T> = Option<Arc<T>>;
+
+/// Immutable graph whose updates return a new graph and leave the old one
+/// intact, as for undo history. Versions share all but the updated paths
+/// of a 32-way trie, so an update costs `O(log n)` plus the degrees of the
+/// nodes it touches, and cloning is `O(1)`. Slots are never reused, so all
+/// ids have generation 0 and stay unique across versions. Versions can be
+/// handed to other threads.
+pub struct ImGraph<N, E, Ty = Directed> {
+    nodes: PersistentVec<Slot<ImNode<N>>>,
+    edges: PersistentVec<Slot<ImEdge<E>>>,
+    node_count: usize,
+    edge_count: usize,
+    ty: PhantomData<Ty>
+}
+
+impl<N, E, Ty> Clone for ImGraph<N, E, Ty> {
+    fn clone(&self) -> Self {
+        ImGraph {
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+            node_count: self.node_count,
+            edge_count: self.edge_count,
+            ty: PhantomData
+        }
+    }
+}
+
+impl<N, E, Ty: EdgeType> Default for ImGraph<N, E, Ty> {
+    fn default() -> Self {
+        ImGraph::new()
+    }
+}
+
+impl<N, E, Ty: EdgeType> ImGraph<N, E, Ty> {
+    pub fn new() -> ImGraph<N, E, Ty> {
+        ImGraph {
+            nodes: PersistentVec::new(),
+            edges: PersistentVec::new(),
+            node_count: 0,
+            edge_count: 0,
+            ty: PhantomData
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// One more than the largest node index ever used.
+    pub fn node_bound(&self) -> usize {
+        self.nodes.len
+    }
+
+    fn node(&self, id: NodeId) -> Option<&ImNode<N>> {
+        if id.generation() != 0 {
+            return None;
+        }
+
+        self.nodes.get(id.index())?.as_deref()
+    }
+
+    fn edge(&self, id: EdgeId) -> Option<&ImEdge<E>> {
+        if id.generation() != 0 {
+            return None;
+        }
+
+        self.edges.get(id.index())?.as_deref()
+    }
+
+    pub fn contains_node(&self, node: NodeId) -> bool {
+        self.node(node).is_some()
+    }
+
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.edge(edge).is_some()
+    }
+
+    pub fn node_weight(&self, node: NodeId) -> Option<&N> {
+        self.node(node).map(|n| &*n.weight)
+    }
+
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<&E> {
+        self.edge(edge).map(|e| &e.weight)
+    }
+
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.edge(edge).map(|e| (e.source, e.target))
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len).map(NodeId::new).filter(|&n| self.contains_node(n))
+    }
+
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        (0..self.edges.len).map(EdgeId::new).filter(|&e| self.contains_edge(e))
+    }
+
+    /// See [`Graph::neighbors_directed`](crate::Graph::neighbors_directed).
+    /// Panics if the node is not in the graph.
+    pub fn neighbors_directed(&self, node: NodeId, dir: Direction) -> impl Iterator<Item = NodeId> + '_ {
+        let edges = &self.node(node).expect("node not in graph").edges;
+
+        let (first, second): (&[EdgeId], &[EdgeId]) = if Ty::is_directed() {
+            (&edges[dir.index()], &[])
+        } else {
+            (&edges[0], &edges[1])
+        };
+
+        let other = move |edge: &EdgeId| {
+            let edge = self.edge(*edge).unwrap();
+            if edge.source == node { edge.target } else { edge.source }
+        };
+
+        // as in `Graph`, an undirected self loop shows up once
+        let second = second.iter().filter(move |&&e| self.edge(e).is_some_and(|e| e.source != e.target));
+
+        first.iter().map(other).chain(second.map(other))
+    }
+
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.neighbors_directed(node, Direction::Outgoing)
+    }
+
+    pub fn find_edge(&self, source: NodeId, target: NodeId) -> Option<EdgeId> {
+        let edges = &self.node(source)?.edges;
+
+        let outgoing = edges[0].iter().find(|&&e| self.edge(e).unwrap().target == target);
+
+        if outgoing.is_some() || Ty::is_directed() {
+            return outgoing.copied();
+        }
+
+        edges[1].iter().find(|&&e| self.edge(e).unwrap().source == target).copied()
+    }
+
+    pub fn add_node(&self, weight: N) -> (ImGraph<N, E, Ty>, NodeId) {
+        let id = NodeId::new(self.nodes.len);
+        let node = ImNode { weight: Arc::new(weight), edges: [Vec::new(), Vec::new()] };
+
+        let graph = ImGraph {
+            nodes: self.nodes.push(Some(Arc::new(node))),
+            node_count: self.node_count + 1,
+            ..self.clone()
+        };
+
+        (graph, id)
+    }
+
+    // copy of `node` with its edge lists changed by `change`
+    fn update_node(
+        nodes: &PersistentVec<Slot<ImNode<N>>>,
+        node: NodeId,
+        change: impl FnOnce(&mut [Vec<EdgeId>; 2])
+    ) -> PersistentVec<Slot<ImNode<N>>> {
+        let old = nodes.get(node.index()).unwrap().as_ref().unwrap();
+        let mut edges = old.edges.clone();
+        change(&mut edges);
+        nodes.set(node.index(), Some(Arc::new(ImNode { weight: Arc::clone(&old.weight), edges })))
+    }
+
+    /// Panics if either endpoint is not in the graph.
+    pub fn add_edge(&self, source: NodeId, target: NodeId, weight: E) -> (ImGraph<N, E, Ty>, EdgeId) {
+        assert!(self.contains_node(source), "source node not in graph");
+        assert!(self.contains_node(target), "target node not in graph");
+
+        let id = EdgeId::new(self.edges.len);
+        let nodes = Self::update_node(&self.nodes, source, |edges| edges[0].push(id));
+        let nodes = Self::update_node(&nodes, target, |edges| edges[1].push(id));
+
+        let graph = ImGraph {
+            nodes,
+            edges: self.edges.push(Some(Arc::new(ImEdge { weight, source, target }))),
+            node_count: self.node_count,
+            edge_count: self.edge_count + 1,
+            ty: PhantomData
+        };
+
+        (graph, id)
+    }
+
+    /// New graph without `edge`; a plain copy if the edge is not there.
+    pub fn remove_edge(&self, edge: EdgeId) -> ImGraph<N, E, Ty> {
+        let Some(&ImEdge { source, target, .. }) = self.edge(edge) else { return self.clone() };
+
+        let nodes = Self::update_node(&self.nodes, source, |edges| edges[0].retain(|&e| e != edge));
+        let nodes = Self::update_node(&nodes, target, |edges| edges[1].retain(|&e| e != edge));
+
+        ImGraph {
+            nodes,
+            edges: self.edges.set(edge.index(), None),
+            node_count: self.node_count,
+            edge_count: self.edge_count - 1,
+            ty: PhantomData
+        }
+    }
+
+    /// New graph without `node` and its edges; a plain copy if the node is
+    /// not there.
+    pub fn remove_node(&self, node: NodeId) -> ImGraph<N, E, Ty> {
+        let Some(entry) = self.node(node) else { return self.clone() };
+
+        let mut incident: Vec<_> = entry.edges.iter().flatten().copied().collect();
+        incident.sort_unstable();
+        incident.dedup();
+
+        let mut graph = self.clone();
+
+        for edge in incident {
+            graph = graph.remove_edge(edge);
+        }
+
+        ImGraph { nodes: graph.nodes.set(node.index(), None), node_count: graph.node_count - 1, ..graph }
+    }
+
+    /// New graph with the node's weight replaced. Panics if the node is
+    /// not in the graph.
+    pub fn set_node_weight(&self, node: NodeId, weight: N) -> ImGraph<N, E, Ty> {
+        let edges = self.node(node).expect("node not in graph").edges.clone();
+        let entry = ImNode { weight: Arc::new(weight), edges };
+
+        ImGraph { nodes: self.nodes.set(node.index(), Some(Arc::new(entry))), ..self.clone() }
+    }
+}
+
+/// Panics if the node is not in the graph.
+impl<N, E, Ty: EdgeType> Index<NodeId> for ImGraph<N, E, Ty> {
+    type Output = N;
+
+    fn index(&self, node: NodeId) -> &N {
+        self.node_weight(node).expect("node not in graph")
+    }
+}
+
+/// Panics if the edge is not in the graph.
+impl<N, E, Ty: EdgeType> Index<EdgeId> for ImGraph<N, E, Ty> {
+    type Output = E;
+
+    fn index(&self, edge: EdgeId) -> &E {
+        self.edge_weight(edge).expect("edge not in graph")
+    }
+}
+
+impl<N, E, Ty> GraphBase for ImGraph<N, E, Ty> {
+    type NodeId = NodeId;
+}
+
+impl<N, E, Ty: EdgeType> IntoNeighbors for ImGraph<N, E, Ty> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        ImGraph::neighbors(self, node)
+    }
+}
+
+impl<N, E, Ty: EdgeType> Visitable for ImGraph<N, E, Ty> {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.node_bound())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.node_bound());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Undirected;
+    use crate::visit::Bfs;
+
+    #[test]
+    fn persistent_vec_shares_old_versions() {
+        let mut versions = vec![PersistentVec::new()];
+
+        for i in 0..2000 {
+            versions.push(versions.last().unwrap().push(i));
+        }
+
+        let last = versions.last().unwrap();
+        let changed = last.set(1500, -1);
+
+        assert_eq!(Some(&1500), last.get(1500));
+        assert_eq!(Some(&-1), changed.get(1500));
+        assert_eq!(None, versions[1000].get(1000));
+        assert!((0..1000).all(|i| versions[1000].get(i) == Some(&(i as i32))));
+        assert!((0..2000).filter(|&i| i != 1500).all(|i| changed.get(i) == Some(&(i as i32))));
+    }
+
+    #[test]
+    fn updates_leave_earlier_versions_intact() {
+        let empty: ImGraph<&str, u32> = ImGraph::new();
+        let (one, a) = empty.add_node("a");
+        let (two, b) = one.add_node("b");
+        let (linked, ab) = two.add_edge(a, b, 7);
+        let renamed = linked.set_node_weight(a, "z");
+        let removed = renamed.remove_node(b);
+
+        assert_eq!(0, two.edge_count());
+        assert_eq!(Some(ab), linked.find_edge(a, b));
+        assert_eq!(None, linked.find_edge(b, a));
+        assert_eq!(Some(&"a"), linked.node_weight(a));
+        assert_eq!(("z", 7), (renamed[a], renamed[ab]));
+        assert_eq!(Some(&7), renamed.edge_weight(ab));
+
+        assert_eq!((1, 0), (removed.node_count(), removed.edge_count()));
+        assert_eq!(0, removed.neighbors(a).count());
+        assert_eq!(vec![b], renamed.neighbors(a).collect::<Vec<_>>());
+        assert!(!removed.contains_edge(ab));
+        assert!(renamed.contains_node(b));
+    }
+
+    #[test]
+    fn undirected_graphs_traverse_like_graph() {
+        let mut graph: ImGraph<(), (), Undirected> = ImGraph::new();
+        let mut nodes = Vec::new();
+
+        for _ in 0..100 {
+            let (next, node) = graph.add_node(());
+            graph = next;
+            nodes.push(node);
+        }
+
+        for pair in nodes.windows(2) {
+            graph = graph.add_edge(pair[1], pair[0], ()).0;
+        }
+
+        let (looped, _) = graph.add_edge(nodes[0], nodes[0], ());
+
+        assert_eq!(100, Bfs::new(&graph, nodes[0]).count());
+        assert_eq!(vec![nodes[98]], graph.neighbors(nodes[99]).collect::<Vec<_>>());
+        assert_eq!(2, looped.neighbors(nodes[0]).count());
+        assert!(graph.find_edge(nodes[10], nodes[11]).is_some());
+
+        let cut = graph.remove_node(nodes[50]);
+        assert_eq!(50, Bfs::new(&cut, nodes[0]).count());
+        assert_eq!(100, Bfs::new(&graph, nodes[0]).count());
+    }
+}
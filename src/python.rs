@@ -0,0 +1,480 @@
+use std::collections::HashSet;
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PySet};
+
+use crate::algo::{
+    connected_components, harmonic_centrality, k_core_decomposition, strongly_connected_components, toposort,
+    try_dijkstra
+};
+use crate::graph::{DiGraph, Direction, EdgePolicy, EdgeType, Graph, NodeId, UnGraph};
+use crate::visit::Bfs;
+
+create_exception!(graphs_rs, NetworkXError, PyException);
+create_exception!(graphs_rs, NodeNotFound, NetworkXError);
+create_exception!(graphs_rs, NetworkXNoPath, NetworkXError);
+create_exception!(graphs_rs, NetworkXUnfeasible, NetworkXError);
+create_exception!(graphs_rs, NetworkXNotImplemented, NetworkXError);
+
+/// Graph behind a Python `Graph` or `DiGraph`. Nodes are the Python
+/// objects themselves and edges carry a float weight.
+enum Inner {
+    Directed(DiGraph<Py<PyAny>, f64>),
+    Undirected(UnGraph<Py<PyAny>, f64>)
+}
+
+macro_rules! with_graph {
+    ($inner:expr, $graph:ident => $body:expr) => {
+        match $inner {
+            Inner::Directed($graph) => $body,
+            Inner::Undirected($graph) => $body
+        }
+    };
+}
+
+/// Undirected graph with networkx's method names, over any hashable nodes.
+/// Adding an edge between nodes already joined replaces its weight.
+#[pyclass(name = "Graph", module = "graphs_rs", subclass)]
+pub struct PyGraph {
+    inner: Inner,
+    // node object to the index of its slot
+    index: Py<PyDict>
+}
+
+/// Directed graph, a `Graph` whose `neighbors` are the successors.
+#[pyclass(name = "DiGraph", module = "graphs_rs", extends = PyGraph)]
+pub struct PyDiGraph;
+
+impl PyGraph {
+    fn with_inner(py: Python<'_>, mut inner: Inner) -> PyGraph {
+        with_graph!(&mut inner, graph => graph.set_edge_policy(EdgePolicy::MergeWith(|weight, new| *weight = new)));
+
+        PyGraph { inner, index: PyDict::new(py).unbind() }
+    }
+
+    fn node(&self, py: Python<'_>, node: &Bound<'_, PyAny>) -> PyResult<NodeId> {
+        let index = self.index.bind(py).get_item(node)?;
+        let id = index.map(|index| index.extract::<usize>()).transpose()?;
+
+        id.and_then(|index| with_graph!(&self.inner, graph => graph.node_id_at(index)))
+            .ok_or_else(|| NodeNotFound::new_err(format!("node {} is not in the graph", node.repr().unwrap())))
+    }
+
+    fn object(&self, py: Python<'_>, node: NodeId) -> Py<PyAny> {
+        with_graph!(&self.inner, graph => graph[node].clone_ref(py))
+    }
+
+    fn objects(&self, py: Python<'_>, nodes: impl IntoIterator<Item = NodeId>) -> Vec<Py<PyAny>> {
+        nodes.into_iter().map(|node| self.object(py, node)).collect()
+    }
+
+    fn insert(&mut self, node: &Bound<'_, PyAny>) -> PyResult<NodeId> {
+        let py = node.py();
+
+        if let Ok(id) = self.node(py, node) {
+            return Ok(id);
+        }
+
+        let id = with_graph!(&mut self.inner, graph => graph.add_node(node.clone().unbind()));
+        self.index.bind(py).set_item(node, id.index())?;
+        Ok(id)
+    }
+
+    fn is_directed_graph(&self) -> bool {
+        matches!(self.inner, Inner::Directed(_))
+    }
+
+    fn undirected_only(&self, name: &str) -> PyResult<()> {
+        match self.is_directed_graph() {
+            true => Err(NetworkXNotImplemented::new_err(format!("{name} is not implemented for directed graphs"))),
+            false => Ok(())
+        }
+    }
+}
+
+#[pymethods]
+impl PyGraph {
+    #[new]
+    #[pyo3(signature = (incoming_graph_data=None))]
+    fn new(py: Python<'_>, incoming_graph_data: Option<&Bound<'_, PyAny>>) -> PyResult<PyGraph> {
+        let mut graph = PyGraph::with_inner(py, Inner::Undirected(Graph::new()));
+
+        if let Some(edges) = incoming_graph_data {
+            graph.add_edges_from(edges, 1.0)?;
+        }
+
+        Ok(graph)
+    }
+
+    fn is_directed(&self) -> bool {
+        self.is_directed_graph()
+    }
+
+    fn add_node(&mut self, node: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.insert(node).map(drop)
+    }
+
+    fn add_nodes_from(&mut self, nodes: &Bound<'_, PyAny>) -> PyResult<()> {
+        for node in nodes.try_iter()? {
+            self.insert(&node?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes `node` and its edges.
+    fn remove_node(&mut self, node: &Bound<'_, PyAny>) -> PyResult<()> {
+        let id = self.node(node.py(), node)?;
+
+        with_graph!(&mut self.inner, graph => graph.remove_node(id));
+        self.index.bind(node.py()).del_item(node)
+    }
+
+    #[pyo3(signature = (u, v, weight=1.0))]
+    fn add_edge(&mut self, u: &Bound<'_, PyAny>, v: &Bound<'_, PyAny>, weight: f64) -> PyResult<()> {
+        let (u, v) = (self.insert(u)?, self.insert(v)?);
+
+        with_graph!(&mut self.inner, graph => graph.add_edge(u, v, weight));
+        Ok(())
+    }
+
+    /// Adds `(u, v)` pairs with `weight`, or `(u, v, w)` triples.
+    #[pyo3(signature = (edges, weight=1.0))]
+    fn add_edges_from(&mut self, edges: &Bound<'_, PyAny>, weight: f64) -> PyResult<()> {
+        for edge in edges.try_iter()? {
+            let edge = edge?;
+
+            match edge.len()? {
+                2 => self.add_edge(&edge.get_item(0)?, &edge.get_item(1)?, weight)?,
+                3 => self.add_edge(&edge.get_item(0)?, &edge.get_item(1)?, edge.get_item(2)?.extract()?)?,
+                _ => return Err(NetworkXError::new_err("edges must be 2-tuples or 3-tuples"))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_weighted_edges_from(&mut self, edges: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.add_edges_from(edges, 1.0)
+    }
+
+    fn remove_edge(&mut self, u: &Bound<'_, PyAny>, v: &Bound<'_, PyAny>) -> PyResult<()> {
+        let (u, v) = (self.node(u.py(), u)?, self.node(v.py(), v)?);
+
+        match with_graph!(&mut self.inner, graph => graph.find_edge(u, v).and_then(|e| graph.remove_edge(e))) {
+            Some(_) => Ok(()),
+            None => Err(NetworkXError::new_err("the edge is not in the graph"))
+        }
+    }
+
+    fn has_node(&self, node: &Bound<'_, PyAny>) -> bool {
+        self.node(node.py(), node).is_ok()
+    }
+
+    fn has_edge(&self, u: &Bound<'_, PyAny>, v: &Bound<'_, PyAny>) -> bool {
+        match (self.node(u.py(), u), self.node(v.py(), v)) {
+            (Ok(u), Ok(v)) => with_graph!(&self.inner, graph => graph.has_edge(u, v)),
+            _ => false
+        }
+    }
+
+    /// Weight of the edge from `u` to `v`, or `default` if there is none.
+    #[pyo3(signature = (u, v, default=None))]
+    fn get_edge_weight(
+        &self,
+        u: &Bound<'_, PyAny>,
+        v: &Bound<'_, PyAny>,
+        default: Option<f64>
+    ) -> PyResult<Option<f64>> {
+        let (u, v) = (self.node(u.py(), u)?, self.node(v.py(), v)?);
+        let edge = with_graph!(&self.inner, graph => graph.find_edge(u, v).map(|edge| graph[edge]));
+        Ok(edge.or(default))
+    }
+
+    /// Successors in directed graphs, each once.
+    fn neighbors(&self, node: &Bound<'_, PyAny>) -> PyResult<Vec<Py<PyAny>>> {
+        let id = self.node(node.py(), node)?;
+        let around = with_graph!(&self.inner, graph => adjacent(graph, id, Direction::Outgoing));
+        Ok(self.objects(node.py(), around))
+    }
+
+    /// Edges at `node`, with self loops counted twice, or for directed
+    /// graphs edges in and out.
+    fn degree(&self, node: &Bound<'_, PyAny>) -> PyResult<usize> {
+        let id = self.node(node.py(), node)?;
+
+        Ok(match &self.inner {
+            Inner::Directed(graph) => {
+                graph.neighbors_directed(id, Direction::Outgoing).count()
+                    + graph.neighbors_directed(id, Direction::Incoming).count()
+            },
+            Inner::Undirected(graph) => graph.neighbors(id).map(|other| if other == id { 2 } else { 1 }).sum()
+        })
+    }
+
+    fn number_of_nodes(&self) -> usize {
+        with_graph!(&self.inner, graph => graph.node_count())
+    }
+
+    fn number_of_edges(&self) -> usize {
+        with_graph!(&self.inner, graph => graph.edge_count())
+    }
+
+    #[getter]
+    fn nodes(&self, py: Python<'_>) -> Vec<Py<PyAny>> {
+        with_graph!(&self.inner, graph => self.objects(py, graph.node_ids()))
+    }
+
+    /// Every edge as `(u, v)`, or `(u, v, weight)` with `data`.
+    #[pyo3(signature = (data=false))]
+    fn edges(&self, py: Python<'_>, data: bool) -> PyResult<Vec<Py<PyAny>>> {
+        with_graph!(&self.inner, graph => graph
+            .edge_ids()
+            .map(|edge| {
+                let (u, v) = graph.edge_endpoints(edge).unwrap();
+                let (u, v) = (self.object(py, u), self.object(py, v));
+
+                match data {
+                    true => (u, v, graph[edge]).into_pyobject(py).map(|edge| edge.into_any().unbind()),
+                    false => (u, v).into_pyobject(py).map(|edge| edge.into_any().unbind())
+                }
+            })
+            .collect())
+    }
+
+    fn __len__(&self) -> usize {
+        self.number_of_nodes()
+    }
+
+    fn __contains__(&self, node: &Bound<'_, PyAny>) -> bool {
+        self.has_node(node)
+    }
+
+    fn __iter__(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        Ok(self.nodes(py).into_pyobject(py)?.try_iter()?.into_any().unbind())
+    }
+}
+
+#[pymethods]
+impl PyDiGraph {
+    #[new]
+    #[pyo3(signature = (incoming_graph_data=None))]
+    fn new(
+        py: Python<'_>,
+        incoming_graph_data: Option<&Bound<'_, PyAny>>
+    ) -> PyResult<PyClassInitializer<PyDiGraph>> {
+        let mut graph = PyGraph::with_inner(py, Inner::Directed(Graph::new()));
+
+        if let Some(edges) = incoming_graph_data {
+            graph.add_edges_from(edges, 1.0)?;
+        }
+
+        Ok(PyClassInitializer::from(graph).add_subclass(PyDiGraph))
+    }
+
+    fn successors(slf: PyRef<'_, Self>, node: &Bound<'_, PyAny>) -> PyResult<Vec<Py<PyAny>>> {
+        slf.as_super().neighbors(node)
+    }
+
+    fn predecessors(slf: PyRef<'_, Self>, node: &Bound<'_, PyAny>) -> PyResult<Vec<Py<PyAny>>> {
+        let graph = slf.as_super();
+        let id = graph.node(node.py(), node)?;
+        let around = with_graph!(&graph.inner, inner => adjacent(inner, id, Direction::Incoming));
+        Ok(graph.objects(node.py(), around))
+    }
+
+    fn in_degree(slf: PyRef<'_, Self>, node: &Bound<'_, PyAny>) -> PyResult<usize> {
+        let graph = slf.as_super();
+        let id = graph.node(node.py(), node)?;
+        Ok(with_graph!(&graph.inner, inner => inner.neighbors_directed(id, Direction::Incoming).count()))
+    }
+
+    fn out_degree(slf: PyRef<'_, Self>, node: &Bound<'_, PyAny>) -> PyResult<usize> {
+        let graph = slf.as_super();
+        let id = graph.node(node.py(), node)?;
+        Ok(with_graph!(&graph.inner, inner => inner.neighbors_directed(id, Direction::Outgoing).count()))
+    }
+}
+
+/// Nodes joined to `node` in direction `dir`, once each, in the order
+/// networkx keeps: by the first edge added between them.
+fn adjacent<Ty: EdgeType>(graph: &Graph<Py<PyAny>, f64, Ty>, node: NodeId, dir: Direction) -> Vec<NodeId> {
+    let mut edges: Vec<_> = graph
+        .edges_directed(node, dir)
+        .map(|edge| (edge.id, if edge.source == node { edge.target } else { edge.source }))
+        .collect();
+    let mut seen = HashSet::new();
+
+    edges.sort_unstable_by_key(|&(id, _)| id);
+    edges.into_iter().map(|(_, other)| other).filter(|&other| seen.insert(other)).collect()
+}
+
+/// Cost of an edge: its weight if `weight` names it, 1 otherwise, as
+/// networkx charges edges without the attribute.
+fn cost(weight: Option<&str>) -> impl Fn(&f64) -> f64 {
+    let weighted = weight == Some("weight");
+    move |&w| if weighted { w } else { 1.0 }
+}
+
+fn dijkstra<Ty: EdgeType>(
+    graph: &Graph<Py<PyAny>, f64, Ty>,
+    source: NodeId,
+    weight: Option<&str>
+) -> PyResult<crate::algo::ShortestPaths<f64>> {
+    try_dijkstra(graph, source, cost(weight)).map_err(|_| PyValueError::new_err("negative or NaN edge weight"))
+}
+
+/// Nodes of a shortest path from `source` to `target`, counting every edge
+/// as 1 unless `weight` is `"weight"`.
+#[pyfunction]
+#[pyo3(signature = (graph, source, target, weight=None))]
+fn shortest_path(
+    graph: PyRef<'_, PyGraph>,
+    source: &Bound<'_, PyAny>,
+    target: &Bound<'_, PyAny>,
+    weight: Option<&str>
+) -> PyResult<Vec<Py<PyAny>>> {
+    let py = source.py();
+    let (s, t) = (graph.node(py, source)?, graph.node(py, target)?);
+    let path = with_graph!(&graph.inner, inner => dijkstra(inner, s, weight)?.path_to(t));
+
+    match path {
+        Some(path) => Ok(graph.objects(py, path)),
+        None => Err(NetworkXNoPath::new_err(format!("no path from {} to {}", source.repr()?, target.repr()?)))
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (graph, source, target, weight=None))]
+fn shortest_path_length(
+    graph: PyRef<'_, PyGraph>,
+    source: &Bound<'_, PyAny>,
+    target: &Bound<'_, PyAny>,
+    weight: Option<&str>
+) -> PyResult<f64> {
+    let py = source.py();
+    let (s, t) = (graph.node(py, source)?, graph.node(py, target)?);
+    let length = with_graph!(&graph.inner, inner => dijkstra(inner, s, weight)?.distance(t));
+
+    length.ok_or_else(|| NetworkXNoPath::new_err("target is not reachable from source"))
+}
+
+/// Weighted distance from `source` to every node it reaches.
+#[pyfunction]
+fn single_source_dijkstra_path_length<'py>(
+    graph: PyRef<'_, PyGraph>,
+    source: &Bound<'py, PyAny>
+) -> PyResult<Bound<'py, PyDict>> {
+    let py = source.py();
+    let s = graph.node(py, source)?;
+    let reached: Vec<_> = with_graph!(&graph.inner, inner => dijkstra(inner, s, Some("weight"))?.reached().collect());
+    let lengths = PyDict::new(py);
+
+    for (node, length) in reached {
+        lengths.set_item(graph.object(py, node), length)?;
+    }
+
+    Ok(lengths)
+}
+
+#[pyfunction]
+fn has_path(graph: PyRef<'_, PyGraph>, source: &Bound<'_, PyAny>, target: &Bound<'_, PyAny>) -> PyResult<bool> {
+    let py = source.py();
+    let (s, t) = (graph.node(py, source)?, graph.node(py, target)?);
+    Ok(with_graph!(&graph.inner, inner => Bfs::new(inner, s).any(|node| node == t)))
+}
+
+fn sets<'py>(py: Python<'py>, graph: &PyGraph, groups: Vec<Vec<NodeId>>) -> PyResult<Vec<Bound<'py, PySet>>> {
+    groups.into_iter().map(|group| PySet::new(py, graph.objects(py, group))).collect()
+}
+
+#[pyfunction(name = "connected_components")]
+fn py_connected_components<'py>(py: Python<'py>, graph: PyRef<'_, PyGraph>) -> PyResult<Vec<Bound<'py, PySet>>> {
+    graph.undirected_only("connected_components")?;
+    let groups = with_graph!(&graph.inner, inner => connected_components(inner));
+    sets(py, &graph, groups)
+}
+
+#[pyfunction]
+fn number_connected_components(graph: PyRef<'_, PyGraph>) -> PyResult<usize> {
+    graph.undirected_only("number_connected_components")?;
+    Ok(with_graph!(&graph.inner, inner => connected_components(inner).len()))
+}
+
+/// Components in reverse topological order of the condensation.
+#[pyfunction(name = "strongly_connected_components")]
+fn py_strongly_connected_components<'py>(
+    py: Python<'py>,
+    graph: PyRef<'_, PyGraph>
+) -> PyResult<Vec<Bound<'py, PySet>>> {
+    match &graph.inner {
+        Inner::Directed(inner) => sets(py, &graph, strongly_connected_components(inner)),
+        Inner::Undirected(_) => Err(NetworkXNotImplemented::new_err("not implemented for undirected graphs"))
+    }
+}
+
+#[pyfunction]
+fn topological_sort(py: Python<'_>, graph: PyRef<'_, PyGraph>) -> PyResult<Vec<Py<PyAny>>> {
+    match &graph.inner {
+        Inner::Directed(inner) => match toposort(inner) {
+            Ok(order) => Ok(graph.objects(py, order)),
+            Err(_) => Err(NetworkXUnfeasible::new_err("graph contains a cycle"))
+        },
+        Inner::Undirected(_) => Err(NetworkXError::new_err("topological sort not defined on undirected graphs"))
+    }
+}
+
+/// Sum of reciprocal hop distances to every other node, as in networkx.
+#[pyfunction(name = "harmonic_centrality")]
+fn py_harmonic_centrality<'py>(py: Python<'py>, graph: PyRef<'_, PyGraph>) -> PyResult<Bound<'py, PyDict>> {
+    let scores = with_graph!(&graph.inner, inner => harmonic_centrality(inner));
+    let dict = PyDict::new(py);
+
+    for (node, &score) in scores.iter() {
+        dict.set_item(graph.object(py, node), score)?;
+    }
+
+    Ok(dict)
+}
+
+/// Largest `k` such that each node is in the `k`-core.
+#[pyfunction]
+fn core_number<'py>(py: Python<'py>, graph: PyRef<'_, PyGraph>) -> PyResult<Bound<'py, PyDict>> {
+    let cores = with_graph!(&graph.inner, inner => k_core_decomposition(inner));
+    let dict = PyDict::new(py);
+
+    for (node, &core) in cores.iter() {
+        dict.set_item(graph.object(py, node), core)?;
+    }
+
+    Ok(dict)
+}
+
+#[pymodule]
+fn graphs_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+
+    m.add_class::<PyGraph>()?;
+    m.add_class::<PyDiGraph>()?;
+
+    m.add("NetworkXError", py.get_type::<NetworkXError>())?;
+    m.add("NodeNotFound", py.get_type::<NodeNotFound>())?;
+    m.add("NetworkXNoPath", py.get_type::<NetworkXNoPath>())?;
+    m.add("NetworkXUnfeasible", py.get_type::<NetworkXUnfeasible>())?;
+    m.add("NetworkXNotImplemented", py.get_type::<NetworkXNotImplemented>())?;
+
+    m.add_function(wrap_pyfunction!(shortest_path, m)?)?;
+    m.add_function(wrap_pyfunction!(shortest_path_length, m)?)?;
+    m.add_function(wrap_pyfunction!(single_source_dijkstra_path_length, m)?)?;
+    m.add_function(wrap_pyfunction!(has_path, m)?)?;
+    m.add_function(wrap_pyfunction!(py_connected_components, m)?)?;
+    m.add_function(wrap_pyfunction!(number_connected_components, m)?)?;
+    m.add_function(wrap_pyfunction!(py_strongly_connected_components, m)?)?;
+    m.add_function(wrap_pyfunction!(topological_sort, m)?)?;
+    m.add_function(wrap_pyfunction!(py_harmonic_centrality, m)?)?;
+    m.add_function(wrap_pyfunction!(core_number, m)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use crate::algo::subgraph_monomorphisms;
+use crate::graph::{Directed, EdgeId, EdgeType, Graph, NodeId};
+
+/// Chain of labeled nodes and the labeled edges between them, matched
+/// against a graph by [`Query::run`]. Built from [`find`], alternating
+/// between nodes and edges, as in
+/// `find().node("person").edge("works_at").node("company")`.
+pub struct Query<NL, EL, Ty = Directed> {
+    pattern: Graph<Option<NL>, Option<EL>, Ty>,
+    last: Option<NodeId>,
+    pending: Option<Option<EL>>
+}
+
+/// Nodes and edges of the graph bound by one match of a [`Query`], in the
+/// order they were added to it.
+#[derive(Debug, PartialEq)]
+pub struct QueryMatch {
+    pub nodes: Vec<NodeId>,
+    pub edges: Vec<EdgeId>
+}
+
+/// Starts an empty [`Query`].
+pub fn find<NL, EL, Ty: EdgeType>() -> Query<NL, EL, Ty> {
+    Query { pattern: Graph::new(), last: None, pending: None }
+}
+
+impl<NL, EL, Ty: EdgeType> Query<NL, EL, Ty> {
+    /// Adds a node labeled `label`, joined to the one before by the edge
+    /// added last if there is one. Without an edge in between, it starts a
+    /// part of the pattern not connected to the rest.
+    pub fn node(self, label: NL) -> Self {
+        self.push_node(Some(label))
+    }
+
+    /// Adds a node matching any node.
+    pub fn any_node(self) -> Self {
+        self.push_node(None)
+    }
+
+    /// Adds an edge labeled `label` from the node added last to the next
+    /// one. Panics if no node comes before it or another edge does.
+    pub fn edge(self, label: EL) -> Self {
+        self.push_edge(Some(label))
+    }
+
+    /// Adds an edge matching any edge.
+    pub fn any_edge(self) -> Self {
+        self.push_edge(None)
+    }
+
+    fn push_node(mut self, label: Option<NL>) -> Self {
+        let node = self.pattern.add_node(label);
+
+        if let Some(edge) = self.pending.take() {
+            self.pattern.add_edge(self.last.unwrap(), node, edge);
+        }
+
+        self.last = Some(node);
+        self
+    }
+
+    fn push_edge(mut self, label: Option<EL>) -> Self {
+        assert!(self.last.is_some() && self.pending.is_none(), "an edge must follow a node");
+        self.pending = Some(label);
+        self
+    }
+
+    /// Every binding of the query in `graph`, comparing labels to weights
+    /// with `==`. Distinct query nodes bind distinct nodes, and symmetric
+    /// queries on undirected graphs are reported once per symmetry. Panics
+    /// if the query ends with an edge.
+    pub fn run<N, E>(&self, graph: &Graph<N, E, Ty>) -> Vec<QueryMatch>
+    where
+        N: PartialEq<NL>,
+        E: PartialEq<EL>
+    {
+        self.run_with(graph, |label, weight| weight == label, |label, weight| weight == label)
+    }
+
+    /// Same as [`run`](Query::run), with labels compared to weights by
+    /// `node_match` and `edge_match`.
+    pub fn run_with<N, E>(
+        &self,
+        graph: &Graph<N, E, Ty>,
+        mut node_match: impl FnMut(&NL, &N) -> bool,
+        mut edge_match: impl FnMut(&EL, &E) -> bool
+    ) -> Vec<QueryMatch> {
+        assert!(self.pending.is_none(), "a query cannot end with an edge");
+
+        let found = subgraph_monomorphisms(
+            &self.pattern,
+            graph,
+            |label, weight| label.as_ref().is_none_or(|label| node_match(label, weight)),
+            |label, weight| label.as_ref().is_none_or(|label| edge_match(label, weight))
+        );
+
+        found
+            .into_iter()
+            .map(|m| QueryMatch {
+                nodes: self.pattern.node_ids().map(|node| m.nodes[node]).collect(),
+                edges: self.pattern.edge_ids().map(|edge| m.edges[edge]).collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn binds_labeled_chains() {
+        let mut graph: DiGraph<&str, &str> = Graph::new();
+        let [ann, bob, cat] = ["person"; 3].map(|label| graph.add_node(label));
+        let acme = graph.add_node("company");
+
+        let knows = graph.add_edge(ann, bob, "knows");
+        graph.add_edge(cat, bob, "knows");
+        graph.add_edge(bob, acme, "works_at");
+        graph.add_edge(cat, acme, "owns");
+
+        let found = find().node("person").edge("knows").node("person").run(&graph);
+        assert_eq!(2, found.len());
+        assert!(found.contains(&QueryMatch { nodes: vec![ann, bob], edges: vec![knows] }));
+
+        let employers = find().node("person").edge("works_at").node("company").run(&graph);
+        assert_eq!(vec![vec![bob, acme]], employers.into_iter().map(|m| m.nodes).collect::<Vec<_>>());
+
+        let linked = find::<_, &str, _>().any_node().any_edge().node("company").run(&graph);
+        assert_eq!(2, linked.len());
+        assert!(find().node("company").edge("knows").any_node().run(&graph).is_empty());
+    }
+
+    #[test]
+    fn matches_with_custom_comparison() {
+        let mut graph: UnGraph<(char, u32), u32> = Graph::new();
+        let a = graph.add_node(('a', 30));
+        let b = graph.add_node(('b', 40));
+        graph.add_edge(a, b, 5);
+
+        let found = find().node('a').edge(3).any_node().run_with(
+            &graph,
+            |&label, &(name, _)| label == name,
+            |&min, &weight| weight >= min
+        );
+
+        assert_eq!(vec![vec![a, b]], found.into_iter().map(|m| m.nodes).collect::<Vec<_>>());
+    }
+}
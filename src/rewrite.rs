@@ -0,0 +1,220 @@
+use crate::algo::{subgraph_monomorphisms, SubgraphMatch};
+use crate::bitset::FixedBitSet;
+use crate::graph::{Direction, EdgeType, Graph, NodeId};
+
+/// Rewrite rule: occurrences of `pattern` are replaced by `replacement`.
+/// Interface nodes are kept, taking the weight of their replacement node;
+/// the other matched nodes and all matched edges are removed, and the rest
+/// of the replacement is added around the kept nodes.
+pub struct Rule<N, E, Ty> {
+    pattern: Graph<N, E, Ty>,
+    replacement: Graph<N, E, Ty>,
+    interface: Vec<(NodeId, NodeId)>
+}
+
+impl<N, E, Ty: EdgeType> Rule<N, E, Ty> {
+    /// `interface` pairs pattern nodes with the replacement nodes they
+    /// become. Panics if a node is not in its graph or paired twice.
+    pub fn new(
+        pattern: Graph<N, E, Ty>,
+        replacement: Graph<N, E, Ty>,
+        interface: Vec<(NodeId, NodeId)>
+    ) -> Rule<N, E, Ty> {
+        let mut left = FixedBitSet::with_capacity(pattern.node_bound());
+        let mut right = FixedBitSet::with_capacity(replacement.node_bound());
+
+        for &(p, r) in &interface {
+            assert!(pattern.contains_node(p) && replacement.contains_node(r), "interface node not in graph");
+            assert!(!left.put(p.index()) && !right.put(r.index()), "interface node paired twice");
+        }
+
+        Rule { pattern, replacement, interface }
+    }
+
+    pub fn pattern(&self) -> &Graph<N, E, Ty> {
+        &self.pattern
+    }
+
+    pub fn replacement(&self) -> &Graph<N, E, Ty> {
+        &self.replacement
+    }
+}
+
+/// Applies `rule` at as many node-disjoint occurrences in `graph` as it
+/// finds, taken first come first served, and returns how many it rewrote.
+/// Weights match by equality. All occurrences are found and checked on the
+/// unchanged graph before any is rewritten, so no rewrite can interfere
+/// with another or leave the graph half done.
+///
+/// An occurrence is skipped when a node it would remove has edges outside
+/// it, rather than leaving those edges dangling. Kept edges are removed
+/// and added again, so they get fresh ids.
+pub fn rewrite_all<N, E, Ty>(graph: &mut Graph<N, E, Ty>, rule: &Rule<N, E, Ty>) -> usize
+where
+    N: Clone + PartialEq,
+    E: Clone + PartialEq,
+    Ty: EdgeType
+{
+    let found = subgraph_monomorphisms(&rule.pattern, graph, |a, b| a == b, |a, b| a == b);
+    let mut kept = FixedBitSet::with_capacity(rule.pattern.node_bound());
+    let mut taken = FixedBitSet::with_capacity(graph.node_bound());
+    let mut chosen = Vec::new();
+
+    for &(p, _) in &rule.interface {
+        kept.insert(p.index());
+    }
+
+    for m in found {
+        let images = || m.nodes.iter().map(|(_, &n)| n.index());
+
+        if images().any(|n| taken.contains(n)) || dangles(graph, rule, &kept, &m) {
+            continue;
+        }
+
+        images().for_each(|n| taken.insert(n));
+        chosen.push(m);
+    }
+
+    for m in &chosen {
+        apply(graph, rule, &kept, m);
+    }
+
+    chosen.len()
+}
+
+fn dangles<N, E, Ty: EdgeType>(
+    graph: &Graph<N, E, Ty>,
+    rule: &Rule<N, E, Ty>,
+    kept: &FixedBitSet,
+    m: &SubgraphMatch
+) -> bool {
+    let mut matched = FixedBitSet::with_capacity(graph.edge_bound());
+
+    for (_, &edge) in m.edges.iter() {
+        matched.insert(edge.index());
+    }
+
+    rule.pattern.node_ids().filter(|p| !kept.contains(p.index())).any(|p| {
+        [Direction::Outgoing, Direction::Incoming].into_iter().any(|dir| {
+            let mut around = graph.neighbors_directed(m.nodes[p], dir);
+
+            while let Some((edge, _)) = around.next_edge() {
+                if !matched.contains(edge.index()) {
+                    return true;
+                }
+            }
+
+            false
+        })
+    })
+}
+
+fn apply<N, E, Ty>(graph: &mut Graph<N, E, Ty>, rule: &Rule<N, E, Ty>, kept: &FixedBitSet, m: &SubgraphMatch)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    for (_, &edge) in m.edges.iter() {
+        graph.remove_edge(edge);
+    }
+
+    for p in rule.pattern.node_ids().filter(|p| !kept.contains(p.index())) {
+        graph.remove_node(m.nodes[p]);
+    }
+
+    let mut remap = vec![None; rule.replacement.node_bound()];
+
+    for &(p, r) in &rule.interface {
+        let node = m.nodes[p];
+        *graph.node_weight_mut(node).unwrap() = rule.replacement.node_weight(r).unwrap().clone();
+        remap[r.index()] = Some(node);
+    }
+
+    for r in rule.replacement.node_ids() {
+        if remap[r.index()].is_none() {
+            remap[r.index()] = Some(graph.add_node(rule.replacement.node_weight(r).unwrap().clone()));
+        }
+    }
+
+    for edge in rule.replacement.edge_ids() {
+        let (source, target) = rule.replacement.edge_endpoints(edge).unwrap();
+        let weight = rule.replacement.edge_weight(edge).unwrap().clone();
+        graph.add_edge(remap[source.index()].unwrap(), remap[target.index()].unwrap(), weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, Directed};
+
+    // x -> neg -> neg -> out  becomes  x -> out
+    fn double_negation() -> Rule<&'static str, u8, Directed> {
+        let mut pattern = Graph::new();
+        let x = pattern.add_node("x");
+        let a = pattern.add_node("neg");
+        let b = pattern.add_node("neg");
+        let out = pattern.add_node("out");
+        pattern.add_edge(x, a, 0);
+        pattern.add_edge(a, b, 0);
+        pattern.add_edge(b, out, 0);
+
+        let mut replacement = Graph::new();
+        let x2 = replacement.add_node("x");
+        let out2 = replacement.add_node("out");
+        replacement.add_edge(x2, out2, 0);
+
+        Rule::new(pattern, replacement, vec![(x, x2), (out, out2)])
+    }
+
+    fn chain(graph: &mut DiGraph<&'static str, u8>) -> (NodeId, NodeId) {
+        let x = graph.add_node("x");
+        let a = graph.add_node("neg");
+        let b = graph.add_node("neg");
+        let out = graph.add_node("out");
+        graph.add_edge(x, a, 0);
+        graph.add_edge(a, b, 0);
+        graph.add_edge(b, out, 0);
+        (x, out)
+    }
+
+    #[test]
+    fn rewrites_disjoint_occurrences() {
+        let rule = double_negation();
+        let mut graph = Graph::new();
+        let (x1, out1) = chain(&mut graph);
+        let (x2, out2) = chain(&mut graph);
+
+        assert_eq!(2, rewrite_all(&mut graph, &rule));
+        assert_eq!(4, graph.node_count());
+        assert_eq!(2, graph.edge_count());
+        assert!(graph.find_edge(x1, out1).is_some());
+        assert!(graph.find_edge(x2, out2).is_some());
+
+        assert_eq!(0, rewrite_all(&mut graph, &rule));
+    }
+
+    #[test]
+    fn leaves_occurrences_with_outside_edges_alone() {
+        let rule = double_negation();
+        let mut graph = Graph::new();
+        let (x, _) = chain(&mut graph);
+
+        // the first negation is also read elsewhere
+        let neg = graph.neighbors(x).next().unwrap();
+        let other = graph.add_node("out");
+        graph.add_edge(neg, other, 0);
+
+        assert_eq!(0, rewrite_all(&mut graph, &rule));
+        assert_eq!(5, graph.node_count());
+
+        // weights must match too
+        let mut graph = Graph::new();
+        let (x, _) = chain(&mut graph);
+        let neg = graph.neighbors(x).next().unwrap();
+        *graph.node_weight_mut(neg).unwrap() = "not";
+
+        assert_eq!(0, rewrite_all(&mut graph, &rule));
+    }
+}
@@ -0,0 +1,126 @@
+/// Source of random bits for the randomized algorithms. Only
+/// [`next_u64`](Rng::next_u64) is required; none of this is suitable for
+/// cryptography.
+pub trait Rng {
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `0..bound`. Panics if `bound` is zero.
+    fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "empty range");
+
+        // Lemire's multiply-and-reject, unbiased for every bound
+        let bound = bound as u64;
+        let threshold = bound.wrapping_neg() % bound;
+
+        loop {
+            let product = self.next_u64() as u128 * bound as u128;
+
+            if product as u64 >= threshold {
+                return (product >> 64) as usize;
+            }
+        }
+    }
+
+    /// `true` with probability `p`.
+    fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    /// Index drawn with probability proportional to its weight, or `None`
+    /// if no weight is positive. Negative and NaN weights count as zero.
+    fn weighted_index(&mut self, weights: &[f64]) -> Option<usize> {
+        let weight = |w: f64| if w > 0.0 { w } else { 0.0 };
+        let total: f64 = weights.iter().map(|&w| weight(w)).sum();
+
+        if total <= 0.0 || !total.is_finite() {
+            return None;
+        }
+
+        let mut r = self.next_f64() * total;
+
+        for (i, &w) in weights.iter().enumerate() {
+            if w > 0.0 && r < w {
+                return Some(i);
+            }
+
+            r -= weight(w);
+        }
+
+        // rounding may leave a sliver past the last positive weight
+        weights.iter().rposition(|&w| w > 0.0)
+    }
+
+    /// Fisher-Yates shuffle.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+impl<R: Rng + ?Sized> Rng for &mut R {
+    fn next_u64(&mut self) -> u64 {
+        (**self).next_u64()
+    }
+}
+
+/// Sebastiano Vigna's SplitMix64: tiny state, fast, and good enough for
+/// sampling and randomized heuristics.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+
+        // reference output of the C implementation for seed 0
+        assert_eq!(0xe220a8397b1dcdaf, SplitMix64::new(0).next_u64());
+        assert!((0..100).all(|_| a.next_u64() == b.next_u64()));
+    }
+
+    #[test]
+    fn ranges_are_respected() {
+        let mut rng = SplitMix64::new(7);
+        let mut counts = [0; 5];
+
+        for _ in 0..10_000 {
+            counts[rng.below(5)] += 1;
+
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+
+        assert!(counts.iter().all(|&c| (1800..2200).contains(&c)));
+        assert_eq!(None, rng.weighted_index(&[0.0, -1.0]));
+        assert!((0..100).all(|_| rng.weighted_index(&[0.0, 3.0, 0.0]) == Some(1)));
+    }
+}
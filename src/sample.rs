@@ -0,0 +1,378 @@
+use std::collections::VecDeque;
+
+use crate::algo::{dijkstra, Measure};
+use crate::bitset::FixedBitSet;
+use crate::graph::{EdgeType, Graph, NodeId};
+use crate::rng::Rng;
+
+/// Sampled subgraph. Node `i` of `graph` is a copy of node `origin[i]` of
+/// the graph it was drawn from.
+pub struct Sample<N, E, Ty> {
+    pub graph: Graph<N, E, Ty>,
+    pub origin: Vec<NodeId>
+}
+
+/// Subgraph on `nodes` holding every edge between them, with nodes in the
+/// given order and edges in their original order. Repeated nodes are
+/// taken once.
+pub fn induced_subgraph<N, E, Ty>(graph: &Graph<N, E, Ty>, nodes: &[NodeId]) -> Sample<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    let mut sub = Graph::new();
+    let mut remap = vec![None; graph.node_bound()];
+    let mut origin = Vec::with_capacity(nodes.len());
+
+    for &node in nodes {
+        if remap[node.index()].is_none() {
+            remap[node.index()] = Some(sub.add_node(graph.node_weight(node).unwrap().clone()));
+            origin.push(node);
+        }
+    }
+
+    for edge in graph.edge_ids() {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+
+        if let (Some(s), Some(t)) = (remap[source.index()], remap[target.index()]) {
+            sub.add_edge(s, t, graph.edge_weight(edge).unwrap().clone());
+        }
+    }
+
+    Sample { graph: sub, origin }
+}
+
+/// Induced subgraph on `count` nodes chosen uniformly at random.
+pub fn random_nodes<N, E, Ty, R>(graph: &Graph<N, E, Ty>, count: usize, rng: &mut R) -> Sample<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    R: Rng
+{
+    let mut nodes: Vec<_> = graph.node_ids().collect();
+    let count = count.min(nodes.len());
+
+    for i in 0..count {
+        let j = i + rng.below(nodes.len() - i);
+        nodes.swap(i, j);
+    }
+
+    nodes.truncate(count);
+
+    induced_subgraph(graph, &nodes)
+}
+
+/// `count` edges chosen uniformly at random, together with their endpoints.
+pub fn random_edges<N, E, Ty, R>(graph: &Graph<N, E, Ty>, count: usize, rng: &mut R) -> Sample<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    R: Rng
+{
+    let mut edges: Vec<_> = graph.edge_ids().collect();
+    let count = count.min(edges.len());
+
+    for i in 0..count {
+        let j = i + rng.below(edges.len() - i);
+        edges.swap(i, j);
+    }
+
+    edges.truncate(count);
+    edges.sort_by_key(|edge| edge.index());
+
+    let mut sub = Graph::new();
+    let mut remap = vec![None; graph.node_bound()];
+    let mut origin = Vec::new();
+
+    for edge in edges {
+        let (source, target) = graph.edge_endpoints(edge).unwrap();
+
+        let mut node = |node: NodeId| {
+            *remap[node.index()].get_or_insert_with(|| {
+                origin.push(node);
+                sub.add_node(graph.node_weight(node).unwrap().clone())
+            })
+        };
+
+        let (s, t) = (node(source), node(target));
+        sub.add_edge(s, t, graph.edge_weight(edge).unwrap().clone());
+    }
+
+    Sample { graph: sub, origin }
+}
+
+/// Grows a BFS-like sample from random seeds until `max_nodes` are taken,
+/// where `spread` says how many not yet sampled neighbors of the current
+/// node to take next. A new seed is drawn whenever the sample stops
+/// growing.
+fn grow<N, E, Ty, R, F>(graph: &Graph<N, E, Ty>, max_nodes: usize, rng: &mut R, mut spread: F) -> Vec<NodeId>
+where
+    Ty: EdgeType,
+    R: Rng,
+    F: FnMut(&mut R) -> usize
+{
+    let max_nodes = max_nodes.min(graph.node_count());
+    let mut seeds: Vec<_> = graph.node_ids().collect();
+    let mut taken = FixedBitSet::with_capacity(graph.node_bound());
+    let mut order = Vec::with_capacity(max_nodes);
+    let mut queue = VecDeque::new();
+    let mut candidates = Vec::new();
+
+    rng.shuffle(&mut seeds);
+
+    let mut seeds = seeds.into_iter();
+
+    while order.len() < max_nodes {
+        let Some(node) = queue.pop_front() else {
+            let seed = seeds.by_ref().find(|seed: &NodeId| !taken.contains(seed.index())).unwrap();
+
+            taken.insert(seed.index());
+            order.push(seed);
+            queue.push_back(seed);
+            continue;
+        };
+
+        candidates.clear();
+        candidates.extend(graph.neighbors(node).filter(|n| !taken.contains(n.index())));
+        candidates.sort_by_key(|n| n.index());
+        candidates.dedup();
+        rng.shuffle(&mut candidates);
+
+        let take = spread(rng).min(candidates.len()).min(max_nodes - order.len());
+
+        for &next in &candidates[..take] {
+            if !taken.put(next.index()) {
+                order.push(next);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    order
+}
+
+/// Snowball sample: starting from a random node, repeatedly takes up to
+/// `per_node` random unsampled neighbors of each sampled node, breadth
+/// first, until `max_nodes` nodes are in the induced subgraph.
+pub fn snowball<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    max_nodes: usize,
+    per_node: usize,
+    rng: &mut R
+) -> Sample<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    R: Rng
+{
+    let nodes = grow(graph, max_nodes, rng, |_| per_node);
+
+    induced_subgraph(graph, &nodes)
+}
+
+/// Forest fire sampling (Leskovec & Faloutsos, 2006): every burning node
+/// sets fire to a geometrically distributed number of its unburned
+/// neighbors, with mean `p / (1 - p)`. The result tends to keep the degree
+/// distribution and densification of the original graph. Panics unless
+/// `0 <= p < 1`.
+pub fn forest_fire<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    max_nodes: usize,
+    p: f64,
+    rng: &mut R
+) -> Sample<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    R: Rng
+{
+    assert!((0.0..1.0).contains(&p), "burning probability must be in [0, 1)");
+
+    let nodes = grow(graph, max_nodes, rng, |rng| {
+        let mut count = 0;
+
+        while rng.chance(p) {
+            count += 1;
+        }
+
+        count
+    });
+
+    induced_subgraph(graph, &nodes)
+}
+
+/// Ego network of `center`: the subgraph induced by the nodes at most
+/// `radius` steps away along [`Graph::neighbors`], so successors for
+/// directed graphs. Returns it with the id of `center` in it, which comes
+/// first, followed by the other nodes in BFS order.
+pub fn ego_graph<N, E, Ty>(
+    graph: &Graph<N, E, Ty>,
+    center: NodeId,
+    radius: usize
+) -> (Sample<N, E, Ty>, NodeId)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    let mut depth = vec![usize::MAX; graph.node_bound()];
+    let mut order = vec![center];
+    let mut next = 0;
+
+    depth[center.index()] = 0;
+
+    while let Some(&node) = order.get(next) {
+        next += 1;
+
+        if depth[node.index()] == radius {
+            continue;
+        }
+
+        for other in graph.neighbors(node) {
+            if depth[other.index()] == usize::MAX {
+                depth[other.index()] = depth[node.index()] + 1;
+                order.push(other);
+            }
+        }
+    }
+
+    ego(graph, &order)
+}
+
+/// Same as [`ego_graph`], taking the nodes whose shortest distance from
+/// `center` is at most `radius`, with edges costing `edge_cost`, in order
+/// of distance.
+pub fn weighted_ego_graph<N, E, Ty, K, F>(
+    graph: &Graph<N, E, Ty>,
+    center: NodeId,
+    radius: K,
+    edge_cost: F
+) -> (Sample<N, E, Ty>, NodeId)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    K: Measure,
+    F: FnMut(&E) -> K
+{
+    let paths = dijkstra(graph, center, edge_cost);
+    let order: Vec<_> = paths.reached().take_while(|&(_, d)| d <= radius).map(|(node, _)| node).collect();
+
+    ego(graph, &order)
+}
+
+/// Subgraph induced by `order`, which starts with the center, and the
+/// center's id in it.
+fn ego<N, E, Ty>(graph: &Graph<N, E, Ty>, order: &[NodeId]) -> (Sample<N, E, Ty>, NodeId)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    let sample = induced_subgraph(graph, order);
+    let center = sample.graph.node_id_at(0).unwrap();
+
+    (sample, center)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::SplitMix64;
+
+    fn grid(side: usize) -> UnGraph<usize, ()> {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..side * side).map(|i| graph.add_node(i)).collect();
+
+        for i in 0..side * side {
+            if i % side + 1 < side {
+                graph.add_edge(n[i], n[i + 1], ());
+            }
+
+            if i + side < side * side {
+                graph.add_edge(n[i], n[i + side], ());
+            }
+        }
+
+        graph
+    }
+
+    fn check_origin<Ty: EdgeType>(graph: &Graph<usize, (), Ty>, sample: &Sample<usize, (), Ty>) {
+        for (i, node) in sample.graph.node_ids().enumerate() {
+            assert_eq!(graph.node_weight(sample.origin[i]), sample.graph.node_weight(node));
+        }
+
+        for edge in sample.graph.edge_ids() {
+            let (s, t) = sample.graph.edge_endpoints(edge).unwrap();
+            assert!(graph.find_edge(sample.origin[s.index()], sample.origin[t.index()]).is_some());
+        }
+    }
+
+    #[test]
+    fn node_and_edge_samples_have_requested_size() {
+        let graph = grid(6);
+        let mut rng = SplitMix64::new(11);
+
+        let nodes = random_nodes(&graph, 10, &mut rng);
+        let edges = random_edges(&graph, 10, &mut rng);
+
+        assert_eq!(10, nodes.graph.node_count());
+        assert_eq!(10, edges.graph.edge_count());
+        assert_eq!(36, random_nodes(&graph, 100, &mut rng).graph.node_count());
+
+        check_origin(&graph, &nodes);
+        check_origin(&graph, &edges);
+    }
+
+    #[test]
+    fn growing_samples_stay_connected_when_possible() {
+        let graph = grid(8);
+        let mut rng = SplitMix64::new(5);
+
+        for sample in [snowball(&graph, 20, 2, &mut rng), forest_fire(&graph, 20, 0.7, &mut rng)] {
+            assert_eq!(20, sample.graph.node_count());
+            check_origin(&graph, &sample);
+        }
+
+        // seeds are redrawn once the fire dies out
+        let sample = forest_fire(&graph, 64, 0.0, &mut rng);
+
+        assert_eq!(64, sample.graph.node_count());
+        assert_eq!(graph.edge_count(), sample.graph.edge_count());
+
+        let same = |seed| snowball(&graph, 30, 3, &mut SplitMix64::new(seed)).origin;
+        assert_eq!(same(9), same(9));
+
+        let mut directed: DiGraph<usize, ()> = Graph::new();
+        let a = directed.add_node(0);
+        let b = directed.add_node(1);
+        directed.add_edge(b, a, ());
+
+        assert_eq!(2, snowball(&directed, 5, 1, &mut rng).graph.node_count());
+    }
+
+    #[test]
+    fn ego_graphs_keep_nodes_near_the_center() {
+        let graph = grid(5);
+        let middle = graph.node_id_at(12).unwrap();
+
+        let (near, center) = ego_graph(&graph, middle, 1);
+        assert_eq!((5, 4), (near.graph.node_count(), near.graph.edge_count()));
+        assert_eq!(Some(&12), near.graph.node_weight(center));
+        check_origin(&graph, &near);
+
+        assert_eq!(13, ego_graph(&graph, middle, 2).0.graph.node_count());
+        assert_eq!(1, ego_graph(&graph, middle, 0).0.graph.node_count());
+
+        // at 0.75 a step, two steps fit in the radius
+        let (cheap, center) = weighted_ego_graph(&graph, middle, 1.5, |_| 0.75);
+        assert_eq!(13, cheap.graph.node_count());
+        assert_eq!(middle, cheap.origin[center.index()]);
+    }
+}
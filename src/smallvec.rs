@@ -0,0 +1,139 @@
+use std::ops::{Deref, DerefMut};
+
+/// Vector holding up to `N` items inline before moving them to the heap,
+/// for the many short adjacency lists of a sparse graph. Items are `Copy`,
+/// so the inline array is filled with copies of the first item pushed
+/// rather than left uninitialized.
+#[derive(Clone, Debug)]
+pub(crate) enum SmallVec<T, const N: usize> {
+    Empty,
+    Inline(u32, [T; N]),
+    Heap(Vec<T>)
+}
+
+impl<T: Copy, const N: usize> SmallVec<T, N> {
+    pub(crate) fn new() -> SmallVec<T, N> {
+        SmallVec::Empty
+    }
+
+    /// Room on the heap, zero while the items are inline.
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            SmallVec::Heap(items) => items.capacity(),
+            _ => 0
+        }
+    }
+
+    /// Panics if `at` is past the end.
+    pub(crate) fn insert(&mut self, at: usize, val: T) {
+        let len = self.len();
+
+        assert!(at <= len, "insertion index out of bounds");
+
+        match self {
+            SmallVec::Heap(items) => items.insert(at, val),
+            _ if len == N => {
+                let mut items = Vec::with_capacity(2 * N.max(1));
+
+                items.extend_from_slice(&self[..at]);
+                items.push(val);
+                items.extend_from_slice(&self[at..]);
+                *self = SmallVec::Heap(items);
+            },
+            SmallVec::Empty => *self = SmallVec::Inline(1, [val; N]),
+            SmallVec::Inline(len, items) => {
+                items.copy_within(at..*len as usize, at + 1);
+                items[at] = val;
+                *len += 1;
+            }
+        }
+    }
+
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        match self {
+            SmallVec::Empty => {},
+            SmallVec::Inline(len, items) => {
+                let mut kept = 0;
+
+                for i in 0..*len as usize {
+                    if keep(&items[i]) {
+                        items[kept] = items[i];
+                        kept += 1;
+                    }
+                }
+
+                *len = kept as u32;
+            },
+            SmallVec::Heap(items) => items.retain(keep)
+        }
+    }
+
+    /// Removes every item, keeping any room on the heap.
+    pub(crate) fn clear(&mut self) {
+        match self {
+            SmallVec::Empty => {},
+            SmallVec::Inline(len, _) => *len = 0,
+            SmallVec::Heap(items) => items.clear()
+        }
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match self {
+            SmallVec::Empty => &[],
+            SmallVec::Inline(len, items) => &items[..*len as usize],
+            SmallVec::Heap(items) => items
+        }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self {
+            SmallVec::Empty => &mut [],
+            SmallVec::Inline(len, items) => &mut items[..*len as usize],
+            SmallVec::Heap(items) => items
+        }
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spills_to_the_heap_past_n() {
+        let mut list: SmallVec<u32, 3> = SmallVec::new();
+
+        for val in [1, 3, 4] {
+            list.insert(list.len(), val);
+        }
+
+        list.insert(1, 2);
+        assert_eq!(&[1, 2, 3, 4], &list[..]);
+        assert!(list.capacity() >= 4);
+
+        list.retain(|&val| val % 2 == 0);
+        list.clear();
+        assert!(list.is_empty() && list.capacity() >= 4);
+
+        let mut inline: SmallVec<u32, 3> = SmallVec::new();
+        inline.insert(0, 5);
+        inline.insert(0, 6);
+        inline.retain(|&val| val != 6);
+        inline.sort();
+        assert_eq!((&[5][..], 0), (&inline[..], inline.capacity()));
+    }
+}
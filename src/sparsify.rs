@@ -0,0 +1,223 @@
+use crate::bitset::FixedBitSet;
+use crate::graph::{Direction, EdgeId, EdgeType, Graph, NodeId};
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+
+/// Greedy `stretch`-spanner: a subgraph in which every shortest distance is
+/// at most `stretch` times what it is in `graph`, under the costs given by
+/// `edge_cost`. Edges are taken cheapest first and kept only if the edges
+/// kept so far do not already join their endpoints within `stretch` times
+/// their cost, which admits few edges: for `stretch = 2k - 1`, about
+/// `n^(1 + 1/k)`. Costs must not be negative. Runs a bounded Dijkstra
+/// search per edge, so it fits graphs of up to a few thousand nodes, dense
+/// or not. Node and edge ids carry over. Panics if `stretch` is below 1.
+pub fn greedy_spanner<N, E, Ty>(
+    graph: &Graph<N, E, Ty>,
+    stretch: f64,
+    mut edge_cost: impl FnMut(&E) -> f64
+) -> Graph<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    assert!(stretch >= 1.0, "stretch must be at least 1");
+
+    let mut edges: Vec<_> = graph.edge_ids().map(|edge| (edge_cost(&graph[edge]), edge)).collect();
+    edges.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut search = BoundedSearch {
+        adjacency: vec![Vec::new(); graph.node_bound()],
+        dist: vec![f64::INFINITY; graph.node_bound()],
+        touched: Vec::new(),
+        heap: IndexedBinaryHeap::new()
+    };
+    let mut kept = FixedBitSet::with_capacity(graph.edge_bound());
+
+    for (cost, edge) in edges {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+
+        // self loops never shorten a path
+        if a == b || search.reaches(a, b, stretch * cost) {
+            continue;
+        }
+
+        search.adjacency[a.index()].push((b, cost));
+
+        if !Ty::is_directed() {
+            search.adjacency[b.index()].push((a, cost));
+        }
+
+        kept.insert(edge.index());
+    }
+
+    retain(graph, |edge| kept.contains(edge.index()))
+}
+
+/// Keeps the `k` strongest edges at every node, by `strength`, of those at
+/// it in either direction, as when thinning a similarity graph to its
+/// nearest neighbors. An edge stays if one of its endpoints keeps it, or
+/// with `mutual` only if both do, a self loop if its node does. Ties go to
+/// the edge added first. Node and edge ids carry over.
+pub fn top_k_edges<N, E, Ty>(
+    graph: &Graph<N, E, Ty>,
+    k: usize,
+    mutual: bool,
+    mut strength: impl FnMut(&E) -> f64
+) -> Graph<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    let mut strengths = vec![0.0; graph.edge_bound()];
+    let mut votes = vec![0u8; graph.edge_bound()];
+
+    for edge in graph.edge_ids() {
+        strengths[edge.index()] = strength(&graph[edge]);
+    }
+
+    for node in graph.node_ids() {
+        let mut at: Vec<EdgeId> =
+            graph.edges_directed(node, Direction::Outgoing).map(|edge| edge.id).collect();
+
+        if Ty::is_directed() {
+            at.extend(graph.edges_directed(node, Direction::Incoming).map(|edge| edge.id));
+        }
+
+        // a directed self loop is listed both ways
+        at.sort_unstable();
+        at.dedup();
+        at.sort_by(|a, b| strengths[b.index()].total_cmp(&strengths[a.index()]));
+
+        for edge in at.into_iter().take(k) {
+            votes[edge.index()] += 1;
+        }
+    }
+
+    retain(graph, |edge| {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        votes[edge.index()] >= if mutual && a != b { 2 } else { 1 }
+    })
+}
+
+/// Dijkstra over the edges kept so far, stopping at a cost bound.
+struct BoundedSearch {
+    adjacency: Vec<Vec<(NodeId, f64)>>,
+    dist: Vec<f64>,
+    touched: Vec<NodeId>,
+    heap: IndexedBinaryHeap<NodeId, f64>
+}
+
+impl BoundedSearch {
+    /// Whether `to` is at most `bound` away from `from`.
+    fn reaches(&mut self, from: NodeId, to: NodeId, bound: f64) -> bool {
+        let mut found = false;
+
+        self.dist[from.index()] = 0.0;
+        self.touched.push(from);
+        self.heap.push(from, 0.0);
+
+        while let Some((node, d)) = self.heap.pop_min() {
+            if node == to {
+                found = true;
+                break;
+            }
+
+            for &(next, cost) in &self.adjacency[node.index()] {
+                let candidate = d + cost;
+
+                if candidate > bound || candidate >= self.dist[next.index()] {
+                    continue;
+                }
+
+                if self.dist[next.index()].is_infinite() {
+                    self.touched.push(next);
+                }
+
+                self.dist[next.index()] = candidate;
+
+                if self.heap.contains(&next) {
+                    self.heap.decrease_key(&next, candidate);
+                } else {
+                    self.heap.push(next, candidate);
+                }
+            }
+        }
+
+        while self.heap.pop_min().is_some() {}
+
+        for node in self.touched.drain(..) {
+            self.dist[node.index()] = f64::INFINITY;
+        }
+
+        found
+    }
+}
+
+/// Copy of `graph` with only the edges `keep` holds for.
+fn retain<N, E, Ty>(graph: &Graph<N, E, Ty>, mut keep: impl FnMut(EdgeId) -> bool) -> Graph<N, E, Ty>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType
+{
+    let mut kept = graph.map(|_, weight| weight.clone(), |_, weight| weight.clone());
+
+    for edge in graph.edge_ids().filter(|&edge| !keep(edge)) {
+        kept.remove_edge(edge);
+    }
+
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::dijkstra;
+    use crate::graph::{DiGraph, UnGraph};
+
+    #[test]
+    fn spanners_bound_the_stretch() {
+        // points on a circle, joined by their straight-line distances
+        let mut graph: UnGraph<(), f64> = Graph::new();
+        let n: Vec<_> = (0..24).map(|_| graph.add_node(())).collect();
+        let at = |i: usize| (i as f64 * std::f64::consts::TAU / 24.0).sin_cos();
+
+        for i in 0..24 {
+            for j in i + 1..24 {
+                let ((ay, ax), (by, bx)) = (at(i), at(j));
+                graph.add_edge(n[i], n[j], (ax - bx).hypot(ay - by));
+            }
+        }
+
+        for stretch in [1.01, 1.5, 3.0] {
+            let spanner = greedy_spanner(&graph, stretch, |&w| w);
+            let (full, thin) = (dijkstra(&graph, n[0], |&w| w), dijkstra(&spanner, n[0], |&w| w));
+            let stretched =
+                |v: NodeId| thin.distance(v).unwrap() <= stretch * full.distance(v).unwrap() + 1e-9;
+
+            assert!(spanner.edge_count() < graph.edge_count());
+            assert!(n.iter().all(|&v| stretched(v)));
+        }
+
+        // the circle's own arcs already give stretch under 1.6
+        assert_eq!(24, greedy_spanner(&graph, 3.0, |&w| w).edge_count());
+    }
+
+    #[test]
+    fn keeps_the_strongest_edges_per_node() {
+        let mut graph: DiGraph<(), u32> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        let e: Vec<_> = [(0, 1, 9), (0, 2, 5), (0, 3, 1), (2, 3, 4), (3, 3, 8)]
+            .into_iter()
+            .map(|(a, b, w)| graph.add_edge(n[a], n[b], w))
+            .collect();
+
+        let either = top_k_edges(&graph, 1, false, |&w| w as f64);
+        assert_eq!(vec![e[0], e[1], e[4]], either.edge_ids().collect::<Vec<_>>());
+
+        let both = top_k_edges(&graph, 1, true, |&w| w as f64);
+        assert_eq!(vec![e[0], e[4]], both.edge_ids().collect::<Vec<_>>());
+        assert_eq!(0, top_k_edges(&graph, 0, false, |&w| w as f64).edge_count());
+    }
+}
@@ -0,0 +1,319 @@
+use crate::bitset::FixedBitSet;
+use crate::graph::{Directed, EdgeId, EdgeType, Graph, NodeId, NodeIds};
+use crate::heap::{AddressableHeap, IndexedBinaryHeap};
+use crate::visit::{GraphBase, IntoNeighbors, Visitable};
+
+/// Half-open time interval `[start, end)`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T
+}
+
+impl<T: Copy + PartialOrd> Interval<T> {
+    pub fn new(start: T, end: T) -> Interval<T> {
+        Interval { start, end }
+    }
+
+    pub fn contains(&self, t: T) -> bool {
+        self.start <= t && t < self.end
+    }
+
+    /// Whether the intervals share at least one instant.
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+struct TemporalEdge<E, T> {
+    weight: E,
+    interval: Interval<T>
+}
+
+/// Graph whose edges only exist during a time interval, as in contact
+/// networks. Static views of it are taken with [`snapshot_at`] and
+/// [`window`].
+///
+/// [`snapshot_at`]: TemporalGraph::snapshot_at
+/// [`window`]: TemporalGraph::window
+pub struct TemporalGraph<N, E, T, Ty = Directed> {
+    graph: Graph<N, TemporalEdge<E, T>, Ty>
+}
+
+impl<N, E, T: Copy + PartialOrd, Ty: EdgeType> Default for TemporalGraph<N, E, T, Ty> {
+    fn default() -> Self {
+        TemporalGraph::new()
+    }
+}
+
+impl<N, E, T: Copy + PartialOrd, Ty: EdgeType> TemporalGraph<N, E, T, Ty> {
+    pub fn new() -> TemporalGraph<N, E, T, Ty> {
+        TemporalGraph { graph: Graph::new() }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn add_node(&mut self, weight: N) -> NodeId {
+        self.graph.add_node(weight)
+    }
+
+    /// Adds an edge that exists during `interval`. Panics if either endpoint
+    /// is not in the graph.
+    pub fn add_edge(&mut self, source: NodeId, target: NodeId, interval: Interval<T>, weight: E) -> EdgeId {
+        self.graph.add_edge(source, target, TemporalEdge { weight, interval })
+    }
+
+    pub fn remove_edge(&mut self, edge: EdgeId) -> Option<(Interval<T>, E)> {
+        self.graph.remove_edge(edge).map(|edge| (edge.interval, edge.weight))
+    }
+
+    pub fn remove_node(&mut self, node: NodeId) -> Option<N> {
+        self.graph.remove_node(node).map(|removed| removed.weight)
+    }
+
+    pub fn node_weight(&self, node: NodeId) -> Option<&N> {
+        self.graph.node_weight(node)
+    }
+
+    pub fn edge_weight(&self, edge: EdgeId) -> Option<&E> {
+        self.graph.edge_weight(edge).map(|edge| &edge.weight)
+    }
+
+    pub fn edge_interval(&self, edge: EdgeId) -> Option<Interval<T>> {
+        self.graph.edge_weight(edge).map(|edge| edge.interval)
+    }
+
+    pub fn edge_endpoints(&self, edge: EdgeId) -> Option<(NodeId, NodeId)> {
+        self.graph.edge_endpoints(edge)
+    }
+
+    pub fn node_ids(&self) -> NodeIds<'_, N> {
+        self.graph.node_ids()
+    }
+
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + '_ {
+        self.graph.edge_ids()
+    }
+
+    /// The graph as it is at instant `t`.
+    pub fn snapshot_at(&self, t: T) -> TemporalView<'_, N, E, T, Ty> {
+        TemporalView { graph: self, filter: Filter::At(t) }
+    }
+
+    /// Every edge present at some point of `[start, end)`.
+    pub fn window(&self, start: T, end: T) -> TemporalView<'_, N, E, T, Ty> {
+        TemporalView { graph: self, filter: Filter::Window(Interval::new(start, end)) }
+    }
+
+    /// Earliest arrival times from `source` when leaving at `departure`.
+    /// Traversing an edge takes no time but has to happen within its
+    /// interval and never before arriving at its tail, so paths respect
+    /// the order in which contacts occur.
+    pub fn earliest_arrival(&self, source: NodeId, departure: T) -> TemporalPaths<T> {
+        let bound = self.graph.node_bound();
+        let mut arrival = vec![None; bound];
+        let mut pred = vec![None; bound];
+        let mut settled = FixedBitSet::with_capacity(bound);
+        let mut heap = IndexedBinaryHeap::new();
+
+        arrival[source.index()] = Some(departure);
+        heap.push(source, departure);
+
+        while let Some((node, t)) = heap.pop_min() {
+            settled.insert(node.index());
+
+            let mut neighbors = self.graph.neighbors(node);
+
+            while let Some((edge, next)) = neighbors.next_edge() {
+                let interval = self.graph.edge_weight(edge).unwrap().interval;
+                let at = if interval.start > t { interval.start } else { t };
+
+                if settled.contains(next.index()) || !interval.contains(at) {
+                    continue;
+                }
+
+                if arrival[next.index()].is_some_and(|curr| at >= curr) {
+                    continue;
+                }
+
+                arrival[next.index()] = Some(at);
+                pred[next.index()] = Some(node);
+
+                if heap.contains(&next) {
+                    heap.decrease_key(&next, at);
+                } else {
+                    heap.push(next, at);
+                }
+            }
+        }
+
+        TemporalPaths { arrival, pred }
+    }
+
+    /// Whether `target` can be reached from `source` leaving at `departure`.
+    pub fn is_time_reachable(&self, source: NodeId, target: NodeId, departure: T) -> bool {
+        self.earliest_arrival(source, departure).arrival(target).is_some()
+    }
+}
+
+pub struct TemporalPaths<T> {
+    arrival: Vec<Option<T>>,
+    pred: Vec<Option<NodeId>>
+}
+
+impl<T: Copy> TemporalPaths<T> {
+    /// `None` if `node` cannot be reached in time order.
+    pub fn arrival(&self, node: NodeId) -> Option<T> {
+        self.arrival.get(node.index()).copied().flatten()
+    }
+
+    /// A time-respecting path from the source to `node`, both included.
+    pub fn path_to(&self, node: NodeId) -> Option<Vec<NodeId>> {
+        self.arrival(node)?;
+
+        let mut path = vec![node];
+        let mut curr = node;
+
+        while let Some(prev) = self.pred[curr.index()] {
+            path.push(prev);
+            curr = prev;
+        }
+
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Filter<T> {
+    At(T),
+    Window(Interval<T>)
+}
+
+/// Static view of a [`TemporalGraph`] holding only the edges active at an
+/// instant or during a window. All nodes are part of every view.
+pub struct TemporalView<'a, N, E, T, Ty = Directed> {
+    graph: &'a TemporalGraph<N, E, T, Ty>,
+    filter: Filter<T>
+}
+
+impl<N, E, T: Copy, Ty> Clone for TemporalView<'_, N, E, T, Ty> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<N, E, T: Copy, Ty> Copy for TemporalView<'_, N, E, T, Ty> {}
+
+impl<'a, N, E, T: Copy + PartialOrd, Ty: EdgeType> TemporalView<'a, N, E, T, Ty> {
+    pub fn contains_edge(&self, edge: EdgeId) -> bool {
+        self.graph.edge_interval(edge).is_some_and(|interval| match self.filter {
+            Filter::At(t) => interval.contains(t),
+            Filter::Window(window) => interval.overlaps(&window)
+        })
+    }
+
+    pub fn edge_ids(&self) -> impl Iterator<Item = EdgeId> + 'a {
+        let view = *self;
+        self.graph.graph.edge_ids().filter(move |&edge| view.contains_edge(edge))
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_ids().count()
+    }
+
+    /// Neighbors over the edges in view, see [`Graph::neighbors`].
+    pub fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + 'a {
+        let view = *self;
+        let mut neighbors = self.graph.graph.neighbors(node);
+
+        std::iter::from_fn(move || {
+            while let Some((edge, next)) = neighbors.next_edge() {
+                if view.contains_edge(edge) {
+                    return Some(next);
+                }
+            }
+
+            None
+        })
+    }
+}
+
+impl<N, E, T, Ty> GraphBase for TemporalView<'_, N, E, T, Ty> {
+    type NodeId = NodeId;
+}
+
+impl<N, E, T: Copy + PartialOrd, Ty: EdgeType> IntoNeighbors for TemporalView<'_, N, E, T, Ty> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        TemporalView::neighbors(self, node)
+    }
+}
+
+impl<N, E, T: Copy + PartialOrd, Ty: EdgeType> Visitable for TemporalView<'_, N, E, T, Ty> {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.graph.graph.node_bound())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.graph.graph.node_bound());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Undirected;
+    use crate::visit::Bfs;
+
+    // a -[1, 3)- b -[2, 4)- c -[0, 2)- d
+    fn contacts() -> (TemporalGraph<char, (), u32, Undirected>, Vec<NodeId>) {
+        let mut graph = TemporalGraph::new();
+        let n: Vec<_> = "abcd".chars().map(|c| graph.add_node(c)).collect();
+
+        graph.add_edge(n[0], n[1], Interval::new(1, 3), ());
+        graph.add_edge(n[1], n[2], Interval::new(2, 4), ());
+        graph.add_edge(n[2], n[3], Interval::new(0, 2), ());
+
+        (graph, n)
+    }
+
+    #[test]
+    fn views_filter_edges_by_time() {
+        let (graph, n) = contacts();
+
+        assert_eq!(2, graph.snapshot_at(1).edge_count());
+        assert_eq!(vec![n[1]], graph.snapshot_at(2).neighbors(n[0]).collect::<Vec<_>>());
+        assert_eq!(0, graph.snapshot_at(4).edge_count());
+        assert_eq!(3, graph.window(1, 3).edge_count());
+        assert_eq!(1, graph.window(3, 10).edge_count());
+
+        assert_eq!(2, Bfs::new(&graph.snapshot_at(0), n[3]).count());
+        assert_eq!(4, Bfs::new(&graph.window(0, 4), n[3]).count());
+    }
+
+    #[test]
+    fn earliest_arrival_respects_time_order() {
+        let (graph, n) = contacts();
+
+        let paths = graph.earliest_arrival(n[0], 0);
+
+        assert_eq!(Some(1), paths.arrival(n[1]));
+        assert_eq!(Some(2), paths.arrival(n[2]));
+        assert_eq!(None, paths.arrival(n[3]));
+        assert_eq!(Some(vec![n[0], n[1], n[2]]), paths.path_to(n[2]));
+
+        assert!(graph.is_time_reachable(n[3], n[0], 0));
+        assert!(!graph.is_time_reachable(n[3], n[0], 2));
+        assert!(!graph.is_time_reachable(n[0], n[1], 3));
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::algo::{connected_components, undirected_neighbors};
+use crate::attr::NodeMap;
+use crate::error::GraphError;
+use crate::graph::{DiGraph, Direction, EdgeId, EdgeType, Graph, NodeId, UnGraph};
+use crate::graphmap::GraphMap;
+
+/// Line graph: one node per edge of `graph`, weighted with that edge's id.
+/// Undirected, two nodes are adjacent when their edges share an endpoint,
+/// once however many they share. Directed, `a -> b` leads to every edge
+/// leaving `b`, so a self loop leads to itself. Coloring the nodes of the
+/// line graph colors the edges of `graph`.
+pub fn line_graph<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>) -> Graph<EdgeId, (), Ty> {
+    let mut line = Graph::new();
+    let mut remap = vec![None; graph.edge_bound()];
+
+    for edge in graph.edge_ids() {
+        remap[edge.index()] = Some(line.add_node(edge));
+    }
+
+    let node_of = |edge: EdgeId| remap[edge.index()].unwrap();
+
+    if Ty::is_directed() {
+        for edge in graph.edge_ids() {
+            let (_, target) = graph.edge_endpoints(edge).unwrap();
+            let mut leaving = graph.neighbors_directed(target, Direction::Outgoing);
+
+            while let Some((next, _)) = leaving.next_edge() {
+                line.add_edge(node_of(edge), node_of(next), ());
+            }
+        }
+
+        return line;
+    }
+
+    for node in graph.node_ids() {
+        let mut incident = Vec::new();
+        let mut around = graph.neighbors(node);
+
+        while let Some(pair) = around.next_edge() {
+            incident.push(pair);
+        }
+
+        for (i, &(e, a)) in incident.iter().enumerate() {
+            for &(f, b) in &incident[i + 1..] {
+                // parallel edges meet at both ends; join them at the lower one
+                if a == b && a != node && a.index() < node.index() {
+                    continue;
+                }
+
+                line.add_edge(node_of(e), node_of(f), ());
+            }
+        }
+    }
+
+    line
+}
+
+/// Dual of a connected plane graph, its embedding given by `rotation`:
+/// every node's incident edges in clockwise order. Each face becomes a node
+/// weighted with the nodes along its boundary walk, and each edge becomes
+/// an edge, weighted with its id, between the faces on either side. A
+/// bridge becomes a self loop.
+///
+/// Returns `None` if `graph` is not connected, has self loops, `rotation`
+/// does not list exactly the edges at every node, or the embedding is not
+/// planar by Euler's formula.
+pub fn planar_dual<N, E>(
+    graph: &UnGraph<N, E>,
+    rotation: &NodeMap<Vec<EdgeId>>
+) -> Option<UnGraph<Vec<NodeId>, EdgeId>> {
+    if graph.node_count() == 0 || connected_components(graph).len() != 1 {
+        return None;
+    }
+
+    // dart 2e leaves the source of edge e, dart 2e + 1 its target
+    let dart = |edge: EdgeId, from: NodeId| {
+        let (source, _) = graph.edge_endpoints(edge)?;
+        Some(2 * edge.index() + usize::from(source != from))
+    };
+
+    let mut position = vec![None; 2 * graph.edge_bound()];
+
+    for node in graph.node_ids() {
+        let edges = rotation.get(node)?;
+
+        if edges.len() != graph.degree(node) {
+            return None;
+        }
+
+        for (i, &edge) in edges.iter().enumerate() {
+            let (source, target) = graph.edge_endpoints(edge)?;
+
+            if source == target || (source != node && target != node) {
+                return None;
+            }
+
+            let slot = &mut position[dart(edge, node)?];
+
+            if slot.is_some() {
+                return None;
+            }
+
+            *slot = Some((node, i));
+        }
+    }
+
+    // the face to the left of a dart continues clockwise past its head
+    let next = |d: usize| {
+        let (head, i) = position[d ^ 1].unwrap();
+        let edges = &rotation[head];
+        dart(edges[(i + 1) % edges.len()], head).unwrap()
+    };
+
+    let mut face = vec![None; position.len()];
+    let mut dual = Graph::new();
+
+    for start in (0..position.len()).filter(|&d| position[d].is_some()) {
+        if face[start].is_some() {
+            continue;
+        }
+
+        let id = dual.add_node(Vec::new());
+        let mut d = start;
+
+        while face[d].is_none() {
+            face[d] = Some(id);
+            dual.node_weight_mut(id).unwrap().push(position[d].unwrap().0);
+            d = next(d);
+        }
+    }
+
+    if graph.node_count() + dual.node_count() != graph.edge_count() + 2 {
+        return None;
+    }
+
+    for edge in graph.edge_ids() {
+        let d = 2 * edge.index();
+        dual.add_edge(face[d].unwrap(), face[d + 1].unwrap(), edge);
+    }
+
+    Some(dual)
+}
+
+/// Scales the weights of the edges leaving every node to sum to one, as a
+/// random walk's transition probabilities. Nodes whose weights sum to zero
+/// are left alone.
+pub fn normalize_weights<N>(graph: &mut DiGraph<N, f64>) {
+    let mut totals = vec![0.0; graph.node_bound()];
+    let sources: Vec<_> = graph.edge_ids().map(|edge| graph.edge_endpoints(edge).unwrap().0).collect();
+
+    for (source, weight) in sources.iter().zip(graph.edge_weights()) {
+        totals[source.index()] += weight;
+    }
+
+    for (source, weight) in sources.iter().zip(graph.edge_weights_mut()) {
+        if totals[source.index()] != 0.0 {
+            *weight /= totals[source.index()];
+        }
+    }
+}
+
+/// Maps the weights linearly onto `[low, high]`, the smallest going to
+/// `low` and the largest to `high`. If they are all equal they become
+/// `low`.
+pub fn rescale_weights<N, Ty: EdgeType>(graph: &mut Graph<N, f64, Ty>, low: f64, high: f64) {
+    let min = graph.edge_weights().copied().fold(f64::INFINITY, f64::min);
+    let max = graph.edge_weights().copied().fold(f64::NEG_INFINITY, f64::max);
+    let span = max - min;
+
+    for weight in graph.edge_weights_mut() {
+        *weight = if span > 0.0 { low + (*weight - min) / span * (high - low) } else { low };
+    }
+}
+
+/// Replaces every weight by its reciprocal, turning similarities into
+/// distances and back. Zero becomes infinity.
+pub fn invert_weights<N, Ty: EdgeType>(graph: &mut Graph<N, f64, Ty>) {
+    for weight in graph.edge_weights_mut() {
+        *weight = 1.0 / *weight;
+    }
+}
+
+/// Divides every weight by the square root of the weighted degrees of its
+/// ends, as in the normalized Laplacian `I - D^-1/2 A D^-1/2`. A self loop
+/// counts twice toward its node's degree; edges at a node of degree zero
+/// are left alone.
+pub fn symmetric_normalize_weights<N>(graph: &mut UnGraph<N, f64>) {
+    let mut degree = vec![0.0; graph.node_bound()];
+    let ends: Vec<_> = graph.edge_ids().map(|edge| graph.edge_endpoints(edge).unwrap()).collect();
+
+    for (&(a, b), weight) in ends.iter().zip(graph.edge_weights()) {
+        degree[a.index()] += weight;
+        degree[b.index()] += weight;
+    }
+
+    for (&(a, b), weight) in ends.iter().zip(graph.edge_weights_mut()) {
+        let scale = (degree[a.index()] * degree[b.index()]).sqrt();
+
+        if scale != 0.0 {
+            *weight /= scale;
+        }
+    }
+}
+
+/// Copy of `graph` whose nodes are found by the label `label(node,
+/// weight)` gives them, such as a name shared with another source, so two
+/// graphs can be aligned by label before diffing or merging. A fixed
+/// mapping is passed as a closure looking it up, e.g. `|node, _|
+/// names[&node]`. Fails with the first two nodes given the same label.
+/// Nodes are added in [`node_ids`](Graph::node_ids) order, so their new
+/// ids are their positions in [`Graph::to_index_map`].
+pub fn relabel<N, E, Ty, K>(
+    graph: &Graph<N, E, Ty>,
+    mut label: impl FnMut(NodeId, &N) -> K
+) -> Result<GraphMap<K, N, E, Ty>, GraphError>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    K: Hash + Eq + Clone
+{
+    let mut relabeled = GraphMap::with_capacity(graph.node_count(), graph.edge_count());
+    let mut labels = NodeMap::with_capacity(graph.node_bound());
+    let mut first = HashMap::with_capacity(graph.node_count());
+
+    for node in graph.node_ids() {
+        let key = label(node, &graph[node]);
+
+        if let Some(&other) = first.get(&key) {
+            return Err(GraphError::DuplicateLabel(other, node));
+        }
+
+        first.insert(key.clone(), node);
+        labels.insert(node, key.clone());
+        relabeled.node_entry(key).or_insert(graph[node].clone());
+    }
+
+    for edge in graph.edge_ids() {
+        let (a, b) = graph.edge_endpoints(edge).unwrap();
+        relabeled.add_edge(&labels[a], &labels[b], graph[edge].clone());
+    }
+
+    Ok(relabeled)
+}
+
+/// Removes every node without edges, returning them.
+pub fn remove_isolated_nodes<N, E, Ty: EdgeType>(graph: &mut Graph<N, E, Ty>) -> Vec<NodeId> {
+    let isolated: Vec<_> =
+        graph.node_ids().filter(|&node| undirected_neighbors(graph, node).next().is_none()).collect();
+
+    for &node in &isolated {
+        graph.remove_node(node);
+    }
+
+    isolated
+}
+
+/// Repeatedly removes nodes whose degree passes `prunable`, counting edges
+/// either way with their multiplicity but not self loops, and returns them
+/// in the order removed.
+fn prune<N, E, Ty: EdgeType>(graph: &mut Graph<N, E, Ty>, prunable: impl Fn(usize) -> bool) -> Vec<NodeId> {
+    let mut degree = vec![0; graph.node_bound()];
+
+    for node in graph.node_ids() {
+        degree[node.index()] = undirected_neighbors(graph, node).filter(|&n| n != node).count();
+    }
+
+    let mut queue: Vec<_> = graph.node_ids().filter(|n| prunable(degree[n.index()])).collect();
+    let mut removed = Vec::new();
+
+    queue.reverse();
+
+    while let Some(node) = queue.pop() {
+        if !graph.contains_node(node) || !prunable(degree[node.index()]) {
+            continue;
+        }
+
+        for other in undirected_neighbors(graph, node).filter(|&n| n != node) {
+            degree[other.index()] -= 1;
+
+            if prunable(degree[other.index()]) {
+                queue.push(other);
+            }
+        }
+
+        graph.remove_node(node);
+        removed.push(node);
+    }
+
+    removed
+}
+
+/// Repeatedly removes nodes with a single neighbor, returning them in the
+/// order removed, so that trees shrink to one node and every other
+/// component to its 2-core with the trees hanging off it cut away. Edge
+/// direction is ignored, parallel edges count with their multiplicity and
+/// self loops are not counted.
+pub fn prune_leaves<N, E, Ty: EdgeType>(graph: &mut Graph<N, E, Ty>) -> Vec<NodeId> {
+    prune(graph, |degree| degree == 1)
+}
+
+/// Repeatedly removes nodes of degree below `k` until none is left,
+/// leaving the `k`-core as [`k_core`](crate::algo::k_core) would, in
+/// place. Returns the removed nodes in the order removed. Degrees count as
+/// for [`prune_leaves`].
+pub fn prune_below_degree<N, E, Ty: EdgeType>(graph: &mut Graph<N, E, Ty>, k: usize) -> Vec<NodeId> {
+    prune(graph, |degree| degree < k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::DiGraph;
+
+    #[test]
+    fn relabels_injectively() {
+        let mut graph: DiGraph<&str, u32> = Graph::new();
+        let [a, b, c] = ["a", "b", "c"].map(|name| graph.add_node(name));
+        graph.add_edge(a, b, 1);
+        graph.add_edge(a, b, 2);
+        graph.add_edge(c, a, 3);
+        graph.remove_node(b);
+        let d = graph.add_node("d");
+        graph.add_edge(c, d, 4);
+
+        let upper = relabel(&graph, |_, name| name.to_uppercase()).unwrap();
+        let (c2, d2) = (upper.node_id(&"C".to_string()).unwrap(), upper.node_id(&"D".to_string()).unwrap());
+
+        assert_eq!((3, 2), (upper.graph().node_count(), upper.graph().edge_count()));
+        assert_eq!(Some(&4), upper.graph().find_edge(c2, d2).and_then(|e| upper.graph().edge_weight(e)));
+        assert_eq!(graph.to_index_map()[d], d2.index());
+        assert_eq!(Some(&2), upper.to_index_map().get("C"));
+
+        let names: HashMap<_, _> = [(a, 1), (c, 2), (d, 1)].into_iter().collect();
+        let clash = relabel(&graph, |node, _| names[&node]).map(|_| ());
+        assert_eq!(Err(GraphError::DuplicateLabel(a, d)), clash);
+    }
+
+    #[test]
+    fn line_graphs_join_edges_sharing_endpoints() {
+        // a triangle with a pendant edge and a doubled edge
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+        let ab = graph.add_edge(n[0], n[1], ());
+        let bc = graph.add_edge(n[1], n[2], ());
+        let ca = graph.add_edge(n[2], n[0], ());
+        let cd = graph.add_edge(n[2], n[3], ());
+        let ab2 = graph.add_edge(n[0], n[1], ());
+
+        let line = line_graph(&graph);
+        let node = |edge| line.node_ids().find(|&x| line.node_weight(x) == Some(&edge)).unwrap();
+
+        assert_eq!(5, line.node_count());
+        assert_eq!(2, line.degree(node(cd)));
+        assert_eq!(3, line.degree(node(ab)));
+        assert!(line.find_edge(node(ab), node(ab2)).is_some());
+        assert!(line.find_edge(node(ab), node(cd)).is_none());
+        assert_eq!(8, line.edge_count());
+        assert!(line.find_edge(node(bc), node(ca)).is_some());
+
+        let mut path: DiGraph<(), ()> = Graph::new();
+        let p: Vec<_> = (0..3).map(|_| path.add_node(())).collect();
+        path.add_edge(p[0], p[1], ());
+        path.add_edge(p[1], p[2], ());
+        path.add_edge(p[2], p[2], ());
+
+        let line = line_graph(&path);
+        assert_eq!(3, line.edge_count());
+        assert_eq!(1, line.node_ids().filter(|&x| line.find_edge(x, x).is_some()).count());
+    }
+
+    fn embedded(n: usize, clockwise: &[&[usize]]) -> (UnGraph<(), ()>, NodeMap<Vec<EdgeId>>) {
+        let mut graph = Graph::new();
+        let nodes: Vec<_> = (0..n).map(|_| graph.add_node(())).collect();
+        let mut rotation = NodeMap::new();
+
+        for (u, around) in clockwise.iter().enumerate() {
+            let edges = around
+                .iter()
+                .map(|&v| match graph.find_edge(nodes[u], nodes[v]) {
+                    Some(edge) => edge,
+                    None => graph.add_edge(nodes[u], nodes[v], ())
+                })
+                .collect();
+            rotation.insert(nodes[u], edges);
+        }
+
+        (graph, rotation)
+    }
+
+    #[test]
+    fn duals_of_plane_graphs() {
+        let (triangle, rotation) = embedded(3, &[&[1, 2], &[2, 0], &[0, 1]]);
+        let dual = planar_dual(&triangle, &rotation).unwrap();
+
+        assert_eq!(2, dual.node_count());
+        assert_eq!(3, dual.edge_count());
+        assert!(dual.node_ids().all(|f| dual.node_weight(f).unwrap().len() == 3));
+
+        // K4 drawn with node 3 in the middle is self-dual
+        let (k4, rotation) = embedded(4, &[&[1, 3, 2], &[2, 3, 0], &[0, 3, 1], &[0, 1, 2]]);
+        let dual = planar_dual(&k4, &rotation).unwrap();
+
+        assert_eq!(4, dual.node_count());
+        assert!(dual.node_ids().all(|f| dual.degree(f) == 3));
+
+        // a path has one face, each edge a loop on it
+        let (path, rotation) = embedded(3, &[&[1], &[0, 2], &[1]]);
+        let dual = planar_dual(&path, &rotation).unwrap();
+
+        let walk = dual.node_weight(dual.node_ids().next().unwrap()).unwrap();
+
+        assert_eq!(1, dual.node_count());
+        assert_eq!(vec![0, 1, 2, 1], walk.iter().map(|n| n.index()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn rejects_non_planar_or_mismatched_rotations() {
+        let around: Vec<Vec<usize>> = (0..5).map(|u| (0..5).filter(|&v| v != u).collect()).collect();
+        let around: Vec<&[usize]> = around.iter().map(Vec::as_slice).collect();
+        let (k5, rotation) = embedded(5, &around);
+
+        assert!(planar_dual(&k5, &rotation).is_none());
+
+        let (triangle, mut rotation) = embedded(3, &[&[1, 2], &[2, 0], &[0, 1]]);
+        let first = triangle.node_ids().next().unwrap();
+        rotation[first].pop();
+
+        assert!(planar_dual(&triangle, &rotation).is_none());
+    }
+
+    #[test]
+    fn transforms_weights_in_place() {
+        let mut graph: DiGraph<(), f64> = Graph::new();
+        let n: Vec<_> = (0..3).map(|_| graph.add_node(())).collect();
+        let edges: Vec<_> = [(0, 1, 1.0), (0, 2, 3.0), (1, 2, 2.0), (2, 0, 0.0)]
+            .into_iter()
+            .map(|(a, b, w)| graph.add_edge(n[a], n[b], w))
+            .collect();
+        let weights = |graph: &DiGraph<(), f64>| -> Vec<f64> { edges.iter().map(|&e| graph[e]).collect() };
+
+        normalize_weights(&mut graph);
+        assert_eq!(vec![0.25, 0.75, 1.0, 0.0], weights(&graph));
+
+        rescale_weights(&mut graph, 1.0, 5.0);
+        assert_eq!(vec![2.0, 4.0, 5.0, 1.0], weights(&graph));
+
+        invert_weights(&mut graph);
+        assert_eq!(vec![0.5, 0.25, 0.2, 1.0], weights(&graph));
+
+        // a path weighted 1 and 3: degrees 1, 4 and 3
+        let mut path: UnGraph<(), f64> = Graph::new();
+        let m: Vec<_> = (0..3).map(|_| path.add_node(())).collect();
+        let first = path.add_edge(m[0], m[1], 1.0);
+        let second = path.add_edge(m[1], m[2], 3.0);
+
+        symmetric_normalize_weights(&mut path);
+        assert_eq!((0.5, 3.0 / 12f64.sqrt()), (path[first], path[second]));
+    }
+
+    #[test]
+    fn prunes_in_place() {
+        // a triangle with a two-node tail off node 2, and an isolated node
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..6).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 4)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        let mut leaves = graph.map(|_, _| (), |_, _| ());
+        assert_eq!(vec![n[4], n[3]], prune_leaves(&mut leaves));
+        assert_eq!(4, leaves.node_count());
+
+        let mut core = graph.map(|_, _| (), |_, _| ());
+        assert_eq!(vec![n[4], n[3], n[5]], prune_below_degree(&mut core, 2));
+        assert_eq!(vec![n[0], n[1], n[2]], core.node_ids().collect::<Vec<_>>());
+
+        assert_eq!(vec![n[5]], remove_isolated_nodes(&mut graph));
+        let mut gone = prune_below_degree(&mut graph, 3);
+        gone.sort();
+        assert_eq!(n[..5], gone);
+        assert_eq!(0, graph.node_count());
+    }
+}
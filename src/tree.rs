@@ -0,0 +1,463 @@
+use crate::bitset::FixedBitSet;
+use crate::graph::{NodeId, UnGraph};
+
+const NONE: usize = usize::MAX;
+
+pub fn is_tree<N, E>(graph: &UnGraph<N, E>) -> bool {
+    let count = graph.node_count();
+
+    if count == 0 || graph.edge_count() != count - 1 {
+        return false;
+    }
+
+    let start = graph.node_ids().next().unwrap();
+
+    bfs_order(graph, start).len() == count
+}
+
+fn bfs_order<N, E>(graph: &UnGraph<N, E>, start: NodeId) -> Vec<NodeId> {
+    let mut seen = FixedBitSet::with_capacity(graph.node_bound());
+    let mut order = vec![start];
+    let mut next = 0;
+
+    seen.insert(start.index());
+
+    while next < order.len() {
+        let node = order[next];
+        next += 1;
+
+        for neighbor in graph.neighbors(node) {
+            if !seen.put(neighbor.index()) {
+                order.push(neighbor);
+            }
+        }
+    }
+
+    order
+}
+
+// ids of the live nodes by slot index, for turning index tables back into
+// ids; entries for free slots are never read
+fn id_table<N, E>(graph: &UnGraph<N, E>) -> Vec<NodeId> {
+    let mut ids = vec![NodeId::new(0); graph.node_bound()];
+
+    for node in graph.node_ids() {
+        ids[node.index()] = node;
+    }
+
+    ids
+}
+
+struct Rooted {
+    ids: Vec<NodeId>,
+    order: Vec<NodeId>,
+    parent: Vec<usize>,
+    depth: Vec<usize>
+}
+
+impl Rooted {
+    fn new<N, E>(graph: &UnGraph<N, E>, root: NodeId) -> Option<Rooted> {
+        if !graph.contains_node(root) || !is_tree(graph) {
+            return None;
+        }
+
+        let order = bfs_order(graph, root);
+        let mut parent = vec![NONE; graph.node_bound()];
+        let mut depth = vec![0; graph.node_bound()];
+
+        parent[root.index()] = root.index();
+
+        for &node in &order {
+            for neighbor in graph.neighbors(node) {
+                if parent[neighbor.index()] == NONE {
+                    parent[neighbor.index()] = node.index();
+                    depth[neighbor.index()] = depth[node.index()] + 1;
+                }
+            }
+        }
+
+        Some(Rooted { ids: id_table(graph), order, parent, depth })
+    }
+}
+
+/// Lowest common ancestor queries in O(log n) after O(n log n)
+/// preprocessing.
+pub struct BinaryLiftingLca {
+    root: NodeId,
+    ids: Vec<NodeId>,
+    depth: Vec<usize>,
+    up: Vec<Vec<usize>>
+}
+
+impl BinaryLiftingLca {
+    /// Returns `None` if the graph is not a tree or does not contain `root`.
+    pub fn new<N, E>(graph: &UnGraph<N, E>, root: NodeId) -> Option<BinaryLiftingLca> {
+        let rooted = Rooted::new(graph, root)?;
+
+        let levels = usize::BITS - rooted.order.len().leading_zeros();
+        let mut up = vec![rooted.parent];
+
+        for k in 1..levels.max(1) as usize {
+            let prev = &up[k - 1];
+            let next = prev
+                .iter()
+                .map(|&p| if p == NONE { NONE } else { prev[p] })
+                .collect();
+            up.push(next);
+        }
+
+        Some(BinaryLiftingLca { root, ids: rooted.ids, depth: rooted.depth, up })
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn depth(&self, node: NodeId) -> usize {
+        self.depth[node.index()]
+    }
+
+    /// The ancestor `k` levels above `node`, or `None` past the root.
+    pub fn ancestor(&self, node: NodeId, k: usize) -> Option<NodeId> {
+        if k > self.depth(node) {
+            return None;
+        }
+
+        let mut curr = node.index();
+
+        for (level, up) in self.up.iter().enumerate() {
+            if k >> level & 1 == 1 {
+                curr = up[curr];
+            }
+        }
+
+        Some(self.ids[curr])
+    }
+
+    pub fn lca(&self, a: NodeId, b: NodeId) -> NodeId {
+        let (mut a, mut b) = if self.depth(a) >= self.depth(b) { (a, b) } else { (b, a) };
+
+        a = self.ancestor(a, self.depth(a) - self.depth(b)).unwrap();
+
+        if a == b {
+            return a;
+        }
+
+        let (mut a_idx, mut b_idx) = (a.index(), b.index());
+
+        for up in self.up.iter().rev() {
+            if up[a_idx] != up[b_idx] {
+                a_idx = up[a_idx];
+                b_idx = up[b_idx];
+            }
+        }
+
+        a = self.ids[self.up[0][a_idx]];
+        b = self.ids[self.up[0][b_idx]];
+
+        debug_assert_eq!(a, b);
+
+        a
+    }
+
+    pub fn distance(&self, a: NodeId, b: NodeId) -> usize {
+        self.depth(a) + self.depth(b) - 2 * self.depth(self.lca(a, b))
+    }
+}
+
+/// Lowest common ancestor queries in O(1) via an Euler tour and a sparse
+/// table of range minima, after O(n log n) preprocessing.
+pub struct EulerTourLca {
+    root: NodeId,
+    ids: Vec<NodeId>,
+    depth: Vec<usize>,
+    first: Vec<usize>,
+    table: Vec<Vec<usize>>
+}
+
+impl EulerTourLca {
+    /// Returns `None` if the graph is not a tree or does not contain `root`.
+    pub fn new<N, E>(graph: &UnGraph<N, E>, root: NodeId) -> Option<EulerTourLca> {
+        let rooted = Rooted::new(graph, root)?;
+
+        let mut children = vec![Vec::new(); graph.node_bound()];
+
+        for &node in &rooted.order[1..] {
+            children[rooted.parent[node.index()]].push(node.index());
+        }
+
+        let mut tour = Vec::with_capacity(2 * rooted.order.len() - 1);
+        let mut first = vec![NONE; graph.node_bound()];
+        let mut stack = vec![(root.index(), 0)];
+
+        while let Some((node, child)) = stack.pop() {
+            if child == 0 {
+                first[node] = tour.len();
+            }
+
+            tour.push(node);
+
+            if let Some(&next) = children[node].get(child) {
+                stack.push((node, child + 1));
+                stack.push((next, 0));
+            }
+        }
+
+        let depth = rooted.depth;
+        let mut table = vec![tour];
+
+        while 1 << table.len() <= table[0].len() {
+            let prev = table.last().unwrap();
+            let half = 1 << (table.len() - 1);
+            let next = (0..prev.len() - half)
+                .map(|i| {
+                    let (a, b) = (prev[i], prev[i + half]);
+                    if depth[a] <= depth[b] { a } else { b }
+                })
+                .collect();
+            table.push(next);
+        }
+
+        Some(EulerTourLca { root, ids: rooted.ids, depth, first, table })
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn depth(&self, node: NodeId) -> usize {
+        self.depth[node.index()]
+    }
+
+    pub fn lca(&self, a: NodeId, b: NodeId) -> NodeId {
+        let (mut lo, mut hi) = (self.first[a.index()], self.first[b.index()]);
+
+        if lo > hi {
+            std::mem::swap(&mut lo, &mut hi);
+        }
+
+        let level = (usize::BITS - (hi - lo + 1).leading_zeros() - 1) as usize;
+        let row = &self.table[level];
+        let (x, y) = (row[lo], row[hi + 1 - (1 << level)]);
+
+        self.ids[if self.depth[x] <= self.depth[y] { x } else { y }]
+    }
+
+    pub fn distance(&self, a: NodeId, b: NodeId) -> usize {
+        self.depth(a) + self.depth(b) - 2 * self.depth(self.lca(a, b))
+    }
+}
+
+/// Longest path in the tree, counted in edges.
+pub fn diameter<N, E>(graph: &UnGraph<N, E>) -> Option<usize> {
+    diameter_path(graph).map(|path| path.len() - 1)
+}
+
+/// Nodes along a longest path of the tree, found with two breadth-first
+/// sweeps.
+pub fn diameter_path<N, E>(graph: &UnGraph<N, E>) -> Option<Vec<NodeId>> {
+    let start = graph.node_ids().next()?;
+
+    let rooted = Rooted::new(graph, start)?;
+    let far = *rooted.order.last().unwrap();
+
+    let rooted = Rooted::new(graph, far)?;
+    let mut curr = rooted.order.last().unwrap().index();
+    let mut path = vec![rooted.ids[curr]];
+
+    while curr != far.index() {
+        curr = rooted.parent[curr];
+        path.push(rooted.ids[curr]);
+    }
+
+    Some(path)
+}
+
+/// Centroid decomposition: every node is a centroid of exactly one
+/// component, and the centroids form a tree of depth O(log n).
+pub struct CentroidDecomposition {
+    root: NodeId,
+    ids: Vec<NodeId>,
+    parent: Vec<usize>,
+    level: Vec<usize>
+}
+
+impl CentroidDecomposition {
+    /// Returns `None` if the graph is not a tree.
+    pub fn new<N, E>(graph: &UnGraph<N, E>) -> Option<CentroidDecomposition> {
+        if !is_tree(graph) {
+            return None;
+        }
+
+        let bound = graph.node_bound();
+        let mut removed = FixedBitSet::with_capacity(bound);
+        let mut parent = vec![NONE; bound];
+        let mut level = vec![0; bound];
+        let mut size = vec![0; bound];
+        let mut bfs_parent = vec![NONE; bound];
+        let mut root = None;
+
+        let start = graph.node_ids().next().unwrap();
+        let mut pending = vec![(start, NONE, 0)];
+
+        while let Some((entry, centroid_parent, depth)) = pending.pop() {
+            let mut order = vec![entry];
+            let mut next = 0;
+
+            bfs_parent[entry.index()] = NONE;
+
+            while next < order.len() {
+                let node = order[next];
+                next += 1;
+
+                for neighbor in graph.neighbors(node) {
+                    if !removed.contains(neighbor.index()) && bfs_parent[node.index()] != neighbor.index() {
+                        bfs_parent[neighbor.index()] = node.index();
+                        order.push(neighbor);
+                    }
+                }
+            }
+
+            for &node in order.iter().rev() {
+                size[node.index()] = 1 + graph
+                    .neighbors(node)
+                    .filter(|n| !removed.contains(n.index()) && bfs_parent[n.index()] == node.index())
+                    .map(|n| size[n.index()])
+                    .sum::<usize>();
+            }
+
+            let total = order.len();
+            let mut centroid = entry;
+
+            while let Some(heavy) = graph.neighbors(centroid).find(|n| {
+                !removed.contains(n.index())
+                    && bfs_parent[n.index()] == centroid.index()
+                    && size[n.index()] > total / 2
+            }) {
+                centroid = heavy;
+            }
+
+            removed.insert(centroid.index());
+            parent[centroid.index()] = centroid_parent;
+            level[centroid.index()] = depth;
+            root.get_or_insert(centroid);
+
+            for neighbor in graph.neighbors(centroid) {
+                if !removed.contains(neighbor.index()) {
+                    pending.push((neighbor, centroid.index(), depth + 1));
+                }
+            }
+        }
+
+        Some(CentroidDecomposition { root: root.unwrap(), ids: id_table(graph), parent, level })
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    /// The centroid whose removal split off the component `node` is the
+    /// centroid of.
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        match self.parent[node.index()] {
+            NONE => None,
+            p => Some(self.ids[p])
+        }
+    }
+
+    pub fn level(&self, node: NodeId) -> usize {
+        self.level[node.index()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Graph;
+
+    //       0
+    //      / \
+    //     1   2
+    //    / \   \
+    //   3   4   5
+    //           |
+    //           6
+    fn sample_tree() -> (UnGraph<(), ()>, Vec<NodeId>) {
+        let mut graph = Graph::new();
+        let nodes: Vec<_> = (0..7).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 1), (0, 2), (1, 3), (1, 4), (2, 5), (5, 6)] {
+            graph.add_edge(nodes[a], nodes[b], ());
+        }
+
+        (graph, nodes)
+    }
+
+    #[test]
+    fn is_tree_rejects_cycles_and_forests() {
+        let (mut graph, nodes) = sample_tree();
+
+        assert!(is_tree(&graph));
+
+        let extra = graph.add_edge(nodes[3], nodes[6], ());
+        assert!(!is_tree(&graph));
+
+        graph.remove_edge(extra);
+        graph.add_node(());
+        assert!(!is_tree(&graph));
+
+        assert!(!is_tree(&UnGraph::<(), ()>::new()));
+    }
+
+    #[test]
+    fn lca_implementations_agree() {
+        let (graph, n) = sample_tree();
+
+        let lifting = BinaryLiftingLca::new(&graph, n[0]).unwrap();
+        let euler = EulerTourLca::new(&graph, n[0]).unwrap();
+
+        for &a in &n {
+            for &b in &n {
+                assert_eq!(lifting.lca(a, b), euler.lca(a, b));
+                assert_eq!(lifting.distance(a, b), euler.distance(a, b));
+            }
+        }
+
+        assert_eq!(n[1], lifting.lca(n[3], n[4]));
+        assert_eq!(n[0], euler.lca(n[4], n[6]));
+        assert_eq!(n[2], euler.lca(n[2], n[6]));
+        assert_eq!(5, lifting.distance(n[3], n[6]));
+        assert_eq!(Some(n[2]), lifting.ancestor(n[6], 2));
+        assert_eq!(None, lifting.ancestor(n[6], 4));
+    }
+
+    #[test]
+    fn diameter_of_sample_tree() {
+        let (graph, n) = sample_tree();
+
+        let path = diameter_path(&graph).unwrap();
+
+        assert_eq!(Some(5), diameter(&graph));
+        assert_eq!(6, path.len());
+        assert!(path.contains(&n[6]));
+        assert_eq!(None, diameter(&UnGraph::<(), ()>::new()));
+    }
+
+    #[test]
+    fn centroid_decomposition_of_path() {
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..7).map(|_| graph.add_node(())).collect();
+
+        for w in n.windows(2) {
+            graph.add_edge(w[0], w[1], ());
+        }
+
+        let decomposition = CentroidDecomposition::new(&graph).unwrap();
+
+        assert_eq!(n[3], decomposition.root());
+        assert_eq!(None, decomposition.parent(n[3]));
+        assert_eq!(Some(n[3]), decomposition.parent(n[1]));
+        assert_eq!(Some(n[1]), decomposition.parent(n[0]));
+        assert!(n.iter().all(|&node| decomposition.level(node) <= 2));
+    }
+}
@@ -0,0 +1,759 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::mem;
+
+use crate::algo::Control;
+use crate::bitset::FixedBitSet;
+use crate::graph::{EdgeType, Graph, NodeId};
+
+pub trait GraphBase {
+    type NodeId: Copy + Eq + Hash;
+}
+
+pub trait IntoNeighbors: GraphBase {
+    /// Successors for directed graphs, all adjacent nodes otherwise.
+    fn neighbors(&self, node: Self::NodeId) -> impl Iterator<Item = Self::NodeId> + '_;
+}
+
+/// Set of already visited nodes, as tracked by traversals.
+pub trait VisitMap<N> {
+    /// Marks `node` as visited and returns `true` if it was not before.
+    fn visit(&mut self, node: N) -> bool;
+
+    fn is_visited(&self, node: &N) -> bool;
+}
+
+/// Graphs that can hand out a visit map sized for their nodes.
+pub trait Visitable: GraphBase {
+    type Map: VisitMap<Self::NodeId>;
+
+    fn visit_map(&self) -> Self::Map;
+
+    /// Clears `map` and makes it large enough for the current nodes.
+    fn reset_map(&self, map: &mut Self::Map);
+}
+
+/// Grows to fit nodes added after the map was made.
+impl VisitMap<NodeId> for FixedBitSet {
+    fn visit(&mut self, node: NodeId) -> bool {
+        if node.index() >= self.len() {
+            self.grow((node.index() + 1).max(2 * self.len()));
+        }
+
+        !self.put(node.index())
+    }
+
+    fn is_visited(&self, node: &NodeId) -> bool {
+        self.contains(node.index())
+    }
+}
+
+impl<N: Eq + Hash> VisitMap<N> for HashSet<N> {
+    fn visit(&mut self, node: N) -> bool {
+        self.insert(node)
+    }
+
+    fn is_visited(&self, node: &N) -> bool {
+        self.contains(node)
+    }
+}
+
+impl<N, E, Ty> GraphBase for Graph<N, E, Ty> {
+    type NodeId = NodeId;
+}
+
+impl<N, E, Ty: EdgeType> IntoNeighbors for Graph<N, E, Ty> {
+    fn neighbors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        Graph::neighbors(self, node)
+    }
+}
+
+impl<N, E, Ty: EdgeType> Visitable for Graph<N, E, Ty> {
+    type Map = FixedBitSet;
+
+    fn visit_map(&self) -> FixedBitSet {
+        FixedBitSet::with_capacity(self.node_bound())
+    }
+
+    fn reset_map(&self, map: &mut FixedBitSet) {
+        map.clear();
+        map.grow(self.node_bound());
+    }
+}
+
+/// Traversal state advanced one step at a time against a graph handed in
+/// on every call, so that nothing borrows the graph between steps.
+pub trait Walker<G> {
+    type Item;
+
+    fn walk_next(&mut self, graph: G) -> Option<Self::Item>;
+
+    /// Iterator running the walker over `graph` to the end.
+    fn iter(self, graph: G) -> WalkerIter<Self, G>
+    where
+        Self: Sized,
+        G: Copy
+    {
+        WalkerIter { walker: self, graph }
+    }
+}
+
+/// Iterator over a [`Walker`] bound to one graph, from [`Walker::iter`].
+pub struct WalkerIter<W, G> {
+    walker: W,
+    graph: G
+}
+
+impl<W: Walker<G>, G: Copy> Iterator for WalkerIter<W, G> {
+    type Item = W::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walker.walk_next(self.graph)
+    }
+}
+
+/// State of a [`Bfs`] that does not hold on to the graph, so node weights
+/// can be changed between steps.
+pub struct BfsWalker<Id, M> {
+    queue: VecDeque<Id>,
+    discovered: M
+}
+
+impl<Id: Copy, M: VisitMap<Id>> BfsWalker<Id, M> {
+    pub fn new<G: Visitable<NodeId = Id, Map = M>>(graph: &G, start: Id) -> BfsWalker<Id, M> {
+        let mut discovered = graph.visit_map();
+
+        discovered.visit(start);
+
+        BfsWalker { queue: VecDeque::from([start]), discovered }
+    }
+
+    /// Starts over from `start`, reusing the queue and visit map.
+    pub fn restart<G: Visitable<NodeId = Id, Map = M>>(&mut self, graph: &G, start: Id) {
+        graph.reset_map(&mut self.discovered);
+        self.discovered.visit(start);
+        self.queue.clear();
+        self.queue.push_back(start);
+    }
+
+    /// Next node in discovery order. `graph` should be the one the walker
+    /// was made for; nodes added since are walked like the others.
+    pub fn next<G: IntoNeighbors<NodeId = Id>>(&mut self, graph: &G) -> Option<Id> {
+        let node = self.queue.pop_front()?;
+
+        for next in graph.neighbors(node) {
+            if self.discovered.visit(next) {
+                self.queue.push_back(next);
+            }
+        }
+
+        Some(node)
+    }
+}
+
+impl<G: IntoNeighbors, M: VisitMap<G::NodeId>> Walker<&G> for BfsWalker<G::NodeId, M> {
+    type Item = G::NodeId;
+
+    fn walk_next(&mut self, graph: &G) -> Option<G::NodeId> {
+        self.next(graph)
+    }
+}
+
+/// State of a [`Dfs`] that does not hold on to the graph.
+pub struct DfsWalker<Id, M> {
+    stack: Vec<Id>,
+    finished: M
+}
+
+impl<Id: Copy, M: VisitMap<Id>> DfsWalker<Id, M> {
+    pub fn new<G: Visitable<NodeId = Id, Map = M>>(graph: &G, start: Id) -> DfsWalker<Id, M> {
+        DfsWalker { stack: vec![start], finished: graph.visit_map() }
+    }
+
+    /// Starts over from `start`, reusing the stack and visit map.
+    pub fn restart<G: Visitable<NodeId = Id, Map = M>>(&mut self, graph: &G, start: Id) {
+        graph.reset_map(&mut self.finished);
+        self.stack.clear();
+        self.stack.push(start);
+    }
+
+    /// Next node in preorder.
+    pub fn next<G: IntoNeighbors<NodeId = Id>>(&mut self, graph: &G) -> Option<Id> {
+        while let Some(node) = self.stack.pop() {
+            if !self.finished.visit(node) {
+                continue;
+            }
+
+            let start = self.stack.len();
+
+            self.stack.extend(graph.neighbors(node).filter(|next| !self.finished.is_visited(next)));
+
+            // reversed so neighbors are entered in iteration order
+            self.stack[start..].reverse();
+
+            return Some(node);
+        }
+
+        None
+    }
+}
+
+impl<G: IntoNeighbors, M: VisitMap<G::NodeId>> Walker<&G> for DfsWalker<G::NodeId, M> {
+    type Item = G::NodeId;
+
+    fn walk_next(&mut self, graph: &G) -> Option<G::NodeId> {
+        self.next(graph)
+    }
+}
+
+/// Breadth-first traversal yielding nodes in the order they are discovered.
+pub struct Bfs<'a, G: Visitable> {
+    graph: &'a G,
+    walker: BfsWalker<G::NodeId, G::Map>
+}
+
+impl<'a, G: Visitable> Bfs<'a, G> {
+    pub fn new(graph: &'a G, start: G::NodeId) -> Bfs<'a, G> {
+        Bfs { graph, walker: BfsWalker::new(graph, start) }
+    }
+}
+
+impl<G: Visitable + IntoNeighbors> Iterator for Bfs<'_, G> {
+    type Item = G::NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walker.next(self.graph)
+    }
+}
+
+/// Breadth-first traversal yielding one frontier at a time, from
+/// [`bfs_layers`].
+pub struct BfsLayers<'a, G: Visitable> {
+    graph: &'a G,
+    frontier: Vec<G::NodeId>,
+    discovered: G::Map
+}
+
+/// Nodes reachable from `start` grouped by their distance from it in hops,
+/// nearest first: `start` alone, then its neighbors, then theirs. Within a
+/// layer, nodes come in the order [`Bfs`] discovers them.
+pub fn bfs_layers<G: Visitable>(graph: &G, start: G::NodeId) -> BfsLayers<'_, G> {
+    let mut discovered = graph.visit_map();
+
+    discovered.visit(start);
+
+    BfsLayers { graph, frontier: vec![start], discovered }
+}
+
+impl<G: Visitable + IntoNeighbors> Iterator for BfsLayers<'_, G> {
+    type Item = Vec<G::NodeId>;
+
+    fn next(&mut self) -> Option<Vec<G::NodeId>> {
+        if self.frontier.is_empty() {
+            return None;
+        }
+
+        let mut next = Vec::new();
+
+        for &node in &self.frontier {
+            next.extend(self.graph.neighbors(node).filter(|&n| self.discovered.visit(n)));
+        }
+
+        Some(mem::replace(&mut self.frontier, next))
+    }
+}
+
+/// Depth-first traversal yielding nodes in preorder.
+pub struct Dfs<'a, G: Visitable> {
+    graph: &'a G,
+    walker: DfsWalker<G::NodeId, G::Map>
+}
+
+impl<'a, G: Visitable> Dfs<'a, G> {
+    pub fn new(graph: &'a G, start: G::NodeId) -> Dfs<'a, G> {
+        Dfs { graph, walker: DfsWalker::new(graph, start) }
+    }
+}
+
+impl<G: Visitable + IntoNeighbors> Iterator for Dfs<'_, G> {
+    type Item = G::NodeId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.walker.next(self.graph)
+    }
+}
+
+/// Depth-first traversal yielding the nodes within `limit` steps of the
+/// start once each, in preorder, with their depth along the path taken. A
+/// node reached again by a shorter path is explored again from there, so
+/// none within the limit is missed.
+pub struct DfsLimited<'a, G: Visitable> {
+    graph: &'a G,
+    stack: Vec<(G::NodeId, usize)>,
+    depth: HashMap<G::NodeId, usize>,
+    yielded: G::Map,
+    limit: usize
+}
+
+impl<'a, G: Visitable> DfsLimited<'a, G> {
+    pub fn new(graph: &'a G, start: G::NodeId, limit: usize) -> DfsLimited<'a, G> {
+        DfsLimited {
+            graph,
+            stack: vec![(start, 0)],
+            depth: HashMap::new(),
+            yielded: graph.visit_map(),
+            limit
+        }
+    }
+}
+
+impl<G: Visitable + IntoNeighbors> Iterator for DfsLimited<'_, G> {
+    type Item = (G::NodeId, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth)) = self.stack.pop() {
+            if self.depth.get(&node).is_some_and(|&seen| seen <= depth) {
+                continue;
+            }
+
+            self.depth.insert(node, depth);
+
+            if depth < self.limit {
+                let start = self.stack.len();
+
+                self.stack.extend(self.graph.neighbors(node).map(|next| (next, depth + 1)));
+                self.stack[start..].reverse();
+            }
+
+            if self.yielded.visit(node) {
+                return Some((node, depth));
+            }
+        }
+
+        None
+    }
+}
+
+/// Iterative deepening: depth-limited searches from `start` for a node
+/// satisfying `goal`, with limits `0, 1, …, max_depth`, returning the
+/// first path found, which has the fewest steps. Memory stays linear in the
+/// depth, at the cost of revisiting shallow nodes. Stops early once a
+/// search reaches no node at its limit that has neighbors left.
+pub fn iddfs<G: IntoNeighbors>(
+    graph: &G,
+    start: G::NodeId,
+    max_depth: usize,
+    mut goal: impl FnMut(G::NodeId) -> bool
+) -> Option<Vec<G::NodeId>> {
+    if goal(start) {
+        return Some(vec![start]);
+    }
+
+    let reversed = |node| {
+        let mut next: Vec<_> = graph.neighbors(node).collect();
+        next.reverse();
+        next
+    };
+
+    for limit in 1..=max_depth {
+        let mut path = vec![start];
+        let mut pending = vec![reversed(start)];
+        let mut cut_off = false;
+
+        while let Some(frame) = pending.last_mut() {
+            let Some(next) = frame.pop() else {
+                pending.pop();
+                path.pop();
+                continue;
+            };
+
+            // only cycles along the current path are avoided
+            if path.contains(&next) {
+                continue;
+            }
+
+            path.push(next);
+
+            if goal(next) {
+                return Some(path);
+            }
+
+            if path.len() <= limit {
+                pending.push(reversed(next));
+            } else {
+                cut_off |= graph.neighbors(next).next().is_some();
+                path.pop();
+            }
+        }
+
+        if !cut_off {
+            break;
+        }
+    }
+
+    None
+}
+
+/// What [`bfs_visit`] reports, in the order it happens.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BfsEvent<Id> {
+    /// First time a node is reached. `Prune` leaves its neighbors out.
+    Discover(Id),
+    /// Edge to a node not reached before, reported ahead of its discovery.
+    /// `Prune` leaves the node undiscovered through this edge.
+    TreeEdge(Id, Id),
+    /// Edge to a node already reached.
+    NonTreeEdge(Id, Id)
+}
+
+/// Breadth-first traversal from `start` reporting every step to `visitor`,
+/// which can stop it with [`Control::Break`], returned as is, or prune
+/// parts of it. Returns [`Control::Continue`] once everything reachable is
+/// done.
+pub fn bfs_visit<G, B>(
+    graph: &G,
+    start: G::NodeId,
+    mut visitor: impl FnMut(BfsEvent<G::NodeId>) -> Control<B>
+) -> Control<B>
+where
+    G: IntoNeighbors + Visitable
+{
+    let mut discovered = graph.visit_map();
+    let mut queue = VecDeque::new();
+
+    discovered.visit(start);
+
+    match visitor(BfsEvent::Discover(start)) {
+        Control::Break(value) => return Control::Break(value),
+        Control::Prune => {},
+        Control::Continue => queue.push_back(start)
+    }
+
+    while let Some(node) = queue.pop_front() {
+        for next in graph.neighbors(node) {
+            if discovered.is_visited(&next) {
+                if let Control::Break(value) = visitor(BfsEvent::NonTreeEdge(node, next)) {
+                    return Control::Break(value);
+                }
+
+                continue;
+            }
+
+            match visitor(BfsEvent::TreeEdge(node, next)) {
+                Control::Break(value) => return Control::Break(value),
+                Control::Prune => continue,
+                Control::Continue => {}
+            }
+
+            discovered.visit(next);
+
+            match visitor(BfsEvent::Discover(next)) {
+                Control::Break(value) => return Control::Break(value),
+                Control::Prune => {},
+                Control::Continue => queue.push_back(next)
+            }
+        }
+    }
+
+    Control::Continue
+}
+
+/// What [`dfs_visit`] reports, in the order it happens.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DfsEvent<Id> {
+    /// First time a node is entered. `Prune` leaves its neighbors out,
+    /// finishing it right away.
+    Discover(Id),
+    /// Edge to a node not entered before, which is entered next. `Prune`
+    /// skips the edge.
+    TreeEdge(Id, Id),
+    /// Edge back to a node still being explored, closing a cycle in a
+    /// directed graph. Undirected graphs report the tree edge they came in
+    /// by here too.
+    BackEdge(Id, Id),
+    /// Edge to a node already finished.
+    CrossForwardEdge(Id, Id),
+    /// All neighbors of a node are done.
+    Finish(Id)
+}
+
+/// Depth-first traversal from `start` reporting every step to `visitor`,
+/// which can stop it with [`Control::Break`], returned as is, or prune
+/// parts of it. Returns [`Control::Continue`] once everything reachable is
+/// done.
+pub fn dfs_visit<G, B>(
+    graph: &G,
+    start: G::NodeId,
+    mut visitor: impl FnMut(DfsEvent<G::NodeId>) -> Control<B>
+) -> Control<B>
+where
+    G: IntoNeighbors + Visitable
+{
+    let mut discovered = graph.visit_map();
+    let mut finished = graph.visit_map();
+    let mut stack = Vec::new();
+
+    discovered.visit(start);
+
+    match visitor(DfsEvent::Discover(start)) {
+        Control::Break(value) => return Control::Break(value),
+        Control::Prune => {
+            finished.visit(start);
+
+            return match visitor(DfsEvent::Finish(start)) {
+                Control::Break(value) => Control::Break(value),
+                _ => Control::Continue
+            };
+        },
+        Control::Continue => stack.push((start, graph.neighbors(start)))
+    }
+
+    while let Some((node, neighbors)) = stack.last_mut() {
+        let node = *node;
+
+        let Some(next) = neighbors.next() else {
+            stack.pop();
+            finished.visit(node);
+
+            if let Control::Break(value) = visitor(DfsEvent::Finish(node)) {
+                return Control::Break(value);
+            }
+
+            continue;
+        };
+
+        let event = if !discovered.is_visited(&next) {
+            DfsEvent::TreeEdge(node, next)
+        } else if !finished.is_visited(&next) {
+            DfsEvent::BackEdge(node, next)
+        } else {
+            DfsEvent::CrossForwardEdge(node, next)
+        };
+
+        match visitor(event) {
+            Control::Break(value) => return Control::Break(value),
+            Control::Prune => continue,
+            Control::Continue => {}
+        }
+
+        if !matches!(event, DfsEvent::TreeEdge(..)) {
+            continue;
+        }
+
+        discovered.visit(next);
+
+        match visitor(DfsEvent::Discover(next)) {
+            Control::Break(value) => return Control::Break(value),
+            Control::Prune => {
+                finished.visit(next);
+
+                if let Control::Break(value) = visitor(DfsEvent::Finish(next)) {
+                    return Control::Break(value);
+                }
+            },
+            Control::Continue => stack.push((next, graph.neighbors(next)))
+        }
+    }
+
+    Control::Continue
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+
+    //   0 -> 1 -> 3
+    //   |         ^
+    //   v         |
+    //   2 -> 4 ---+
+    fn sample() -> (DiGraph<(), ()>, Vec<NodeId>) {
+        let mut graph = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+
+        for (a, b) in [(0, 1), (0, 2), (1, 3), (2, 4), (4, 3)] {
+            graph.add_edge(n[a], n[b], ());
+        }
+
+        (graph, n)
+    }
+
+    #[test]
+    fn bfs_visits_level_by_level() {
+        let (graph, n) = sample();
+
+        let order: Vec<_> = Bfs::new(&graph, n[0]).collect();
+
+        assert_eq!(order, vec![n[0], n[1], n[2], n[3], n[4]]);
+    }
+
+    #[test]
+    fn dfs_visits_in_preorder() {
+        let (graph, n) = sample();
+
+        let order: Vec<_> = Dfs::new(&graph, n[0]).collect();
+
+        assert_eq!(order, vec![n[0], n[1], n[3], n[2], n[4]]);
+    }
+
+    #[test]
+    fn hash_set_visit_map() {
+        let mut map = HashSet::new();
+
+        assert!(map.visit("a"));
+        assert!(!map.visit("a"));
+        assert!(map.is_visited(&"a"));
+        assert!(!map.is_visited(&"b"));
+
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let a = graph.add_node(());
+        let b = graph.add_node(());
+        graph.add_edge(a, b, ());
+
+        let mut bits = graph.visit_map();
+        graph.add_node(());
+        graph.reset_map(&mut bits);
+
+        assert_eq!(3, bits.len());
+        assert_eq!(2, Dfs::new(&graph, a).count());
+    }
+
+    #[test]
+    fn walkers_let_the_graph_change_between_steps() {
+        let (graph, n) = sample();
+        let mut order = graph.map(|_, _| usize::MAX, |_, _| ());
+        let mut walker = BfsWalker::new(&order, n[0]);
+        let mut seen = 0;
+
+        while let Some(node) = walker.next(&order) {
+            order[node] = seen;
+            seen += 1;
+        }
+
+        assert_eq!(vec![0, 1, 2, 3, 4], n.iter().map(|&node| order[node]).collect::<Vec<_>>());
+
+        let walked: Vec<_> = DfsWalker::new(&graph, n[0]).iter(&graph).collect();
+        assert_eq!(Dfs::new(&graph, n[0]).collect::<Vec<_>>(), walked);
+
+        walker.restart(&order, n[2]);
+        assert_eq!(Some(n[2]), walker.next(&order));
+        assert_eq!(vec![n[4], n[3]], std::iter::from_fn(|| walker.next(&order)).collect::<Vec<_>>());
+
+        let mut dfs = DfsWalker::new(&graph, n[0]);
+        while dfs.next(&graph).is_some() {}
+        dfs.restart(&graph, n[1]);
+        assert_eq!(vec![n[1], n[3]], dfs.iter(&graph).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn walkers_reach_nodes_added_mid_walk() {
+        let (mut graph, n) = sample();
+        let mut bfs = BfsWalker::new(&graph, n[0]);
+        let mut dfs = DfsWalker::new(&graph, n[0]);
+
+        assert_eq!((Some(n[0]), Some(n[0])), (bfs.next(&graph), dfs.next(&graph)));
+
+        // added past the bound both visit maps were sized for
+        let late: Vec<_> = (0..70).map(|_| graph.add_node(())).collect();
+        graph.add_edge(n[3], late[69], ());
+        graph.add_edge(late[69], late[0], ());
+
+        let walked: Vec<_> = std::iter::from_fn(|| bfs.next(&graph)).collect();
+        assert_eq!(vec![n[1], n[2], n[3], n[4], late[69], late[0]], walked);
+        assert_eq!(vec![n[1], n[3], late[69], late[0], n[2], n[4]], dfs.iter(&graph).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn layers_by_hop_distance() {
+        let (mut graph, n) = sample();
+        graph.add_edge(n[3], n[0], ());
+
+        let layers: Vec<_> = bfs_layers(&graph, n[0]).collect();
+        assert_eq!(vec![vec![n[0]], vec![n[1], n[2]], vec![n[3], n[4]]], layers);
+        assert_eq!(Bfs::new(&graph, n[0]).collect::<Vec<_>>(), layers.concat());
+
+        let from_leaf: Vec<_> = bfs_layers(&graph, n[4]).collect();
+        assert_eq!(vec![vec![n[4]], vec![n[3]], vec![n[0]], vec![n[1], n[2]]], from_leaf);
+    }
+
+    #[test]
+    fn visitors_stop_and_prune() {
+        let (mut graph, n) = sample();
+
+        let parent = bfs_visit(&graph, n[0], |event| match event {
+            BfsEvent::TreeEdge(from, to) if to == n[4] => Control::Break(from),
+            _ => Control::Continue
+        });
+        assert_eq!(Some(n[2]), parent.break_value());
+
+        let mut reached = Vec::new();
+        let pruned: Control = bfs_visit(&graph, n[0], |event| match event {
+            BfsEvent::Discover(node) if node == n[2] => Control::Prune,
+            BfsEvent::Discover(node) => {
+                reached.push(node);
+                Control::Continue
+            },
+            _ => Control::Continue
+        });
+        assert_eq!((Control::Continue, vec![n[0], n[1], n[3]]), (pruned, reached));
+
+        graph.add_edge(n[3], n[0], ());
+
+        let mut events = Vec::new();
+        let cycle = dfs_visit(&graph, n[0], |event| {
+            events.push(event);
+
+            match event {
+                DfsEvent::BackEdge(from, to) => Control::Break((from, to)),
+                _ => Control::Continue
+            }
+        });
+
+        assert_eq!(Control::Break((n[3], n[0])), cycle);
+        assert_eq!(
+            vec![
+                DfsEvent::Discover(n[0]),
+                DfsEvent::TreeEdge(n[0], n[1]),
+                DfsEvent::Discover(n[1]),
+                DfsEvent::TreeEdge(n[1], n[3]),
+                DfsEvent::Discover(n[3]),
+                DfsEvent::BackEdge(n[3], n[0])
+            ],
+            events
+        );
+
+        let mut finished = Vec::new();
+        let done: Control = dfs_visit(&graph, n[2], |event| {
+            if let DfsEvent::Finish(node) = event {
+                finished.push(node);
+            }
+
+            Control::Continue
+        });
+        assert_eq!((Control::Continue, vec![n[1], n[0], n[3], n[4], n[2]]), (done, finished));
+    }
+
+    #[test]
+    fn depth_limited_and_iterative_deepening() {
+        let (graph, n) = sample();
+
+        let shallow: Vec<_> = DfsLimited::new(&graph, n[0], 1).collect();
+        assert_eq!(vec![(n[0], 0), (n[1], 1), (n[2], 1)], shallow);
+
+        // 0 -> 1 -> 2 -> 3 with a shortcut 0 -> 2, taken second
+        let mut chain: DiGraph<(), ()> = Graph::new();
+        let c: Vec<_> = (0..4).map(|_| chain.add_node(())).collect();
+
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (0, 2)] {
+            chain.add_edge(c[a], c[b], ());
+        }
+
+        let reached: Vec<_> = DfsLimited::new(&chain, c[0], 2).map(|(node, _)| node).collect();
+        assert_eq!(vec![c[0], c[1], c[2], c[3]], reached);
+
+        assert_eq!(Some(vec![c[0], c[2], c[3]]), iddfs(&chain, c[0], 5, |node| node == c[3]));
+        assert_eq!(Some(vec![n[0], n[1], n[3]]), iddfs(&graph, n[0], 2, |node| node == n[3]));
+        assert_eq!(None, iddfs(&graph, n[0], 1, |node| node == n[3]));
+        assert_eq!(None, iddfs(&graph, n[3], 100, |node| node == n[0]));
+    }
+}
@@ -0,0 +1,240 @@
+use crate::graph::{EdgeType, Graph, NodeId};
+use crate::rng::Rng;
+
+/// Walk of up to `len` nodes starting at `start`, moving to a uniformly
+/// chosen neighbor at every step. Stops early at nodes without neighbors.
+pub fn random_walk<N, E, Ty, R>(
+    graph: &Graph<N, E, Ty>,
+    start: NodeId,
+    len: usize,
+    rng: &mut R
+) -> Vec<NodeId>
+where
+    Ty: EdgeType,
+    R: Rng
+{
+    let mut walk = Vec::with_capacity(len);
+    let mut neighbors = Vec::new();
+
+    if len > 0 {
+        walk.push(start);
+    }
+
+    while walk.len() < len {
+        neighbors.clear();
+        neighbors.extend(graph.neighbors(*walk.last().unwrap()));
+
+        if neighbors.is_empty() {
+            break;
+        }
+
+        walk.push(neighbors[rng.below(neighbors.len())]);
+    }
+
+    walk
+}
+
+/// Like [`random_walk`], but moving along each edge with probability
+/// proportional to `weight`. Stops early where no edge has positive weight.
+pub fn weighted_random_walk<N, E, Ty, R, F>(
+    graph: &Graph<N, E, Ty>,
+    start: NodeId,
+    len: usize,
+    rng: &mut R,
+    mut weight: F
+) -> Vec<NodeId>
+where
+    Ty: EdgeType,
+    R: Rng,
+    F: FnMut(&E) -> f64
+{
+    let mut walk = Vec::with_capacity(len);
+    let mut candidates = Vec::new();
+    let mut weights = Vec::new();
+
+    if len > 0 {
+        walk.push(start);
+    }
+
+    while walk.len() < len {
+        candidates.clear();
+        weights.clear();
+
+        let mut neighbors = graph.neighbors(*walk.last().unwrap());
+
+        while let Some((edge, next)) = neighbors.next_edge() {
+            candidates.push(next);
+            weights.push(weight(graph.edge_weight(edge).unwrap()));
+        }
+
+        match rng.weighted_index(&weights) {
+            Some(i) => walk.push(candidates[i]),
+            None => break
+        }
+    }
+
+    walk
+}
+
+/// Second-order biased walks from node2vec (Grover & Leskovec, 2016).
+/// Stepping from `v` having come from `t`, the weight of edge `(v, x)` is
+/// divided by `p` if `x` is `t` and by `q` if `x` is not adjacent to `t`,
+/// so a low `p` keeps walks local and a low `q` pushes them outwards.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Node2Vec {
+    pub p: f64,
+    pub q: f64
+}
+
+impl Node2Vec {
+    /// Panics unless both parameters are positive.
+    pub fn new(p: f64, q: f64) -> Node2Vec {
+        assert!(p > 0.0 && q > 0.0, "p and q must be positive");
+        Node2Vec { p, q }
+    }
+
+    pub fn walk<N, E, Ty, R, F>(
+        &self,
+        graph: &Graph<N, E, Ty>,
+        start: NodeId,
+        len: usize,
+        rng: &mut R,
+        mut weight: F
+    ) -> Vec<NodeId>
+    where
+        Ty: EdgeType,
+        R: Rng,
+        F: FnMut(&E) -> f64
+    {
+        let mut walk = Vec::with_capacity(len);
+        let mut candidates = Vec::new();
+        let mut weights = Vec::new();
+
+        if len > 0 {
+            walk.push(start);
+        }
+
+        while walk.len() < len {
+            candidates.clear();
+            weights.clear();
+
+            let curr = walk[walk.len() - 1];
+            let prev = walk.len().checked_sub(2).map(|i| walk[i]);
+            let mut neighbors = graph.neighbors(curr);
+
+            while let Some((edge, next)) = neighbors.next_edge() {
+                let w = weight(graph.edge_weight(edge).unwrap());
+
+                let bias = match prev {
+                    None => 1.0,
+                    Some(prev) if prev == next => 1.0 / self.p,
+                    Some(prev) if graph.find_edge(prev, next).is_some() => 1.0,
+                    Some(_) => 1.0 / self.q
+                };
+
+                candidates.push(next);
+                weights.push(w * bias);
+            }
+
+            match rng.weighted_index(&weights) {
+                Some(i) => walk.push(candidates[i]),
+                None => break
+            }
+        }
+
+        walk
+    }
+
+    /// `walks_per_node` walks from every node, the usual training corpus for
+    /// skip-gram embeddings. Rounds visit the nodes in shuffled order.
+    pub fn corpus<N, E, Ty, R, F>(
+        &self,
+        graph: &Graph<N, E, Ty>,
+        walks_per_node: usize,
+        len: usize,
+        rng: &mut R,
+        mut weight: F
+    ) -> Vec<Vec<NodeId>>
+    where
+        Ty: EdgeType,
+        R: Rng,
+        F: FnMut(&E) -> f64
+    {
+        let mut nodes: Vec<_> = graph.node_ids().collect();
+        let mut walks = Vec::with_capacity(walks_per_node * nodes.len());
+
+        for _ in 0..walks_per_node {
+            rng.shuffle(&mut nodes);
+
+            for &node in &nodes {
+                walks.push(self.walk(graph, node, len, rng, &mut weight));
+            }
+        }
+
+        walks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{DiGraph, UnGraph};
+    use crate::rng::SplitMix64;
+
+    fn is_walk<N, E, Ty: EdgeType>(graph: &Graph<N, E, Ty>, walk: &[NodeId]) -> bool {
+        walk.windows(2).all(|pair| graph.find_edge(pair[0], pair[1]).is_some())
+    }
+
+    #[test]
+    fn walks_follow_edges_and_stop_at_sinks() {
+        let mut graph: DiGraph<(), f64> = Graph::new();
+        let n: Vec<_> = (0..4).map(|_| graph.add_node(())).collect();
+
+        graph.add_edge(n[0], n[1], 1.0);
+        graph.add_edge(n[1], n[0], 1.0);
+        graph.add_edge(n[1], n[2], 0.0);
+        graph.add_edge(n[0], n[3], 1.0);
+
+        let mut rng = SplitMix64::new(1);
+
+        for _ in 0..50 {
+            let walk = random_walk(&graph, n[0], 10, &mut rng);
+            assert!(is_walk(&graph, &walk));
+            assert!(walk.len() == 10 || [n[2], n[3]].contains(walk.last().unwrap()));
+
+            let walk = weighted_random_walk(&graph, n[0], 10, &mut rng, |&w| w);
+            assert!(is_walk(&graph, &walk) && !walk.contains(&n[2]));
+        }
+
+        assert!(random_walk(&graph, n[0], 0, &mut rng).is_empty());
+    }
+
+    #[test]
+    fn node2vec_return_parameter_biases_backtracking() {
+        // from the middle of a path the walker can go either way
+        let mut graph: UnGraph<(), ()> = Graph::new();
+        let n: Vec<_> = (0..5).map(|_| graph.add_node(())).collect();
+
+        for i in 0..4 {
+            graph.add_edge(n[i], n[i + 1], ());
+        }
+
+        let backtracks = |p: f64| {
+            let mut rng = SplitMix64::new(3);
+            let walker = Node2Vec::new(p, 1.0);
+
+            (0..500)
+                .map(|_| walker.walk(&graph, n[2], 3, &mut rng, |_| 1.0))
+                .filter(|walk| walk[0] == walk[2])
+                .count()
+        };
+
+        assert!(backtracks(0.1) > 400);
+        assert!(backtracks(10.0) < 100);
+
+        let corpus = Node2Vec::new(1.0, 1.0).corpus(&graph, 2, 4, &mut SplitMix64::new(0), |_| 1.0);
+
+        assert_eq!(10, corpus.len());
+        assert!(corpus.iter().all(|walk| walk.len() == 4 && is_walk(&graph, walk)));
+    }
+}
@@ -0,0 +1,163 @@
+use wasm_bindgen::prelude::*;
+
+use crate::algo::try_dijkstra;
+use crate::attr::NodeMap;
+use crate::graph::{DiGraph, EdgeId, EdgeType, Graph, NodeId, UnGraph};
+use crate::layout::{circular_layout, spring_layout};
+use crate::rng::SplitMix64;
+
+enum Inner {
+    Directed(DiGraph<(), f64>),
+    Undirected(UnGraph<(), f64>)
+}
+
+macro_rules! with_graph {
+    ($inner:expr, $graph:ident => $body:expr) => {
+        match $inner {
+            Inner::Directed($graph) => $body,
+            Inner::Undirected($graph) => $body
+        }
+    };
+}
+
+/// Graph exported to JavaScript as `Graph`. Nodes are unweighted, edges
+/// carry a number, and both are named by their index, which stays valid
+/// until they are removed.
+#[wasm_bindgen(js_name = Graph)]
+pub struct WasmGraph {
+    inner: Inner
+}
+
+#[wasm_bindgen(js_class = Graph)]
+impl WasmGraph {
+    #[wasm_bindgen(constructor)]
+    pub fn new(directed: bool) -> WasmGraph {
+        let inner = match directed {
+            true => Inner::Directed(Graph::new()),
+            false => Inner::Undirected(Graph::new())
+        };
+
+        WasmGraph { inner }
+    }
+
+    #[wasm_bindgen(getter, js_name = isDirected)]
+    pub fn is_directed(&self) -> bool {
+        matches!(self.inner, Inner::Directed(_))
+    }
+
+    #[wasm_bindgen(getter, js_name = nodeCount)]
+    pub fn node_count(&self) -> usize {
+        with_graph!(&self.inner, graph => graph.node_count())
+    }
+
+    #[wasm_bindgen(getter, js_name = edgeCount)]
+    pub fn edge_count(&self) -> usize {
+        with_graph!(&self.inner, graph => graph.edge_count())
+    }
+
+    #[wasm_bindgen(js_name = addNode)]
+    pub fn add_node(&mut self) -> usize {
+        with_graph!(&mut self.inner, graph => graph.add_node(()).index())
+    }
+
+    /// Removes a node with its edges.
+    #[wasm_bindgen(js_name = removeNode)]
+    pub fn remove_node(&mut self, node: usize) -> Result<(), JsError> {
+        with_graph!(&mut self.inner, graph => {
+            let node = checked(graph, node)?;
+            graph.remove_node(node);
+            Ok(())
+        })
+    }
+
+    /// Adds an edge and returns its index.
+    #[wasm_bindgen(js_name = addEdge)]
+    pub fn add_edge(&mut self, source: usize, target: usize, weight: f64) -> Result<usize, JsError> {
+        with_graph!(&mut self.inner, graph => {
+            let (source, target) = (checked(graph, source)?, checked(graph, target)?);
+            Ok(graph.add_edge(source, target, weight).index())
+        })
+    }
+
+    #[wasm_bindgen(js_name = removeEdge)]
+    pub fn remove_edge(&mut self, edge: usize) -> Result<(), JsError> {
+        with_graph!(&mut self.inner, graph => match graph.remove_edge(EdgeId::new(edge)) {
+            Some(_) => Ok(()),
+            None => Err(JsError::new(&format!("edge {edge} is not in the graph")))
+        })
+    }
+
+    /// Node indices, in order.
+    pub fn nodes(&self) -> Vec<usize> {
+        with_graph!(&self.inner, graph => graph.node_ids().map(NodeId::index).collect())
+    }
+
+    /// Every edge as an index, source, target triple, flattened.
+    pub fn edges(&self) -> Vec<usize> {
+        with_graph!(&self.inner, graph => graph
+            .edge_ids()
+            .flat_map(|edge| {
+                let (source, target) = graph.edge_endpoints(edge).unwrap();
+                [edge.index(), source.index(), target.index()]
+            })
+            .collect())
+    }
+
+    /// Cheapest path from `source` to `target` as node indices, or
+    /// `undefined` if there is none. Fails if a weight is negative.
+    #[wasm_bindgen(js_name = shortestPath)]
+    pub fn shortest_path(&self, source: usize, target: usize) -> Result<Option<Vec<usize>>, JsError> {
+        with_graph!(&self.inner, graph => {
+            let target = checked(graph, target)?;
+            let paths = try_dijkstra(graph, checked(graph, source)?, |&weight| weight)?;
+            Ok(paths.path_to(target).map(|path| path.into_iter().map(NodeId::index).collect()))
+        })
+    }
+
+    /// Distance from `source` to every node, indexed by node, `Infinity`
+    /// where unreachable and for removed nodes. Fails if a weight is
+    /// negative.
+    #[wasm_bindgen(js_name = shortestDistances)]
+    pub fn shortest_distances(&self, source: usize) -> Result<Vec<f64>, JsError> {
+        with_graph!(&self.inner, graph => {
+            let paths = try_dijkstra(graph, checked(graph, source)?, |&weight| weight)?;
+            let mut distances = vec![f64::INFINITY; graph.node_bound()];
+
+            for node in graph.node_ids() {
+                if let Some(distance) = paths.distance(node) {
+                    distances[node.index()] = distance;
+                }
+            }
+
+            Ok(distances)
+        })
+    }
+
+    /// Nodes on the unit circle, as `x, y` pairs flattened in the order of
+    /// [`nodes`](WasmGraph::nodes).
+    #[wasm_bindgen(js_name = circularLayout)]
+    pub fn circular_layout(&self) -> Vec<f64> {
+        with_graph!(&self.inner, graph => flatten(graph, &circular_layout(graph)))
+    }
+
+    /// Force-directed positions in `[-1, 1]`, as `x, y` pairs flattened in
+    /// the order of [`nodes`](WasmGraph::nodes). The same `seed` gives the
+    /// same layout.
+    #[wasm_bindgen(js_name = springLayout)]
+    pub fn spring_layout(&self, iterations: usize, seed: u32) -> Vec<f64> {
+        with_graph!(&self.inner, graph => {
+            let layout = spring_layout(graph, iterations, &mut SplitMix64::new(seed.into()));
+            flatten(graph, &layout)
+        })
+    }
+}
+
+fn checked<Ty: EdgeType>(graph: &Graph<(), f64, Ty>, index: usize) -> Result<NodeId, JsError> {
+    Some(NodeId::new(index))
+        .filter(|&node| graph.contains_node(node))
+        .ok_or_else(|| JsError::new(&format!("node {index} is not in the graph")))
+}
+
+fn flatten<Ty: EdgeType>(graph: &Graph<(), f64, Ty>, layout: &NodeMap<(f64, f64)>) -> Vec<f64> {
+    graph.node_ids().flat_map(|node| <[f64; 2]>::from(layout[node])).collect()
+}